@@ -0,0 +1,31 @@
+//! 通过使用私有 CA 的网关访问 OSS，可选经过 HTTP 代理
+//!
+//! ```bash
+//! OSS_CA_CERT=/etc/ssl/gateway-ca.pem OSS_PROXY=http://proxy.internal:3128 \
+//!     cargo run --example custom_tls -- some/prefix/
+//! ```
+//!
+//! 需要客户端证书（mTLS）或 SOCKS 代理时，`root_certificates` 和 `proxy` 不够用：
+//! 自己构造 hyper 连接器并实现 `aws_smithy_runtime_api::client::http::HttpClient`，
+//! 再通过 `OssClientBuilder::http_client` 传入。
+
+use oss_uploader::{OssClient, OssConfig, ProxyConfig};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let prefix = std::env::args().nth(1).unwrap_or_default();
+    let mut builder = OssClient::builder(OssConfig::from_env()?);
+    if let Ok(path) = std::env::var("OSS_CA_CERT") {
+        builder = builder.root_certificates(std::fs::read(path)?);
+    }
+    if let Ok(url) = std::env::var("OSS_PROXY") {
+        // no_proxy 中的 host 不经过代理
+        builder = builder.proxy(ProxyConfig::all(url)?.no_proxy("localhost,127.0.0.1"));
+    }
+    let client = builder.build().await?;
+
+    for object in client.list_objects(&prefix).await? {
+        println!("{}\t{}", object.size, object.key);
+    }
+    Ok(())
+}
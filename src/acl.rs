@@ -0,0 +1,133 @@
+//! 对象 ACL（GetObjectAcl / PutObjectAcl）
+
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{AccessControlPolicy, ObjectCannedAcl, Type};
+use serde::Serialize;
+
+use crate::error::{Context, OssError, Result};
+use crate::{is_not_found, is_not_implemented, OssClient};
+
+/// 支持的预设 ACL
+pub const CANNED_ACLS: [&str; 7] = [
+    "private",
+    "public-read",
+    "public-read-write",
+    "authenticated-read",
+    "aws-exec-read",
+    "bucket-owner-read",
+    "bucket-owner-full-control",
+];
+
+const OWNERSHIP_ENFORCED: &str = "bucket 开启了 Object Ownership（BucketOwnerEnforced），ACL 已被禁用，\
+    请改用 bucket policy 控制访问权限";
+
+/// 一条授权
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Grant {
+    /// 被授权者：用户名或 ID、邮箱，或者 AllUsers 等预定义组
+    pub grantee: String,
+    /// READ、WRITE、READ_ACP、WRITE_ACP 或 FULL_CONTROL
+    pub permission: String,
+}
+
+/// 对象的所有者和授权列表
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ObjectAcl {
+    pub owner: Option<String>,
+    pub grants: Vec<Grant>,
+}
+
+impl OssClient {
+    /// 查询对象的 ACL
+    pub async fn get_acl(&self, key: &str) -> Result<ObjectAcl> {
+        let resp = match self.client.get_object_acl().bucket(&self.config.bucket).key(key).send().await {
+            Ok(resp) => resp,
+            Err(err) if is_not_found(&err) => return Err(OssError::not_found(key)),
+            Err(err) => return Err(err).with_context(|| format!("无法查询 {} 的 ACL", key)),
+        };
+        let owner = resp.owner().and_then(|o| o.display_name().or(o.id())).map(str::to_string);
+        let grants = resp
+            .grants()
+            .iter()
+            .map(|grant| Grant {
+                grantee: grant.grantee().map(grantee_name).unwrap_or_else(|| "-".to_string()),
+                permission: grant.permission().map(|p| p.as_str().to_string()).unwrap_or_default(),
+            })
+            .collect();
+        Ok(ObjectAcl { owner, grants })
+    }
+
+    /// 把对象的 ACL 设置为预设 ACL，如 `public-read`、`private`
+    pub async fn set_canned_acl(&self, key: &str, acl: &str) -> Result<()> {
+        match self.client
+            .put_object_acl()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .acl(ObjectCannedAcl::from(acl))
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if err.code() == Some("AccessControlListNotSupported") => Err(OssError::unsupported(OWNERSHIP_ENFORCED, err)),
+            Err(err) if is_not_found(&err) => Err(OssError::not_found(key)),
+            Err(err) => Err(err).with_context(|| format!("无法设置 {} 的 ACL", key)),
+        }
+    }
+
+    /// 读取对象完整的 ACL，用于复制后原样恢复；bucket 禁用了 ACL 时返回 None
+    pub(crate) async fn acl_policy(&self, key: &str) -> Result<Option<AccessControlPolicy>> {
+        match self.client.get_object_acl().bucket(&self.config.bucket).key(key).send().await {
+            Ok(resp) => Ok(Some(
+                AccessControlPolicy::builder()
+                    .set_owner(resp.owner().cloned())
+                    .set_grants(Some(resp.grants().to_vec()))
+                    .build(),
+            )),
+            Err(err) if err.code() == Some("AccessControlListNotSupported") || is_not_implemented(&err) => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("无法查询 {} 的 ACL", key)),
+        }
+    }
+
+    pub(crate) async fn put_acl_policy(&self, key: &str, policy: AccessControlPolicy) -> Result<()> {
+        self.client
+            .put_object_acl()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .access_control_policy(policy)
+            .send()
+            .await
+            .with_context(|| format!("无法恢复 {} 的 ACL", key))?;
+        Ok(())
+    }
+}
+
+fn grantee_name(grantee: &aws_sdk_s3::types::Grantee) -> String {
+    match grantee.r#type() {
+        // 预定义组只保留最后一段，如 http://acs.amazonaws.com/groups/global/AllUsers -> AllUsers
+        Type::Group => grantee
+            .uri()
+            .map(|uri| uri.rsplit('/').next().unwrap_or(uri).to_string())
+            .unwrap_or_default(),
+        Type::AmazonCustomerByEmail => grantee.email_address().unwrap_or_default().to_string(),
+        _ => grantee.display_name().or(grantee.id()).unwrap_or_default().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::types::Grantee;
+
+    #[test]
+    fn test_grantee_name() {
+        let group = Grantee::builder()
+            .r#type(Type::Group)
+            .uri("http://acs.amazonaws.com/groups/global/AllUsers")
+            .build()
+            .unwrap();
+        assert_eq!(grantee_name(&group), "AllUsers");
+
+        let user = Grantee::builder().r#type(Type::CanonicalUser).id("abc123").build().unwrap();
+        assert_eq!(grantee_name(&user), "abc123");
+    }
+}
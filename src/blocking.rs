@@ -0,0 +1,112 @@
+//! 同步（阻塞）接口：客户端内部持有一个 tokio 运行时，每个方法阻塞等待对应的异步实现完成
+//!
+//! 需要开启 `blocking` feature。不能在 tokio 运行时内创建或调用，此时返回 [`OssError::Config`]。
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tokio::runtime::{Builder, Handle, Runtime};
+
+use crate::error::{OssError, Result};
+use crate::{DeleteOutcome, ObjectInfo, OssClientBuilder, OssConfig};
+
+/// [`crate::OssClient`] 的同步版本
+pub struct OssClient {
+    inner: crate::OssClient,
+    runtime: Runtime,
+}
+
+impl OssClient {
+    /// 使用默认参数创建客户端
+    pub fn new(config: OssConfig) -> Result<Self> {
+        Self::from_builder(crate::OssClient::builder(config))
+    }
+
+    /// 用 [`OssClientBuilder`] 设置分块大小、并发数等参数后创建客户端
+    pub fn from_builder(builder: OssClientBuilder) -> Result<Self> {
+        check_outside_runtime()?;
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| OssError::Internal(format!("无法创建 tokio 运行时: {}", err)))?;
+        let inner = runtime.block_on(builder.build())?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// 内部的异步客户端
+    pub fn async_client(&self) -> &crate::OssClient {
+        &self.inner
+    }
+
+    /// 上传文件，返回对象的 URL
+    pub fn upload(&self, path: &Path, key: &str) -> Result<String> {
+        self.block_on(self.inner.upload(path, key))
+    }
+
+    /// 下载文件，`output_path` 为 None 时保存为 key 的文件名
+    pub fn download(&self, key: &str, output_path: Option<&Path>) -> Result<PathBuf> {
+        self.block_on(self.inner.download(key, output_path))
+    }
+
+    /// 删除对象
+    pub fn delete(&self, key: &str) -> Result<DeleteOutcome> {
+        self.block_on(self.inner.delete(key))
+    }
+
+    /// 生成带签名的临时下载 URL，有效期 `expires_in_secs` 秒
+    pub fn presigned_url(&self, key: &str, expires_in_secs: u64) -> Result<String> {
+        self.block_on(self.inner.generate_presigned_url(key, expires_in_secs, None, None))
+    }
+
+    /// 列出前缀下的所有对象
+    pub fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>> {
+        self.block_on(self.inner.list_objects(prefix))
+    }
+
+    fn block_on<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        check_outside_runtime()?;
+        self.runtime.block_on(future)
+    }
+}
+
+/// 在 tokio 运行时内阻塞会 panic，提前返回错误
+fn check_outside_runtime() -> Result<()> {
+    if Handle::try_current().is_ok() {
+        return Err(OssError::Config(
+            "不能在 tokio 运行时内使用 blocking::OssClient，请直接使用异步的 OssClient".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> OssConfig {
+        OssConfig {
+            access_key: "a".to_string(),
+            secret_key: "b".to_string(),
+            bucket: "my-bucket".to_string(),
+            endpoint: "https://s3.example.com".to_string(),
+            region: "us-east-1".to_string(),
+            public_base_url: None,
+        }
+    }
+
+    #[test]
+    fn test_blocking_client() {
+        let client = OssClient::new(config()).unwrap();
+        let url = client.presigned_url("a.txt", 60).unwrap();
+        assert!(url.starts_with("https://my-bucket.s3.example.com/a.txt?"), "{}", url);
+        assert!(client.presigned_url("a.txt", 0).is_err());
+
+        let err = OssClient::from_builder(crate::OssClient::builder(config()).max_workers(0)).err().unwrap();
+        assert!(matches!(err, OssError::Config(_)));
+    }
+
+    #[tokio::test]
+    async fn test_inside_runtime() {
+        let err = OssClient::new(config()).err().unwrap();
+        assert!(matches!(&err, OssError::Config(message) if message.contains("tokio")), "{}", err);
+    }
+}
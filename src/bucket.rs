@@ -0,0 +1,162 @@
+//! bucket 的创建、删除和版本控制
+
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{
+    BucketLocationConstraint, BucketVersioningStatus, CreateBucketConfiguration, VersioningConfiguration,
+};
+use futures::TryStreamExt;
+use std::sync::Arc;
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::{is_not_implemented, ListOptions, OssClient};
+
+/// 创建 bucket 的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateBucketOutcome {
+    Created,
+    /// bucket 已经存在且属于当前账号，什么也没做
+    AlreadyOwned,
+}
+
+/// bucket 的版本控制状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BucketVersioning {
+    /// `Enabled` 或 `Suspended`，从未开启过时为 None
+    pub status: Option<String>,
+    /// MFA Delete 状态，未配置时为 None
+    pub mfa_delete: Option<String>,
+}
+
+impl OssClient {
+    /// 操作另一个 bucket 的客户端，共享连接、并发数和限流统计
+    pub fn with_bucket(&self, bucket: &str) -> OssClient {
+        let mut config = (*self.config).clone();
+        config.bucket = bucket.to_string();
+        OssClient { config: Arc::new(config), ..self.clone() }
+    }
+
+    /// 在配置的区域创建 bucket
+    /// us-east-1 不能指定 LocationConstraint，其他区域必须指定
+    pub async fn create_bucket(&self, bucket: &str) -> Result<CreateBucketOutcome> {
+        let configuration = location_constraint(&self.config.region).map(|region| {
+            CreateBucketConfiguration::builder()
+                .location_constraint(BucketLocationConstraint::from(region))
+                .build()
+        });
+
+        match self.client
+            .create_bucket()
+            .bucket(bucket)
+            .set_create_bucket_configuration(configuration)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(CreateBucketOutcome::Created),
+            Err(err) if err.code() == Some("BucketAlreadyOwnedByYou") => Ok(CreateBucketOutcome::AlreadyOwned),
+            Err(err) if err.code() == Some("BucketAlreadyExists") => {
+                bail!("bucket {} 已被其他账号占用，请换一个名字", bucket)
+            }
+            Err(err) => Err(err).with_context(|| format!("无法创建 bucket {}", bucket)),
+        }
+    }
+
+    /// 删除 bucket
+    /// bucket 不为空时拒绝删除；`force` 为 true 时先批量删除其中所有对象（包括历史版本）
+    pub async fn delete_bucket(&self, bucket: &str, force: bool) -> Result<()> {
+        let target = self.with_bucket(bucket);
+        if force {
+            target.empty_bucket().await?;
+        } else if !target.is_bucket_empty().await? {
+            bail!("bucket {} 不为空，使用 --force 先删除其中所有对象", bucket);
+        }
+
+        match self.client.delete_bucket().bucket(bucket).send().await {
+            Ok(_) => Ok(()),
+            // 列表为空但仍有历史版本或删除标记时也会返回 BucketNotEmpty
+            Err(err) if err.code() == Some("BucketNotEmpty") => {
+                bail!("bucket {} 仍有对象或历史版本，使用 --force 先全部删除", bucket)
+            }
+            Err(err) => Err(err).with_context(|| format!("无法删除 bucket {}", bucket)),
+        }
+    }
+
+    /// 查询当前 bucket 的版本控制状态
+    pub async fn get_bucket_versioning(&self) -> Result<BucketVersioning> {
+        let resp = match self.client.get_bucket_versioning().bucket(&self.config.bucket).send().await {
+            Ok(resp) => resp,
+            Err(err) if is_not_implemented(&err) => return Err(OssError::unsupported("当前服务商不支持版本控制", err)),
+            Err(err) => return Err(err).with_context(|| format!("无法查询 {} 的版本控制状态", self.config.bucket)),
+        };
+        Ok(BucketVersioning {
+            status: resp.status().map(|s| s.as_str().to_string()),
+            mfa_delete: resp.mfa_delete().map(|s| s.as_str().to_string()),
+        })
+    }
+
+    /// 开启（`enabled` 为 true）或暂停当前 bucket 的版本控制
+    /// 开启过版本控制的 bucket 只能暂停，不能回到未开启的状态
+    pub async fn set_bucket_versioning(&self, enabled: bool) -> Result<()> {
+        let status = if enabled { BucketVersioningStatus::Enabled } else { BucketVersioningStatus::Suspended };
+        match self.client
+            .put_bucket_versioning()
+            .bucket(&self.config.bucket)
+            .versioning_configuration(VersioningConfiguration::builder().status(status).build())
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if is_not_implemented(&err) => Err(OssError::unsupported("当前服务商不支持版本控制", err)),
+            Err(err) => Err(err).with_context(|| format!("无法修改 {} 的版本控制状态", self.config.bucket)),
+        }
+    }
+
+    async fn is_bucket_empty(&self) -> Result<bool> {
+        let options = ListOptions { max_keys: Some(1), ..Default::default() };
+        let first = self.list_stream("", &options)
+            .try_next()
+            .await
+            .with_context(|| format!("无法列出 bucket {}", self.config.bucket))?;
+        Ok(first.is_none())
+    }
+
+    /// 删除当前 bucket 中的所有对象，再删除剩余的历史版本和删除标记
+    async fn empty_bucket(&self) -> Result<()> {
+        let keys: Vec<String> = self.list_objects("").await?.into_iter().map(|obj| obj.key).collect();
+        let report = self.delete_many(&keys).await?;
+        if !report.is_success() {
+            bail!("有 {} 个对象删除失败，bucket 未删除", report.errors.len());
+        }
+
+        // 不支持版本控制的服务商可能不支持 ListObjectVersions，此时没有历史版本需要删除
+        let Ok(versions) = self.list_object_versions("").await else {
+            return Ok(());
+        };
+        let versions: Vec<(String, String)> = versions.into_iter().map(|v| (v.key, v.version_id)).collect();
+        let report = self.delete_versions(&versions).await?;
+        if !report.is_success() {
+            bail!("有 {} 个历史版本删除失败，bucket 未删除", report.errors.len());
+        }
+        Ok(())
+    }
+}
+
+/// 创建 bucket 时需要的 LocationConstraint，us-east-1（默认区域）不需要
+fn location_constraint(region: &str) -> Option<&str> {
+    match region {
+        "" | "us-east-1" => None,
+        region => Some(region),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_constraint() {
+        assert_eq!(location_constraint("us-east-1"), None);
+        assert_eq!(location_constraint(""), None);
+        assert_eq!(location_constraint("eu-west-1"), Some("eu-west-1"));
+        assert_eq!(location_constraint("cn-beijing"), Some("cn-beijing"));
+    }
+}
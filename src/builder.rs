@@ -0,0 +1,406 @@
+//! OssClient 的构建器：凭据之外的行为（分块大小、并发数、重试、超时、连接池、path-style 等）
+
+use aws_config::BehaviorVersion;
+use aws_sdk_s3::config::retry::RetryConfig;
+use aws_sdk_s3::config::timeout::TimeoutConfig;
+use aws_sdk_s3::config::{Credentials, HttpClient, Region, SharedHttpClient};
+use aws_sdk_s3::Client;
+use aws_smithy_http_client::proxy::ProxyConfig;
+use aws_smithy_http_client::tls::{self, rustls_provider::CryptoMode, TlsContext, TrustStore};
+use rustls::pki_types::{pem::PemObject, CertificateDer};
+use aws_smithy_http_client::{Builder as HttpClientBuilder, ConnectorBuilder};
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use crate::error::{OssError, Result};
+use crate::metrics::MetricsInterceptor;
+use crate::retry::PolicyClassifier;
+use crate::{urls, KeyPolicy, Metrics, NoMetrics, OssClient, OssConfig, ProgressObserver, RateLimiter, RetryPolicy, DEFAULT_PART_SIZE, MAX_WORKERS, MIN_PART_SIZE};
+
+/// 分块上传的分块大小上限 5GB
+const MAX_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// 创建 [`OssClient`]，参数在 [`build`](Self::build) 时统一检查
+pub struct OssClientBuilder {
+    config: OssConfig,
+    part_size: u64,
+    workers: usize,
+    retry: Option<RetryConfig>,
+    retry_policy: Option<RetryPolicy>,
+    timeout: Option<TimeoutConfig>,
+    path_style: bool,
+    progress: Option<Arc<dyn ProgressObserver>>,
+    metrics: Option<Arc<dyn Metrics>>,
+    rate_limiter: Option<Arc<dyn RateLimiter>>,
+    http_client: Option<SharedHttpClient>,
+    connector: ConnectorOptions,
+    key_policy: KeyPolicy,
+}
+
+/// 默认 HTTPS 客户端的设置，都没有设置时使用 SDK 自带的客户端
+#[derive(Debug, Clone, Default)]
+struct ConnectorOptions {
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Option<Duration>>,
+    root_certificates: Vec<Vec<u8>>,
+    proxy: Option<ProxyConfig>,
+}
+
+impl ConnectorOptions {
+    fn is_set(&self) -> bool {
+        self.pool_max_idle_per_host.is_some() || self.pool_idle_timeout.is_some() || !self.root_certificates.is_empty() || self.proxy.is_some()
+    }
+}
+
+impl OssClientBuilder {
+    pub(crate) fn new(config: OssConfig) -> Self {
+        Self {
+            config,
+            part_size: DEFAULT_PART_SIZE,
+            workers: MAX_WORKERS,
+            retry: None,
+            retry_policy: None,
+            timeout: None,
+            path_style: false,
+            progress: None,
+            metrics: None,
+            rate_limiter: None,
+            http_client: None,
+            connector: ConnectorOptions::default(),
+            key_policy: KeyPolicy::default(),
+        }
+    }
+
+    /// 分块上传/下载的分块大小（字节），范围 5MB 到 5GB，默认 10MB；不超过分块大小的文件用单次请求传输
+    pub fn part_size(mut self, bytes: u64) -> Self {
+        self.part_size = bytes;
+        self
+    }
+
+    /// 分块请求的并发数，默认 10。同一客户端（包括它的 clone）上同时进行的所有分块上传/下载和单次 PUT 上传共享这些名额，
+    /// 单次传输还可以用 [`with_workers`](OssClient::with_workers) 或传输选项的 `workers` 设置更低的上限
+    pub fn max_workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    /// SDK 对单个请求的重试策略，默认使用 SDK 的标准重试（3 次）
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// 额外视为可重试的错误码、状态码（如服务商特有的限流错误），同时作用于 SDK 的重试和分段下载等自带的重试
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// 连接、单次请求等超时，默认不限制
+    pub fn timeout(mut self, timeout: TimeoutConfig) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 使用 path-style 地址（`endpoint/bucket/key`），MinIO 等不支持 bucket 子域名的服务需要开启
+    pub fn path_style(mut self, path_style: bool) -> Self {
+        self.path_style = path_style;
+        self
+    }
+
+    /// 上传、下载、删除、复制和预签名之前检查 key 的规则，默认为 [`KeyPolicy::Standard`]
+    pub fn key_policy(mut self, policy: KeyPolicy) -> Self {
+        self.key_policy = policy;
+        self
+    }
+
+    /// 没有在 [`TransferOptions`](crate::TransferOptions) 中指定时，上传/下载的进度事件发送给 `observer`
+    pub fn progress(mut self, observer: Arc<dyn ProgressObserver>) -> Self {
+        self.progress = Some(observer);
+        self
+    }
+
+    /// 每个请求的操作名、流量、重试和失败发送给 `metrics`，如 [`MetricsCounters`](crate::MetricsCounters)
+    pub fn metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// 上传的请求体和下载的数据块在传输前向 `limiter` 申请带宽，如 [`TokenBucket`](crate::TokenBucket)；
+    /// 同一个 `limiter` 可以设置给多个客户端，限制它们的合计带宽。默认不限制
+    pub fn rate_limiter(mut self, limiter: Arc<dyn RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// 连接池中每个 host 最多保留的空闲连接数，默认不限制。
+    /// 大量小文件并发上传时，保留不少于并发数的连接可以避免反复建立 TCP/TLS 连接；设为 0 时每个请求都新建连接
+    pub fn pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.connector.pool_max_idle_per_host = Some(max_idle);
+        self
+    }
+
+    /// 空闲连接在连接池中保留的时间，默认 90 秒，None 表示不过期。
+    /// 服务端或负载均衡器会主动关闭空闲连接时，设得比它的超时短一些可以减少复用到已关闭连接的失败
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.connector.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// 默认 HTTPS 客户端额外信任的 CA 证书（PEM 格式，可以包含多个），如自建网关使用的私有 CA，
+    /// 系统自带的根证书仍然有效。可以多次调用，证书无效时 [`build`](Self::build) 返回 [`OssError::Config`]
+    pub fn root_certificates(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.connector.root_certificates.push(pem.into());
+        self
+    }
+
+    /// 默认 HTTPS 客户端使用的 HTTP/HTTPS 代理，默认读取 `HTTPS_PROXY` 等环境变量
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.connector.proxy = Some(proxy);
+        self
+    }
+
+    /// 使用已经配置好的 HTTP 客户端代替默认的 HTTPS 客户端，如需要客户端证书（mTLS）或 SOCKS 代理时，
+    /// 自己构造 hyper 连接器并实现 [`HttpClient`]；测试时也可以用它返回模拟的响应。
+    /// 不能同时设置连接池、TLS 和代理。预签名 URL 在本地计算，不经过 HTTP 客户端
+    pub fn http_client(mut self, http_client: impl HttpClient + 'static) -> Self {
+        self.http_client = Some(SharedHttpClient::new(http_client));
+        self
+    }
+
+    /// 检查参数并创建客户端，参数不合法时返回 [`OssError::Config`]
+    pub async fn build(self) -> Result<OssClient> {
+        self.validate()?;
+
+        let mut loader = aws_config::defaults(BehaviorVersion::latest())
+            .endpoint_url(&self.config.endpoint)
+            .region(Region::new(self.config.region.clone()))
+            .credentials_provider(Credentials::new(
+                &self.config.access_key,
+                &self.config.secret_key,
+                None,
+                None,
+                "env",
+            ));
+        if let Some(retry) = self.retry {
+            loader = loader.retry_config(retry);
+        }
+        if let Some(timeout) = self.timeout {
+            loader = loader.timeout_config(timeout);
+        }
+        if let Some(http_client) = self.http_client {
+            loader = loader.http_client(http_client);
+        } else if self.connector.is_set() {
+            loader = loader.http_client(default_http_client(self.connector.clone()));
+        }
+        let sdk_config = loader.load().await;
+        let mut s3_config = aws_sdk_s3::config::Builder::from(&sdk_config).force_path_style(self.path_style);
+        let retry_policy = Arc::new(self.retry_policy.clone().unwrap_or_default());
+        if self.retry_policy.is_some() {
+            s3_config = s3_config.retry_classifier(PolicyClassifier(retry_policy.clone()));
+        }
+        if let Some(metrics) = &self.metrics {
+            s3_config = s3_config.interceptor(MetricsInterceptor(metrics.clone()));
+        }
+        let s3_config = s3_config.build();
+
+        Ok(OssClient {
+            client: Client::from_conf(s3_config),
+            config: Arc::new(self.config),
+            workers: self.workers,
+            permits: Arc::new(Semaphore::new(self.workers)),
+            part_size: self.part_size,
+            path_style: self.path_style,
+            progress: self.progress,
+            throttled: Arc::new(AtomicU64::new(0)),
+            retry_policy,
+            metrics: self.metrics.unwrap_or_else(|| Arc::new(NoMetrics)),
+            key_policy: self.key_policy,
+            rate_limiter: self.rate_limiter,
+        })
+    }
+
+    fn validate(&self) -> Result<()> {
+        check_part_size(self.part_size)?;
+        if self.workers == 0 {
+            return Err(OssError::Config("并发数必须大于 0".to_string()));
+        }
+        if self.http_client.is_some() && self.connector.is_set() {
+            return Err(OssError::Config("连接池、TLS 和代理设置对自定义的 HTTP 客户端无效，请在该客户端上配置".to_string()));
+        }
+        for pem in &self.connector.root_certificates {
+            check_root_certificates(pem)?;
+        }
+        if self.config.endpoint.trim().is_empty() {
+            return Err(OssError::Config("endpoint 不能为空".to_string()));
+        }
+        if self.config.bucket.trim().is_empty() {
+            return Err(OssError::Config("bucket 不能为空".to_string()));
+        }
+        urls::parse_base(&self.config.endpoint)?;
+        if let Some(base) = &self.config.public_base_url {
+            urls::parse_base(base)?;
+        }
+        Ok(())
+    }
+}
+
+/// 分块大小必须在 5MB 到 5GB 之间
+pub(crate) fn check_part_size(bytes: u64) -> Result<()> {
+    if !(MIN_PART_SIZE..=MAX_PART_SIZE).contains(&bytes) {
+        return Err(OssError::Config(format!("分块大小 {} 字节超出范围，必须在 5MB 到 5GB 之间", bytes)));
+    }
+    Ok(())
+}
+
+/// SDK 在建立连接时才解析证书，解析失败会直接 panic，所以提前检查
+fn check_root_certificates(pem: &[u8]) -> Result<()> {
+    let certs = CertificateDer::pem_slice_iter(pem)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| OssError::Config(format!("CA 证书不是有效的 PEM: {}", e)))?;
+    if certs.is_empty() {
+        return Err(OssError::Config("CA 证书中没有找到证书".to_string()));
+    }
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in certs {
+        roots.add(cert).map_err(|e| OssError::Config(format!("无效的 CA 证书: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// 与 SDK 默认相同的 HTTPS 客户端（rustls、HTTPS 连接通过 ALPN 协商 HTTP/2、读取代理环境变量），
+/// 只修改 `options` 中设置了的连接池、TLS 和代理参数。
+/// ALPN 总是优先 h2，服务端不支持时退回 HTTP/1.1；SDK 的连接器没有开放 hyper 的 `http2_only` 和 ALPN 列表，
+/// 因此不提供强制 HTTP/2 的设置，需要时用 [`OssClientBuilder::http_client`] 传入自己构造的客户端
+fn default_http_client(options: ConnectorOptions) -> SharedHttpClient {
+    HttpClientBuilder::new().build_with_connector_fn(move |settings, runtime_components| {
+        let mut builder = ConnectorBuilder::default();
+        builder.set_pool_max_idle_per_host(options.pool_max_idle_per_host).set_pool_idle_timeout(options.pool_idle_timeout);
+        let mut builder = builder.tls_provider(tls::Provider::Rustls(CryptoMode::AwsLc));
+        if !options.root_certificates.is_empty() {
+            let trust_store = options.root_certificates.iter().fold(TrustStore::default(), |store, pem| store.with_pem_certificate(pem.clone()));
+            builder.set_tls_context(TlsContext::builder().with_trust_store(trust_store).build().expect("TLS 设置不会出错"));
+        }
+        builder.set_connector_settings(settings.cloned());
+        if let Some(components) = runtime_components {
+            builder.set_sleep_impl(components.sleep_impl());
+        }
+        builder.set_proxy_config(Some(options.proxy.clone().unwrap_or_else(ProxyConfig::from_env)));
+        builder.build()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProgressEvent;
+
+    fn config() -> OssConfig {
+        OssConfig {
+            access_key: "a".to_string(),
+            secret_key: "b".to_string(),
+            bucket: "my-bucket".to_string(),
+            endpoint: "https://s3.example.com".to_string(),
+            region: "us-east-1".to_string(),
+            public_base_url: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_with_options() {
+        let observer = |_event: ProgressEvent<'_>| {};
+        let client = OssClient::builder(config())
+            .part_size(64 * 1024 * 1024)
+            .max_workers(4)
+            .retry(RetryConfig::standard().with_max_attempts(5))
+            .timeout(TimeoutConfig::builder().connect_timeout(Duration::from_secs(3)).build())
+            .path_style(true)
+            .pool_max_idle_per_host(16)
+            .pool_idle_timeout(Some(Duration::from_secs(30)))
+            .progress(Arc::new(observer))
+            .build()
+            .await
+            .unwrap();
+        assert_eq!((client.part_size(), client.workers()), (64 * 1024 * 1024, 4));
+        assert_eq!(client.generate_url("a b.txt"), "https://s3.example.com/my-bucket/a%20b.txt");
+        assert_eq!(client.client.config().retry_config().unwrap().max_attempts(), 5);
+
+        let client = OssClient::new(config()).await.unwrap();
+        assert_eq!((client.part_size(), client.workers()), (DEFAULT_PART_SIZE, MAX_WORKERS));
+        assert_eq!(client.generate_url("a.txt"), "https://my-bucket.s3.example.com/a.txt");
+    }
+
+    /// 自签名的测试 CA
+    const TEST_CA: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBlTCCATugAwIBAgIUTC+aWT32i6feC9qbidBREEh9LJkwCgYIKoZIzj0EAwIw
+HzEdMBsGA1UEAwwUb3NzLXVwbG9hZGVyIHRlc3QgQ0EwIBcNMjYxMDE0MDg1ODE3
+WhgPMjEyNjA5MjAwODU4MTdaMB8xHTAbBgNVBAMMFG9zcy11cGxvYWRlciB0ZXN0
+IENBMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEnlBlcZhAXp7bUL6IK7iTnowM
+8YifXHEkqvatfgkDNRQhps3FakckejqvEtFxI56XQrVT5z3FnOGXR8X1GtQkT6NT
+MFEwHQYDVR0OBBYEFE4sq61P+qfzmZyxgzuCXftWhgu8MB8GA1UdIwQYMBaAFE4s
+q61P+qfzmZyxgzuCXftWhgu8MA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwID
+SAAwRQIgJxRFnjJXHrNLCLSWORBxkkJA2kb+8wvLD72tw20q2ksCIQDVrBrhB+pi
+AVnVRxXZi/+T465l/+9zP5F4emMxrjN/cA==
+-----END CERTIFICATE-----\n";
+
+    #[tokio::test]
+    async fn test_custom_http_client_and_tls() {
+        use crate::mock::response;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // 预签名在本地计算，不经过自定义的 HTTP 客户端
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = crate::mock::mock_builder(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            response(200, &[], "")
+        })
+        .build()
+        .await
+        .unwrap();
+        let url = client.generate_presigned_url("a.txt", 60, None, None).await.unwrap();
+        assert!(url.contains("X-Amz-Signature="), "{}", url);
+        client.upload_bytes("a.txt", "data").await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let client = OssClient::builder(config())
+            .root_certificates(TEST_CA)
+            .proxy(ProxyConfig::disabled())
+            .build()
+            .await
+            .unwrap();
+        assert!(client.generate_presigned_url("a.txt", 60, None, None).await.is_ok());
+
+        let err = crate::mock::mock_builder(|_| response(200, &[], "")).root_certificates(TEST_CA).build().await.err().unwrap();
+        assert!(matches!(&err, OssError::Config(message) if message.contains("TLS")), "{}", err);
+        for pem in ["-----BEGIN CERTIFICATE-----", "", "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n"] {
+            let err = OssClient::builder(config()).root_certificates(pem).build().await.err().unwrap();
+            assert!(matches!(&err, OssError::Config(message) if message.contains("CA 证书")), "{}", err);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_rejects_invalid_options() {
+        let err = OssClient::builder(config()).part_size(1024 * 1024).build().await.err().unwrap();
+        assert!(matches!(&err, OssError::Config(message) if message.contains("5MB")), "{}", err);
+        assert!(OssClient::builder(config()).part_size(6 * 1024 * 1024 * 1024).build().await.is_err());
+        assert!(OssClient::builder(config()).part_size(MIN_PART_SIZE).build().await.is_ok());
+        let err = OssClient::builder(config()).max_workers(0).build().await.err().unwrap();
+        assert!(matches!(err, OssError::Config(_)));
+        let err = OssClient::builder(config())
+            .pool_max_idle_per_host(0)
+            .http_client(default_http_client(ConnectorOptions::default()))
+            .build()
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(&err, OssError::Config(message) if message.contains("连接池")), "{}", err);
+        let empty_bucket = OssConfig { bucket: String::new(), ..config() };
+        assert!(OssClient::builder(empty_bucket).build().await.is_err());
+        let bad_public_url = OssConfig { public_base_url: Some("https://cdn.example.com/?a=1".to_string()), ..config() };
+        assert!(matches!(OssClient::builder(bad_public_url).build().await, Err(OssError::Config(_))));
+    }
+}
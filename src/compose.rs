@@ -0,0 +1,229 @@
+//! 把多个远程对象按顺序拼接为一个对象（UploadPartCopy），不经过本地
+
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use futures::{StreamExt, TryStreamExt};
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::copy::{COPY_OBJECT_MAX, COPY_PART_SIZE};
+use crate::{format_size, ObjectInfo, OssClient, MAX_PARTS, MIN_PART_SIZE};
+
+/// 一个分块：复制源对象的 `start..=end` 字节
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PartCopy<'a> {
+    key: &'a str,
+    start: u64,
+    end: u64,
+}
+
+impl OssClient {
+    /// 按顺序拼接 `sources` 为 `dst`，返回目标对象的大小
+    /// 每个源对象对应一个或多个 UploadPartCopy 分块，超过 5 GiB 的源对象按范围拆分。
+    /// 除最后一个外的源对象不能小于 5 MiB（分块的最小大小），空对象会被跳过。
+    /// 目标的 HTTP 头（Content-Type、Cache-Control 等）和自定义元数据取自第一个源对象，不复制标签。
+    /// 完成后通过 HEAD 确认目标大小等于所有源对象之和；中途失败时中止分块上传
+    pub async fn compose(&self, dst: &str, sources: &[String]) -> Result<u64> {
+        let [first, rest @ ..] = sources else {
+            bail!("至少需要一个源对象");
+        };
+        let dst = &self.object_key(dst)?;
+        let (first, headers) = self.head_headers(first, None).await?;
+        let rest: Vec<ObjectInfo> = futures::stream::iter(rest)
+            .map(|key| self.head(key, None))
+            .buffered(self.workers)
+            .try_collect()
+            .await?;
+        let infos: Vec<ObjectInfo> = std::iter::once(first).chain(rest).collect();
+        let parts = plan_parts(&infos)?;
+        let total: u64 = infos.iter().map(|info| info.size).sum();
+
+        let request = self.client.create_multipart_upload().bucket(&self.config.bucket).key(dst);
+        let upload_id = headers
+            .apply_create(request)
+            .send()
+            .await
+            .with_context(|| format!("无法创建分块上传: {}", dst))?
+            .upload_id()
+            .context("无法获取 upload id")?
+            .to_string();
+
+        let completed = futures::stream::iter(parts.iter().enumerate())
+            .map(|(index, part)| {
+                let part_number = index as i32 + 1;
+                let upload_id = &upload_id;
+                async move {
+                    let resp = self.client
+                        .upload_part_copy()
+                        .bucket(&self.config.bucket)
+                        .key(dst)
+                        .upload_id(upload_id)
+                        .part_number(part_number)
+                        .copy_source(self.copy_source(part.key, None))
+                        .copy_source_range(format!("bytes={}-{}", part.start, part.end))
+                        .send()
+                        .await
+                        .with_context(|| format!("无法复制 {} 的 {}-{} 字节", part.key, part.start, part.end))?;
+                    let etag = resp.copy_part_result().and_then(|r| r.e_tag()).unwrap_or_default();
+                    Ok::<_, OssError>(CompletedPart::builder().part_number(part_number).e_tag(etag).build())
+                }
+            })
+            .buffered(self.workers)
+            .try_collect::<Vec<_>>()
+            .await;
+        let completed = match completed {
+            Ok(completed) => completed,
+            Err(err) => {
+                self.abort_quietly(dst, &upload_id).await;
+                return Err(err);
+            }
+        };
+
+        let result = self.client
+            .complete_multipart_upload()
+            .bucket(&self.config.bucket)
+            .key(dst)
+            .upload_id(&upload_id)
+            .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(completed)).build())
+            .send()
+            .await
+            .with_context(|| format!("无法完成分块上传: {}", dst));
+        if let Err(err) = result {
+            self.abort_quietly(dst, &upload_id).await;
+            return Err(err);
+        }
+
+        let target = self.head(dst, None).await?;
+        if target.size != total {
+            bail!("拼接后 {} 的大小 {} 与源对象大小之和 {} 不一致", dst, target.size, total);
+        }
+        Ok(total)
+    }
+}
+
+/// 规划分块：每个源对象一块，超过 5 GiB 的按 [`COPY_PART_SIZE`] 拆分，
+/// 拆分后不足 5 MiB 的尾部并入前一块
+fn plan_parts(sources: &[ObjectInfo]) -> Result<Vec<PartCopy<'_>>> {
+    let non_empty: Vec<_> = sources.iter().filter(|info| info.size > 0).collect();
+    if non_empty.is_empty() {
+        bail!("所有源对象都为空");
+    }
+
+    let too_small: Vec<_> = non_empty[..non_empty.len() - 1]
+        .iter()
+        .filter(|info| info.size < MIN_PART_SIZE)
+        .map(|info| format!("{} ({})", info.key, format_size(info.size)))
+        .collect();
+    if !too_small.is_empty() {
+        bail!(
+            "分块上传除最后一块外每块至少 5 MiB，UploadPartCopy 无法把多个对象合并为一块，\
+             以下源对象太小（只有最后一个源对象可以小于 5 MiB）:\n  {}",
+            too_small.join("\n  "),
+        );
+    }
+
+    let mut parts = Vec::new();
+    for info in non_empty {
+        if info.size <= COPY_OBJECT_MAX {
+            parts.push(PartCopy { key: &info.key, start: 0, end: info.size - 1 });
+            continue;
+        }
+        let mut start = 0;
+        while start < info.size {
+            let mut end = (start + COPY_PART_SIZE).min(info.size);
+            if info.size - end < MIN_PART_SIZE {
+                end = info.size;
+            }
+            parts.push(PartCopy { key: &info.key, start, end: end - 1 });
+            start = end;
+        }
+    }
+    if parts.len() > MAX_PARTS {
+        bail!("需要 {} 个分块，超过了分块上传的上限 {}", parts.len(), MAX_PARTS);
+    }
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(key: &str, size: u64) -> ObjectInfo {
+        ObjectInfo {
+            key: key.to_string(),
+            size,
+            etag: None,
+            storage_class: "STANDARD".to_string(),
+            last_modified: None,
+            owner: None,
+            content_type: None,
+            sse: None,
+            metadata: Default::default(),
+            version_id: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_parts() {
+        const MIB: u64 = 1024 * 1024;
+        let sources = [info("a", 10 * MIB), info("empty", 0), info("b", 1)];
+        assert_eq!(
+            plan_parts(&sources).unwrap(),
+            vec![PartCopy { key: "a", start: 0, end: 10 * MIB - 1 }, PartCopy { key: "b", start: 0, end: 0 }],
+        );
+
+        let err = plan_parts(&[info("small", MIB), info("b", 10 * MIB)]).unwrap_err();
+        assert!(err.to_string().contains("small"), "{}", err);
+        assert!(plan_parts(&[info("empty", 0)]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_compose_keeps_headers_and_aborts_when_complete_fails() {
+        use crate::mock::{mock_client, query, response};
+        use std::sync::{Arc, Mutex};
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let client = mock_client(move |req| {
+            let header = |name: &str| req.headers().get(name).unwrap_or("-").to_string();
+            let params = query(req);
+            match req.method() {
+                "HEAD" => response(
+                    200,
+                    &[("Content-Length", "1"), ("Content-Type", "text/plain"), ("Cache-Control", "no-cache"), ("x-amz-meta-build", "42")],
+                    "",
+                ),
+                "POST" if params.contains("uploads") => {
+                    requests.lock().unwrap().push(format!(
+                        "create {} {} {}",
+                        header("content-type"),
+                        header("cache-control"),
+                        header("x-amz-meta-build"),
+                    ));
+                    response(200, &[], "<InitiateMultipartUploadResult><UploadId>u1</UploadId></InitiateMultipartUploadResult>")
+                }
+                "PUT" => response(200, &[], "<CopyPartResult><ETag>\"p\"</ETag></CopyPartResult>"),
+                "POST" => response(400, &[], "<Error><Code>InvalidPart</Code></Error>"),
+                "DELETE" => {
+                    requests.lock().unwrap().push(format!("abort {}", params.contains("uploadId=u1")));
+                    response(204, &[], "")
+                }
+                _ => response(500, &[], ""),
+            }
+        })
+        .await;
+
+        let err = client.compose("c.txt", &["a.txt".to_string()]).await.unwrap_err();
+        assert!(err.to_string().contains("无法完成分块上传"), "{}", err);
+        assert_eq!(*log.lock().unwrap(), ["create text/plain no-cache 42", "abort true"]);
+    }
+
+    #[test]
+    fn test_plan_parts_splits_large_sources() {
+        // 拆分后剩下的 1 字节并入最后一块
+        let size = 10 * COPY_PART_SIZE + 1;
+        let sources = [info("big", size)];
+        let parts = plan_parts(&sources).unwrap();
+        assert_eq!(parts.len(), 10);
+        assert_eq!(parts[0], PartCopy { key: "big", start: 0, end: COPY_PART_SIZE - 1 });
+        assert_eq!(parts[9], PartCopy { key: "big", start: 9 * COPY_PART_SIZE, end: size - 1 });
+    }
+}
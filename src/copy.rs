@@ -0,0 +1,602 @@
+//! 服务端复制（CopyObject / UploadPartCopy）、移动，修改存储类型和元数据
+
+use aws_sdk_s3::primitives::DateTime;
+use aws_sdk_s3::types::{
+    CompletedMultipartUpload, CompletedPart, MetadataDirective, ServerSideEncryption, StorageClass,
+    TaggingDirective,
+};
+use aws_sdk_s3::operation::create_multipart_upload::builders::CreateMultipartUploadFluentBuilder;
+use futures::{StreamExt, TryStreamExt};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::{head_info, is_not_found, ObjectInfo, OssClient};
+
+/// 单次 CopyObject 能复制的最大对象大小（5 GiB），更大的对象使用分块复制
+pub(crate) const COPY_OBJECT_MAX: u64 = 5 * 1024 * 1024 * 1024;
+/// 分块复制时每块的大小
+pub(crate) const COPY_PART_SIZE: u64 = 512 * 1024 * 1024;
+
+/// 对象的 HTTP 头和自定义元数据，使用 REPLACE 复制时整体替换
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ObjectHeaders {
+    content_type: Option<String>,
+    cache_control: Option<String>,
+    content_disposition: Option<String>,
+    content_encoding: Option<String>,
+    content_language: Option<String>,
+    metadata: HashMap<String, String>,
+}
+
+impl ObjectHeaders {
+    /// 把 HTTP 头和自定义元数据设置到新建的分块上传
+    pub(crate) fn apply_create(self, request: CreateMultipartUploadFluentBuilder) -> CreateMultipartUploadFluentBuilder {
+        request
+            .set_content_type(self.content_type)
+            .set_cache_control(self.cache_control)
+            .set_content_disposition(self.content_disposition)
+            .set_content_encoding(self.content_encoding)
+            .set_content_language(self.content_language)
+            .set_metadata(Some(self.metadata))
+    }
+}
+
+/// [`OssClient::copy_with_options`] 的选项，未指定的字段与 CopyObject 的默认行为一致：
+/// 复制源对象的元数据和标签，使用默认的存储类型
+#[derive(Debug, Clone, Default)]
+pub struct CopyOptions {
+    /// 源对象所在的 bucket，None 时为当前 bucket
+    pub source_bucket: Option<String>,
+    /// 复制源对象的指定版本，None 时为最新版本
+    pub source_version_id: Option<String>,
+    /// 用它替换目标的自定义元数据（REPLACE），None 时复制源对象的元数据
+    pub metadata: Option<HashMap<String, String>>,
+    /// 目标的 Content-Type；指定时同样使用 REPLACE，没有替换的元数据和 HTTP 头从源对象带过去
+    pub content_type: Option<String>,
+    /// 目标的存储类型，如 STANDARD_IA
+    pub storage_class: Option<String>,
+    /// 用它替换目标的标签，None 时复制源对象的标签
+    pub tags: Option<BTreeMap<String, String>>,
+}
+
+/// 复制结果：目标对象的新 ETag、修改时间和版本 ID（bucket 开启版本控制时）
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyResult {
+    pub etag: Option<String>,
+    pub last_modified: Option<DateTime>,
+    pub version_id: Option<String>,
+}
+
+/// [`OssClient::copy_sized`] 对目标的设置，默认保留源对象的元数据和标签
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct CopySpec<'a> {
+    /// None 时使用默认存储类型
+    pub storage_class: Option<&'a str>,
+    /// None 时保留源对象的元数据，否则用它替换（REPLACE）
+    pub headers: Option<&'a ObjectHeaders>,
+    /// 源对象的版本
+    pub version_id: Option<&'a str>,
+    /// None 时保留源对象的标签，否则用它替换
+    pub tags: Option<&'a BTreeMap<String, String>>,
+}
+
+/// 要修改的元数据，未指定的字段保持不变
+#[derive(Debug, Clone, Default)]
+pub struct MetadataUpdate {
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    /// 添加或覆盖的自定义元数据（x-amz-meta-*），其他已有的元数据保留
+    pub metadata: Vec<(String, String)>,
+}
+
+impl MetadataUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.content_type.is_none() && self.cache_control.is_none() && self.metadata.is_empty()
+    }
+
+    fn apply(&self, headers: &mut ObjectHeaders) {
+        if let Some(content_type) = &self.content_type {
+            headers.content_type = Some(content_type.clone());
+        }
+        if let Some(cache_control) = &self.cache_control {
+            headers.cache_control = Some(cache_control.clone());
+        }
+        for (key, value) in &self.metadata {
+            headers.metadata.insert(key.to_ascii_lowercase(), value.clone());
+        }
+    }
+}
+
+/// 解析 `key=value` 形式的自定义元数据，key 只能包含字母、数字、`-` 和 `_`
+pub fn parse_metadata(s: &str) -> Result<(String, String)> {
+    let (key, value) = s.split_once('=').with_context(|| format!("无效的元数据 {}，格式应为 key=value", s))?;
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        bail!("无效的元数据 key {}，只能包含字母、数字、- 和 _", key);
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+impl OssClient {
+    /// 在 bucket 内服务端复制对象，不经过本地
+    /// 超过 5 GiB 的对象自动使用 UploadPartCopy 分块复制
+    pub async fn copy(&self, src: &str, dst: &str) -> Result<()> {
+        self.copy_to(src, self, dst).await
+    }
+
+    /// 服务端复制对象到 `to` 所在的 bucket（可以是另一个 bucket），保留元数据
+    pub async fn copy_to(&self, src: &str, to: &OssClient, dst: &str) -> Result<()> {
+        let (src, dst) = (&self.object_key(src)?, &to.object_key(dst)?);
+        let info = self.head(src, None).await?;
+        self.copy_sized(&info, to, dst, CopySpec::default()).await?;
+        Ok(())
+    }
+
+    /// 服务端复制 `src` 到当前 bucket 的 `dst`，可以指定源 bucket 和版本、替换元数据、存储类型和标签
+    /// 超过 5 GiB 的对象同样自动使用分块复制
+    pub async fn copy_with_options(&self, src: &str, dst: &str, options: &CopyOptions) -> Result<CopyResult> {
+        let (src, dst) = (&self.object_key(src)?, &self.object_key(dst)?);
+        let other_bucket = options.source_bucket.as_deref().map(|bucket| self.with_bucket(bucket));
+        let source = other_bucket.as_ref().unwrap_or(self);
+        let version_id = options.source_version_id.as_deref();
+
+        let (info, headers) = if options.metadata.is_some() || options.content_type.is_some() {
+            let (info, mut headers) = source.head_headers(src, version_id).await?;
+            if let Some(metadata) = &options.metadata {
+                headers.metadata = metadata.clone();
+            }
+            if let Some(content_type) = &options.content_type {
+                headers.content_type = Some(content_type.clone());
+            }
+            (info, Some(headers))
+        } else {
+            (source.head(src, version_id).await?, None)
+        };
+        let spec = CopySpec {
+            storage_class: options.storage_class.as_deref(),
+            headers: headers.as_ref(),
+            version_id,
+            tags: options.tags.as_ref(),
+        };
+        source.copy_sized(&info, self, dst, spec).await
+    }
+
+    /// 移动对象：先复制，确认目标存在且大小一致后再删除源对象
+    /// 复制成功但删除源对象失败时返回的错误会说明对象同时存在于两处
+    pub async fn move_object(&self, src: &str, dst: &str) -> Result<()> {
+        let (src, dst) = (&self.object_key(src)?, &self.object_key(dst)?);
+        self.copy_verified(src, dst)
+            .await
+            .with_context(|| format!("未删除源对象 {}", src))?;
+        self.client
+            .delete_object()
+            .bucket(&self.config.bucket)
+            .key(src)
+            .send()
+            .await
+            .with_context(|| format!("已复制到 {}，但删除源对象 {} 失败，对象目前同时存在于两处", dst, src))?;
+        Ok(())
+    }
+
+    /// 复制对象，并确认目标存在且大小与源对象一致
+    pub async fn copy_verified(&self, src: &str, dst: &str) -> Result<()> {
+        let (src, dst) = (&self.object_key(src)?, &self.object_key(dst)?);
+        let source = self.head(src, None).await?;
+        self.copy_sized(&source, self, dst, CopySpec::default()).await?;
+
+        let target = self.head_if_exists(dst)
+            .await?
+            .with_context(|| format!("复制 {} 后目标 {} 不存在", src, dst))?;
+        if target.size != source.size {
+            bail!(
+                "复制后目标 {} 大小 {} 与源对象 {} 的大小 {} 不一致",
+                dst, target.size, src, source.size,
+            );
+        }
+        Ok(())
+    }
+
+    /// 原地修改对象的存储类型（复制到自身），保留元数据、Content-Type、加密方式、标签和 ACL
+    /// 完成后通过 HEAD 确认新的存储类型已生效
+    pub async fn set_storage_class(&self, key: &str, class: &str) -> Result<()> {
+        let key = &self.object_key(key)?;
+        let info = self.head(key, None).await?;
+        // 复制会把 ACL 重置为 private，先记下来再恢复；不支持 ACL 的 bucket 返回 None
+        let acl = self.acl_policy(key).await?;
+        let spec = CopySpec { storage_class: Some(class), ..Default::default() };
+        self.copy_sized(&info, self, key, spec).await?;
+        if let Some(acl) = acl {
+            self.put_acl_policy(key, acl).await?;
+        }
+
+        let updated = self.head(key, None).await?;
+        if !updated.storage_class.eq_ignore_ascii_case(class) {
+            bail!("{} 的存储类型仍为 {}，未能修改为 {}", key, updated.storage_class, class);
+        }
+        Ok(())
+    }
+
+    /// 修改对象的 Content-Type、Cache-Control 和自定义元数据（使用 REPLACE 复制到自身）
+    /// 未指定的 HTTP 头、元数据、存储类型、标签和 ACL 保持不变，内容不变。
+    /// 修改后与原来完全相同时不做复制，返回 `false`
+    pub async fn set_metadata(&self, key: &str, update: &MetadataUpdate) -> Result<bool> {
+        let key = &self.object_key(key)?;
+        let (info, mut headers) = self.head_headers(key, None).await?;
+        let original = headers.clone();
+        update.apply(&mut headers);
+        if headers == original {
+            return Ok(false);
+        }
+
+        let acl = self.acl_policy(key).await?;
+        let spec = CopySpec { storage_class: Some(&info.storage_class), headers: Some(&headers), ..Default::default() };
+        self.copy_sized(&info, self, key, spec).await?;
+        if let Some(acl) = acl {
+            self.put_acl_policy(key, acl).await?;
+        }
+        Ok(true)
+    }
+
+    /// HEAD 对象，同时返回复制时需要保留的 HTTP 头
+    pub(crate) async fn head_headers(&self, key: &str, version_id: Option<&str>) -> Result<(ObjectInfo, ObjectHeaders)> {
+        let key = &self.object_key(key)?;
+        let resp = match self.client
+            .head_object()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .set_version_id(version_id.map(str::to_string))
+            .send()
+            .await
+        {
+            Ok(resp) => resp,
+            Err(err) if is_not_found(&err) => return Err(OssError::not_found(key)),
+            Err(err) => return Err(err).with_context(|| format!("无法获取对象信息: {}", key)),
+        };
+        let headers = ObjectHeaders {
+            content_type: resp.content_type().map(str::to_string),
+            cache_control: resp.cache_control().map(str::to_string),
+            content_disposition: resp.content_disposition().map(str::to_string),
+            content_encoding: resp.content_encoding().map(str::to_string),
+            content_language: resp.content_language().map(str::to_string),
+            metadata: resp.metadata().cloned().unwrap_or_default(),
+        };
+        Ok((head_info(key, &resp), headers))
+    }
+
+    /// 复制已知大小的源对象到 `to` 所在 bucket 的 `dst`，按 `spec` 设置目标的存储类型、元数据和标签。
+    /// 分块复制时显式复制元数据和标签，与 CopyObject 的行为保持一致
+    pub(crate) async fn copy_sized(
+        &self,
+        source: &ObjectInfo,
+        to: &OssClient,
+        dst: &str,
+        spec: CopySpec<'_>,
+    ) -> Result<CopyResult> {
+        let src = source.key.as_str();
+        if source.size > COPY_OBJECT_MAX {
+            return self.copy_multipart(source, to, dst, spec).await;
+        }
+
+        let mut request = self.client
+            .copy_object()
+            .bucket(&to.config.bucket)
+            .key(dst)
+            .copy_source(self.copy_source(src, spec.version_id))
+            .set_storage_class(spec.storage_class.map(StorageClass::from))
+            .set_server_side_encryption(source.sse.as_deref().map(ServerSideEncryption::from));
+        if let Some(tags) = spec.tags {
+            request = request.tagging_directive(TaggingDirective::Replace).tagging(encode_tagging(tags));
+        }
+        if let Some(headers) = spec.headers {
+            request = request
+                .metadata_directive(MetadataDirective::Replace)
+                .set_content_type(headers.content_type.clone())
+                .set_cache_control(headers.cache_control.clone())
+                .set_content_disposition(headers.content_disposition.clone())
+                .set_content_encoding(headers.content_encoding.clone())
+                .set_content_language(headers.content_language.clone())
+                .set_metadata(Some(headers.metadata.clone()));
+        } else if src == dst {
+            // 复制到自身必须修改点什么，保留原有的元数据
+            request = request.metadata_directive(MetadataDirective::Copy);
+        }
+        let resp = request
+            .send()
+            .await
+            .with_context(|| format!("无法复制 {} 到 {}", src, dst))?;
+        let result = resp.copy_object_result();
+        Ok(CopyResult {
+            etag: result.and_then(|r| r.e_tag()).map(str::to_string),
+            last_modified: result.and_then(|r| r.last_modified()).cloned(),
+            version_id: resp.version_id().map(str::to_string),
+        })
+    }
+
+    async fn copy_multipart(
+        &self,
+        source: &ObjectInfo,
+        to: &OssClient,
+        dst: &str,
+        spec: CopySpec<'_>,
+    ) -> Result<CopyResult> {
+        let (src, size) = (source.key.as_str(), source.size);
+        // 与 CopyObject 的 COPY 一样保留所有 HTTP 头，ObjectInfo 里只有 Content-Type 和元数据
+        let headers = match spec.headers {
+            Some(headers) => headers.clone(),
+            None => self.head_headers(src, spec.version_id).await?.1,
+        };
+        let request = self.client.create_multipart_upload().bucket(&to.config.bucket).key(dst);
+        let upload_id = headers
+            .apply_create(request)
+            .set_storage_class(spec.storage_class.map(StorageClass::from))
+            .set_server_side_encryption(source.sse.as_deref().map(ServerSideEncryption::from))
+            .send()
+            .await
+            .with_context(|| format!("无法创建分块复制: {}", dst))?
+            .upload_id()
+            .context("无法获取 upload id")?
+            .to_string();
+
+        let source = self.copy_source(src, spec.version_id);
+        let parts = futures::stream::iter((0..size).step_by(COPY_PART_SIZE as usize).enumerate())
+            .map(|(index, start)| {
+                let end = (start + COPY_PART_SIZE).min(size) - 1;
+                let part_number = index as i32 + 1;
+                let (source, upload_id) = (&source, &upload_id);
+                async move {
+                    let resp = self.client
+                        .upload_part_copy()
+                        .bucket(&to.config.bucket)
+                        .key(dst)
+                        .upload_id(upload_id)
+                        .part_number(part_number)
+                        .copy_source(source)
+                        .copy_source_range(format!("bytes={}-{}", start, end))
+                        .send()
+                        .await
+                        .with_context(|| format!("无法复制 {} 的第 {} 块", src, part_number))?;
+                    let etag = resp.copy_part_result().and_then(|r| r.e_tag()).unwrap_or_default();
+                    Ok::<_, OssError>(CompletedPart::builder().part_number(part_number).e_tag(etag).build())
+                }
+            })
+            .buffered(self.workers)
+            .try_collect::<Vec<_>>()
+            .await;
+
+        let parts = match parts {
+            Ok(parts) => parts,
+            Err(err) => {
+                to.abort_quietly(dst, &upload_id).await;
+                return Err(err);
+            }
+        };
+
+        let resp = self.client
+            .complete_multipart_upload()
+            .bucket(&to.config.bucket)
+            .key(dst)
+            .upload_id(&upload_id)
+            .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+            .send()
+            .await
+            .with_context(|| format!("无法完成分块复制: {}", dst));
+        let resp = match resp {
+            Ok(resp) => resp,
+            Err(err) => {
+                to.abort_quietly(dst, &upload_id).await;
+                return Err(err);
+            }
+        };
+        let version_id = resp.version_id().map(str::to_string);
+
+        match spec.tags {
+            Some(tags) if !tags.is_empty() => to.set_tags(dst, version_id.as_deref(), tags).await?,
+            Some(_) => {}
+            // 不支持标签的服务商查询会失败，此时也没有标签需要复制
+            None => {
+                if let Ok(tags) = self.get_tags(src, spec.version_id).await {
+                    if !tags.is_empty() {
+                        to.set_tags(dst, version_id.as_deref(), &tags).await?;
+                    }
+                }
+            }
+        }
+        // CompleteMultipartUpload 不返回修改时间
+        let target = to.head(dst, version_id.as_deref()).await?;
+        Ok(CopyResult { etag: target.etag, last_modified: target.last_modified, version_id })
+    }
+
+    /// 尽量清理未完成的分块上传，失败也不影响返回原始错误
+    pub(crate) async fn abort_quietly(&self, key: &str, upload_id: &str) {
+        let _ = self.client
+            .abort_multipart_upload()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await;
+    }
+
+    /// CopySource 头：`bucket/key[?versionId=...]`，key 按路径段做 URL 编码
+    pub(crate) fn copy_source(&self, key: &str, version_id: Option<&str>) -> String {
+        let source = format!("{}/{}", self.config.bucket, encode_key(key));
+        match version_id {
+            Some(version_id) => format!("{}?versionId={}", source, urlencoding::encode(version_id)),
+            None => source,
+        }
+    }
+}
+
+/// x-amz-tagging 头：URL 查询字符串形式的 `key=value&...`
+pub(crate) fn encode_tagging(tags: &BTreeMap<String, String>) -> String {
+    tags.iter()
+        .map(|(key, value)| format!("{}={}", urlencoding::encode(key), urlencoding::encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// 按 `/` 分段对 key 做 URL 编码，保留分隔符
+fn encode_key(key: &str) -> String {
+    key.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_update_keeps_unspecified_fields() {
+        let mut headers = ObjectHeaders {
+            content_type: Some("application/octet-stream".to_string()),
+            cache_control: Some("no-cache".to_string()),
+            content_encoding: Some("gzip".to_string()),
+            metadata: HashMap::from([("owner".to_string(), "ci".to_string())]),
+            ..Default::default()
+        };
+        let update = MetadataUpdate {
+            content_type: Some("text/html".to_string()),
+            metadata: vec![("Build".to_string(), "42".to_string())],
+            ..Default::default()
+        };
+        update.apply(&mut headers);
+        assert_eq!(headers.content_type.as_deref(), Some("text/html"));
+        assert_eq!(headers.cache_control.as_deref(), Some("no-cache"));
+        assert_eq!(headers.content_encoding.as_deref(), Some("gzip"));
+        assert_eq!(headers.metadata["owner"], "ci");
+        assert_eq!(headers.metadata["build"], "42");
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        assert_eq!(parse_metadata("build=42").unwrap(), ("build".to_string(), "42".to_string()));
+        assert_eq!(parse_metadata("note=a=b").unwrap(), ("note".to_string(), "a=b".to_string()));
+        assert!(parse_metadata("build").is_err());
+        assert!(parse_metadata("bad key=1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_copy_with_options() {
+        use crate::mock::{mock_client, response};
+        use std::sync::{Arc, Mutex};
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let client = mock_client(move |req| {
+            let header = |name: &str| req.headers().get(name).unwrap_or("").to_string();
+            if req.method() == "HEAD" {
+                return response(
+                    200,
+                    &[("Content-Length", "5"), ("Content-Type", "text/plain"), ("Cache-Control", "no-cache")],
+                    "",
+                );
+            }
+            log.lock().unwrap().push((
+                req.uri().to_string(),
+                header("x-amz-copy-source"),
+                header("x-amz-metadata-directive"),
+                header("content-type"),
+                header("cache-control"),
+                header("x-amz-meta-build"),
+                header("x-amz-tagging-directive"),
+                header("x-amz-tagging"),
+                header("x-amz-storage-class"),
+            ));
+            response(
+                200,
+                &[("x-amz-version-id", "v2")],
+                "<CopyObjectResult><ETag>\"e2\"</ETag><LastModified>2024-01-02T03:04:05.000Z</LastModified></CopyObjectResult>",
+            )
+        })
+        .await;
+
+        let options = CopyOptions {
+            source_bucket: Some("other".to_string()),
+            source_version_id: Some("v 1".to_string()),
+            metadata: Some(HashMap::from([("build".to_string(), "42".to_string())])),
+            content_type: Some("text/html".to_string()),
+            storage_class: Some("STANDARD_IA".to_string()),
+            tags: Some(BTreeMap::from([("env".to_string(), "a b".to_string())])),
+        };
+        let result = client.copy_with_options("目录/a b.txt", "dst.txt", &options).await.unwrap();
+        assert_eq!(result.etag.as_deref(), Some("\"e2\""));
+        assert_eq!(result.last_modified, Some(DateTime::from_secs(1704164645)));
+        assert_eq!(result.version_id.as_deref(), Some("v2"));
+
+        // 省略选项时与 CopyObject 默认行为一致：复制元数据和标签
+        client.copy_with_options("a.txt", "b.txt", &CopyOptions::default()).await.unwrap();
+
+        let requests = requests.lock().unwrap().clone();
+        let (uri, source, directive, content_type, cache_control, build, tagging_directive, tagging, class) =
+            &requests[0];
+        assert!(uri.starts_with("https://my-bucket.s3.example.com/dst.txt"), "{}", uri);
+        assert_eq!(source, "other/%E7%9B%AE%E5%BD%95/a%20b.txt?versionId=v%201");
+        assert_eq!((directive.as_str(), content_type.as_str()), ("REPLACE", "text/html"));
+        assert_eq!((cache_control.as_str(), build.as_str()), ("no-cache", "42"));
+        assert_eq!((tagging_directive.as_str(), tagging.as_str()), ("REPLACE", "env=a%20b"));
+        assert_eq!(class, "STANDARD_IA");
+
+        let (_, source, directive, _, _, _, tagging_directive, _, class) = &requests[1];
+        assert_eq!(source, "my-bucket/a.txt");
+        assert_eq!((directive.as_str(), tagging_directive.as_str(), class.as_str()), ("", "", ""));
+    }
+
+    #[tokio::test]
+    async fn test_multipart_copy_keeps_headers_and_aborts_when_complete_fails() {
+        use crate::mock::{mock_client, query, response};
+        use std::sync::{Arc, Mutex};
+
+        let aborted = Arc::new(Mutex::new(Vec::new()));
+        let log = aborted.clone();
+        let created = Arc::new(Mutex::new(String::new()));
+        let create_log = created.clone();
+        let size = (COPY_OBJECT_MAX + 1).to_string();
+        let client = mock_client(move |req| {
+            let params = query(req);
+            let header = |name: &str| req.headers().get(name).unwrap_or("-").to_string();
+            match req.method() {
+                "HEAD" => response(
+                    200,
+                    &[
+                        ("Content-Length", size.as_str()),
+                        ("Cache-Control", "no-cache"),
+                        ("Content-Disposition", "inline"),
+                        ("Content-Encoding", "gzip"),
+                        ("Content-Language", "zh"),
+                    ],
+                    "",
+                ),
+                "POST" if params == "uploads" => {
+                    *create_log.lock().unwrap() = ["cache-control", "content-disposition", "content-encoding", "content-language"]
+                        .map(header)
+                        .join(" ");
+                    response(200, &[], "<InitiateMultipartUploadResult><UploadId>u1</UploadId></InitiateMultipartUploadResult>")
+                }
+                "PUT" => response(200, &[], "<CopyPartResult><ETag>\"p\"</ETag></CopyPartResult>"),
+                "POST" => response(400, &[], "<Error><Code>InvalidPart</Code></Error>"),
+                "DELETE" => {
+                    log.lock().unwrap().push(params.to_string());
+                    response(204, &[], "")
+                }
+                _ => response(500, &[], ""),
+            }
+        })
+        .await;
+
+        let err = client.copy_with_options("big.bin", "dst.bin", &CopyOptions::default()).await.unwrap_err();
+        assert!(err.to_string().contains("无法完成分块复制"), "{}", err);
+        // 没有指定 HTTP 头时与 CopyObject 一样保留源对象的所有 HTTP 头
+        assert_eq!(*created.lock().unwrap(), "no-cache inline gzip zh");
+        let aborted = aborted.lock().unwrap();
+        assert_eq!(aborted.len(), 1);
+        assert!(aborted[0].contains("uploadId=u1"), "{}", aborted[0]);
+    }
+
+    #[test]
+    fn test_encode_key() {
+        assert_eq!(encode_key("a/b c/d+e.txt"), "a/b%20c/d%2Be.txt");
+        assert_eq!(encode_key("目录/文件"), "%E7%9B%AE%E5%BD%95/%E6%96%87%E4%BB%B6");
+        assert_eq!(encode_key("dir//x"), "dir//x");
+    }
+}
@@ -0,0 +1,184 @@
+//! 服务端批量复制前缀（可以复制到另一个 bucket），限流、网络错误等临时失败的对象自动重试
+
+use futures::StreamExt;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::copy::CopySpec;
+use crate::error::{bail, Result};
+use crate::{rename_target, KeyFilter, OssClient};
+
+/// 每个对象最多尝试的次数
+const COPY_ATTEMPTS: u32 = 3;
+
+/// 复制失败的 key
+#[derive(Debug, Clone, Serialize)]
+pub struct CopyFailure {
+    pub key: String,
+    pub message: String,
+}
+
+/// 批量复制结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CopyReport {
+    /// (源 key, 目标 key)，dry-run 时为将要复制的对象
+    pub copied: Vec<(String, String)>,
+    /// 已复制的字节数，dry-run 时为将要复制的字节数
+    pub bytes: u64,
+    pub errors: Vec<CopyFailure>,
+}
+
+impl CopyReport {
+    /// 是否全部成功
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// 批量复制的进度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CopyProgress {
+    pub objects: usize,
+    pub total_objects: usize,
+    pub bytes: u64,
+    pub total_bytes: u64,
+}
+
+impl OssClient {
+    /// 把 `src_prefix` 下的所有对象服务端复制到 `to` 所在 bucket 的 `dst_prefix` 下，并发数为 workers
+    /// 超过 5 GiB 的对象使用分块复制，元数据保持不变；
+    /// 限流、网络错误、5xx 和 [`RetryPolicy`](crate::RetryPolicy) 认为可重试的失败最多尝试 3 次，
+    /// 没有权限、对象不存在等其他错误不重试，直接记入报告。
+    /// `dry_run` 为 true 时只返回 key 的对应关系，不实际复制。
+    /// 每处理完一个对象（成功或最终失败）回调一次 `on_progress`
+    pub async fn copy_prefix(
+        &self,
+        src_prefix: &str,
+        to: &OssClient,
+        dst_prefix: &str,
+        dry_run: bool,
+        on_progress: impl Fn(CopyProgress) + Sync,
+    ) -> Result<CopyReport> {
+        self.copy_prefix_filtered(src_prefix, to, dst_prefix, &KeyFilter::new(&[], &[])?, dry_run, on_progress).await
+    }
+
+    /// 同 [`copy_prefix`](Self::copy_prefix)，只复制 `filter` 匹配的对象（匹配 key 中 `src_prefix` 之后的部分）
+    pub async fn copy_prefix_filtered(
+        &self,
+        src_prefix: &str,
+        to: &OssClient,
+        dst_prefix: &str,
+        filter: &KeyFilter,
+        dry_run: bool,
+        on_progress: impl Fn(CopyProgress) + Sync,
+    ) -> Result<CopyReport> {
+        if src_prefix == dst_prefix && self.config.bucket == to.config.bucket {
+            bail!("源前缀和目标前缀相同: {}", src_prefix);
+        }
+        let mut objects = self.list_objects(src_prefix).await?;
+        objects.retain(|obj| filter.matches(&obj.key[src_prefix.len()..]));
+        let mut report = CopyReport::default();
+        if dry_run {
+            report.copied = objects
+                .iter()
+                .map(|obj| (obj.key.clone(), rename_target(src_prefix, dst_prefix, &obj.key)))
+                .collect();
+            report.bytes = objects.iter().map(|obj| obj.size).sum();
+            return Ok(report);
+        }
+
+        let total_objects = objects.len();
+        let total_bytes = objects.iter().map(|obj| obj.size).sum();
+        let (done, done_bytes) = (AtomicUsize::new(0), AtomicU64::new(0));
+        let results: Vec<_> = futures::stream::iter(&objects)
+            .map(|obj| {
+                let target = rename_target(src_prefix, dst_prefix, &obj.key);
+                let (done, done_bytes, on_progress) = (&done, &done_bytes, &on_progress);
+                async move {
+                    let mut attempt = 0;
+                    let result = loop {
+                        match self.copy_sized(obj, to, &target, CopySpec::default()).await {
+                            Err(e) if attempt + 1 < COPY_ATTEMPTS && self.retry_policy.should_retry(&e) => {
+                                tokio::time::sleep(self.retry_policy.delay(attempt)).await;
+                                attempt += 1;
+                            }
+                            result => break result,
+                        }
+                    };
+                    on_progress(CopyProgress {
+                        objects: done.fetch_add(1, Ordering::Relaxed) + 1,
+                        total_objects,
+                        bytes: done_bytes.fetch_add(obj.size, Ordering::Relaxed) + obj.size,
+                        total_bytes,
+                    });
+                    (obj, target, result)
+                }
+            })
+            .buffer_unordered(self.workers)
+            .collect()
+            .await;
+
+        for (obj, target, result) in results {
+            match result {
+                Ok(_) => {
+                    report.bytes += obj.size;
+                    report.copied.push((obj.key.clone(), target));
+                }
+                Err(e) => report.errors.push(CopyFailure { key: obj.key.clone(), message: e.to_string() }),
+            }
+        }
+        report.copied.sort();
+        report.errors.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{mock_builder, response};
+    use crate::RetryPolicy;
+    use aws_sdk_s3::config::retry::RetryConfig;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// 列出 a/1，复制请求的响应为 `status` 和 `code`，返回客户端和复制请求的次数
+    async fn copy_client(status: u16, code: &'static str) -> (OssClient, Arc<AtomicUsize>) {
+        let copies = Arc::new(AtomicUsize::new(0));
+        let counter = copies.clone();
+        let client = mock_builder(move |req| match req.method() {
+            "GET" => response(
+                200,
+                &[],
+                "<ListBucketResult><IsTruncated>false</IsTruncated>\
+                 <Contents><Key>a/1</Key><Size>3</Size></Contents></ListBucketResult>",
+            ),
+            _ => {
+                counter.fetch_add(1, Ordering::SeqCst);
+                response(status, &[], format!("<Error><Code>{}</Code></Error>", code))
+            }
+        })
+        .retry(RetryConfig::disabled())
+        .retry_policy(RetryPolicy::new().backoff(|_| Duration::from_millis(1)))
+        .build()
+        .await
+        .unwrap();
+        (client, copies)
+    }
+
+    #[tokio::test]
+    async fn test_copy_prefix_does_not_retry_access_denied() {
+        let (client, copies) = copy_client(403, "AccessDenied").await;
+        let report = client.copy_prefix("a/", &client, "b/", false, |_| {}).await.unwrap();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(copies.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_copy_prefix_retries_transient_errors() {
+        let (client, copies) = copy_client(500, "InternalError").await;
+        let report = client.copy_prefix("a/", &client, "b/", false, |_| {}).await.unwrap();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(copies.load(Ordering::SeqCst), COPY_ATTEMPTS as usize);
+    }
+}
@@ -0,0 +1,163 @@
+//! bucket 的跨域（CORS）规则，浏览器直传和预签名 URL 需要
+
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::CorsConfiguration;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::{is_not_implemented, OssClient};
+
+/// 一条 CORS 规则，字段与 S3 的 CORSRule 一一对应
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CorsRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_headers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expose_headers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_seconds: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CorsFile {
+    rules: Vec<CorsRule>,
+}
+
+/// 解析规则文件：`{"rules": [...]}`，与 `cors get --output json` 的输出格式相同
+pub fn parse_cors(json: &str) -> Result<Vec<CorsRule>> {
+    let file: CorsFile = serde_json::from_str(json).context("无效的 CORS 规则文件")?;
+    for (index, rule) in file.rules.iter().enumerate() {
+        rule.validate().with_context(|| format!("rules[{}] 无效", index))?;
+    }
+    Ok(file.rules)
+}
+
+/// S3 支持的跨域请求方法
+const METHODS: [&str; 5] = ["GET", "PUT", "POST", "DELETE", "HEAD"];
+
+impl CorsRule {
+    pub fn validate(&self) -> Result<()> {
+        if self.allowed_origins.is_empty() {
+            bail!("allowed_origins 不能为空");
+        }
+        if self.allowed_methods.is_empty() {
+            bail!("allowed_methods 不能为空");
+        }
+        if let Some(method) = self.allowed_methods.iter().find(|m| !METHODS.contains(&m.as_str())) {
+            bail!("allowed_methods 中的 {} 无效，只支持 {}", method, METHODS.join("/"));
+        }
+        Ok(())
+    }
+
+    fn to_sdk(&self) -> Result<aws_sdk_s3::types::CorsRule> {
+        Ok(aws_sdk_s3::types::CorsRule::builder()
+            .set_id(self.id.clone())
+            .set_allowed_origins(Some(self.allowed_origins.clone()))
+            .set_allowed_methods(Some(self.allowed_methods.clone()))
+            .set_allowed_headers(Some(self.allowed_headers.clone()))
+            .set_expose_headers(Some(self.expose_headers.clone()))
+            .set_max_age_seconds(self.max_age_seconds)
+            .build()?)
+    }
+
+    fn from_sdk(rule: &aws_sdk_s3::types::CorsRule) -> Self {
+        CorsRule {
+            id: rule.id().map(str::to_string),
+            allowed_origins: rule.allowed_origins().to_vec(),
+            allowed_methods: rule.allowed_methods().to_vec(),
+            allowed_headers: rule.allowed_headers().to_vec(),
+            expose_headers: rule.expose_headers().to_vec(),
+            max_age_seconds: rule.max_age_seconds(),
+        }
+    }
+}
+
+impl OssClient {
+    /// 查询当前 bucket 的 CORS 规则，没有配置时返回空列表
+    pub async fn get_cors(&self) -> Result<Vec<CorsRule>> {
+        match self.client.get_bucket_cors().bucket(&self.config.bucket).send().await {
+            Ok(resp) => Ok(resp.cors_rules().iter().map(CorsRule::from_sdk).collect()),
+            Err(err) if err.code() == Some("NoSuchCORSConfiguration") => Ok(Vec::new()),
+            Err(err) if is_not_implemented(&err) => Err(OssError::unsupported("当前服务商不支持 CORS 配置", err)),
+            Err(err) => Err(err).with_context(|| format!("无法查询 {} 的 CORS 规则", self.config.bucket)),
+        }
+    }
+
+    /// 用 `rules` 替换当前 bucket 的全部 CORS 规则，`rules` 为空时删除 CORS 配置
+    pub async fn set_cors(&self, rules: &[CorsRule]) -> Result<()> {
+        for (index, rule) in rules.iter().enumerate() {
+            rule.validate().with_context(|| format!("rules[{}] 无效", index))?;
+        }
+        if rules.is_empty() {
+            return self.delete_cors().await;
+        }
+
+        let configuration = CorsConfiguration::builder()
+            .set_cors_rules(Some(rules.iter().map(CorsRule::to_sdk).collect::<Result<_>>()?))
+            .build()?;
+        match self.client
+            .put_bucket_cors()
+            .bucket(&self.config.bucket)
+            .cors_configuration(configuration)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if is_not_implemented(&err) => Err(OssError::unsupported("当前服务商不支持 CORS 配置", err)),
+            Err(err) => Err(err).with_context(|| format!("无法修改 {} 的 CORS 规则", self.config.bucket)),
+        }
+    }
+
+    /// 删除当前 bucket 的全部 CORS 规则
+    pub async fn delete_cors(&self) -> Result<()> {
+        self.client
+            .delete_bucket_cors()
+            .bucket(&self.config.bucket)
+            .send()
+            .await
+            .with_context(|| format!("无法删除 {} 的 CORS 规则", self.config.bucket))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cors() {
+        let rules = parse_cors(
+            r#"{"rules": [{"allowed_origins": ["https://app.example.com"], "allowed_methods": ["GET", "PUT"],
+                "allowed_headers": ["*"], "max_age_seconds": 3000}]}"#,
+        )
+        .unwrap();
+        assert_eq!(rules[0].allowed_methods, vec!["GET", "PUT"]);
+        assert_eq!(rules[0].max_age_seconds, Some(3000));
+
+        let err = parse_cors(r#"{"rules": [{"allowed_origins": ["*"], "allowed_methods": ["PATCH"]}]}"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("PATCH"), "{:#}", err);
+        let err = parse_cors(r#"{"rules": [{"allowed_origins": ["*"], "methods": ["GET"]}]}"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("methods"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_sdk_round_trip_keeps_all_fields() {
+        let rule = CorsRule {
+            id: Some("app".to_string()),
+            allowed_origins: vec!["https://app.example.com".to_string()],
+            allowed_methods: vec!["GET".to_string(), "PUT".to_string()],
+            allowed_headers: vec!["*".to_string()],
+            expose_headers: vec!["ETag".to_string()],
+            max_age_seconds: Some(600),
+        };
+        assert_eq!(CorsRule::from_sdk(&rule.to_sdk().unwrap()), rule);
+        let json = serde_json::to_string(&serde_json::json!({ "rules": [&rule] })).unwrap();
+        assert_eq!(parse_cors(&json).unwrap(), vec![rule]);
+    }
+}
@@ -0,0 +1,447 @@
+//! 批量删除（DeleteObjects）
+
+use aws_sdk_s3::error::{DisplayErrorContext, ProvideErrorMetadata};
+use aws_sdk_s3::operation::{RequestId, RequestIdExt};
+use aws_sdk_s3::types::{Delete, ObjectIdentifier};
+use futures::TryStreamExt;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::{ListEntry, ListOptions, ObjectInfo, OssClient};
+
+/// 单次 DeleteObjects 请求最多包含的 key 数量
+pub const DELETE_BATCH_SIZE: usize = 1000;
+
+/// 删除失败的 key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeleteFailure {
+    pub key: String,
+    /// 删除指定版本时的版本 ID
+    pub version_id: Option<String>,
+    /// 服务端返回的原始错误码，如 `AccessDenied`、`NoSuchKey`，可以据此区分失败原因
+    pub code: String,
+    pub message: String,
+    /// DeleteObjects 请求的 request id 和 extended request id，同一批的 key 相同
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended_request_id: Option<String>,
+}
+
+/// 批量删除结果，部分失败时 `errors` 中逐个列出失败的 key
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeleteReport {
+    /// 已删除的 key；dry-run 时为将要删除的 key
+    pub deleted: Vec<String>,
+    pub errors: Vec<DeleteFailure>,
+    /// 被排除而保留的对象数量
+    pub skipped: usize,
+}
+
+impl DeleteReport {
+    /// 是否全部删除成功（没有任何失败的 key）
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// 合并另一批的结果
+    pub fn merge(&mut self, other: DeleteReport) {
+        self.deleted.extend(other.deleted);
+        self.errors.extend(other.errors);
+        self.skipped += other.skipped;
+    }
+}
+
+/// [`OssClient::delete_prefix`] 的选项
+#[derive(Default)]
+pub struct DeletePrefixOptions<'a> {
+    /// 返回 true 的对象不删除，计入 [`DeleteReport::skipped`]
+    pub exclude: Option<&'a (dyn Fn(&ObjectInfo) -> bool + Send + Sync)>,
+    /// 只列出将要删除的 key，不发出删除请求
+    pub dry_run: bool,
+    /// 前缀为空字符串（整个 bucket）时必须显式设置，防止误删
+    pub allow_all: bool,
+    /// 取消后不再发出新的删除请求，返回 [`OssError::Cancelled`]；已经发出的批次不会回滚
+    pub cancel: Option<CancellationToken>,
+    /// 每完成一批后以已处理（删除或失败）的 key 数量回调
+    pub on_progress: Option<&'a (dyn Fn(usize) + Send + Sync)>,
+}
+
+impl DeletePrefixOptions<'_> {
+    fn check_cancelled(&self) -> Result<()> {
+        match &self.cancel {
+            Some(token) if token.is_cancelled() => Err(OssError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 单个 key 的删除结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeleteOutcome {
+    /// 对象存在并已删除
+    Deleted,
+    /// 对象本来就不存在
+    DidNotExist,
+    /// 已发送删除，但没有 HEAD 权限（403），无法确认对象原本是否存在
+    Unknown,
+}
+
+/// 删除指定版本的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedVersion {
+    pub version_id: String,
+    /// 删除的是否为删除标记（delete marker）
+    pub delete_marker: bool,
+}
+
+impl OssClient {
+    /// 永久删除对象的指定版本（需要 bucket 开启版本控制）
+    pub async fn delete_version(&self, key: &str, version_id: &str) -> Result<DeletedVersion> {
+        let key = &self.object_key(key)?;
+        let resp = self.client
+            .delete_object()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .version_id(version_id)
+            .send()
+            .await;
+
+        let resp = match resp {
+            Ok(resp) => resp,
+            Err(err) if err.code() == Some("InvalidArgument") => {
+                bail!(
+                    "无法删除 {} 的版本 {}: {}（版本 ID 无效，或 bucket 未开启版本控制）",
+                    key,
+                    version_id,
+                    err.message().unwrap_or_default(),
+                );
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("无法删除 {} 的版本 {}", key, version_id));
+            }
+        };
+
+        Ok(DeletedVersion {
+            version_id: resp.version_id().unwrap_or(version_id).to_string(),
+            delete_marker: resp.delete_marker().unwrap_or(false),
+        })
+    }
+
+    /// 批量删除多个 key
+    /// 按每批最多 1000 个拆分成多个 DeleteObjects 请求，单个 key 或者整批失败不会影响其他批次；
+    /// 不符合 key 规则的 key 不会发送，以错误码 `InvalidKey` 记在 [`DeleteReport::errors`] 中
+    pub async fn delete_many(&self, keys: &[String]) -> Result<DeleteReport> {
+        self.delete_many_with(keys, |_| {}).await
+    }
+
+    /// 同 [`delete_many`](Self::delete_many)，每完成一批后以已处理的 key 数量回调 `on_progress`
+    pub async fn delete_many_with(
+        &self,
+        keys: &[String],
+        mut on_progress: impl FnMut(usize),
+    ) -> Result<DeleteReport> {
+        let targets: Vec<(&str, Option<&str>)> = keys.iter().map(|key| (key.as_str(), None)).collect();
+        self.delete_batched(&targets, &mut on_progress).await
+    }
+
+    /// 删除前缀下的所有对象
+    /// 边列出边删除，每凑满 1000 个 key 发出一次 DeleteObjects，不会把所有 key 读入内存。
+    /// 前缀为空字符串时除非设置了 `allow_all`，否则直接报错，不发出任何请求。
+    /// 前缀按 key 规则检查；列出的 key 是服务端已有的对象，原样删除
+    pub async fn delete_prefix(&self, prefix: &str, options: &DeletePrefixOptions<'_>) -> Result<DeleteReport> {
+        if prefix.is_empty() && !options.allow_all {
+            return Err(OssError::Config(
+                "前缀为空会删除整个 bucket 的对象，确认需要时请设置 allow_all".to_string(),
+            ));
+        }
+        let prefix = if prefix.is_empty() { String::new() } else { self.object_key(prefix)? };
+        let prefix = prefix.as_str();
+
+        let mut report = DeleteReport::default();
+        let mut batch = Vec::with_capacity(DELETE_BATCH_SIZE);
+        let mut entries = self.list_stream(prefix, &ListOptions::default());
+        loop {
+            let entry = match &options.cancel {
+                Some(token) => tokio::select! {
+                    biased;
+                    _ = token.cancelled() => return Err(OssError::Cancelled),
+                    entry = entries.try_next() => entry?,
+                },
+                None => entries.try_next().await?,
+            };
+            let Some(entry) = entry else { break };
+            let ListEntry::Object(obj) = entry else { continue };
+            if options.exclude.is_some_and(|exclude| exclude(&obj)) {
+                report.skipped += 1;
+                continue;
+            }
+            batch.push(obj.key);
+            if batch.len() == DELETE_BATCH_SIZE {
+                self.delete_prefix_batch(&mut batch, &mut report, options).await?;
+            }
+        }
+        if !batch.is_empty() {
+            self.delete_prefix_batch(&mut batch, &mut report, options).await?;
+        }
+        Ok(report)
+    }
+
+    async fn delete_prefix_batch(
+        &self,
+        batch: &mut Vec<String>,
+        report: &mut DeleteReport,
+        options: &DeletePrefixOptions<'_>,
+    ) -> Result<()> {
+        if options.dry_run {
+            report.deleted.append(batch);
+            return Ok(());
+        }
+        options.check_cancelled()?;
+        let targets: Vec<(&str, Option<&str>)> = batch.iter().map(|key| (key.as_str(), None)).collect();
+        report.merge(self.send_delete_batches(&targets, DeleteReport::default(), &mut |_| {}).await?);
+        batch.clear();
+        if let Some(on_progress) = options.on_progress {
+            on_progress(report.deleted.len() + report.errors.len());
+        }
+        Ok(())
+    }
+
+    /// 批量永久删除指定的版本，参数为 (key, version_id)
+    pub async fn delete_versions(&self, versions: &[(String, String)]) -> Result<DeleteReport> {
+        let targets: Vec<(&str, Option<&str>)> = versions
+            .iter()
+            .map(|(key, version_id)| (key.as_str(), Some(version_id.as_str())))
+            .collect();
+        self.delete_batched(&targets, &mut |_| {}).await
+    }
+
+    /// 按 key 规则检查后批量删除，不符合规则的 key 直接记为失败
+    async fn delete_batched(
+        &self,
+        targets: &[(&str, Option<&str>)],
+        on_progress: &mut dyn FnMut(usize),
+    ) -> Result<DeleteReport> {
+        let mut report = DeleteReport::default();
+        let mut keys = Vec::with_capacity(targets.len());
+        for (key, version_id) in targets {
+            match self.object_key(key) {
+                Ok(normalized) => keys.push((normalized, *version_id)),
+                Err(e) => report.errors.push(DeleteFailure {
+                    key: key.to_string(),
+                    version_id: version_id.map(|v| v.to_string()),
+                    code: "InvalidKey".to_string(),
+                    message: e.to_string(),
+                    request_id: None,
+                    extended_request_id: None,
+                }),
+            }
+        }
+        let targets: Vec<(&str, Option<&str>)> = keys.iter().map(|(key, version_id)| (key.as_str(), *version_id)).collect();
+        self.send_delete_batches(&targets, report, on_progress).await
+    }
+
+    /// 每批最多 1000 个发出 DeleteObjects，结果追加到 `report`
+    async fn send_delete_batches(
+        &self,
+        targets: &[(&str, Option<&str>)],
+        mut report: DeleteReport,
+        on_progress: &mut dyn FnMut(usize),
+    ) -> Result<DeleteReport> {
+        let mut processed = report.errors.len();
+
+        for batch in targets.chunks(DELETE_BATCH_SIZE) {
+            let objects = batch
+                .iter()
+                .map(|(key, version_id)| {
+                    ObjectIdentifier::builder()
+                        .key(*key)
+                        .set_version_id(version_id.map(|v| v.to_string()))
+                        .build()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let delete = Delete::builder().set_objects(Some(objects)).build()?;
+
+            let resp = self.client
+                .delete_objects()
+                .bucket(&self.config.bucket)
+                .delete(delete)
+                .send()
+                .await;
+
+            match resp {
+                Ok(resp) => {
+                    for obj in resp.deleted() {
+                        report.deleted.push(obj.key().unwrap_or_default().to_string());
+                    }
+                    for err in resp.errors() {
+                        report.errors.push(DeleteFailure {
+                            key: err.key().unwrap_or_default().to_string(),
+                            version_id: err.version_id().map(|v| v.to_string()),
+                            code: err.code().unwrap_or_default().to_string(),
+                            message: err.message().unwrap_or_default().to_string(),
+                            request_id: resp.request_id().map(str::to_string),
+                            extended_request_id: resp.extended_request_id().map(str::to_string),
+                        });
+                    }
+                }
+                // 整批请求失败，这一批的 key 都记为失败
+                Err(err) => {
+                    let code = err.code().unwrap_or("RequestFailed").to_string();
+                    let message = DisplayErrorContext(&err).to_string();
+                    report.errors.extend(batch.iter().map(|(key, version_id)| DeleteFailure {
+                        key: key.to_string(),
+                        version_id: version_id.map(|v| v.to_string()),
+                        code: code.clone(),
+                        message: message.clone(),
+                        request_id: err.request_id().map(str::to_string),
+                        extended_request_id: err.extended_request_id().map(str::to_string),
+                    }));
+                }
+            }
+
+            processed += batch.len();
+            on_progress(processed);
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(key: &str, code: &str) -> DeleteFailure {
+        DeleteFailure {
+            key: key.to_string(),
+            version_id: None,
+            code: code.to_string(),
+            message: "message".to_string(),
+            request_id: None,
+            extended_request_id: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_reports() {
+        let mut report = DeleteReport { deleted: vec!["a".to_string()], ..Default::default() };
+        assert!(report.is_success());
+
+        report.merge(DeleteReport {
+            deleted: vec!["b".to_string()],
+            errors: vec![failure("c", "AccessDenied")],
+            skipped: 2,
+        });
+        assert_eq!(report.deleted, vec!["a", "b"]);
+        assert_eq!(report.skipped, 2);
+        assert_eq!(report.errors.len(), 1);
+        assert!(!report.is_success());
+    }
+
+    #[tokio::test]
+    async fn test_delete_prefix() {
+        use crate::mock::{mock_client, query, response};
+        use std::sync::{Arc, Mutex};
+
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let log = bodies.clone();
+        let client = mock_client(move |req| {
+            if query(req).contains("delete") {
+                let body = String::from_utf8(req.body().bytes().unwrap().to_vec()).unwrap();
+                log.lock().unwrap().push(body);
+                return response(
+                    200,
+                    &[("x-amz-request-id", "REQ1"), ("x-amz-id-2", "HOST1")],
+                    "<DeleteResult><Deleted><Key>logs/a.txt</Key></Deleted>\
+                     <Error><Key>logs/c.txt</Key><Code>AccessDenied</Code><Message>denied</Message></Error></DeleteResult>",
+                );
+            }
+            response(
+                200,
+                &[],
+                "<ListBucketResult><IsTruncated>false</IsTruncated>\
+                 <Contents><Key>logs/a.txt</Key><Size>1</Size></Contents>\
+                 <Contents><Key>logs/b.keep</Key><Size>1</Size></Contents>\
+                 <Contents><Key>logs/c.txt</Key><Size>1</Size></Contents></ListBucketResult>",
+            )
+        })
+        .await;
+
+        let exclude = |obj: &ObjectInfo| obj.key.ends_with(".keep");
+        let options = DeletePrefixOptions { exclude: Some(&exclude), dry_run: true, ..Default::default() };
+        let report = client.delete_prefix("logs/", &options).await.unwrap();
+        assert_eq!((report.deleted, report.skipped), (vec!["logs/a.txt".to_string(), "logs/c.txt".to_string()], 1));
+        assert!(bodies.lock().unwrap().is_empty());
+
+        let progress = Mutex::new(Vec::new());
+        let on_progress = |done| progress.lock().unwrap().push(done);
+        let options = DeletePrefixOptions { exclude: Some(&exclude), on_progress: Some(&on_progress), ..Default::default() };
+        let report = client.delete_prefix("logs/", &options).await.unwrap();
+        assert_eq!(*progress.lock().unwrap(), [2]);
+        assert_eq!(report.deleted, ["logs/a.txt"]);
+        assert_eq!((report.errors[0].key.as_str(), report.errors[0].code.as_str()), ("logs/c.txt", "AccessDenied"));
+        assert_eq!(report.errors[0].request_id.as_deref(), Some("REQ1"));
+        assert_eq!(report.errors[0].extended_request_id.as_deref(), Some("HOST1"));
+        let body = bodies.lock().unwrap()[0].clone();
+        assert!(body.contains("logs/a.txt") && body.contains("logs/c.txt") && !body.contains("logs/b.keep"), "{}", body);
+
+        // 空前缀必须显式允许
+        let err = client.delete_prefix("", &DeletePrefixOptions::default()).await.unwrap_err();
+        assert!(matches!(err, OssError::Config(_)), "{:?}", err);
+        let options = DeletePrefixOptions { allow_all: true, dry_run: true, ..Default::default() };
+        assert_eq!(client.delete_prefix("", &options).await.unwrap().deleted.len(), 3);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let options = DeletePrefixOptions { cancel: Some(cancel), ..Default::default() };
+        assert!(matches!(client.delete_prefix("logs/", &options).await, Err(OssError::Cancelled)));
+        assert_eq!(bodies.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_applies_key_policy() {
+        use crate::mock::{mock_client, response};
+        use std::sync::{Arc, Mutex};
+
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let log = bodies.clone();
+        let client = mock_client(move |req| {
+            log.lock().unwrap().push(String::from_utf8(req.body().bytes().unwrap_or_default().to_vec()).unwrap());
+            response(200, &[], "<DeleteResult><Deleted><Key>ok.txt</Key></Deleted><Deleted><Key>lead.txt</Key></Deleted></DeleteResult>")
+        })
+        .await;
+
+        // 不符合规则的 key 不发送，只记为失败；开头的 / 按默认规则去掉
+        let keys = ["ok.txt", "a\nb", "/lead.txt"].map(String::from);
+        let report = client.delete_many(&keys).await.unwrap();
+        assert_eq!(report.deleted, ["ok.txt", "lead.txt"]);
+        assert_eq!((report.errors[0].key.as_str(), report.errors[0].code.as_str()), ("a\nb", "InvalidKey"));
+        let body = bodies.lock().unwrap()[0].clone();
+        assert!(body.contains("<Key>lead.txt</Key>") && !body.contains("a\nb"), "{}", body);
+
+        let versions = [("a\u{1}".to_string(), "v1".to_string())];
+        let report = client.delete_versions(&versions).await.unwrap();
+        assert_eq!((report.errors.len(), report.errors[0].version_id.as_deref()), (1, Some("v1")));
+        assert!(matches!(client.delete_version("a\u{1}", "v1").await, Err(OssError::Invalid(_))));
+        assert_eq!(bodies.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_report_json_keeps_error_codes() {
+        let report = DeleteReport {
+            deleted: vec!["a".to_string()],
+            errors: vec![failure("b", "AccessDenied"), failure("c", "NoSuchKey")],
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["deleted"][0], "a");
+        assert_eq!(json["errors"][0]["key"], "b");
+        assert_eq!(json["errors"][0]["code"], "AccessDenied");
+        assert_eq!(json["errors"][1]["code"], "NoSuchKey");
+        assert!(json["errors"][1]["version_id"].is_null());
+        assert!(json["errors"][1].get("request_id").is_none());
+    }
+}
@@ -0,0 +1,122 @@
+//! bucket 的默认服务端加密（GetBucketEncryption / PutBucketEncryption），上传时不需要再指定加密方式
+
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{
+    ServerSideEncryption, ServerSideEncryptionByDefault, ServerSideEncryptionConfiguration, ServerSideEncryptionRule,
+};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::{is_not_found, is_not_implemented, OssClient};
+
+/// bucket 的默认加密配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BucketEncryption {
+    /// AES256 或 aws:kms
+    pub algorithm: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kms_key_id: Option<String>,
+    pub bucket_key_enabled: bool,
+}
+
+/// 上传后 HEAD 得到的实际结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UploadCheck {
+    pub size: u64,
+    /// 实际使用的服务端加密，未加密时为 None
+    pub sse: Option<String>,
+    pub kms_key_id: Option<String>,
+}
+
+/// 解析加密方式：aes256 或 aws:kms（不区分大小写），KMS 密钥只能与 aws:kms 一起使用
+pub fn parse_sse(algorithm: &str, kms_key_id: Option<&str>) -> Result<ServerSideEncryption> {
+    let sse = match algorithm.to_ascii_lowercase().as_str() {
+        "aes256" => ServerSideEncryption::Aes256,
+        "aws:kms" => ServerSideEncryption::AwsKms,
+        other => bail!("无效的加密方式: {}（可选 aes256、aws:kms）", other),
+    };
+    if kms_key_id.is_some() && sse != ServerSideEncryption::AwsKms {
+        bail!("--kms-key-id 只能与 aws:kms 一起使用");
+    }
+    Ok(sse)
+}
+
+impl OssClient {
+    /// 查询当前 bucket 的默认加密，没有配置时返回 None
+    pub async fn get_encryption(&self) -> Result<Option<BucketEncryption>> {
+        let resp = match self.client.get_bucket_encryption().bucket(&self.config.bucket).send().await {
+            Ok(resp) => resp,
+            Err(err) if err.code() == Some("ServerSideEncryptionConfigurationNotFoundError") => return Ok(None),
+            Err(err) if is_not_implemented(&err) => return Err(OssError::unsupported("当前服务商不支持 bucket 默认加密", err)),
+            Err(err) => return Err(err).with_context(|| format!("无法查询 {} 的默认加密", self.config.bucket)),
+        };
+        let rule = resp.server_side_encryption_configuration().and_then(|c| c.rules().first());
+        Ok(rule.and_then(|rule| {
+            let default = rule.apply_server_side_encryption_by_default()?;
+            Some(BucketEncryption {
+                algorithm: default.sse_algorithm().as_str().to_string(),
+                kms_key_id: default.kms_master_key_id().map(str::to_string),
+                bucket_key_enabled: rule.bucket_key_enabled().unwrap_or(false),
+            })
+        }))
+    }
+
+    /// 设置当前 bucket 的默认加密，`kms_key_id` 为 None 时使用账号默认的 KMS 密钥
+    pub async fn set_encryption(&self, sse: ServerSideEncryption, kms_key_id: Option<&str>) -> Result<()> {
+        let default = ServerSideEncryptionByDefault::builder()
+            .sse_algorithm(sse)
+            .set_kms_master_key_id(kms_key_id.map(str::to_string))
+            .build()?;
+        let configuration = ServerSideEncryptionConfiguration::builder()
+            .rules(ServerSideEncryptionRule::builder().apply_server_side_encryption_by_default(default).build())
+            .build()?;
+        match self.client
+            .put_bucket_encryption()
+            .bucket(&self.config.bucket)
+            .server_side_encryption_configuration(configuration)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if is_not_implemented(&err) => Err(OssError::unsupported("当前服务商不支持 bucket 默认加密", err)),
+            Err(err) => Err(err).with_context(|| format!("无法修改 {} 的默认加密", self.config.bucket)),
+        }
+    }
+
+    /// 上传后确认远程对象的大小与本地文件一致，并返回实际使用的加密方式
+    pub async fn verify_upload(&self, path: &Path, key: &str) -> Result<UploadCheck> {
+        let local = tokio::fs::metadata(path)
+            .await
+            .with_context(|| format!("无法读取 {}", path.display()))?
+            .len();
+        let resp = match self.client.head_object().bucket(&self.config.bucket).key(key).send().await {
+            Ok(resp) => resp,
+            Err(err) if is_not_found(&err) => bail!("校验失败：上传后找不到 {}", key),
+            Err(err) => return Err(err).with_context(|| format!("无法获取 {} 的信息", key)),
+        };
+        let size = resp.content_length().unwrap_or_default().max(0) as u64;
+        if size != local {
+            bail!("校验失败：{} 的大小 {} 与本地文件大小 {} 不一致", key, size, local);
+        }
+        Ok(UploadCheck {
+            size,
+            sse: resp.server_side_encryption().map(|s| s.as_str().to_string()),
+            kms_key_id: resp.ssekms_key_id().map(str::to_string),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sse() {
+        assert_eq!(parse_sse("aes256", None).unwrap(), ServerSideEncryption::Aes256);
+        assert_eq!(parse_sse("AES256", None).unwrap(), ServerSideEncryption::Aes256);
+        assert_eq!(parse_sse("aws:kms", Some("arn:aws:kms:us-east-1:1:key/abc")).unwrap(), ServerSideEncryption::AwsKms);
+        assert!(parse_sse("aes256", Some("arn:aws:kms:us-east-1:1:key/abc")).is_err());
+        assert!(parse_sse("des", None).is_err());
+    }
+}
@@ -0,0 +1,460 @@
+//! 库的错误类型：调用方可以按原因区分对象不存在、没有权限、被限流、网络错误、本地文件错误等
+
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::operation::{RequestId, RequestIdExt};
+use std::error::Error as StdError;
+use std::fmt;
+use std::time::Duration;
+
+/// 库中所有函数的返回类型
+pub type Result<T, E = OssError> = std::result::Result<T, E>;
+
+type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// 服务端或 SDK 返回的错误，保留原始错误、错误码和 request id
+#[derive(Debug)]
+pub struct ServiceError {
+    context: String,
+    code: Option<String>,
+    message: Option<String>,
+    status: Option<u16>,
+    request_id: Option<String>,
+    extended_request_id: Option<String>,
+    source: BoxError,
+}
+
+impl ServiceError {
+    fn new(source: impl StdError + Send + Sync + 'static) -> Self {
+        Self {
+            context: String::new(),
+            code: None,
+            message: None,
+            status: None,
+            request_id: None,
+            extended_request_id: None,
+            source: Box::new(source),
+        }
+    }
+
+    /// 保留 SDK 错误中的错误码、HTTP 状态码、request id 和 extended request id
+    pub(crate) fn from_sdk<E>(context: String, err: SdkError<E, HttpResponse>) -> Self
+    where
+        E: ProvideErrorMetadata + StdError + Send + Sync + 'static,
+    {
+        Self {
+            context,
+            code: err.code().map(str::to_string),
+            message: err.message().map(str::to_string),
+            status: err.raw_response().map(|r| r.status().as_u16()),
+            request_id: err.request_id().map(str::to_string),
+            extended_request_id: err.extended_request_id().map(str::to_string),
+            ..Self::new(err)
+        }
+    }
+
+    /// 出错的操作，如 `无法上传 a.txt`
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    /// 服务端错误码，如 NoSuchBucket、AccessDenied
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// 服务端返回的说明
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// HTTP 状态码，没有收到响应时为 None
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    /// 服务端的 request id，联系服务商排查问题时需要
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// 服务端的 extended request id（x-amz-id-2），S3 排查问题时通常和 request id 一起提供
+    pub fn extended_request_id(&self) -> Option<&str> {
+        self.extended_request_id.as_deref()
+    }
+
+    /// 错误信息后附加的错误码、HTTP 状态码和 request id，都没有时为空
+    fn details(&self) -> String {
+        let mut parts = Vec::new();
+        parts.extend(self.code.clone());
+        parts.extend(self.status.map(|status| format!("HTTP {}", status)));
+        parts.extend(self.request_id.as_ref().map(|id| format!("request id {}", id)));
+        parts.extend(self.extended_request_id.as_ref().map(|id| format!("extended request id {}", id)));
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!("（{}）", parts.join("，"))
+        }
+    }
+}
+
+/// 库的错误
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum OssError {
+    /// 对象不存在
+    NotFound { key: String },
+    /// 分块上传不存在（NoSuchUpload）：已经完成、被中止或过期，需要重新开始上传
+    UploadNotFound { key: String, upload_id: String },
+    /// bucket 不存在（NoSuchBucket）
+    BucketNotFound(Box<ServiceError>),
+    /// 没有权限（AccessDenied / 403）
+    AccessDenied(Box<ServiceError>),
+    /// 被服务端限流（SlowDown / 503 / 429）
+    Throttled(Box<ServiceError>),
+    /// 网络错误或超时，没有收到服务端的响应
+    Network(Box<ServiceError>),
+    /// 其他服务端或 SDK 错误
+    Service(Box<ServiceError>),
+    /// 当前服务商不支持该功能（NotImplemented / 501 等）
+    Unsupported(Box<ServiceError>),
+    /// 本地文件读写失败
+    LocalIo { context: String, source: std::io::Error },
+    /// 配置错误，如缺少环境变量
+    Config(String),
+    /// 参数、文件内容或服务端返回的数据不符合要求
+    Invalid(String),
+    /// 后台任务异常退出
+    Internal(String),
+    /// 传输被调用方取消
+    Cancelled,
+    /// 传输没有在选项的 `timeout` 内完成（包括重试的时间）
+    TimedOut { timeout: Duration },
+}
+
+impl OssError {
+    /// 对象不存在：[`OssError::NotFound`]，或者没有单独处理的 404 / NoSuchKey 服务端错误
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            OssError::NotFound { .. } => true,
+            OssError::Service(err) => err.status == Some(404) || matches!(err.code(), Some("NoSuchKey" | "NotFound")),
+            _ => false,
+        }
+    }
+
+    /// 服务端错误的详情，本地错误时为 None
+    pub fn service_error(&self) -> Option<&ServiceError> {
+        match self {
+            OssError::BucketNotFound(err)
+            | OssError::AccessDenied(err)
+            | OssError::Throttled(err)
+            | OssError::Network(err)
+            | OssError::Unsupported(err)
+            | OssError::Service(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn not_found(key: &str) -> Self {
+        OssError::NotFound { key: key.to_string() }
+    }
+
+    /// 服务商不支持的功能：错误信息为 `message`，同时保留 SDK 错误中的错误码和 request id
+    pub(crate) fn unsupported<E>(message: &str, err: SdkError<E, HttpResponse>) -> Self
+    where
+        E: ProvideErrorMetadata + StdError + Send + Sync + 'static,
+    {
+        OssError::Unsupported(Box::new(ServiceError::from_sdk(message.to_string(), err)))
+    }
+
+    /// 在错误信息前加上出错的操作；[`OssError::NotFound`] 等已经包含 key，保持不变
+    pub(crate) fn add_context(mut self, context: String) -> Self {
+        let prefix = |inner: &mut String| {
+            *inner = if inner.is_empty() { context.clone() } else { format!("{}: {}", context, inner) };
+        };
+        match &mut self {
+            OssError::NotFound { .. } | OssError::UploadNotFound { .. } | OssError::Cancelled | OssError::TimedOut { .. } => {}
+            OssError::BucketNotFound(err)
+            | OssError::AccessDenied(err)
+            | OssError::Throttled(err)
+            | OssError::Network(err)
+            | OssError::Unsupported(err)
+            | OssError::Service(err) => prefix(&mut err.context),
+            OssError::LocalIo { context, .. } => prefix(context),
+            OssError::Config(message)
+            | OssError::Invalid(message)
+            | OssError::Internal(message) => prefix(message),
+        }
+        self
+    }
+
+    /// 服务端或 SDK 错误，按错误码和 HTTP 状态码分类
+    fn from_sdk<E>(err: SdkError<E, HttpResponse>) -> Self
+    where
+        E: ProvideErrorMetadata + StdError + Send + Sync + 'static,
+    {
+        let network = matches!(err, SdkError::DispatchFailure(_) | SdkError::TimeoutError(_));
+        let service = Box::new(ServiceError::from_sdk(String::new(), err));
+        if network {
+            return OssError::Network(service);
+        }
+        match (service.code(), service.status()) {
+            (Some("NoSuchBucket"), _) => OssError::BucketNotFound(service),
+            (Some("AccessDenied"), _) | (_, Some(403)) => OssError::AccessDenied(service),
+            (Some("SlowDown" | "Throttling" | "TooManyRequests" | "RequestLimitExceeded"), _)
+            | (_, Some(503 | 429)) => OssError::Throttled(service),
+            (Some("NotImplemented" | "NotSupported"), _) | (_, Some(501)) => OssError::Unsupported(service),
+            _ => OssError::Service(service),
+        }
+    }
+
+    /// 其他服务端错误（如事件流中的错误），没有 HTTP 响应
+    pub(crate) fn service<E>(context: String, err: E) -> Self
+    where
+        E: ProvideErrorMetadata + StdError + Send + Sync + 'static,
+    {
+        OssError::Service(Box::new(ServiceError {
+            context,
+            code: err.code().map(str::to_string),
+            message: err.message().map(str::to_string),
+            ..ServiceError::new(err)
+        }))
+    }
+}
+
+impl fmt::Display for OssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let service = |f: &mut fmt::Formatter<'_>, err: &ServiceError, label: &str| {
+            let context = if err.context.is_empty() { label } else { &err.context };
+            write!(f, "{}{}", context, err.details())
+        };
+        match self {
+            OssError::NotFound { key } => write!(f, "对象不存在: {}", key),
+            OssError::UploadNotFound { key, upload_id } => write!(f, "分块上传不存在: {} (upload id {})", key, upload_id),
+            OssError::Cancelled => f.write_str("已取消"),
+            OssError::TimedOut { timeout } => write!(f, "超时：没有在 {:?} 内完成", timeout),
+            OssError::BucketNotFound(err) => service(f, err, "bucket 不存在"),
+            OssError::AccessDenied(err) => service(f, err, "没有权限"),
+            OssError::Throttled(err) => service(f, err, "请求被服务端限流"),
+            OssError::Network(err) => service(f, err, "网络错误"),
+            OssError::Unsupported(err) => service(f, err, "当前服务商不支持该操作"),
+            OssError::Service(err) => service(f, err, "请求失败"),
+            OssError::LocalIo { context, .. } if context.is_empty() => f.write_str("本地文件读写失败"),
+            OssError::LocalIo { context, .. } => f.write_str(context),
+            OssError::Config(message)
+            | OssError::Invalid(message)
+            | OssError::Internal(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl StdError for OssError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            OssError::LocalIo { source, .. } => Some(source),
+            _ => self.service_error().map(|err| err.source.as_ref() as &(dyn StdError + 'static)),
+        }
+    }
+}
+
+impl<E> From<SdkError<E, HttpResponse>> for OssError
+where
+    E: ProvideErrorMetadata + StdError + Send + Sync + 'static,
+{
+    fn from(err: SdkError<E, HttpResponse>) -> Self {
+        OssError::from_sdk(err)
+    }
+}
+
+impl From<std::io::Error> for OssError {
+    fn from(source: std::io::Error) -> Self {
+        OssError::LocalIo { context: String::new(), source }
+    }
+}
+
+impl From<serde_json::Error> for OssError {
+    fn from(err: serde_json::Error) -> Self {
+        OssError::Invalid(err.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for OssError {
+    fn from(err: std::num::ParseIntError) -> Self {
+        OssError::Invalid(err.to_string())
+    }
+}
+
+impl From<std::num::ParseFloatError> for OssError {
+    fn from(err: std::num::ParseFloatError) -> Self {
+        OssError::Invalid(err.to_string())
+    }
+}
+
+impl From<std::path::StripPrefixError> for OssError {
+    fn from(err: std::path::StripPrefixError) -> Self {
+        OssError::Invalid(err.to_string())
+    }
+}
+
+impl From<globset::Error> for OssError {
+    fn from(err: globset::Error) -> Self {
+        OssError::Invalid(err.to_string())
+    }
+}
+
+#[cfg(feature = "watch")]
+impl From<notify::Error> for OssError {
+    fn from(err: notify::Error) -> Self {
+        OssError::LocalIo { context: String::new(), source: std::io::Error::other(err) }
+    }
+}
+
+impl From<tokio::task::JoinError> for OssError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        OssError::Internal(err.to_string())
+    }
+}
+
+impl From<tokio::sync::AcquireError> for OssError {
+    fn from(err: tokio::sync::AcquireError) -> Self {
+        OssError::Internal(err.to_string())
+    }
+}
+
+impl From<aws_sdk_s3::error::BuildError> for OssError {
+    fn from(err: aws_sdk_s3::error::BuildError) -> Self {
+        OssError::Invalid(err.to_string())
+    }
+}
+
+impl From<aws_sdk_s3::presigning::PresigningConfigError> for OssError {
+    fn from(err: aws_sdk_s3::presigning::PresigningConfigError) -> Self {
+        OssError::Invalid(err.to_string())
+    }
+}
+
+impl From<aws_sdk_s3::primitives::ByteStreamError> for OssError {
+    fn from(err: aws_sdk_s3::primitives::ByteStreamError) -> Self {
+        OssError::Network(Box::new(ServiceError::new(err)))
+    }
+}
+
+/// 给错误加上出错的操作，用法同 anyhow 的 Context
+pub(crate) trait Context<T> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T>;
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T>;
+}
+
+impl<T, E: Into<OssError>> Context<T> for std::result::Result<T, E> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T> {
+        self.map_err(|err| err.into().add_context(context.to_string()))
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T> {
+        self.map_err(|err| err.into().add_context(f().to_string()))
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T> {
+        self.ok_or_else(|| OssError::Invalid(context.to_string()))
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T> {
+        self.ok_or_else(|| OssError::Invalid(f().to_string()))
+    }
+}
+
+/// 返回 [`OssError::Invalid`]，用法同 anyhow 的 bail!
+macro_rules! bail {
+    ($($arg:tt)*) => {
+        return Err($crate::OssError::Invalid(format!($($arg)*)))
+    };
+}
+pub(crate) use bail;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::error::ErrorMetadata;
+    use aws_sdk_s3::operation::head_object::HeadObjectError;
+    use aws_sdk_s3::operation::put_object::PutObjectError;
+    use aws_sdk_s3::primitives::SdkBody;
+
+    fn service_error(status: u16, code: &str) -> SdkError<PutObjectError, HttpResponse> {
+        let mut response = HttpResponse::new(status.try_into().unwrap(), SdkBody::empty());
+        response.headers_mut().insert("x-amz-request-id", "REQ123");
+        let metadata = ErrorMetadata::builder().code(code).message("server message").build();
+        SdkError::service_error(PutObjectError::generic(metadata), response)
+    }
+
+    #[test]
+    fn test_sdk_error_mapping() {
+        let err = OssError::from(service_error(404, "NoSuchBucket"));
+        assert!(matches!(&err, OssError::BucketNotFound(e) if e.request_id() == Some("REQ123")));
+
+        let err = OssError::from(service_error(403, "AccessDenied"));
+        let OssError::AccessDenied(detail) = &err else { panic!("{:?}", err) };
+        assert_eq!(detail.code(), Some("AccessDenied"));
+        assert_eq!((detail.message(), detail.status()), (Some("server message"), Some(403)));
+        // 保留原始的 SDK 错误
+        assert!(err.source().unwrap().downcast_ref::<SdkError<PutObjectError, HttpResponse>>().is_some());
+
+        assert!(matches!(OssError::from(service_error(503, "SlowDown")), OssError::Throttled(_)));
+        assert!(matches!(OssError::from(service_error(501, "NotImplemented")), OssError::Unsupported(_)));
+
+        let err = OssError::from(service_error(404, "NoSuchKey"));
+        assert!(matches!(err, OssError::Service(_)));
+        assert!(err.is_not_found());
+
+        let response = HttpResponse::new(404.try_into().unwrap(), SdkBody::empty());
+        let err: SdkError<HeadObjectError, HttpResponse> =
+            SdkError::service_error(HeadObjectError::generic(ErrorMetadata::builder().build()), response);
+        assert!(OssError::from(err).is_not_found());
+    }
+
+    #[tokio::test]
+    async fn test_error_keeps_request_ids() {
+        use crate::mock::{mock_client, response};
+
+        let client = mock_client(|_| {
+            let body = "<Error><Code>AccessDenied</Code><Message>Access Denied</Message><RequestId>4442587FB7D0A2F9</RequestId></Error>";
+            response(403, &[("x-amz-request-id", "4442587FB7D0A2F9"), ("x-amz-id-2", "vlR7PnpV2Ce81l0PRw6jlUpck7Jo5ZsQjryTjKlc5aLWGVHPZLj5NeC6qMa0emYBDXOo6QBU0Wo=")], body)
+        })
+        .await;
+        let err = client.upload_bytes("a.txt", "data").await.unwrap_err();
+        let detail = err.service_error().unwrap();
+        assert_eq!((detail.code(), detail.status()), (Some("AccessDenied"), Some(403)));
+        assert_eq!(detail.request_id(), Some("4442587FB7D0A2F9"));
+        assert_eq!(detail.extended_request_id(), Some("vlR7PnpV2Ce81l0PRw6jlUpck7Jo5ZsQjryTjKlc5aLWGVHPZLj5NeC6qMa0emYBDXOo6QBU0Wo="));
+        let message = err.to_string();
+        assert!(message.ends_with("（AccessDenied，HTTP 403，request id 4442587FB7D0A2F9，extended request id vlR7PnpV2Ce81l0PRw6jlUpck7Jo5ZsQjryTjKlc5aLWGVHPZLj5NeC6qMa0emYBDXOo6QBU0Wo=）"), "{}", message);
+
+        // 不支持的操作同样保留 request id 和原始错误
+        let client = mock_client(|_| response(501, &[("x-amz-request-id", "REQ501")], "")).await;
+        let err = client.upload_bytes("a.txt", "data").await.unwrap_err();
+        assert!(matches!(&err, OssError::Unsupported(_)), "{:?}", err);
+        assert_eq!(err.service_error().and_then(|detail| detail.request_id()), Some("REQ501"));
+        assert!(err.to_string().contains("request id REQ501") && err.source().is_some(), "{}", err);
+    }
+
+    #[test]
+    fn test_context_keeps_variant() {
+        let err = Err::<(), _>(service_error(403, "AccessDenied")).context("无法上传 a.txt").unwrap_err();
+        assert_eq!(err.to_string(), "无法上传 a.txt（AccessDenied，HTTP 403，request id REQ123）");
+        assert!(matches!(err, OssError::AccessDenied(_)));
+
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        let err = Err::<(), _>(io).context("无法打开 a.txt").unwrap_err();
+        assert!(matches!(&err, OssError::LocalIo { source, .. } if source.kind() == std::io::ErrorKind::NotFound));
+        assert_eq!(err.to_string(), "无法打开 a.txt");
+
+        let err = OssError::NotFound { key: "a.txt".to_string() }.add_context("无法下载".to_string());
+        assert_eq!(err.to_string(), "对象不存在: a.txt");
+        let err = OssError::Invalid("格式错误".to_string()).add_context("rules[0]".to_string());
+        assert_eq!(err.to_string(), "rules[0]: 格式错误");
+    }
+}
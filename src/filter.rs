@@ -0,0 +1,127 @@
+//! 按 glob 模式过滤 key
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::{Context, Result};
+
+/// 过滤结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterVerdict {
+    /// 选中
+    Selected,
+    /// 没有匹配任何 include 模式
+    NotIncluded,
+    /// 匹配了 exclude 模式
+    Excluded,
+}
+
+/// key 过滤器，模式匹配的是相对于前缀的部分
+/// `*` 不跨越 `/`，需要匹配多级目录时使用 `**`，如 `**/manifest.json`。
+/// 未指定 include 时默认全部包含，exclude 优先于 include。
+#[derive(Debug)]
+pub struct KeyFilter {
+    include: GlobSet,
+    exclude: GlobSet,
+    exclude_patterns: Vec<String>,
+    /// 每个 exclude 模式是否匹配过 key，用于提示写错的模式
+    exclude_used: Vec<AtomicBool>,
+}
+
+impl KeyFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            include: build_glob_set(include)?,
+            exclude: build_glob_set(exclude)?,
+            exclude_patterns: exclude.to_vec(),
+            exclude_used: exclude.iter().map(|_| AtomicBool::new(false)).collect(),
+        })
+    }
+
+    /// 判断 key 是否被选中
+    pub fn check(&self, relative_key: &str) -> FilterVerdict {
+        let excluded = self.exclude.matches(relative_key);
+        if !excluded.is_empty() {
+            for index in excluded {
+                self.exclude_used[index].store(true, Ordering::Relaxed);
+            }
+            return FilterVerdict::Excluded;
+        }
+        if !self.include.is_empty() && !self.include.is_match(relative_key) {
+            return FilterVerdict::NotIncluded;
+        }
+        FilterVerdict::Selected
+    }
+
+    /// 是否选中
+    pub fn matches(&self, relative_key: &str) -> bool {
+        self.check(relative_key) == FilterVerdict::Selected
+    }
+
+    /// 到目前为止没有匹配过任何 key 的 exclude 模式
+    pub fn unused_excludes(&self) -> Vec<&str> {
+        self.exclude_patterns
+            .iter()
+            .zip(&self.exclude_used)
+            .filter(|(_, used)| !used.load(Ordering::Relaxed))
+            .map(|(pattern, _)| pattern.as_str())
+            .collect()
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .with_context(|| format!("无效的匹配模式: {}", pattern))?;
+        builder.add(glob);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(p: &[&str]) -> Vec<String> {
+        p.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_exclude() {
+        let filter = KeyFilter::new(&[], &patterns(&["**/manifest.json", "*.tmp"])).unwrap();
+        assert_eq!(filter.check("manifest.json"), FilterVerdict::Excluded);
+        assert_eq!(filter.check("v1/assets/manifest.json"), FilterVerdict::Excluded);
+        assert_eq!(filter.check("a.tmp"), FilterVerdict::Excluded);
+        assert!(filter.matches("dir/a.tmp"));
+        assert!(filter.matches("v1/app.js"));
+    }
+
+    #[test]
+    fn test_include_and_exclude() {
+        let filter = KeyFilter::new(&patterns(&["**/*.log"]), &patterns(&["keep/**"])).unwrap();
+        assert!(filter.matches("a/b.log"));
+        assert_eq!(filter.check("a/b.txt"), FilterVerdict::NotIncluded);
+        assert_eq!(filter.check("keep/b.log"), FilterVerdict::Excluded);
+    }
+
+    #[test]
+    fn test_unused_excludes() {
+        let filter = KeyFilter::new(&[], &patterns(&["*.tmp", "*.tpm"])).unwrap();
+        filter.check("a.tmp");
+        assert_eq!(filter.unused_excludes(), vec!["*.tpm"]);
+    }
+
+    #[test]
+    fn test_empty_filter_selects_everything() {
+        let filter = KeyFilter::new(&[], &[]).unwrap();
+        assert!(filter.matches("anything"));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(KeyFilter::new(&[], &patterns(&["a["])).is_err());
+    }
+}
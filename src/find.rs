@@ -0,0 +1,196 @@
+//! 按属性查找对象（类似 find 的表达式过滤）
+
+use aws_sdk_s3::primitives::DateTime;
+use globset::{Glob, GlobMatcher};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::error::{Context, OssError, Result};
+use crate::{parse_duration, parse_size, ObjectInfo};
+
+/// 比较方向：`+N` 大于，`-N` 小于，`N` 等于
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound<T> {
+    Greater(T),
+    Less(T),
+    Exactly(T),
+}
+
+/// 解析 `+N`、`-N`、`N` 形式的比较条件
+fn parse_bound<T>(s: &str, parse: impl Fn(&str) -> Result<T>) -> Result<Bound<T>> {
+    let s = s.trim();
+    Ok(match s.as_bytes().first() {
+        Some(b'+') => Bound::Greater(parse(&s[1..])?),
+        Some(b'-') => Bound::Less(parse(&s[1..])?),
+        _ => Bound::Exactly(parse(s)?),
+    })
+}
+
+/// 大小条件，如 `+100M`（大于 100 MiB）、`-1K`（小于 1 KiB）、`0`（等于 0）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeTest(pub Bound<u64>);
+
+impl FromStr for SizeTest {
+    type Err = OssError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_bound(s, parse_size).map(SizeTest)
+    }
+}
+
+impl SizeTest {
+    pub fn matches(&self, size: u64) -> bool {
+        match self.0 {
+            Bound::Greater(n) => size > n,
+            Bound::Less(n) => size < n,
+            Bound::Exactly(n) => size == n,
+        }
+    }
+}
+
+/// 修改时间条件（按距今的时长），如 `-7d`（7 天内修改过）、`+30d`（30 天前修改）、`1d`（1 到 2 天前修改）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgeTest(pub Bound<Duration>);
+
+impl FromStr for AgeTest {
+    type Err = OssError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        parse_bound(s, parse_duration).map(AgeTest)
+    }
+}
+
+impl AgeTest {
+    /// `age` 为对象距今的时长
+    pub fn matches(&self, age: Duration) -> bool {
+        match self.0 {
+            Bound::Greater(d) => age > d,
+            Bound::Less(d) => age < d,
+            // 与 find -mtime N 一样，落在 [N, 2N) 区间内
+            Bound::Exactly(d) => age >= d && age < d * 2,
+        }
+    }
+}
+
+/// 查找条件，所有条件同时满足才算匹配
+#[derive(Debug, Clone, Default)]
+pub struct FindFilter {
+    name: Option<GlobMatcher>,
+    sizes: Vec<SizeTest>,
+    ages: Vec<AgeTest>,
+    storage_class: Option<String>,
+}
+
+impl FindFilter {
+    /// 文件名（key 的最后一段）匹配 glob，如 `*.log`
+    pub fn name(mut self, pattern: &str) -> Result<Self> {
+        let glob = Glob::new(pattern).with_context(|| format!("无效的匹配模式: {}", pattern))?;
+        self.name = Some(glob.compile_matcher());
+        Ok(self)
+    }
+
+    /// 大小条件，可以指定多个
+    pub fn size(mut self, test: SizeTest) -> Self {
+        self.sizes.push(test);
+        self
+    }
+
+    /// 修改时间条件，可以指定多个
+    pub fn age(mut self, test: AgeTest) -> Self {
+        self.ages.push(test);
+        self
+    }
+
+    /// 存储类型（不区分大小写），如 GLACIER
+    pub fn storage_class(mut self, class: &str) -> Self {
+        self.storage_class = Some(class.to_string());
+        self
+    }
+
+    pub fn matches(&self, obj: &ObjectInfo) -> bool {
+        self.matches_at(obj, DateTime::from(SystemTime::now()))
+    }
+
+    fn matches_at(&self, obj: &ObjectInfo, now: DateTime) -> bool {
+        if let Some(name) = &self.name {
+            let file_name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
+            if !name.is_match(file_name) {
+                return false;
+            }
+        }
+        if let Some(class) = &self.storage_class {
+            if !obj.storage_class.eq_ignore_ascii_case(class) {
+                return false;
+            }
+        }
+        if !self.sizes.iter().all(|test| test.matches(obj.size)) {
+            return false;
+        }
+        if self.ages.is_empty() {
+            return true;
+        }
+        let Some(modified) = &obj.last_modified else {
+            return false;
+        };
+        let age = Duration::from_secs((now.secs() - modified.secs()).max(0) as u64);
+        self.ages.iter().all(|test| test.matches(age))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(key: &str, size: u64, secs: i64, class: &str) -> ObjectInfo {
+        ObjectInfo {
+            key: key.to_string(),
+            size,
+            etag: None,
+            storage_class: class.to_string(),
+            last_modified: Some(DateTime::from_secs(secs)),
+            owner: None,
+            content_type: None,
+            sse: None,
+            metadata: Default::default(),
+            version_id: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_tests() {
+        assert_eq!("+100M".parse::<SizeTest>().unwrap(), SizeTest(Bound::Greater(100 << 20)));
+        assert_eq!("-1K".parse::<SizeTest>().unwrap(), SizeTest(Bound::Less(1024)));
+        assert_eq!("0".parse::<SizeTest>().unwrap(), SizeTest(Bound::Exactly(0)));
+        assert_eq!("-7d".parse::<AgeTest>().unwrap(), AgeTest(Bound::Less(Duration::from_secs(7 * 86400))));
+        assert!("+abc".parse::<SizeTest>().is_err());
+        assert!("+7x".parse::<AgeTest>().is_err());
+    }
+
+    #[test]
+    fn test_filters_are_combined() {
+        let day = 86400;
+        let now = DateTime::from_secs(100 * day);
+        let filter = FindFilter::default()
+            .name("*.log").unwrap()
+            .size("+1K".parse().unwrap())
+            .age("-7d".parse().unwrap())
+            .storage_class("glacier");
+
+        assert!(filter.matches_at(&object("logs/a/app.log", 2048, 99 * day, "GLACIER"), now));
+        assert!(!filter.matches_at(&object("logs/a/app.txt", 2048, 99 * day, "GLACIER"), now));
+        assert!(!filter.matches_at(&object("logs/a/app.log", 100, 99 * day, "GLACIER"), now));
+        assert!(!filter.matches_at(&object("logs/a/app.log", 2048, 80 * day, "GLACIER"), now));
+        assert!(!filter.matches_at(&object("logs/a/app.log", 2048, 99 * day, "STANDARD"), now));
+        // * 只匹配文件名，不匹配目录
+        assert!(!filter.matches_at(&object("logs/x.log/app", 2048, 99 * day, "GLACIER"), now));
+    }
+
+    #[test]
+    fn test_age_exactly() {
+        let day = Duration::from_secs(86400);
+        let test: AgeTest = "1d".parse().unwrap();
+        assert!(!test.matches(day / 2));
+        assert!(test.matches(day + day / 2));
+        assert!(!test.matches(day * 2));
+    }
+}
@@ -0,0 +1,153 @@
+//! 对象 key 的检查和规范化：开头的 `/`、连续的 `/`、`..` 段和控制字符会让对象在控制台中难以找到或访问
+
+use std::fmt;
+
+use crate::error::{bail, Result};
+use crate::MAX_KEY_LEN;
+
+/// 上传、下载、删除、复制和预签名之前检查 key 的规则，用 [`OssClientBuilder::key_policy`](crate::OssClientBuilder::key_policy) 设置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// 只检查 S3 的限制：非空、不超过 1024 字节（UTF-8），key 原样使用
+    Raw,
+    /// 另外拒绝控制字符，去掉开头的一个 `/` 并记录警告，以多个 `/` 开头时拒绝
+    #[default]
+    Standard,
+    /// 在 Standard 的基础上把连续的 `/` 合并为一个，拒绝 `.` 和 `..` 段
+    Normalize,
+}
+
+impl KeyPolicy {
+    /// 按规则检查 `key`，返回实际使用的 key；不符合规则时返回 [`OssError::Invalid`](crate::OssError::Invalid)
+    pub fn apply(self, key: &str) -> Result<NormalizedKey> {
+        let mut normalized = NormalizedKey { key: key.to_string(), warnings: Vec::new() };
+        if self != KeyPolicy::Raw {
+            if let Some(c) = key.chars().find(|c| c.is_control()) {
+                bail!("key {:?} 中包含控制字符 {:?}", key, c);
+            }
+        }
+        if self == KeyPolicy::Normalize {
+            let mut collapsed = String::with_capacity(key.len());
+            for c in key.chars() {
+                if !(c == '/' && collapsed.ends_with('/')) {
+                    collapsed.push(c);
+                }
+            }
+            if collapsed != normalized.key {
+                normalized.key = collapsed;
+                normalized.warnings.push("合并了连续的 /".to_string());
+            }
+            if normalized.key.split('/').any(|segment| segment == "." || segment == "..") {
+                bail!("key {:?} 中包含 . 或 .. 段", key);
+            }
+        }
+        if self != KeyPolicy::Raw {
+            if let Some(rest) = normalized.key.strip_prefix('/') {
+                // 只去掉一个，以多个 / 开头多半是拼接出错，不猜测原意
+                if rest.starts_with('/') {
+                    bail!("key {:?} 以多个 / 开头", key);
+                }
+                normalized.key = rest.to_string();
+                normalized.warnings.push("去掉了开头的 /".to_string());
+            }
+        }
+        check_key(&normalized.key)?;
+        Ok(normalized)
+    }
+}
+
+/// 检查后实际使用的 key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedKey {
+    key: String,
+    warnings: Vec<String>,
+}
+
+impl NormalizedKey {
+    pub fn as_str(&self) -> &str {
+        &self.key
+    }
+
+    pub fn into_string(self) -> String {
+        self.key
+    }
+
+    /// 对原始 key 做了哪些修改，没有修改时为空
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+}
+
+impl AsRef<str> for NormalizedKey {
+    fn as_ref(&self) -> &str {
+        &self.key
+    }
+}
+
+impl fmt::Display for NormalizedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.key)
+    }
+}
+
+/// 按默认规则（[`KeyPolicy::Standard`]）检查 key
+pub fn validate_key(key: &str) -> Result<NormalizedKey> {
+    KeyPolicy::Standard.apply(key)
+}
+
+/// S3 的 key 不能为空，最长 1024 字节（UTF-8）
+pub(crate) fn check_key(key: &str) -> Result<()> {
+    if key.is_empty() {
+        bail!("key 不能为空");
+    }
+    if key.len() > MAX_KEY_LEN {
+        bail!("key 长度 {} 字节，超过了上限 {} 字节", key.len(), MAX_KEY_LEN);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OssError;
+
+    #[test]
+    fn test_validate_key_rules() {
+        let key = validate_key("logs/2024/a.txt").unwrap();
+        assert_eq!((key.as_str(), key.warnings().is_empty()), ("logs/2024/a.txt", true));
+
+        let key = validate_key("/logs/a.txt").unwrap();
+        assert_eq!(key.as_str(), "logs/a.txt");
+        assert_eq!(key.warnings(), ["去掉了开头的 /"]);
+        // 默认不合并中间的 /，再次检查结果不会继续修改
+        assert_eq!(validate_key("a//b").unwrap().as_str(), "a//b");
+        assert_eq!(validate_key("a/../b").unwrap().as_str(), "a/../b");
+        assert_eq!(validate_key(key.as_str()).unwrap(), validate_key("logs/a.txt").unwrap());
+
+        for key in ["", "/", "//a", "a\nb", "a\u{7f}b", &"a".repeat(MAX_KEY_LEN + 1)] {
+            assert!(matches!(validate_key(key), Err(OssError::Invalid(_))), "{:?}", key);
+        }
+        // 按 UTF-8 字节计算长度
+        assert!(validate_key(&"键".repeat(341)).is_ok());
+        assert!(validate_key(&"键".repeat(342)).is_err());
+    }
+
+    #[test]
+    fn test_key_policy_normalize_and_raw() {
+        let key = KeyPolicy::Normalize.apply("//logs//2024///a.txt").unwrap();
+        assert_eq!(key.as_str(), "logs/2024/a.txt");
+        assert_eq!(key.warnings(), ["合并了连续的 /", "去掉了开头的 /"]);
+        for key in ["a/../b", "./a", "a/.", "a/..//b"] {
+            let err = KeyPolicy::Normalize.apply(key).unwrap_err();
+            assert!(err.to_string().contains(".."), "{}", err);
+        }
+        assert_eq!(KeyPolicy::Normalize.apply("a/.hidden/..b").unwrap().as_str(), "a/.hidden/..b");
+
+        // Raw 只检查长度
+        for key in ["/a", "//a/../b", "a\tb"] {
+            let normalized = KeyPolicy::Raw.apply(key).unwrap();
+            assert_eq!((normalized.as_str(), normalized.warnings().len()), (key, 0));
+        }
+        assert!(KeyPolicy::Raw.apply("").is_err());
+    }
+}
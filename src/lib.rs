@@ -1,18 +1,172 @@
-use aws_config::BehaviorVersion;
 use aws_sdk_s3::Client;
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::operation::head_object::HeadObjectOutput;
+use aws_sdk_s3::primitives::DateTime;
 use aws_sdk_s3::types::CompletedPart;
-use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 use tokio::fs::File;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use anyhow::{Result, Context};
-use tokio::sync::{Mutex, Semaphore};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use futures::{StreamExt, TryStreamExt};
+use tracing::Instrument;
 
-/// 分块大小 10MB
-const BATCH_SIZE: usize = 10 * 1024 * 1024;
-/// 最大并发数
+mod acl;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod bucket;
+mod builder;
+mod compose;
+mod copy;
+mod copy_prefix;
+mod cors;
+mod delete;
+mod encryption;
+mod error;
+mod filter;
+mod find;
+mod key;
+mod lifecycle;
+mod list;
+mod lock;
+mod metrics;
+#[cfg(any(test, feature = "testing"))]
+mod memory;
+#[cfg(test)]
+mod mock;
+#[cfg(feature = "object-store")]
+mod object_store;
+mod policy;
+mod post_policy;
+mod presign;
+mod transfer;
+mod rate_limit;
+mod rename;
+mod restore;
+mod retry;
+mod select;
+mod storage;
+mod stream;
+mod sync;
+mod tags;
+mod upload_dir;
+mod upload_many;
+mod uploads;
+mod throttle;
+mod tree;
+mod urls;
+mod util;
+mod usage;
+mod versions;
+#[cfg(feature = "watch")]
+mod watch;
+
+pub use acl::{Grant, ObjectAcl, CANNED_ACLS};
+pub use bucket::{BucketVersioning, CreateBucketOutcome};
+pub use builder::OssClientBuilder;
+pub use copy::{parse_metadata, CopyOptions, CopyResult, MetadataUpdate};
+pub use copy_prefix::{CopyFailure, CopyProgress, CopyReport};
+pub use cors::{parse_cors, CorsRule};
+pub use delete::{DeleteFailure, DeleteOutcome, DeletePrefixOptions, DeleteReport, DeletedVersion, DELETE_BATCH_SIZE};
+pub use encryption::{parse_sse, BucketEncryption, UploadCheck};
+pub use error::{OssError, Result, ServiceError};
+pub use filter::{FilterVerdict, KeyFilter};
+pub use find::{AgeTest, Bound, FindFilter, SizeTest};
+pub use key::{validate_key, KeyPolicy, NormalizedKey};
+pub use lifecycle::{parse_lifecycle, LifecycleRule, LifecycleTransition};
+pub use list::{directory_prefix, ListEntry, ListOptions};
+pub use lock::{parse_retain_until, ObjectLock};
+pub use metrics::{Direction, FailureKind, Metrics, MetricsCounters, MetricsSnapshot, NoMetrics};
+#[cfg(any(test, feature = "testing"))]
+pub use memory::MemoryStorage;
+#[cfg(feature = "object-store")]
+pub use crate::object_store::OssObjectStore;
+pub use policy::parse_policy;
+pub use post_policy::PostForm;
+pub use presign::{PresignMethod, PresignOptions, PresignedUrl};
+pub use transfer::{
+    DownloadOptions, NoProgress, Overwrite, ProgressEvent, ProgressObserver, TransferOptions, UploadOptions, VerifyMode,
+};
+pub use rate_limit::{RateLimiter, TokenBucket};
+pub use rename::{needs_rename, rename_target, RenameFailure, RenameReport};
+pub use restore::{RestoreOutcome, RestoreStatus};
+pub use retry::{ErrorResponse, RetryPolicy};
+pub use select::{SelectEvent, SelectOptions, SelectStats};
+pub use storage::ObjectStorage;
+pub use stream::UploadResult;
+pub use sync::{
+    CompareMode, DiffReport, SyncAction, SyncActionKind, SyncDest, SyncFailure, SyncOptions, SyncPlan, SyncReason, SyncReport,
+    SyncSource, CONTENT_SHA256_METADATA,
+};
+pub use tags::parse_tag;
+pub use tree::{TreeNode, TreeOptions};
+pub use upload_dir::{DirUploadEntry, DirUploadFailure, DirUploadOptions, DirUploadReport, SymlinkPolicy};
+pub use upload_many::UploadManyItem;
+pub use uploads::{MultipartUpload, UploadedParts};
+pub use aws_smithy_http_client::proxy::ProxyConfig;
+pub use url::Url;
+pub use urls::AddressingStyle;
+pub use util::{derive_key_from_path, format_size, join_key, key_filename, parse_duration, parse_size, ObjectUri};
+pub use usage::{Usage, UsageReport};
+pub use versions::ObjectVersion;
+#[cfg(feature = "watch")]
+pub use watch::{AfterUpload, WatchEvent, WatchOptions, WatchSummary};
+use error::{bail, Context};
+use restore::get_object_error;
+use throttle::{throttle_delay, AdaptiveLimit, MAX_THROTTLE_RETRIES};
+
+/// 默认的分块大小 10MB
+const DEFAULT_PART_SIZE: u64 = 10 * 1024 * 1024;
+/// 分块上传除最后一块外的最小分块大小 5MB
+const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+/// 分块上传最多的分块数
+const MAX_PARTS: usize = 10000;
+/// 默认并发数
 const MAX_WORKERS: usize = 10;
+/// 单次 PUT 的最大对象大小 5GB
+const MAX_PUT_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+/// key 的最大长度（字节）
+const MAX_KEY_LEN: usize = 1024;
+/// 预签名 URL 的最长有效期（SigV4 限制为 7 天）
+pub const MAX_PRESIGN_EXPIRES: u64 = 7 * 86400;
+
+/// 对象元信息（HEAD 结果），序列化为 JSON 时修改时间为 RFC3339 字符串
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: u64,
+    pub etag: Option<String>,
+    /// 存储类型，服务端未返回时为 STANDARD
+    pub storage_class: String,
+    #[serde(serialize_with = "serialize_time")]
+    pub last_modified: Option<DateTime>,
+    /// 所有者（列出时指定 fetch_owner 才会返回）
+    pub owner: Option<String>,
+    /// 以下字段只有 HEAD 时才会返回，列出时为空
+    pub content_type: Option<String>,
+    /// 服务端加密方式，如 AES256、aws:kms
+    pub sse: Option<String>,
+    /// 自定义元数据（x-amz-meta-*），key 不含前缀；序列化时按 key 排序
+    #[serde(serialize_with = "serialize_sorted")]
+    pub metadata: HashMap<String, String>,
+    /// 版本 ID，bucket 未开启版本控制或列出时为空
+    pub version_id: Option<String>,
+}
+
+fn serialize_time<S: Serializer>(time: &Option<DateTime>, serializer: S) -> Result<S::Ok, S::Error> {
+    time.and_then(|t| t.fmt(aws_sdk_s3::primitives::DateTimeFormat::DateTime).ok()).serialize(serializer)
+}
+
+fn serialize_sorted<S: Serializer>(map: &HashMap<String, String>, serializer: S) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<std::collections::BTreeMap<_, _>>().serialize(serializer)
+}
 
 /// OSS 配置
 #[derive(Debug, Clone)]
@@ -22,250 +176,767 @@ pub struct OssConfig {
     pub bucket: String,
     pub endpoint: String,
     pub region: String,
+    /// 公开访问的域名（如 CDN 地址 `https://static.example.com`），设置后公开 URL 使用该域名
+    pub public_base_url: Option<String>,
 }
 
 impl OssConfig {
     /// 从环境变量创建配置
     pub fn from_env() -> Result<Self> {
         Ok(Self {
-            access_key: std::env::var("OSS_ACCESS_KEY")
-                .context("OSS_ACCESS_KEY not set")?,
-            secret_key: std::env::var("OSS_SECRET_KEY")
-                .context("OSS_SECRET_KEY not set")?,
-            bucket: std::env::var("OSS_BUCKET")
-                .context("OSS_BUCKET not set")?,
-            endpoint: std::env::var("OSS_ENDPOINT")
-                .context("OSS_ENDPOINT not set")?,
-            region: std::env::var("OSS_REGION")
-                .context("OSS_REGION not set")?,
+            access_key: required_env("OSS_ACCESS_KEY")?,
+            secret_key: required_env("OSS_SECRET_KEY")?,
+            bucket: required_env("OSS_BUCKET")?,
+            endpoint: required_env("OSS_ENDPOINT")?,
+            region: required_env("OSS_REGION")?,
+            public_base_url: std::env::var("OSS_PUBLIC_URL").ok().filter(|url| !url.is_empty()),
         })
     }
 }
 
+/// 读取必需的环境变量，没有设置时返回配置错误
+fn required_env(name: &str) -> Result<String> {
+    std::env::var(name).map_err(|_| OssError::Config(format!("{} not set", name)))
+}
+
 /// OSS 客户端
+///
+/// clone 的开销很小，可以放进应用状态后在多个任务中并发使用，不需要再包一层 `Arc`。
+/// 所有 clone 共享同一个 HTTP 连接池、进度 observer、限流计数和分块请求的并发名额；
+/// [`with_workers`](Self::with_workers) 只修改调用它的那一个。
+#[derive(Clone)]
 pub struct OssClient {
     client: Client,
-    config: OssConfig,
+    config: Arc<OssConfig>,
+    /// 单次分块上传/下载的并发数
+    workers: usize,
+    /// 所有分块上传/下载和单次 PUT 上传共享的并发名额，数量为构建时的 max_workers
+    permits: Arc<Semaphore>,
+    /// 分块上传/下载的分块大小
+    part_size: u64,
+    /// 使用 path-style 地址（`endpoint/bucket/key`）
+    path_style: bool,
+    /// 没有指定 TransferOptions 时接收进度事件
+    progress: Option<Arc<dyn ProgressObserver>>,
+    /// 被服务端限流的次数
+    throttled: Arc<AtomicU64>,
+    /// 额外视为可重试的错误和自带重试的等待时长
+    retry_policy: Arc<RetryPolicy>,
+    /// 接收自带重试的事件，SDK 请求的事件由 interceptor 发送
+    metrics: Arc<dyn Metrics>,
+    /// 上传、下载、删除、复制和预签名之前检查 key 的规则
+    key_policy: KeyPolicy,
+    /// 上传和下载的带宽限制，可以与其他客户端共享
+    rate_limiter: Option<Arc<dyn RateLimiter>>,
 }
 
 impl OssClient {
-    /// 创建新的 OSS 客户端
+    /// 使用默认设置创建 OSS 客户端
     pub async fn new(config: OssConfig) -> Result<Self> {
-        // 使用静态凭据创建配置
-        let sdk_config = aws_config::defaults(BehaviorVersion::latest())
-            .endpoint_url(&config.endpoint)
-            .region(aws_sdk_s3::config::Region::new(config.region.clone()))
-            .credentials_provider(
-                aws_sdk_s3::config::Credentials::new(
-                    &config.access_key,
-                    &config.secret_key,
-                    None,
-                    None,
-                    "env",
-                )
-            )
-            .load()
-            .await;
+        Self::builder(config).build().await
+    }
+
+    /// 创建客户端的构建器，可以设置分块大小、重试、超时等
+    pub fn builder(config: OssConfig) -> OssClientBuilder {
+        OssClientBuilder::new(config)
+    }
+
+    /// 设置单次分块上传/下载的并发数；同一客户端上同时进行的所有传输合计仍不超过构建时的
+    /// [`max_workers`](OssClientBuilder::max_workers)
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// 单次分块上传/下载的并发数
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// 分块上传/下载的分块大小
+    pub fn part_size(&self) -> u64 {
+        self.part_size
+    }
 
-        let client = Client::new(&sdk_config);
+    /// 没有指定 TransferOptions 时使用的选项：进度事件发送给构建时设置的 observer
+    fn default_options(&self) -> TransferOptions<'_> {
+        TransferOptions { progress: self.progress.as_deref().unwrap_or(&NoProgress), cancel: None }
+    }
+
+    /// 没有指定 UploadOptions 时使用的选项：只有 [`default_options`](Self::default_options) 的进度设置
+    fn default_upload_options(&self) -> UploadOptions<'_> {
+        self.default_options().into()
+    }
 
-        Ok(Self { client, config })
+    /// 到目前为止被服务端限流（503 SlowDown 等）的次数
+    pub fn throttle_count(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+
+    /// 按客户端的 [`KeyPolicy`] 检查 key，返回实际使用的 key；修改了 key 时记录一条警告
+    pub(crate) fn object_key(&self, key: &str) -> Result<String> {
+        let normalized = self.key_policy.apply(key)?;
+        if !normalized.warnings().is_empty() {
+            tracing::warn!("key {:?} {}，实际使用 {:?}", key, normalized.warnings().join("、"), normalized.as_str());
+        }
+        Ok(normalized.into_string())
+    }
+
+    /// 传输 `bytes` 字节之前等待限速器放行，没有设置限速器时直接返回
+    async fn limit_rate(&self, bytes: u64) -> Result<()> {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire(bytes).await;
+        }
+        Ok(())
+    }
+
+    /// 等待一个分块请求或单次 PUT 的并发名额，请求完成后释放
+    async fn part_permit(&self) -> Result<SemaphorePermit<'_>> {
+        self.permits.acquire().await.map_err(|_| OssError::Internal("并发名额已关闭".to_string()))
+    }
+
+    /// 记录一次限流事件，`op` 为将要重试的操作
+    fn note_throttle(&self, op: &str, key: &str, delay: Duration) {
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+        self.metrics.on_retry(op);
+        tracing::warn!(key, "请求被限流，{:.1} 秒后重试", delay.as_secs_f64());
+    }
+
+    /// 发送请求，遇到限流或 [`RetryPolicy`] 认为可重试的错误时，按 Retry-After 或退避时长等待后重试
+    async fn send_throttled<T, E, F, Fut>(&self, op: &str, key: &str, mut send: F) -> Result<T, SdkError<E, HttpResponse>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, SdkError<E, HttpResponse>>>,
+        E: ProvideErrorMetadata,
+    {
+        let mut attempt = 0;
+        loop {
+            match send().await {
+                Err(err) if attempt < MAX_THROTTLE_RETRIES => match throttle_delay(&err, attempt, &self.retry_policy) {
+                    Some(delay) => {
+                        self.note_throttle(op, key, delay);
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => return Err(err),
+                },
+                result => return result,
+            }
+        }
     }
 
     /// 上传文件
     pub async fn upload(&self, path: &Path, key: &str) -> Result<String> {
-        let abs_path = path.canonicalize()
-            .with_context(|| format!("无法找到文件: {}", path.display()))?;
-        
-        let metadata = tokio::fs::metadata(&abs_path).await?;
-        let file_size = metadata.len();
+        self.upload_with_options(path, key, &self.default_upload_options()).await
+    }
 
-        if file_size <= BATCH_SIZE as u64 {
-            self.upload_single(&abs_path, key).await
-        } else {
-            self.upload_multipart(&abs_path, key).await
+    /// 上传文件，按 `options` 设置对象属性和分块参数、发送进度事件；
+    /// 分块上传失败、取消或超时时中止分块上传，取消时返回 [`OssError::Cancelled`]，超时时返回 [`OssError::TimedOut`]
+    pub async fn upload_with_options(&self, path: &Path, key: &str, options: &UploadOptions<'_>) -> Result<String> {
+        self.upload_file(path, key, options).await.map(|result| result.url)
+    }
+
+    /// 同 [`upload_with_options`](Self::upload_with_options)，同时返回上传的字节数和分块数
+    pub(crate) async fn upload_file(&self, path: &Path, key: &str, options: &UploadOptions<'_>) -> Result<UploadResult> {
+        let progress = options.transfer.progress;
+        let key = match self.object_key(key) {
+            Ok(key) => key,
+            Err(err) => return transfer::finish(progress, Err(err)),
+        };
+        let key = key.as_str();
+        let created = OnceLock::new();
+        let result = transfer::with_timeout(options.timeout, async {
+            let client = options.client(self)?;
+            let abs_path = path.canonicalize()
+                .with_context(|| format!("无法找到文件: {}", path.display()))?;
+
+            let metadata = tokio::fs::metadata(&abs_path).await?;
+            let file_size = metadata.len();
+            progress.on_event(ProgressEvent::Started { total: file_size });
+
+            let parts = if file_size <= client.part_size {
+                client.upload_single(&abs_path, key, file_size, options).await?;
+                0
+            } else {
+                client.upload_multipart(&abs_path, key, file_size, options, &created).await?
+            };
+            Ok(UploadResult { url: client.generate_url(key), size: file_size, parts })
+        })
+        .instrument(tracing::info_span!("upload", key))
+        .await;
+        self.abort_timed_out(&result, key, &created).await;
+        if result.is_ok() {
+            tracing::info!(key, "上传完成");
         }
+        transfer::finish(progress, result)
     }
 
-    /// 单文件上传
-    async fn upload_single(&self, path: &Path, key: &str) -> Result<String> {
+    /// 单次 PUT 上传，与分块请求一样在拿到并发名额后才读出文件
+    async fn upload_single(&self, path: &Path, key: &str, file_size: u64, options: &UploadOptions<'_>) -> Result<()> {
+        let _permit = options.transfer.until_cancelled(self.part_permit()).await?;
         let mut file = File::open(path).await?;
         let mut buffer = Vec::new();
         file.read_to_end(&mut buffer).await?;
+        let len = buffer.len() as u64;
+        if len != file_size {
+            bail!("{} 在上传过程中被修改", path.display());
+        }
 
         let body = aws_sdk_s3::primitives::ByteStream::from(buffer);
+        options.transfer.until_cancelled(self.limit_rate(len)).await?;
+
+        let request = self.client.put_object().bucket(&self.config.bucket).key(key).body(body);
+        options.transfer.until_cancelled(options.apply_put(request).send()).await?;
+        options.transfer.progress.on_event(ProgressEvent::BytesTransferred { n: len });
+        Ok(())
+    }
+
+    /// 分块上传，返回分块数
+    async fn upload_multipart(
+        &self,
+        path: &Path,
+        key: &str,
+        file_size: u64,
+        options: &UploadOptions<'_>,
+        created: &OnceLock<String>,
+    ) -> Result<usize> {
+        let total_parts = file_size.div_ceil(self.part_size) as usize;
 
+        tracing::info!(path = %path.display(), parts = total_parts, "开始分块上传");
+
+        // 创建分块上传
+        let create = self.client.create_multipart_upload().bucket(&self.config.bucket).key(key);
+        let create_resp = options.apply_create(create).send().await?;
+
+        let upload_id = create_resp.upload_id()
+            .context("无法获取 upload id")?
+            .to_string();
+        let _ = created.set(upload_id.clone());
+
+        let numbers = (1..=total_parts).collect();
+        let uploaded = self.upload_file_parts(path, key, &upload_id, file_size, numbers, &options.transfer).await;
+        // 出错或取消时中止分块上传，不留下未完成的分块
+        let completed_parts = match uploaded {
+            Ok(parts) => parts,
+            Err(OssError::Cancelled) => {
+                self.abort_quietly(key, &upload_id).await;
+                tracing::info!(upload_id, "上传已取消，已中止分块上传");
+                return Err(OssError::Cancelled);
+            }
+            Err(err) => {
+                self.abort_quietly(key, &upload_id).await;
+                return Err(err);
+            }
+        };
+        self.complete_parts(key, &upload_id, completed_parts).await?;
+        Ok(total_parts)
+    }
+
+    /// 并发上传文件中编号为 `numbers` 的分块（从 1 开始），每个分块在拿到并发名额后才从文件读出；
+    /// 出错或取消时丢弃其余的分块请求，由调用方决定是否中止分块上传
+    async fn upload_file_parts(
+        &self,
+        path: &Path,
+        key: &str,
+        upload_id: &str,
+        file_size: u64,
+        numbers: Vec<usize>,
+        options: &TransferOptions<'_>,
+    ) -> Result<Vec<CompletedPart>> {
+        let part_size = self.part_size;
+        let upload_part = |part_number: usize| async move {
+            options.check_cancelled()?;
+            let _permit = options.until_cancelled(self.part_permit()).await?;
+            let start = (part_number as u64 - 1) * part_size;
+            let mut file = File::open(path).await?;
+            file.seek(SeekFrom::Start(start)).await?;
+            // 单次 read 可能只返回一部分（tokio 的 File 每次最多 2MB），必须读满一个分块
+            let data = stream::read_part(&mut file, part_size as usize).await?;
+            let len = data.len() as u64;
+            if len != part_size.min(file_size.saturating_sub(start)) {
+                bail!("{} 在上传过程中被修改", path.display());
+            }
+            options.until_cancelled(self.limit_rate(len)).await?;
+            let request = self.client
+                .upload_part()
+                .bucket(&self.config.bucket)
+                .key(key)
+                .part_number(part_number as i32)
+                .upload_id(upload_id)
+                .body(aws_sdk_s3::primitives::ByteStream::from(data))
+                .send();
+            let resp = options.until_cancelled(request).await?;
+
+            tracing::debug!(part = part_number, bytes = len, "分块上传完成");
+            let progress = options.progress;
+            progress.on_event(ProgressEvent::BytesTransferred { n: len });
+            progress.on_event(ProgressEvent::PartCompleted { number: part_number });
+
+            Ok::<_, OssError>(
+                CompletedPart::builder()
+                    .part_number(part_number as i32)
+                    .e_tag(resp.e_tag().unwrap_or_default())
+                    .build()
+            )
+        };
+        futures::stream::iter(numbers)
+            .map(upload_part)
+            .buffer_unordered(self.workers)
+            .try_collect()
+            .await
+    }
+
+    /// 上传超时时，中止被丢弃的上传已经创建的分块上传（`created`）
+    async fn abort_timed_out<T>(&self, result: &Result<T>, key: &str, created: &OnceLock<String>) {
+        if let (Err(OssError::TimedOut { .. }), Some(upload_id)) = (result, created.get()) {
+            self.abort_quietly(key, upload_id).await;
+            tracing::info!(upload_id, "上传超时，已中止分块上传");
+        }
+    }
+
+    /// 按 PartNumber 排序后完成分块上传
+    async fn complete_parts(&self, key: &str, upload_id: &str, mut parts: Vec<CompletedPart>) -> Result<()> {
+        parts.sort_by_key(|p| p.part_number());
+        let completed = aws_sdk_s3::types::CompletedMultipartUpload::builder()
+            .set_parts(Some(parts))
+            .build();
         self.client
-            .put_object()
+            .complete_multipart_upload()
             .bucket(&self.config.bucket)
             .key(key)
-            .body(body)
+            .upload_id(upload_id)
+            .multipart_upload(completed)
             .send()
             .await?;
+        Ok(())
+    }
 
-        Ok(self.generate_url(key))
+    /// 获取对象元信息（HEAD），不下载内容；`version_id` 指定版本，None 为最新版本
+    pub async fn head(&self, key: &str, version_id: Option<&str>) -> Result<ObjectInfo> {
+        self.head_object(key, version_id)
+            .await?
+            .ok_or_else(|| OssError::not_found(key))
     }
 
-    /// 分块上传
-    async fn upload_multipart(&self, path: &Path, key: &str) -> Result<String> {
-        let metadata = tokio::fs::metadata(path).await?;
-        let file_size = metadata.len();
-        let total_parts = ((file_size + BATCH_SIZE as u64 - 1) / BATCH_SIZE as u64) as usize;
+    /// 对象是否存在（HeadObject）：成功返回 `true`，404 / NoSuchKey 返回 `false`，其他错误照常返回。
+    /// 注意 403 不会当作不存在：没有 s3:ListBucket 权限时 S3 对不存在的 key 也返回 403，
+    /// 此时无法判断对象是否存在，返回 [`OssError::AccessDenied`]，避免误以为可以安全覆盖或已经删除
+    pub async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.head_if_exists(key).await?.is_some())
+    }
 
-        println!("分块上传 {} 到 {}", path.display(), key);
+    /// 获取对象最新版本的元信息，对象不存在（404）时返回 `None`
+    pub async fn head_if_exists(&self, key: &str) -> Result<Option<ObjectInfo>> {
+        self.head_object(key, None).await
+    }
 
-        // 创建分块上传
-        let create_resp = self.client
-            .create_multipart_upload()
+    async fn head_object(&self, key: &str, version_id: Option<&str>) -> Result<Option<ObjectInfo>> {
+        let key = &self.object_key(key)?;
+        let resp = match self.client
+            .head_object()
             .bucket(&self.config.bucket)
             .key(key)
-            .storage_class(aws_sdk_s3::types::StorageClass::Standard)
+            .set_version_id(version_id.map(str::to_string))
             .send()
-            .await?;
-
-        let upload_id = create_resp.upload_id()
-            .context("无法获取 upload id")?
-            .to_string();
+            .await
+        {
+            Ok(resp) => resp,
+            Err(err) if is_not_found(&err) => return Ok(None),
+            Err(err) => return Err(err).with_context(|| format!("无法获取对象信息: {}", key)),
+        };
 
-        // 进度条
-        let pb = ProgressBar::new(file_size);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}")
-                .unwrap()
-                .progress_chars("#>-"),
-        );
-        pb.set_message(format!("上传 {}", path.file_name()
-            .unwrap_or_default()
-            .to_string_lossy()));
+        Ok(Some(head_info(key, &resp)))
+    }
 
-        let pb = Arc::new(pb);
-        let semaphore = Arc::new(Semaphore::new(MAX_WORKERS));
+    /// 下载文件
+    /// 大于分块大小的对象使用并发的分段（Range）下载
+    pub async fn download(&self, key: &str, output_path: Option<&Path>) -> Result<PathBuf> {
+        self.download_with_options(key, output_path, &self.default_options().into()).await
+    }
 
-        // 读取文件所有数据
-        let mut file = File::open(path).await?;
-        let mut parts_data = Vec::with_capacity(total_parts);
-        
-        for part_num in 1..=total_parts {
-            let mut buffer = vec![0u8; BATCH_SIZE];
-            let bytes_read = file.read(&mut buffer).await?;
-            if bytes_read == 0 {
-                break;
+    /// 下载文件，按 `options` 选择版本、范围、续传和校验方式，发送进度事件；
+    /// 取消或超时时删除未下载完的文件（续传时保留），返回 [`OssError::Cancelled`] 或 [`OssError::TimedOut`]
+    pub async fn download_with_options(
+        &self,
+        key: &str,
+        output_path: Option<&Path>,
+        options: &DownloadOptions<'_>,
+    ) -> Result<PathBuf> {
+        let progress = options.transfer.progress;
+        let key = match self.object_key(key) {
+            Ok(key) => key,
+            Err(err) => return transfer::finish(progress, Err(err)),
+        };
+        let key = key.as_str();
+        let output_path = default_output_path(key, output_path);
+        let result = transfer::with_timeout(options.timeout, async {
+            let client = options.client(self)?;
+            if options.overwrite == Overwrite::Never && tokio::fs::try_exists(&output_path).await? {
+                return Err(OssError::LocalIo {
+                    context: format!("本地文件已存在: {}", output_path.display()),
+                    source: std::io::ErrorKind::AlreadyExists.into(),
+                });
             }
-            buffer.truncate(bytes_read);
-            parts_data.push((part_num, buffer));
+            if let Some(info) = client.ranged_info(key, options).await? {
+                progress.on_event(ProgressEvent::Started { total: info.size });
+                if options.resume {
+                    client.resume_ranged(&info, &output_path, options).await?;
+                } else {
+                    client.download_ranged(&info, &output_path, options).await?;
+                }
+            } else if options.resume {
+                client.resume_to(key, &output_path, options).await?;
+            } else {
+                let mut file = File::create(&output_path).await?;
+                client.write_body(key, &mut file, options).await?;
+            }
+            client.verify_download(key, &output_path, options).await
+        })
+        .instrument(tracing::info_span!("download", key))
+        .await;
+        if matches!(result, Err(OssError::Cancelled | OssError::TimedOut { .. })) && !options.resume {
+            let _ = tokio::fs::remove_file(&output_path).await;
         }
+        transfer::finish(progress, result)?;
+        tracing::info!(output = %output_path.display(), "下载完成");
 
-        // 并发上传分块
-        let mut tasks = Vec::with_capacity(parts_data.len());
-        let parts_data = Arc::new(Mutex::new(parts_data));
-
-        for _ in 0..parts_data.lock().await.len() {
-            let client = self.client.clone();
-            let bucket = self.config.bucket.clone();
-            let key = key.to_string();
-            let upload_id = upload_id.clone();
-            let parts_data = parts_data.clone();
-            let pb = pb.clone();
-            let semaphore = semaphore.clone();
-
-            let task = tokio::spawn(async move {
-                let _permit = semaphore.acquire().await?;
-                
-                let (part_number, data) = {
-                    let mut parts = parts_data.lock().await;
-                    if parts.is_empty() {
-                        return Ok::<Option<CompletedPart>, anyhow::Error>(None);
-                    }
-                    parts.remove(0)
-                };
+        Ok(output_path)
+    }
 
-                let body = aws_sdk_s3::primitives::ByteStream::from(data.clone());
+    /// 需要分段下载时返回对象信息：没有指定范围，且对象大于分块大小
+    async fn ranged_info(&self, key: &str, options: &DownloadOptions<'_>) -> Result<Option<ObjectInfo>> {
+        if options.range.is_some() {
+            return Ok(None);
+        }
+        let info = match &options.head {
+            Some(info) if info.key == key => info.clone(),
+            _ => self.head_for_download(key, options).await?,
+        };
+        Ok((info.size > self.part_size).then_some(info))
+    }
 
-                let resp = client
-                    .upload_part()
-                    .bucket(&bucket)
-                    .key(&key)
-                    .part_number(part_number as i32)
-                    .upload_id(&upload_id)
-                    .body(body)
-                    .send()
-                    .await?;
-
-                pb.inc(data.len() as u64);
-
-                Ok(Some(
-                    CompletedPart::builder()
-                        .part_number(part_number as i32)
-                        .e_tag(resp.e_tag().unwrap_or_default())
-                        .build()
-                ))
-            });
+    /// 按下载选项的版本、SSE-C 和请求者付费 HEAD
+    async fn head_for_download(&self, key: &str, options: &DownloadOptions<'_>) -> Result<ObjectInfo> {
+        let request = self.client.head_object().bucket(&self.config.bucket).key(key);
+        match options.apply_head(request).send().await {
+            Ok(resp) => Ok(head_info(key, &resp)),
+            Err(err) if is_not_found(&err) => Err(OssError::not_found(key)),
+            Err(err) => Err(err).with_context(|| format!("无法获取对象信息: {}", key)),
+        }
+    }
 
-            tasks.push(task);
+    /// 按 `options.verify` 校验下载的文件
+    async fn verify_download(&self, key: &str, path: &Path, options: &DownloadOptions<'_>) -> Result<()> {
+        if options.verify == VerifyMode::None {
+            return Ok(());
+        }
+        let info = self.head_for_download(key, options).await?;
+        let size = tokio::fs::metadata(path).await?.len();
+        if size != info.size {
+            return Err(OssError::Invalid(format!("{} 校验失败：文件大小 {}，对象大小 {}", path.display(), size, info.size)));
+        }
+        let etag = info.etag.as_deref().unwrap_or_default().trim_matches('"');
+        if options.verify == VerifyMode::Md5 && !etag.is_empty() && !etag.contains('-') {
+            let path_buf = path.to_path_buf();
+            let md5 = tokio::task::spawn_blocking(move || sync::file_md5(&path_buf))
+                .await
+                .map_err(|err| OssError::Internal(format!("计算 MD5 的任务异常退出: {}", err)))??;
+            if !md5.eq_ignore_ascii_case(etag) {
+                return Err(OssError::Invalid(format!("{} 校验失败：MD5 {} 与 ETag {} 不一致", path.display(), md5, etag)));
+            }
         }
+        Ok(())
+    }
 
-        // 收集结果
-        let mut completed_parts = Vec::new();
-        for task in tasks {
-            if let Some(part) = task.await?? {
-                completed_parts.push(part);
+    /// 分段下载：按分块大小并发发送 Range 请求，各自写入文件对应的偏移
+    async fn download_ranged(&self, info: &ObjectInfo, output_path: &Path, download: &DownloadOptions<'_>) -> Result<()> {
+        let file = File::create(output_path).await?;
+        file.set_len(info.size).await?;
+        drop(file);
+        self.download_parts(info, output_path, download, &BTreeSet::new(), None).await
+    }
+
+    /// 可以续传的分段下载：数据写入 `<output>.part`，ETag 记录在 `<output>.part.etag`，
+    /// 已完成的分段记录在 `<output>.part.ranges`（首行为分块大小和对象大小）。
+    /// 续传时 ETag、分块大小和对象大小都一致才跳过已完成的分段，否则从头下载
+    async fn resume_ranged(&self, info: &ObjectInfo, output_path: &Path, download: &DownloadOptions<'_>) -> Result<()> {
+        let part_path = sidecar_path(output_path, "part");
+        let etag_path = sidecar_path(output_path, "part.etag");
+        let ranges_path = sidecar_path(output_path, "part.ranges");
+        let etag = info.etag.clone().unwrap_or_default();
+        let header = format!("{} {}", self.part_size, info.size);
+
+        let done = match (
+            tokio::fs::metadata(&part_path).await,
+            tokio::fs::read_to_string(&etag_path).await,
+            tokio::fs::read_to_string(&ranges_path).await,
+        ) {
+            (Ok(meta), Ok(saved), Ok(ranges))
+                if meta.len() == info.size && !etag.is_empty() && saved.trim() == etag =>
+            {
+                let mut lines = ranges.lines();
+                if lines.next() == Some(header.as_str()) {
+                    lines.filter_map(|line| line.parse().ok()).collect()
+                } else {
+                    BTreeSet::new()
+                }
             }
+            _ => BTreeSet::new(),
+        };
+        if done.is_empty() {
+            let file = File::create(&part_path).await?;
+            file.set_len(info.size).await?;
+            drop(file);
+            tokio::fs::write(&etag_path, &etag).await?;
+            tokio::fs::write(&ranges_path, format!("{}\n", header)).await?;
+        } else {
+            let offset: u64 = done.iter().map(|index| self.part_len(*index, info.size)).sum();
+            tracing::info!(parts = done.len(), offset, "从断点继续分段下载");
+            download.transfer.progress.on_event(ProgressEvent::BytesTransferred { n: offset });
         }
 
-        pb.finish_with_message("上传完成");
+        let journal = tokio::sync::Mutex::new(tokio::fs::OpenOptions::new().append(true).open(&ranges_path).await?);
+        self.download_parts(info, &part_path, download, &done, Some(&journal)).await?;
+        drop(journal);
 
-        // 按 PartNumber 排序
-        completed_parts.sort_by_key(|p| p.part_number());
+        tokio::fs::rename(&part_path, output_path).await?;
+        let _ = tokio::fs::remove_file(&etag_path).await;
+        let _ = tokio::fs::remove_file(&ranges_path).await;
+        Ok(())
+    }
 
-        // 完成上传
-        let completed_parts_obj = aws_sdk_s3::types::CompletedMultipartUpload::builder()
-            .set_parts(Some(completed_parts))
-            .build();
+    /// 第 `index` 个分段的长度
+    fn part_len(&self, index: u64, size: u64) -> u64 {
+        let start = index * self.part_size;
+        (start + self.part_size).min(size).saturating_sub(start)
+    }
 
-        self.client
-            .complete_multipart_upload()
-            .bucket(&self.config.bucket)
-            .key(key)
-            .upload_id(&upload_id)
-            .multipart_upload(completed_parts_obj)
-            .send()
-            .await?;
+    /// 并发下载 `done` 以外的分段，写入 `path` 对应的偏移；每完成一个分段把序号追加到 `journal`
+    /// 被限流时临时降低并发，等待后重试该分段
+    async fn download_parts(
+        &self,
+        info: &ObjectInfo,
+        path: &Path,
+        download: &DownloadOptions<'_>,
+        done: &BTreeSet<u64>,
+        journal: Option<&tokio::sync::Mutex<File>>,
+    ) -> Result<()> {
+        let options = &download.transfer;
+        let progress = options.progress;
+        let size = info.size;
+        let (part_size, total_parts) = (self.part_size, size.div_ceil(self.part_size));
 
-        Ok(self.generate_url(key))
+        let limit = &AdaptiveLimit::new(self.workers);
+        let part = |index: u64| async move {
+            let start = index * part_size;
+            let end = (start + part_size).min(size) - 1;
+
+            let mut attempt = 0;
+            let (resp, _permit, _shared) = loop {
+                let permit = limit.acquire().await;
+                let shared = options.until_cancelled(self.part_permit()).await?;
+                let request = self.client.get_object().bucket(&self.config.bucket).key(&info.key);
+                let mut request = download.apply_get(request).range(format!("bytes={}-{}", start, end));
+                // 下载过程中对象被替换时直接失败，避免拼接出不同版本的数据
+                if let Some(etag) = &info.etag {
+                    request = request.if_match(etag);
+                }
+                match options.until_cancelled(async { Ok::<_, OssError>(request.send().await) }).await? {
+                    Ok(resp) => {
+                        limit.on_success();
+                        break (resp, permit, shared);
+                    }
+                    Err(err) => match throttle_delay(&err, attempt, &self.retry_policy) {
+                        Some(delay) if attempt < MAX_THROTTLE_RETRIES => {
+                            limit.on_throttle(permit);
+                            drop(shared);
+                            self.note_throttle("GetObject", &info.key, delay);
+                            tracing::debug!(effective = limit.effective(), "降低分段下载的并发数");
+                            tokio::time::sleep(delay).await;
+                            attempt += 1;
+                        }
+                        _ => {
+                            return Err(get_object_error(&info.key, err)
+                                .add_context(format!("分段下载失败: bytes={}-{}", start, end)));
+                        }
+                    },
+                }
+            };
+
+            let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+            file.seek(SeekFrom::Start(start)).await?;
+            let mut stream = resp.body;
+            while let Some(chunk) = options.until_cancelled(stream.try_next()).await? {
+                options.until_cancelled(self.limit_rate(chunk.len() as u64)).await?;
+                file.write_all(&chunk).await?;
+                progress.on_event(ProgressEvent::BytesTransferred { n: chunk.len() as u64 });
+            }
+            file.flush().await?;
+            if let Some(journal) = journal {
+                let mut journal = journal.lock().await;
+                journal.write_all(format!("{}\n", index).as_bytes()).await?;
+                journal.flush().await?;
+            }
+            tracing::debug!(part = index + 1, start, end, "分段下载完成");
+            progress.on_event(ProgressEvent::PartCompleted { number: index as usize + 1 });
+            Ok::<(), OssError>(())
+        };
+
+        futures::future::try_join_all((0..total_parts).filter(|index| !done.contains(index)).map(part)).await?;
+        Ok(())
     }
 
-    /// 下载文件
-    pub async fn download(&self, key: &str, output_path: Option<&Path>) -> Result<PathBuf> {
-        let output_path = output_path
-            .map(|p| p.to_path_buf())
-            .unwrap_or_else(|| {
-                PathBuf::from(Path::new(key).file_name()
-                    .unwrap_or_default())
+    /// 断点续传下载
+    /// 数据先写入 `<output>.part`，开始下载时对象的 ETag 记录在 `<output>.part.etag`。
+    /// 续传时带上 `If-Range: <etag>`，如果对象已被替换，服务端会返回完整的新内容，
+    /// 此时丢弃旧的部分数据重新写入，避免新旧版本的数据拼接在一起。
+    /// 大于分块大小的对象分段下载，已完成的分段记录在 `<output>.part.ranges`，续传时只下载其余分段。
+    pub async fn download_resume(&self, key: &str, output_path: Option<&Path>) -> Result<PathBuf> {
+        self.download_resume_with_options(key, output_path, &self.default_options()).await
+    }
+
+    /// 断点续传下载，按 `options` 发送进度事件，续传时已有的部分先作为一次 BytesTransferred 发送；
+    /// 取消时保留已下载的部分，下次可以继续
+    pub async fn download_resume_with_options(
+        &self,
+        key: &str,
+        output_path: Option<&Path>,
+        options: &TransferOptions<'_>,
+    ) -> Result<PathBuf> {
+        let transfer = TransferOptions { progress: options.progress, cancel: options.cancel.clone() };
+        let options = DownloadOptions { resume: true, ..transfer.into() };
+        self.download_with_options(key, output_path, &options).await
+    }
+
+    async fn resume_to(&self, key: &str, output_path: &Path, download: &DownloadOptions<'_>) -> Result<()> {
+        let options = &download.transfer;
+        let progress = options.progress;
+        let output_path = output_path.to_path_buf();
+        let part_path = sidecar_path(&output_path, "part");
+        let etag_path = sidecar_path(&output_path, "part.etag");
+        let ranges_path = sidecar_path(&output_path, "part.ranges");
+
+        // 分段续传留下的 .part 是预先分配的完整大小，中间可能有空洞，不能接着写
+        let ranged = tokio::fs::try_exists(&ranges_path).await?;
+        if ranged {
+            tokio::fs::remove_file(&ranges_path).await?;
+        }
+        let partial = match (
+            tokio::fs::metadata(&part_path).await,
+            tokio::fs::read_to_string(&etag_path).await,
+        ) {
+            (Ok(meta), Ok(etag)) if !ranged && meta.len() > 0 && !etag.trim().is_empty() => {
+                Some((meta.len(), etag.trim().to_string()))
+            }
+            _ => None,
+        };
+
+        let request = self
+            .send_throttled("GetObject", key, || {
+                let partial = partial.clone();
+                async move {
+                    let request = download.apply_get(self.client.get_object().bucket(&self.config.bucket).key(key));
+                    match partial {
+                        Some((offset, etag)) => {
+                            request
+                                .range(format!("bytes={}-", offset))
+                                .customize()
+                                .mutate_request(move |req| {
+                                    req.headers_mut().insert("If-Range", etag.clone());
+                                })
+                                .send()
+                                .await
+                        }
+                        None => request.send().await,
+                    }
+                }
             });
+        let result = options.until_cancelled(async { Ok::<_, OssError>(request.await) }).await?;
 
-        let resp = self.client
-            .get_object()
-            .bucket(&self.config.bucket)
-            .key(key)
-            .send()
-            .await?;
+        let resp = match result {
+            Ok(resp) => resp,
+            // 部分文件已经是完整内容，确认对象未变化后直接完成
+            Err(err) if partial.is_some()
+                && err.raw_response().map(|r| r.status().as_u16()) == Some(416) =>
+            {
+                let (offset, etag) = partial.unwrap_or_default();
+                let info = self.head_for_download(key, download).await?;
+                if info.size == offset && info.etag.as_deref() == Some(etag.as_str()) {
+                    progress.on_event(ProgressEvent::Started { total: offset });
+                    progress.on_event(ProgressEvent::BytesTransferred { n: offset });
+                    tokio::fs::rename(&part_path, &output_path).await?;
+                    let _ = tokio::fs::remove_file(&etag_path).await;
+                    tracing::info!(output = %output_path.display(), "部分文件已经完整");
+                    return Ok(());
+                }
+                // 对象已变化，丢弃旧数据重新下载
+                tokio::fs::remove_file(&part_path).await?;
+                tokio::fs::remove_file(&etag_path).await?;
+                return Box::pin(self.resume_to(key, &output_path, download)).await;
+            }
+            Err(err) => return Err(get_object_error(key, err)),
+        };
 
-        let mut file = File::create(&output_path).await?;
-        let mut stream = resp.body;
+        // 返回 206 说明 ETag 仍然匹配，可以追加；返回 200 说明对象已变化，需要从头写入
+        let resumed = partial.is_some() && resp.content_range().is_some();
+        let offset = if resumed { partial.as_ref().map_or(0, |(offset, _)| *offset) } else { 0 };
+        let remaining = resp.content_length().unwrap_or_default().max(0) as u64;
+        progress.on_event(ProgressEvent::Started { total: offset + remaining });
+        if offset > 0 {
+            progress.on_event(ProgressEvent::BytesTransferred { n: offset });
+        }
+        let mut file = if resumed {
+            tracing::info!(offset, "从断点继续下载");
+            tokio::fs::OpenOptions::new().append(true).open(&part_path).await?
+        } else {
+            if partial.is_some() {
+                tracing::info!("远程对象已变化，重新下载");
+            }
+            tokio::fs::write(&etag_path, resp.e_tag().unwrap_or_default()).await?;
+            File::create(&part_path).await?
+        };
 
-        while let Some(chunk) = stream.try_next().await? {
+        let mut stream = resp.body;
+        loop {
+            // 取消时保留已写入的部分，下次续传
+            let chunk = match options.until_cancelled(stream.try_next()).await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(err) => {
+                    file.flush().await?;
+                    return Err(err);
+                }
+            };
+            if let Err(err) = options.until_cancelled(self.limit_rate(chunk.len() as u64)).await {
+                file.flush().await?;
+                return Err(err);
+            }
             file.write_all(&chunk).await?;
+            progress.on_event(ProgressEvent::BytesTransferred { n: chunk.len() as u64 });
         }
-
         file.flush().await?;
-        println!("成功下载 {} 到 {}", key, output_path.display());
+        drop(file);
 
-        Ok(output_path)
+        tokio::fs::rename(&part_path, &output_path).await?;
+        let _ = tokio::fs::remove_file(&etag_path).await;
+        Ok(())
     }
 
     /// 删除文件
-    pub async fn delete(&self, key: &str) -> Result<()> {
+    /// 删除前先 HEAD 确认对象是否存在，因为 DeleteObject 对不存在的 key 也会返回成功；
+    /// 只有删除权限、HEAD 返回 403 时仍然删除，返回 [`DeleteOutcome::Unknown`]
+    pub async fn delete(&self, key: &str) -> Result<DeleteOutcome> {
+        let key = &self.object_key(key)?;
+        let outcome = match self.exists(key).await {
+            Ok(true) => DeleteOutcome::Deleted,
+            Ok(false) => return Ok(DeleteOutcome::DidNotExist),
+            Err(OssError::AccessDenied(_)) => {
+                tracing::debug!(key, "没有权限 HEAD，无法确认对象是否存在");
+                DeleteOutcome::Unknown
+            }
+            Err(err) => return Err(err),
+        };
+
         self.client
             .delete_object()
             .bucket(&self.config.bucket)
@@ -273,53 +944,237 @@ impl OssClient {
             .send()
             .await?;
 
-        println!("成功删除 {}", key);
-        Ok(())
+        tracing::info!(key, "已删除");
+        Ok(outcome)
     }
 
     /// 生成下载 URL（使用 SDK 的 presigned 方法生成带签名的临时 URL）
     /// 适用于私有 bucket，生成有时效性的访问链接
-    pub async fn generate_presigned_url(&self, key: &str, expires_in_secs: u64) -> Result<String> {
-        use aws_sdk_s3::presigning::PresigningConfig;
-        use std::time::Duration;
+    /// `download_as` 和 `response_content_type` 会覆盖响应的 Content-Disposition/Content-Type 并参与签名
+    pub async fn generate_presigned_url(
+        &self,
+        key: &str,
+        expires_in_secs: u64,
+        download_as: Option<&str>,
+        response_content_type: Option<&str>,
+    ) -> Result<String> {
+        let options = PresignOptions {
+            download_as: download_as.map(str::to_string),
+            response_content_type: response_content_type.map(str::to_string),
+            ..Default::default()
+        };
+        Ok(self.presign(key, PresignMethod::Get, expires_in_secs, &options).await?.url)
+    }
 
-        let presigning_config = PresigningConfig::builder()
-            .expires_in(Duration::from_secs(expires_in_secs))
-            .build()?;
+    /// 生成上传 URL：持有者可以在有效期内直接 PUT 文件到该 key，无需凭证
+    /// 指定 `content_type` 时它会参与签名，上传时 Content-Type 必须一致
+    /// `max_size_hint` 为预计的最大文件大小，超过单次 PUT 上限（5 GiB）时直接报错
+    pub async fn generate_presigned_put_url(
+        &self,
+        key: &str,
+        expires_in_secs: u64,
+        content_type: Option<&str>,
+        max_size_hint: Option<u64>,
+    ) -> Result<String> {
+        if let Some(size) = max_size_hint {
+            if size > MAX_PUT_SIZE {
+                bail!(
+                    "预签名 PUT 最多上传 {}，{} 的文件请改用分块上传",
+                    format_size(MAX_PUT_SIZE),
+                    format_size(size),
+                );
+            }
+        }
 
-        let presigned_request = self
-            .client
-            .get_object()
-            .bucket(&self.config.bucket)
-            .key(key)
-            .presigned(presigning_config)
-            .await?;
+        let method = PresignMethod::Put { content_type: content_type.map(str::to_string), content_length: None };
+        Ok(self.presign(key, method, expires_in_secs, &PresignOptions::default()).await?.url)
+    }
+
+    /// 生成简单的公开访问 URL（不带签名，不访问网络）
+    /// 适用于公开可读的 bucket；配置了 public_base_url 时使用该域名，否则同 [`endpoint_url`](Self::endpoint_url)
+    pub fn generate_url(&self, key: &str) -> String {
+        if self.config.public_base_url.is_some() {
+            if let Ok(url) = self.public_url(key, AddressingStyle::CustomDomain) {
+                return url.into();
+            }
+        }
+        self.endpoint_url(key)
+    }
 
-        Ok(presigned_request.uri().to_string())
+    /// 按指定形式生成对象的公开访问 URL（不带签名，不访问网络），返回解析好的 [`Url`]，可以继续安全地添加查询参数
+    /// endpoint 为 IP 地址时不能使用 VirtualHost，没有配置 public_base_url 时不能使用 CustomDomain
+    pub fn public_url(&self, key: &str, style: AddressingStyle) -> Result<Url> {
+        let base = match style {
+            AddressingStyle::CustomDomain => self
+                .config
+                .public_base_url
+                .as_deref()
+                .ok_or_else(|| OssError::Config("没有配置 public_base_url".to_string()))?,
+            _ => &self.config.endpoint,
+        };
+        urls::object_url(base, &self.config.bucket, key, style)
     }
 
-    /// 生成简单的公开访问 URL（不带签名）
-    /// 适用于公开可读的 bucket
-    fn generate_url(&self, key: &str) -> String {
-        let encoded_key = urlencoding::encode(key).replace("%2F", "/");
-        let endpoint = self.config.endpoint.trim_end_matches('/');
-        
-        // 将 bucket 作为子域名插入到 endpoint 中
-        if let Some(pos) = endpoint.find("://") {
-            let protocol = &endpoint[..pos + 3];
-            let domain = &endpoint[pos + 3..];
-            format!("{}{}.{}/{}", protocol, self.config.bucket, domain, encoded_key)
+    /// 公开访问的域名，没有配置时为 None
+    pub fn public_base_url(&self) -> Option<&str> {
+        self.config.public_base_url.as_deref()
+    }
+
+    /// 按 endpoint 生成对象的 URL（bucket 作为子域名；path-style 或 endpoint 为 IP 地址时作为路径），表单直传等需要真实地址时使用
+    pub(crate) fn endpoint_url(&self, key: &str) -> String {
+        let style = if self.path_style || urls::is_ip_endpoint(&self.config.endpoint) {
+            AddressingStyle::Path
         } else {
-            // 如果没有协议前缀，直接使用 bucket 作为前缀
-            format!("{}.{}/{}", self.config.bucket, endpoint, encoded_key)
-        }
+            AddressingStyle::VirtualHost
+        };
+        // bucket 不能作为子域名时退回 path-style；endpoint 已在创建客户端时检查，path-style 不会失败
+        self.public_url(key, style)
+            .or_else(|_| self.public_url(key, AddressingStyle::Path))
+            .map(String::from)
+            .unwrap_or_default()
+    }
+}
+
+/// 检查有效期后创建预签名配置，SigV4 预签名 URL 最长有效 7 天
+fn presigning_config(expires_in_secs: u64) -> Result<aws_sdk_s3::presigning::PresigningConfig> {
+    check_presign_expires(expires_in_secs)?;
+    Ok(aws_sdk_s3::presigning::PresigningConfig::expires_in(Duration::from_secs(expires_in_secs))?)
+}
+
+fn check_presign_expires(secs: u64) -> Result<()> {
+    if secs == 0 {
+        bail!("有效期必须大于 0");
+    }
+    if secs > MAX_PRESIGN_EXPIRES {
+        bail!(
+            "有效期 {} 秒超过了预签名 URL 的上限 {} 秒（7 天）",
+            secs,
+            MAX_PRESIGN_EXPIRES,
+        );
+    }
+    Ok(())
+}
+
+/// URL 中的 key：编码特殊字符，保留 `/`
+fn encode_key(key: &str) -> String {
+    urlencoding::encode(key).replace("%2F", "/")
+}
+
+/// 是否为对象不存在（404 / NoSuchKey）的错误
+fn is_not_found<E: ProvideErrorMetadata>(err: &SdkError<E, HttpResponse>) -> bool {
+    err.raw_response().map(|r| r.status().as_u16()) == Some(404)
+        || matches!(err.code(), Some("NoSuchKey" | "NotFound"))
+}
+
+/// 是否为服务商不支持该功能（501 / NotImplemented）的错误
+fn is_not_implemented<E: ProvideErrorMetadata>(err: &SdkError<E, HttpResponse>) -> bool {
+    err.raw_response().map(|r| r.status().as_u16()) == Some(501)
+        || matches!(err.code(), Some("NotImplemented" | "NotSupported"))
+}
+
+/// 从 HEAD 的响应构造对象元信息
+pub(crate) fn head_info(key: &str, resp: &HeadObjectOutput) -> ObjectInfo {
+    ObjectInfo {
+        key: key.to_string(),
+        size: resp.content_length().unwrap_or_default().max(0) as u64,
+        etag: resp.e_tag().map(|s| s.to_string()),
+        storage_class: resp.storage_class()
+            .map(|c| c.as_str().to_string())
+            .unwrap_or_else(|| "STANDARD".to_string()),
+        last_modified: resp.last_modified().cloned(),
+        owner: None,
+        content_type: resp.content_type().map(|s| s.to_string()),
+        sse: resp.server_side_encryption().map(|s| s.as_str().to_string()),
+        metadata: resp.metadata().cloned().unwrap_or_default(),
+        version_id: resp.version_id().map(|s| s.to_string()),
+    }
+}
+
+/// 选出最新的对象
+/// 默认按 LastModified 选最新的，时间相同时按 key 排序取最大的；
+/// `by_name` 为 true 时只按 key 的字典序选最大的
+pub fn pick_latest(objects: &[ObjectInfo], by_name: bool) -> Option<&ObjectInfo> {
+    if by_name {
+        objects.iter().max_by(|a, b| a.key.cmp(&b.key))
+    } else {
+        objects.iter().max_by(|a, b| {
+            a.last_modified.cmp(&b.last_modified).then_with(|| a.key.cmp(&b.key))
+        })
+    }
+}
+
+/// 默认的本地输出路径：未指定时使用 key 的文件名
+fn default_output_path(key: &str, output_path: Option<&Path>) -> PathBuf {
+    output_path
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from(key_filename(key)))
+}
+
+/// 在路径后追加扩展名，如 `a.bin` -> `a.bin.part`
+fn sidecar_path(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(ext);
+    PathBuf::from(name)
+}
+
+/// 以附件形式下载并使用指定文件名的 Content-Disposition
+/// 非 ASCII 文件名按 RFC 5987 放在 `filename*` 中，`filename` 保留 ASCII 兜底
+fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| if c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\' { c } else { '_' })
+        .collect();
+    if fallback == filename {
+        format!("attachment; filename=\"{}\"", filename)
+    } else {
+        format!(
+            "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+            fallback,
+            urlencoding::encode(filename),
+        )
+    }
+}
+
+/// 解析预签名 URL 的有效期（返回秒数），格式同 [`parse_duration`]，如 `3600`、`30m`、`12h`、`7d`
+/// 必须大于 0 且不超过 7 天
+pub fn parse_expires(s: &str) -> Result<u64> {
+    let secs = parse_duration(s)?.as_secs();
+    check_presign_expires(secs)?;
+    Ok(secs)
+}
+
+/// 解析时间点：RFC3339 时间戳（如 `2024-01-01T00:00:00Z`），或者相对于现在的时长（如 `30d` 表示 30 天前）
+pub fn parse_time(s: &str) -> Result<DateTime> {
+    if let Ok(time) = DateTime::from_str(s.trim(), aws_sdk_s3::primitives::DateTimeFormat::DateTime) {
+        return Ok(time);
     }
+    let age = parse_duration(s)
+        .with_context(|| format!("无效的时间: {}（支持 RFC3339 时间戳或 30d/12h 这样的时长）", s))?;
+    let now = std::time::SystemTime::now();
+    Ok(DateTime::from(now.checked_sub(age).unwrap_or(std::time::UNIX_EPOCH)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_library_does_not_print() {
+        // 库只通过 tracing 输出日志，提示信息由命令行程序输出
+        let patterns = [concat!("print", "ln!("), concat!("eprint", "ln!(")];
+        for entry in std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/src")).unwrap() {
+            let path = entry.unwrap().path();
+            if path.file_name().is_some_and(|name| name == "main.rs") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            for pattern in patterns {
+                assert!(!source.contains(pattern), "{} 中使用了 {}", path.display(), pattern);
+            }
+        }
+    }
+
     #[test]
     fn test_oss_config_from_env() {
         // 设置所有必需的环境变量
@@ -338,4 +1193,611 @@ mod tests {
         assert_eq!(config.endpoint, "https://test.endpoint.com");
         assert_eq!(config.region, "test_region");
     }
+
+    /// 不连接网络的客户端，用于测试签名等本地逻辑
+    async fn test_client() -> OssClient {
+        OssClient::new(OssConfig {
+            access_key: "AKIDEXAMPLE".to_string(),
+            secret_key: "secret".to_string(),
+            bucket: "test-bucket".to_string(),
+            endpoint: "https://oss.example.com".to_string(),
+            region: "us-east-1".to_string(),
+            public_base_url: None,
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_generate_url() {
+        let client = test_client().await;
+        assert_eq!(client.generate_url("docs/a b+c.pdf"), "https://test-bucket.oss.example.com/docs/a%20b%2Bc.pdf");
+
+        let mut config = (*client.config).clone();
+        config.public_base_url = Some("https://static.example.com/".to_string());
+        let client = OssClient::new(config).await.unwrap();
+        assert_eq!(client.generate_url("docs/a b.pdf"), "https://static.example.com/docs/a%20b.pdf");
+        // 预签名 URL 的签名包含 host，仍然使用 endpoint
+        let url = client.generate_presigned_url("docs/a.pdf", 60, None, None).await.unwrap();
+        assert!(url.starts_with("https://test-bucket.oss.example.com/docs/a.pdf?"), "{}", url);
+        let mut url = client.public_url("docs/a.pdf", AddressingStyle::Path).unwrap();
+        url.query_pairs_mut().append_pair("v", "1 2");
+        assert_eq!(url.as_str(), "https://oss.example.com/test-bucket/docs/a.pdf?v=1+2");
+        let url = client.public_url("docs/a.pdf", AddressingStyle::VirtualHost).unwrap();
+        assert_eq!(url.as_str(), "https://test-bucket.oss.example.com/docs/a.pdf");
+        assert_eq!(client.public_url("a.pdf", AddressingStyle::CustomDomain).unwrap().as_str(), "https://static.example.com/a.pdf");
+        let client = client.with_bucket("other");
+        assert!(matches!(test_client().await.public_url("a.pdf", AddressingStyle::CustomDomain), Err(OssError::Config(_))));
+        // IP 地址的 endpoint 默认使用 path-style
+        let mut config = (*client.config).clone();
+        config.endpoint = "http://127.0.0.1:9000".to_string();
+        let client = OssClient::new(config).await.unwrap();
+        assert_eq!(client.endpoint_url("a.pdf"), "http://127.0.0.1:9000/other/a.pdf");
+    }
+
+    #[tokio::test]
+    async fn test_presigned_put_url_signs_content_type() {
+        let client = test_client().await;
+        let url = client.generate_presigned_put_url("uploads/a b.png", 900, Some("image/png"), None).await.unwrap();
+        assert!(url.contains("/uploads/a%20b.png?"), "{}", url);
+        assert!(url.contains("X-Amz-Expires=900"), "{}", url);
+        assert!(url.contains("X-Amz-SignedHeaders=content-type%3Bhost"), "{}", url);
+
+        let err = client.generate_presigned_url("a.png", 14 * 86400, None, None).await.unwrap_err();
+        assert!(err.to_string().contains("604800"), "{}", err);
+        assert!(client.generate_presigned_put_url("a.png", 0, None, None).await.is_err());
+        assert!(client.generate_presigned_url("", 60, None, None).await.is_err());
+        assert!(client.generate_presigned_url(&"a".repeat(1025), 60, None, None).await.is_err());
+
+        let url = client.generate_presigned_put_url("a.png", 900, None, None).await.unwrap();
+        assert!(url.contains("X-Amz-SignedHeaders=host"), "{}", url);
+
+        let err = client.generate_presigned_put_url("a.png", 900, None, Some(6 << 30)).await;
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_content_disposition() {
+        assert_eq!(content_disposition("report.pdf"), "attachment; filename=\"report.pdf\"");
+        assert_eq!(
+            content_disposition("报告 2024.pdf"),
+            "attachment; filename=\"__ 2024.pdf\"; filename*=UTF-8''%E6%8A%A5%E5%91%8A%202024.pdf",
+        );
+        assert_eq!(
+            content_disposition("a\"b.txt"),
+            "attachment; filename=\"a_b.txt\"; filename*=UTF-8''a%22b.txt",
+        );
+    }
+
+    #[tokio::test]
+    async fn test_presigned_get_url_overrides_response_headers() {
+        let client = test_client().await;
+        let url = client.generate_presigned_url("a.bin", 60, Some("报告.pdf"), Some("application/pdf")).await.unwrap();
+        assert!(url.contains("response-content-type=application%2Fpdf"), "{}", url);
+        assert!(url.contains("response-content-disposition=attachment"), "{}", url);
+        assert!(url.contains("X-Amz-Signature="), "{}", url);
+    }
+
+    #[test]
+    fn test_client_is_clone_send_sync() {
+        fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+        assert_shareable::<OssClient>();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_uploads_through_clones() {
+        use crate::mock::{mock_client, response};
+        use std::sync::Mutex;
+
+        let keys = Arc::new(Mutex::new(Vec::new()));
+        let log = keys.clone();
+        let client = mock_client(move |req| {
+            let path = req.uri().split('?').next().unwrap();
+            log.lock().unwrap().push(path.rsplit('/').next().unwrap().to_string());
+            response(200, &[("ETag", "\"e\"")], "")
+        })
+        .await;
+
+        let tasks: Vec<_> = (0..20)
+            .map(|i| {
+                let client = client.clone();
+                tokio::spawn(async move { client.upload_bytes(&format!("{}.txt", i), b"x").await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        // 所有 clone 的请求都经过同一个 HTTP 客户端
+        let mut keys = keys.lock().unwrap().clone();
+        let mut expected: Vec<_> = (0..20).map(|i| format!("{}.txt", i)).collect();
+        keys.sort();
+        expected.sort();
+        assert_eq!(keys, expected);
+
+        let narrow = client.clone().with_workers(2);
+        assert_eq!((narrow.workers(), client.workers()), (2, MAX_WORKERS));
+        client.note_throttle("GetObject", "a", Duration::ZERO);
+        assert_eq!(narrow.throttle_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_head_maps_response() {
+        use crate::mock::{mock_client, query, response};
+        let client = mock_client(|req| match query(req) {
+            "versionId=v1" => response(
+                200,
+                &[
+                    ("Content-Length", "42"),
+                    ("ETag", "\"e1\""),
+                    ("Last-Modified", "Tue, 02 Jan 2024 03:04:05 GMT"),
+                    ("Content-Type", "application/zip"),
+                    ("x-amz-storage-class", "STANDARD_IA"),
+                    ("x-amz-server-side-encryption", "AES256"),
+                    ("x-amz-version-id", "v1"),
+                    ("x-amz-meta-b", "2"),
+                    ("x-amz-meta-a", "1"),
+                ],
+                "",
+            ),
+            _ => response(404, &[], ""),
+        })
+        .await;
+
+        // 与上传和下载一样按 KeyPolicy 去掉开头的 /
+        let info = client.head("/a.zip", Some("v1")).await.unwrap();
+        assert_eq!((info.key.as_str(), info.size, info.etag.as_deref()), ("a.zip", 42, Some("\"e1\"")));
+        assert!(client.exists("/a.zip").await.is_ok());
+        assert!(client.head("a\nb.zip", None).await.is_err());
+        assert_eq!((info.storage_class.as_str(), info.sse.as_deref()), ("STANDARD_IA", Some("AES256")));
+        assert_eq!((info.content_type.as_deref(), info.version_id.as_deref()), (Some("application/zip"), Some("v1")));
+        assert_eq!(
+            serde_json::to_value(&info).unwrap(),
+            serde_json::json!({
+                "key": "a.zip",
+                "size": 42,
+                "etag": "\"e1\"",
+                "storage_class": "STANDARD_IA",
+                "last_modified": "2024-01-02T03:04:05Z",
+                "owner": null,
+                "content_type": "application/zip",
+                "sse": "AES256",
+                "metadata": { "a": "1", "b": "2" },
+                "version_id": "v1",
+            })
+        );
+
+        let err = client.head("a.zip", None).await.unwrap_err();
+        assert!(matches!(&err, OssError::NotFound { key } if key == "a.zip"), "{:?}", err);
+        assert!(client.head_if_exists("a.zip").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_download_options_reach_requests() {
+        use crate::mock::{mock_client, query, response};
+        use std::sync::Mutex;
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let client = mock_client(move |req| {
+            let names = ["range", "x-amz-server-side-encryption-customer-algorithm", "x-amz-request-payer"];
+            let headers: Vec<_> = names.iter().map(|name| req.headers().get(*name).unwrap_or("-")).collect();
+            requests.lock().unwrap().push(format!("{} {} {}", req.method(), query(req), headers.join(" ")));
+            // hello 的 MD5
+            response(200, &[("Content-Length", "5"), ("ETag", "\"5d41402abc4b2a76b9719d911017c592\"")], "hello")
+        })
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        let options = DownloadOptions::default()
+            .version_id("v1")
+            .sse_customer_key([7; 32])
+            .request_payer(true)
+            .verify(VerifyMode::Md5);
+        client.download_with_options("a.txt", Some(&path), &options).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"hello");
+        let options = DownloadOptions::default().range(1, Some(3));
+        client.download_with_options("a.txt", Some(&path), &options).await.unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "HEAD versionId=v1 - AES256 requester",
+                "GET x-id=GetObject&versionId=v1 - AES256 requester",
+                "HEAD versionId=v1 - AES256 requester",
+                "GET x-id=GetObject bytes=1-3 - -",
+            ]
+        );
+
+        let options = DownloadOptions::default().overwrite(Overwrite::Never);
+        let err = client.download_with_options("a.txt", Some(&path), &options).await.unwrap_err();
+        assert!(matches!(&err, OssError::LocalIo { source, .. } if source.kind() == std::io::ErrorKind::AlreadyExists));
+        let invalid = [
+            DownloadOptions::default().range(3, Some(1)),
+            DownloadOptions::default().range(0, None).resume(true),
+            DownloadOptions::default().range(0, None).verify(VerifyMode::Size),
+        ];
+        for options in invalid {
+            let err = client.download_with_options("a.txt", Some(&path), &options).await.unwrap_err();
+            assert!(matches!(err, OssError::Config(_)), "{:?}", err);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_download_branches() {
+        use crate::mock::{mock_client, response};
+        use std::sync::Mutex;
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let client = mock_client(move |req| {
+            let header = |name: &str| req.headers().get(name).unwrap_or("-").to_string();
+            if req.method() == "HEAD" {
+                return response(200, &[("Content-Length", "5"), ("ETag", "\"e1\"")], "");
+            }
+            let (range, if_range) = (header("range"), header("if-range"));
+            requests.lock().unwrap().push(format!("{} {}", range, if_range));
+            match (range.as_str(), if_range.as_str()) {
+                ("bytes=3-", "\"e1\"") => {
+                    response(206, &[("Content-Length", "2"), ("Content-Range", "bytes 3-4/5"), ("ETag", "\"e1\"")], "lo")
+                }
+                ("bytes=5-", "\"e1\"") => response(416, &[], "<Error><Code>InvalidRange</Code></Error>"),
+                _ => response(200, &[("Content-Length", "5"), ("ETag", "\"e1\"")], "hello"),
+            }
+        })
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        // 206 追加到已有部分；200 说明对象已变化，从头写入；416 说明部分文件已经完整
+        let cases = [("hel", "\"e1\"", "bytes=3- \"e1\""), ("xyz", "\"old\"", "bytes=3- \"old\""), ("hello", "\"e1\"", "bytes=5- \"e1\"")];
+        for (partial, etag, request) in cases {
+            std::fs::write(dir.path().join("a.txt.part"), partial).unwrap();
+            std::fs::write(dir.path().join("a.txt.part.etag"), etag).unwrap();
+            client.download_with_options("a.txt", Some(&path), &DownloadOptions::default().resume(true)).await.unwrap();
+            assert_eq!(std::fs::read(&path).unwrap(), b"hello");
+            assert_eq!(log.lock().unwrap().drain(..).collect::<Vec<_>>(), [request]);
+            assert!(!dir.path().join("a.txt.part").exists());
+            assert!(!dir.path().join("a.txt.part.etag").exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_ranged_download_skips_finished_parts() {
+        use crate::mock::{mock_client, response};
+        use std::sync::Mutex;
+
+        let part = MIN_PART_SIZE as usize;
+        let data: Arc<Vec<u8>> = Arc::new((0..part * 2 + 100).map(|i| (i % 251) as u8).collect());
+        let size = data.len().to_string();
+        let (log, body) = (Arc::new(Mutex::new(Vec::new())), data.clone());
+        let requests = log.clone();
+        let client = mock_client(move |req| {
+            if req.method() == "HEAD" {
+                requests.lock().unwrap().push("HEAD".to_string());
+                return response(200, &[("Content-Length", size.as_str()), ("ETag", "\"e1\"")], "");
+            }
+            let range = req.headers().get("range").unwrap_or_default().to_string();
+            requests.lock().unwrap().push(range.clone());
+            let (start, end) = range.trim_start_matches("bytes=").split_once('-').unwrap();
+            let (start, end): (usize, usize) = (start.parse().unwrap(), end.parse().unwrap());
+            let content_range = format!("bytes {}-{}/{}", start, end, body.len());
+            response(206, &[("Content-Range", content_range.as_str())], body[start..=end].to_vec())
+        })
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("big.bin");
+        let ranges = dir.path().join("big.bin.part.ranges");
+        let prepare = |header: String| {
+            let mut partial = vec![0; data.len()];
+            partial[..part].copy_from_slice(&data[..part]);
+            partial[part * 2..].copy_from_slice(&data[part * 2..]);
+            std::fs::write(dir.path().join("big.bin.part"), partial).unwrap();
+            std::fs::write(dir.path().join("big.bin.part.etag"), "\"e1\"").unwrap();
+            std::fs::write(&ranges, format!("{}\n0\n2\n", header)).unwrap();
+        };
+        let options = DownloadOptions::default().resume(true);
+
+        // 只下载记录之外的第 2 个分段
+        prepare(format!("{} {}", part, data.len()));
+        client.download_with_options("big.bin", Some(&path), &options).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), *data);
+        assert_eq!(*log.lock().unwrap(), ["HEAD".to_string(), format!("bytes={}-{}", part, part * 2 - 1)]);
+        assert!(!ranges.exists() && !dir.path().join("big.bin.part").exists());
+
+        // 分块大小变化后记录作废，全部重新下载
+        log.lock().unwrap().clear();
+        prepare(format!("{} {}", part * 2, data.len()));
+        client.download_with_options("big.bin", Some(&path), &options).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), *data);
+        assert_eq!(log.lock().unwrap().len(), 4);
+
+        // 传入已有的对象信息时不再 HEAD
+        log.lock().unwrap().clear();
+        let info = client.head("big.bin", None).await.unwrap();
+        log.lock().unwrap().clear();
+        client.download_with_options("big.bin", Some(&path), &DownloadOptions::default().head(info)).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), *data);
+        assert!(log.lock().unwrap().iter().all(|request| request.starts_with("bytes=")));
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_fills_parts_and_aborts_on_failure() {
+        use crate::mock::{mock_client, query, response};
+        use std::sync::Mutex;
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let client = mock_client(move |req| {
+            let len = req.body().bytes().map_or(0, <[u8]>::len);
+            let query = query(req).replace("x-id=UploadPart&", "").replace("x-id=CreateMultipartUpload", "uploads");
+            requests.lock().unwrap().push(format!("{} {} {}", req.method(), query, len));
+            match req.method() {
+                "POST" if query.starts_with("uploads") => response(200, &[], "<InitiateMultipartUploadResult><UploadId>u1</UploadId></InitiateMultipartUploadResult>"),
+                "PUT" if query.starts_with("partNumber=2") => response(403, &[], "<Error><Code>AccessDenied</Code></Error>"),
+                _ => response(200, &[("ETag", "\"e\"")], "<CompleteMultipartUploadResult/>"),
+            }
+        })
+        .await
+        .with_workers(1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.bin");
+        std::fs::write(&path, vec![7u8; 2 * MIN_PART_SIZE as usize + 3]).unwrap();
+        client.upload(&path, "a.bin").await.unwrap_err();
+        // 每个分块都是完整的 5MB（tokio 的 File 单次 read 最多返回 2MB），失败后中止分块上传
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "POST uploads 0".to_string(),
+                format!("PUT partNumber=1&uploadId=u1 {}", MIN_PART_SIZE),
+                format!("PUT partNumber=2&uploadId=u1 {}", MIN_PART_SIZE),
+                "DELETE x-id=AbortMultipartUpload&uploadId=u1 0".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_uploads_share_worker_limit() {
+        use crate::mock::{counting_builder, query, response};
+
+        let handler = |req: &aws_sdk_s3::config::http::HttpRequest| match req.method() {
+            "POST" if query(req).contains("uploads") => response(200, &[], "<InitiateMultipartUploadResult><UploadId>u1</UploadId></InitiateMultipartUploadResult>"),
+            _ => response(200, &[("ETag", "\"e\"")], "<CompleteMultipartUploadResult/>"),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.bin");
+        std::fs::write(&path, vec![7u8; 2 * MIN_PART_SIZE as usize + 3]).unwrap();
+
+        // 4 个上传各有 3 个分块，同时进行时分块请求合计不超过 max_workers
+        let (builder, in_flight) = counting_builder(Duration::from_millis(20), handler);
+        let client = builder.max_workers(2).build().await.unwrap();
+        let uploads = (0..4).map(|i| {
+            let (client, path) = (client.clone(), &path);
+            async move { client.upload(path, &format!("{}.bin", i)).await }
+        });
+        futures::future::try_join_all(uploads).await.unwrap();
+        assert_eq!(in_flight.max(), 2);
+
+        // 单次传输的上限在全局上限之内继续生效
+        let (builder, in_flight) = counting_builder(Duration::from_millis(20), handler);
+        let client = builder.max_workers(4).build().await.unwrap().with_workers(1);
+        client.upload(&path, "a.bin").await.unwrap();
+        assert_eq!(in_flight.max(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_clients_share_rate_limiter() {
+        use crate::mock::{mock_builder, response};
+
+        const SIZE: usize = 192 * 1024;
+        let handler = |req: &aws_sdk_s3::config::http::HttpRequest| match req.method() {
+            "GET" => response(200, &[("Content-Length", &SIZE.to_string())], "x".repeat(SIZE)),
+            _ => response(200, &[("ETag", "\"e\"")], ""),
+        };
+        // 1 MiB/s，只允许 64 KiB 的突发：三个传输合计 576 KiB，至少需要 0.5 秒
+        let limiter: Arc<dyn RateLimiter> = Arc::new(TokenBucket::new(1024 * 1024).burst(64 * 1024));
+        let first = mock_builder(handler).rate_limiter(limiter.clone()).build().await.unwrap();
+        let second = mock_builder(handler).rate_limiter(limiter).build().await.unwrap();
+        let data = vec![7u8; SIZE];
+        let start = std::time::Instant::now();
+        let (mut downloaded, options) = (Vec::new(), DownloadOptions::default());
+        let (a, b, c) = tokio::join!(
+            first.upload_bytes("a.bin", &data),
+            second.upload_bytes("b.bin", &data),
+            second.download_to_writer("c.bin", &mut downloaded, &options),
+        );
+        a.unwrap();
+        b.unwrap();
+        assert_eq!(c.unwrap(), SIZE as u64);
+        assert!(start.elapsed() >= Duration::from_millis(480), "{:?}", start.elapsed());
+
+        // 没有设置限速器的客户端不受影响
+        let unlimited = mock_builder(handler).build().await.unwrap();
+        let start = std::time::Instant::now();
+        unlimited.upload_bytes("a.bin", &data).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(200), "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_aborts_upload_and_removes_download() {
+        use crate::mock::{counting_builder, query, response};
+        use std::sync::Mutex;
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let size = (2 * MIN_PART_SIZE + 3).to_string();
+        let (builder, _) = counting_builder(Duration::from_millis(200), move |req| {
+            requests.lock().unwrap().push(format!("{} {}", req.method(), query(req)));
+            match req.method() {
+                "POST" => response(200, &[], "<InitiateMultipartUploadResult><UploadId>u1</UploadId></InitiateMultipartUploadResult>"),
+                "HEAD" => response(200, &[("Content-Length", &size), ("ETag", "\"e\"")], ""),
+                _ => response(200, &[("ETag", "\"e\"")], ""),
+            }
+        });
+        let client = builder.build().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.bin");
+        std::fs::write(&path, vec![7u8; 2 * MIN_PART_SIZE as usize + 3]).unwrap();
+
+        // 创建分块上传后，分块请求还没有完成时超时
+        let timeout = Duration::from_millis(300);
+        let err = client.upload_with_options(&path, "a.bin", &UploadOptions::default().timeout(timeout)).await.unwrap_err();
+        assert!(matches!(err, OssError::TimedOut { timeout: t } if t == timeout), "{:?}", err);
+        // 机器繁忙时个别分块请求可能在超时前到达，只检查没有完成上传并且中止了它
+        let requests = log.lock().unwrap().clone();
+        assert_eq!(requests.first().map(String::as_str), Some("POST uploads"));
+        assert_eq!(requests.last().map(String::as_str), Some("DELETE x-id=AbortMultipartUpload&uploadId=u1"));
+        assert!(requests[1..requests.len() - 1].iter().all(|r| r.starts_with("PUT x-id=UploadPart")), "{:?}", requests);
+
+        // HEAD 之后分段下载时超时，删除已经创建的文件
+        let output = dir.path().join("out.bin");
+        let options = DownloadOptions::default().timeout(timeout);
+        let err = client.download_with_options("a.bin", Some(&output), &options).await.unwrap_err();
+        assert!(matches!(err, OssError::TimedOut { .. }), "{:?}", err);
+        assert!(!output.exists());
+    }
+
+    #[tokio::test]
+    async fn test_operations_apply_key_policy() {
+        use crate::mock::{mock_builder, response};
+        use std::sync::Mutex;
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let handler = move |req: &aws_sdk_s3::config::http::HttpRequest| {
+            let uri = req.uri().split('?').next().unwrap_or_default();
+            requests.lock().unwrap().push(format!("{} {}", req.method(), uri.trim_start_matches("https://my-bucket.s3.example.com")));
+            response(200, &[("ETag", "\"e\""), ("Content-Length", "0")], "")
+        };
+        let client = mock_builder(handler.clone()).build().await.unwrap();
+        client.upload_bytes("/a.txt", "data").await.unwrap();
+        assert_eq!(client.delete("/a.txt").await.unwrap(), DeleteOutcome::Deleted);
+        // 不符合规则的 key 不会发出请求
+        for key in ["", "a\nb", "//a.txt"] {
+            assert!(matches!(client.upload_bytes(key, "data").await, Err(OssError::Invalid(_))), "{:?}", key);
+            assert!(matches!(client.delete(key).await, Err(OssError::Invalid(_))), "{:?}", key);
+        }
+        assert_eq!(*log.lock().unwrap(), ["PUT /a.txt", "HEAD /a.txt", "DELETE /a.txt"]);
+
+        log.lock().unwrap().clear();
+        let client = mock_builder(handler.clone()).key_policy(KeyPolicy::Normalize).build().await.unwrap();
+        client.copy("logs//a.txt", "/backup//a.txt").await.unwrap();
+        assert!(matches!(client.upload_bytes("a/../b", "data").await, Err(OssError::Invalid(_))));
+        let client = mock_builder(handler).key_policy(KeyPolicy::Raw).build().await.unwrap();
+        client.upload_bytes("/a\tb", "data").await.unwrap();
+        assert_eq!(*log.lock().unwrap(), ["HEAD /logs/a.txt", "PUT /backup/a.txt", "PUT //a%09b"]);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_includes_retries() {
+        use crate::mock::{mock_builder, response};
+        use aws_sdk_s3::config::retry::RetryConfig;
+        use std::sync::atomic::AtomicUsize;
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let client = mock_builder(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            response(503, &[], "<Error><Code>SlowDown</Code></Error>")
+        })
+        .retry(
+            RetryConfig::standard()
+                .with_max_attempts(1000)
+                .with_initial_backoff(Duration::from_millis(20))
+                .with_max_backoff(Duration::from_millis(20)),
+        )
+        .build()
+        .await
+        .unwrap();
+
+        // 每次重试都很快，但加上重试的总时间超过了上限
+        let started = std::time::Instant::now();
+        let options = UploadOptions::default().timeout(Duration::from_millis(300));
+        let err = client.upload_bytes_with_options("a.txt", "data", &options).await.unwrap_err();
+        assert!(matches!(err, OssError::TimedOut { .. }), "{:?}", err);
+        assert!(started.elapsed() < Duration::from_secs(2), "{:?}", started.elapsed());
+        assert!(attempts.load(Ordering::SeqCst) > 1);
+    }
+
+    #[tokio::test]
+    async fn test_exists_does_not_treat_403_as_absent() {
+        use crate::mock::{mock_client, response};
+        let client = mock_client(|req| match req.uri().rsplit('/').next() {
+            _ if req.method() == "DELETE" => response(204, &[], ""),
+            Some("found.txt") => response(200, &[("Content-Length", "1")], ""),
+            Some("missing.txt") => response(404, &[], ""),
+            _ => response(403, &[], ""),
+        })
+        .await;
+        assert!(client.exists("found.txt").await.unwrap());
+        assert!(!client.exists("missing.txt").await.unwrap());
+        let err = client.exists("forbidden.txt").await.unwrap_err();
+        assert!(matches!(err, OssError::AccessDenied(_)), "{:?}", err);
+
+        // delete 依赖 exists：403 时不当作"不存在"，仍然删除，结果为未知
+        assert_eq!(client.delete("missing.txt").await.unwrap(), DeleteOutcome::DidNotExist);
+        assert_eq!(client.delete("forbidden.txt").await.unwrap(), DeleteOutcome::Unknown);
+    }
+
+    fn object(key: &str, secs: i64) -> ObjectInfo {
+        ObjectInfo {
+            key: key.to_string(),
+            size: 0,
+            etag: None,
+            storage_class: "STANDARD".to_string(),
+            last_modified: Some(DateTime::from_secs(secs)),
+            owner: None,
+            content_type: None,
+            sse: None,
+            metadata: HashMap::new(),
+            version_id: None,
+        }
+    }
+
+    #[test]
+    fn test_pick_latest() {
+        let objects = vec![
+            object("backups/db-20240102.sql.gz", 200),
+            object("backups/db-20240103.sql.gz", 100),
+            object("backups/db-20240101.sql.gz", 300),
+        ];
+        assert_eq!(pick_latest(&objects, false).unwrap().key, "backups/db-20240101.sql.gz");
+        assert_eq!(pick_latest(&objects, true).unwrap().key, "backups/db-20240103.sql.gz");
+        assert!(pick_latest(&[], false).is_none());
+    }
+
+    #[test]
+    fn test_pick_latest_tie_uses_key_order() {
+        let objects = vec![object("b", 100), object("c", 100), object("a", 100)];
+        assert_eq!(pick_latest(&objects, false).unwrap().key, "c");
+    }
+
+    #[test]
+    fn test_sidecar_path() {
+        assert_eq!(sidecar_path(Path::new("a/b.bin"), "part"), PathBuf::from("a/b.bin.part"));
+        assert_eq!(sidecar_path(Path::new("b.bin"), "part.etag"), PathBuf::from("b.bin.part.etag"));
+    }
+
+    #[test]
+    fn test_parse_expires() {
+        assert_eq!(parse_expires("3600").unwrap(), 3600);
+        assert_eq!(parse_expires("12h").unwrap(), 12 * 3600);
+        assert_eq!(parse_expires("7d").unwrap(), MAX_PRESIGN_EXPIRES);
+        assert!(parse_expires("0").is_err());
+        let err = parse_expires("14d").unwrap_err();
+        assert!(err.to_string().contains("7 天"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let time = parse_time("2024-01-01T00:00:00Z").unwrap();
+        assert_eq!(time.secs(), 1704067200);
+
+        let now = DateTime::from(std::time::SystemTime::now()).secs();
+        let time = parse_time("1d").unwrap();
+        assert!((now - 86400 - time.secs()).abs() <= 1);
+
+        assert!(parse_time("yesterday").is_err());
+    }
 }
@@ -0,0 +1,249 @@
+//! bucket 生命周期规则（自动过期、转换存储类型、清理未完成的分块上传）
+
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{
+    AbortIncompleteMultipartUpload, BucketLifecycleConfiguration, ExpirationStatus, LifecycleExpiration,
+    LifecycleRuleFilter, NoncurrentVersionExpiration, Transition, TransitionStorageClass,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::{is_not_implemented, OssClient};
+
+/// 一条生命周期规则，文件格式见 [`parse_lifecycle`]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LifecycleRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// 规则作用的 key 前缀，空字符串表示整个 bucket
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// 对象上传多少天后过期删除
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_days: Option<i32>,
+    /// 历史版本变为非当前版本多少天后删除
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noncurrent_expiration_days: Option<i32>,
+    /// 未完成的分块上传在开始多少天后中止
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abort_incomplete_days: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transitions: Vec<LifecycleTransition>,
+}
+
+/// 对象上传多少天后转换到另一种存储类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LifecycleTransition {
+    pub days: i32,
+    pub storage_class: String,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LifecycleFile {
+    rules: Vec<LifecycleRule>,
+}
+
+/// 解析规则文件：`{"rules": [...]}`，与 `lifecycle get --output json` 的输出格式相同
+///
+/// ```json
+/// {"rules": [{"id": "tmp", "prefix": "tmp/", "expiration_days": 7, "abort_incomplete_days": 3}]}
+/// ```
+pub fn parse_lifecycle(json: &str) -> Result<Vec<LifecycleRule>> {
+    let file: LifecycleFile = serde_json::from_str(json).context("无效的生命周期规则文件")?;
+    for (index, rule) in file.rules.iter().enumerate() {
+        rule.validate().with_context(|| format!("rules[{}] 无效", index))?;
+    }
+    Ok(file.rules)
+}
+
+impl LifecycleRule {
+    /// 检查规则至少包含一个动作，且天数都是正数，错误信息中带有出错的字段名
+    pub fn validate(&self) -> Result<()> {
+        let days = [
+            ("expiration_days", self.expiration_days),
+            ("noncurrent_expiration_days", self.noncurrent_expiration_days),
+            ("abort_incomplete_days", self.abort_incomplete_days),
+        ];
+        for (field, value) in days {
+            if let Some(days) = value {
+                if days <= 0 {
+                    bail!("{} 必须大于 0，实际为 {}", field, days);
+                }
+            }
+        }
+        for (index, transition) in self.transitions.iter().enumerate() {
+            if transition.days < 0 {
+                bail!("transitions[{}].days 不能为负数，实际为 {}", index, transition.days);
+            }
+            if transition.storage_class.is_empty() {
+                bail!("transitions[{}].storage_class 不能为空", index);
+            }
+        }
+        if days.iter().all(|(_, value)| value.is_none()) && self.transitions.is_empty() {
+            bail!(
+                "至少需要 expiration_days、noncurrent_expiration_days、abort_incomplete_days 或 transitions 之一"
+            );
+        }
+        Ok(())
+    }
+
+    fn to_sdk(&self) -> Result<aws_sdk_s3::types::LifecycleRule> {
+        let status = if self.enabled { ExpirationStatus::Enabled } else { ExpirationStatus::Disabled };
+        let rule = aws_sdk_s3::types::LifecycleRule::builder()
+            .set_id(self.id.clone())
+            .filter(LifecycleRuleFilter::builder().prefix(&self.prefix).build())
+            .status(status)
+            .set_expiration(self.expiration_days.map(|days| LifecycleExpiration::builder().days(days).build()))
+            .set_noncurrent_version_expiration(
+                self.noncurrent_expiration_days
+                    .map(|days| NoncurrentVersionExpiration::builder().noncurrent_days(days).build()),
+            )
+            .set_abort_incomplete_multipart_upload(
+                self.abort_incomplete_days
+                    .map(|days| AbortIncompleteMultipartUpload::builder().days_after_initiation(days).build()),
+            )
+            .set_transitions(Some(
+                self.transitions
+                    .iter()
+                    .map(|t| {
+                        Transition::builder()
+                            .days(t.days)
+                            .storage_class(TransitionStorageClass::from(t.storage_class.as_str()))
+                            .build()
+                    })
+                    .collect(),
+            ))
+            .build()?;
+        Ok(rule)
+    }
+
+    fn from_sdk(rule: &aws_sdk_s3::types::LifecycleRule) -> Self {
+        #[allow(deprecated)]
+        let prefix = rule.filter().and_then(|f| f.prefix()).or(rule.prefix()).unwrap_or_default();
+        LifecycleRule {
+            id: rule.id().map(str::to_string),
+            prefix: prefix.to_string(),
+            enabled: rule.status() == &ExpirationStatus::Enabled,
+            expiration_days: rule.expiration().and_then(|e| e.days()),
+            noncurrent_expiration_days: rule.noncurrent_version_expiration().and_then(|e| e.noncurrent_days()),
+            abort_incomplete_days: rule.abort_incomplete_multipart_upload().and_then(|a| a.days_after_initiation()),
+            transitions: rule
+                .transitions()
+                .iter()
+                .filter_map(|t| {
+                    Some(LifecycleTransition {
+                        days: t.days()?,
+                        storage_class: t.storage_class()?.as_str().to_string(),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl OssClient {
+    /// 查询当前 bucket 的生命周期规则，没有配置时返回空列表
+    pub async fn get_lifecycle(&self) -> Result<Vec<LifecycleRule>> {
+        match self.client
+            .get_bucket_lifecycle_configuration()
+            .bucket(&self.config.bucket)
+            .send()
+            .await
+        {
+            Ok(resp) => Ok(resp.rules().iter().map(LifecycleRule::from_sdk).collect()),
+            Err(err) if err.code() == Some("NoSuchLifecycleConfiguration") => Ok(Vec::new()),
+            Err(err) if is_not_implemented(&err) => Err(OssError::unsupported("当前服务商不支持生命周期规则", err)),
+            Err(err) => Err(err).with_context(|| format!("无法查询 {} 的生命周期规则", self.config.bucket)),
+        }
+    }
+
+    /// 用 `rules` 替换当前 bucket 的全部生命周期规则，`rules` 为空时删除生命周期配置
+    pub async fn set_lifecycle(&self, rules: &[LifecycleRule]) -> Result<()> {
+        for (index, rule) in rules.iter().enumerate() {
+            rule.validate().with_context(|| format!("rules[{}] 无效", index))?;
+        }
+
+        if rules.is_empty() {
+            self.client
+                .delete_bucket_lifecycle()
+                .bucket(&self.config.bucket)
+                .send()
+                .await
+                .with_context(|| format!("无法删除 {} 的生命周期规则", self.config.bucket))?;
+            return Ok(());
+        }
+
+        let configuration = BucketLifecycleConfiguration::builder()
+            .set_rules(Some(rules.iter().map(LifecycleRule::to_sdk).collect::<Result<_>>()?))
+            .build()?;
+        match self.client
+            .put_bucket_lifecycle_configuration()
+            .bucket(&self.config.bucket)
+            .lifecycle_configuration(configuration)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if is_not_implemented(&err) => Err(OssError::unsupported("当前服务商不支持生命周期规则", err)),
+            Err(err) => Err(err).with_context(|| format!("无法修改 {} 的生命周期规则", self.config.bucket)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lifecycle() {
+        let rules = parse_lifecycle(
+            r#"{"rules": [
+                {"id": "tmp", "prefix": "tmp/", "expiration_days": 7, "abort_incomplete_days": 3},
+                {"prefix": "logs/", "enabled": false, "transitions": [{"days": 30, "storage_class": "GLACIER"}]}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(rules[0].id.as_deref(), Some("tmp"));
+        assert_eq!(rules[0].expiration_days, Some(7));
+        assert!(rules[0].enabled);
+        assert!(!rules[1].enabled);
+        assert_eq!(rules[1].transitions[0].storage_class, "GLACIER");
+    }
+
+    #[test]
+    fn test_parse_lifecycle_errors_name_the_field() {
+        let err = parse_lifecycle(r#"{"rules": [{"prefix": "a/", "expire_days": 7}]}"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("expire_days"), "{:#}", err);
+
+        let err = parse_lifecycle(r#"{"rules": [{"prefix": "a/", "expiration_days": 1}, {"expiration_days": 0}]}"#)
+            .unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("rules[1]") && message.contains("expiration_days"), "{}", message);
+
+        let err = parse_lifecycle(r#"{"rules": [{"prefix": "a/"}]}"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("至少需要"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_sdk_round_trip() {
+        let rule = LifecycleRule {
+            id: Some("tmp".to_string()),
+            prefix: "tmp/".to_string(),
+            enabled: true,
+            expiration_days: Some(7),
+            noncurrent_expiration_days: Some(30),
+            abort_incomplete_days: Some(3),
+            transitions: vec![LifecycleTransition { days: 1, storage_class: "STANDARD_IA".to_string() }],
+        };
+        assert_eq!(LifecycleRule::from_sdk(&rule.to_sdk().unwrap()), rule);
+    }
+}
@@ -0,0 +1,187 @@
+//! 列出对象（ListObjectsV2）
+
+use aws_sdk_s3::types::Object;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+
+use crate::error::{Context, OssError, Result};
+use crate::{ObjectInfo, OssClient};
+
+/// ListObjectsV2 每页最多返回的条目数
+const MAX_KEYS_PER_PAGE: usize = 1000;
+
+/// 列出结果中的一项
+#[derive(Debug, Clone)]
+pub enum ListEntry {
+    /// 对象（ObjectInfo 较大，装箱以免公共前缀也占用同样的空间）
+    Object(Box<ObjectInfo>),
+    /// 使用分隔符时合并的公共前缀（"目录"），以分隔符结尾
+    Prefix(String),
+}
+
+/// 列出选项
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// 分隔符（通常为 `/`），指定时只列出当前层级，更深的 key 合并为 [`ListEntry::Prefix`]
+    pub delimiter: Option<String>,
+    /// 同时返回对象的所有者（ListObjectsV2 默认不返回）
+    pub fetch_owner: bool,
+    /// 只列出 key 按字典序排在它之后的条目，可以用于从上次中断的位置继续
+    pub start_after: Option<String>,
+    /// 最多返回的条目数（对象和公共前缀合计），达到后不再请求后续的页
+    pub max_keys: Option<usize>,
+}
+
+impl ListEntry {
+    /// 对象的 key 或公共前缀
+    pub fn name(&self) -> &str {
+        match self {
+            ListEntry::Object(obj) => &obj.key,
+            ListEntry::Prefix(prefix) => prefix,
+        }
+    }
+}
+
+impl OssClient {
+    /// 列出前缀下的所有对象（自动处理分页）
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<ObjectInfo>> {
+        self.list_objects_stream(prefix).try_collect().await
+    }
+
+    /// 以流的形式列出前缀下的所有对象（不使用分隔符），不会缓存整个列表
+    pub fn list_objects_stream<'a>(&'a self, prefix: &'a str) -> BoxStream<'a, Result<ObjectInfo>> {
+        self.list_stream(prefix, &ListOptions::default())
+            .try_filter_map(|entry| async move {
+                Ok(match entry {
+                    ListEntry::Object(obj) => Some(*obj),
+                    ListEntry::Prefix(_) => None,
+                })
+            })
+            .boxed()
+    }
+
+    /// 以流的形式列出前缀下的对象，每返回一页就可以处理
+    /// 按需拉取下一页（continuation token），提前停止消费时不再发出后续请求。
+    /// 使用分隔符时对象和公共前缀按名字排序交替返回
+    pub fn list_stream<'a>(&'a self, prefix: &'a str, options: &ListOptions) -> BoxStream<'a, Result<ListEntry>> {
+        let pages = self.client
+            .list_objects_v2()
+            .bucket(&self.config.bucket)
+            .prefix(prefix)
+            .set_delimiter(options.delimiter.clone())
+            .fetch_owner(options.fetch_owner)
+            .set_start_after(options.start_after.clone())
+            .set_max_keys(options.max_keys.map(|n| n.min(MAX_KEYS_PER_PAGE) as i32))
+            .into_paginator()
+            .send();
+        let max_keys = options.max_keys.unwrap_or(usize::MAX);
+
+        stream::try_unfold(pages, move |mut pages| async move {
+            let Some(page) = pages.next().await else {
+                return Ok(None);
+            };
+            let page = page.with_context(|| format!("无法列出前缀: {}", prefix))?;
+            let mut entries: Vec<_> = page.contents()
+                .iter()
+                .map(|obj| ListEntry::Object(Box::new(object_info(obj))))
+                .chain(
+                    page.common_prefixes()
+                        .iter()
+                        .filter_map(|p| p.prefix())
+                        .map(|p| ListEntry::Prefix(p.to_string())),
+                )
+                .collect();
+            // 同一页内的对象和公共前缀分别有序，合并后整体按名字排序
+            entries.sort_by(|a, b| a.name().cmp(b.name()));
+            Ok::<_, OssError>(Some((entries, pages)))
+        })
+        .map_ok(|entries| stream::iter(entries.into_iter().map(Ok)))
+        .try_flatten()
+        .take(max_keys)
+        .boxed()
+    }
+}
+
+/// 按目录列出时的前缀：非空且不以 `/` 结尾时补上 `/`，使 `builds` 和 `builds/` 列出同一层级
+pub fn directory_prefix(prefix: &str) -> String {
+    if prefix.is_empty() || prefix.ends_with('/') {
+        prefix.to_string()
+    } else {
+        format!("{}/", prefix)
+    }
+}
+
+pub(crate) fn object_info(obj: &Object) -> ObjectInfo {
+    ObjectInfo {
+        key: obj.key().unwrap_or_default().to_string(),
+        size: obj.size().unwrap_or_default().max(0) as u64,
+        etag: obj.e_tag().map(|s| s.to_string()),
+        storage_class: obj.storage_class()
+            .map(|c| c.as_str().to_string())
+            .unwrap_or_else(|| "STANDARD".to_string()),
+        last_modified: obj.last_modified().cloned(),
+        owner: obj.owner().and_then(|o| o.display_name().or(o.id())).map(|s| s.to_string()),
+        content_type: None,
+        sse: None,
+        metadata: Default::default(),
+        version_id: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{mock_client, query, response};
+    use std::sync::{Arc, Mutex};
+
+    /// 两页的列出结果，记录每次请求的查询字符串
+    async fn paged_client() -> (OssClient, Arc<Mutex<Vec<String>>>) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let client = mock_client(move |req| {
+            let query = query(req).to_string();
+            let body = if query.contains("continuation-token=t1") {
+                "<ListBucketResult><IsTruncated>false</IsTruncated>\
+                 <Contents><Key>logs/c.txt</Key><Size>3</Size></Contents></ListBucketResult>"
+            } else {
+                "<ListBucketResult><IsTruncated>true</IsTruncated><NextContinuationToken>t1</NextContinuationToken>\
+                 <Contents><Key>logs/a.txt</Key><Size>1</Size></Contents>\
+                 <Contents><Key>logs/b.txt</Key><Size>2</Size><StorageClass>GLACIER</StorageClass></Contents>\
+                 <CommonPrefixes><Prefix>logs/2024/</Prefix></CommonPrefixes></ListBucketResult>"
+            };
+            requests.lock().unwrap().push(query);
+            response(200, &[], body)
+        })
+        .await;
+        (client, log)
+    }
+
+    #[tokio::test]
+    async fn test_list_stream_pages() {
+        let (client, log) = paged_client().await;
+        let options = ListOptions { start_after: Some("logs/0".to_string()), ..Default::default() };
+        let entries: Vec<_> = client.list_stream("logs/", &options).try_collect().await.unwrap();
+        let names: Vec<_> = entries.iter().map(ListEntry::name).collect();
+        assert_eq!(names, ["logs/2024/", "logs/a.txt", "logs/b.txt", "logs/c.txt"]);
+        assert!(matches!(&entries[2], ListEntry::Object(obj) if obj.size == 2 && obj.storage_class == "GLACIER"));
+        let requests = log.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("start-after=logs%2F0"), "{}", requests[0]);
+
+        // 达到 max_keys 后不再请求下一页
+        let (client, log) = paged_client().await;
+        let options = ListOptions { max_keys: Some(2), ..Default::default() };
+        let entries: Vec<_> = client.list_stream("logs/", &options).try_collect().await.unwrap();
+        assert_eq!(entries.len(), 2);
+        let requests = log.lock().unwrap().clone();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("max-keys=2"), "{}", requests[0]);
+    }
+
+    #[test]
+    fn test_directory_prefix() {
+        assert_eq!(directory_prefix(""), "");
+        assert_eq!(directory_prefix("builds"), "builds/");
+        assert_eq!(directory_prefix("builds/"), "builds/");
+        assert_eq!(directory_prefix("a/b"), "a/b/");
+    }
+}
@@ -0,0 +1,196 @@
+//! Object Lock：对象的保留期限（GetObjectRetention / PutObjectRetention）和法律保留（Legal Hold）
+//! 锁定状态是按版本记录的，`version_id` 为 None 时操作当前版本
+
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::primitives::{DateTime, DateTimeFormat};
+use aws_sdk_s3::types::{
+    ObjectLockLegalHold, ObjectLockLegalHoldStatus, ObjectLockRetention, ObjectLockRetentionMode,
+};
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::{is_not_found, parse_duration, OssClient};
+
+const LOCK_NOT_ENABLED: &str = "bucket 没有开启 Object Lock（只能在创建 bucket 时开启）";
+
+/// 对象版本的锁定状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectLock {
+    /// GOVERNANCE 或 COMPLIANCE，没有设置保留期限时为 None
+    pub mode: Option<String>,
+    pub retain_until: Option<DateTime>,
+    pub legal_hold: bool,
+}
+
+impl OssClient {
+    /// 查询对象版本的保留期限和法律保留状态
+    pub async fn get_object_lock(&self, key: &str, version_id: Option<&str>) -> Result<ObjectLock> {
+        let retention = match self.client
+            .get_object_retention()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .set_version_id(version_id.map(str::to_string))
+            .send()
+            .await
+        {
+            Ok(resp) => resp.retention().cloned(),
+            Err(err) if err.code() == Some("NoSuchObjectLockConfiguration") => None,
+            Err(err) if is_lock_not_enabled(err.code(), err.message()) => return Err(OssError::unsupported(LOCK_NOT_ENABLED, err)),
+            Err(err) if is_not_found(&err) => return Err(OssError::not_found(key)),
+            Err(err) => return Err(err).with_context(|| format!("无法查询 {} 的保留期限", key)),
+        };
+        let legal_hold = match self.client
+            .get_object_legal_hold()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .set_version_id(version_id.map(str::to_string))
+            .send()
+            .await
+        {
+            Ok(resp) => resp.legal_hold().and_then(|h| h.status()) == Some(&ObjectLockLegalHoldStatus::On),
+            Err(err) if err.code() == Some("NoSuchObjectLockConfiguration") => false,
+            Err(err) => return Err(err).with_context(|| format!("无法查询 {} 的法律保留状态", key)),
+        };
+        Ok(ObjectLock {
+            mode: retention.as_ref().and_then(|r| r.mode()).map(|m| m.as_str().to_string()),
+            retain_until: retention.as_ref().and_then(|r| r.retain_until_date()).cloned(),
+            legal_hold,
+        })
+    }
+
+    /// 设置对象版本的保留期限，`mode` 为 GOVERNANCE 或 COMPLIANCE
+    /// 先在本地检查：COMPLIANCE 的期限不能缩短也不能改为 GOVERNANCE；
+    /// 缩短 GOVERNANCE 的期限需要 `bypass_governance`（还需要 s3:BypassGovernanceRetention 权限）
+    pub async fn set_retention(
+        &self,
+        key: &str,
+        version_id: Option<&str>,
+        mode: &str,
+        retain_until: DateTime,
+        bypass_governance: bool,
+    ) -> Result<()> {
+        let current = self.get_object_lock(key, version_id).await?;
+        check_retention_change(&current, mode, &retain_until, bypass_governance)?;
+
+        let retention = ObjectLockRetention::builder()
+            .mode(ObjectLockRetentionMode::from(mode))
+            .retain_until_date(retain_until)
+            .build();
+        match self.client
+            .put_object_retention()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .set_version_id(version_id.map(str::to_string))
+            .retention(retention)
+            .bypass_governance_retention(bypass_governance)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if is_lock_not_enabled(err.code(), err.message()) => Err(OssError::unsupported(LOCK_NOT_ENABLED, err)),
+            Err(err) => Err(err).with_context(|| format!("无法设置 {} 的保留期限", key)),
+        }
+    }
+
+    /// 开启或关闭对象版本的法律保留，开启期间不能删除或覆盖（与保留期限无关）
+    pub async fn set_legal_hold(&self, key: &str, version_id: Option<&str>, on: bool) -> Result<()> {
+        let status = if on { ObjectLockLegalHoldStatus::On } else { ObjectLockLegalHoldStatus::Off };
+        match self.client
+            .put_object_legal_hold()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .set_version_id(version_id.map(str::to_string))
+            .legal_hold(ObjectLockLegalHold::builder().status(status).build())
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if is_lock_not_enabled(err.code(), err.message()) => Err(OssError::unsupported(LOCK_NOT_ENABLED, err)),
+            Err(err) if is_not_found(&err) => Err(OssError::not_found(key)),
+            Err(err) => Err(err).with_context(|| format!("无法设置 {} 的法律保留", key)),
+        }
+    }
+}
+
+/// 解析保留截止时间：RFC3339 时间戳（如 `2026-01-01T00:00:00Z`），或者从现在开始的时长（如 `365d`）
+pub fn parse_retain_until(s: &str) -> Result<DateTime> {
+    if let Ok(time) = DateTime::from_str(s.trim(), DateTimeFormat::DateTime) {
+        return Ok(time);
+    }
+    let duration = parse_duration(s)
+        .with_context(|| format!("无效的时间: {}（支持 RFC3339 时间戳或 365d 这样的时长）", s))?;
+    Ok(DateTime::from(std::time::SystemTime::now() + duration))
+}
+
+/// 没有开启 Object Lock 的 bucket 返回 InvalidRequest，说明中提到 Object Lock
+fn is_lock_not_enabled(code: Option<&str>, message: Option<&str>) -> bool {
+    code == Some("InvalidRequest") && message.is_some_and(|m| m.contains("Object Lock"))
+}
+
+/// 检查保留期限的修改是否会被服务端拒绝，提前给出说明
+fn check_retention_change(current: &ObjectLock, mode: &str, retain_until: &DateTime, bypass: bool) -> Result<()> {
+    if retain_until.secs() <= DateTime::from(std::time::SystemTime::now()).secs() {
+        bail!("保留截止时间必须晚于现在");
+    }
+    let (Some(current_mode), Some(current_until)) = (current.mode.as_deref(), current.retain_until.as_ref()) else {
+        return Ok(());
+    };
+    // 期限已过的保留不再生效，可以任意设置
+    if current_until.secs() <= DateTime::from(std::time::SystemTime::now()).secs() {
+        return Ok(());
+    }
+    let shortened = retain_until.secs() < current_until.secs();
+    let until = current_until.fmt(DateTimeFormat::DateTime).unwrap_or_default();
+    match current_mode {
+        "COMPLIANCE" if mode != "COMPLIANCE" => {
+            bail!("当前为 COMPLIANCE 模式（保留至 {}），期限内任何人都不能改为 GOVERNANCE", until)
+        }
+        "COMPLIANCE" if shortened => {
+            bail!("当前为 COMPLIANCE 模式，保留期限只能延长不能缩短（当前保留至 {}）", until)
+        }
+        "GOVERNANCE" if (shortened || mode != "GOVERNANCE") && !bypass => bail!(
+            "缩短 GOVERNANCE 模式的保留期限（当前保留至 {}）或修改模式需要加上 --bypass-governance，\
+             并且需要 s3:BypassGovernanceRetention 权限",
+            until,
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock(mode: &str, until: i64) -> ObjectLock {
+        ObjectLock { mode: Some(mode.to_string()), retain_until: Some(DateTime::from_secs(until)), legal_hold: false }
+    }
+
+    #[test]
+    fn test_check_retention_change() {
+        let now = DateTime::from(std::time::SystemTime::now()).secs();
+        let (later, much_later) = (DateTime::from_secs(now + 86400), DateTime::from_secs(now + 2 * 86400));
+        let none = ObjectLock { mode: None, retain_until: None, legal_hold: false };
+        assert!(check_retention_change(&none, "COMPLIANCE", &later, false).is_ok());
+        assert!(check_retention_change(&none, "GOVERNANCE", &DateTime::from_secs(now - 10), false).is_err());
+
+        let compliance = lock("COMPLIANCE", much_later.secs());
+        assert!(check_retention_change(&compliance, "COMPLIANCE", &later, true).is_err());
+        assert!(check_retention_change(&compliance, "GOVERNANCE", &much_later, true).is_err());
+        assert!(check_retention_change(&compliance, "COMPLIANCE", &DateTime::from_secs(now + 3 * 86400), false).is_ok());
+
+        let governance = lock("GOVERNANCE", much_later.secs());
+        assert!(check_retention_change(&governance, "GOVERNANCE", &later, false).is_err());
+        assert!(check_retention_change(&governance, "GOVERNANCE", &later, true).is_ok());
+
+        // 已经过期的 COMPLIANCE 保留不再限制
+        assert!(check_retention_change(&lock("COMPLIANCE", now - 10), "GOVERNANCE", &later, false).is_ok());
+    }
+
+    #[test]
+    fn test_parse_retain_until() {
+        assert_eq!(parse_retain_until("2026-01-01T00:00:00Z").unwrap().secs(), 1767225600);
+        let now = DateTime::from(std::time::SystemTime::now()).secs();
+        let until = parse_retain_until("1d").unwrap().secs();
+        assert!((until - now - 86400).abs() < 5);
+        assert!(parse_retain_until("tomorrow").is_err());
+    }
+}
@@ -1,14 +1,133 @@
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use aws_sdk_s3::primitives::{DateTime, DateTimeFormat};
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use anyhow::Result;
+use futures::{StreamExt, TryStreamExt};
+use qrcode::types::QrError;
+use qrcode::{EcLevel, QrCode};
+use std::cmp::Ordering;
+use tokio_util::sync::CancellationToken;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
-use oss_uploader::{OssClient, OssConfig};
+use oss_uploader::{
+    derive_key_from_path, directory_prefix, format_size, key_filename, needs_rename, parse_cors, parse_duration,
+    parse_expires, parse_lifecycle, parse_metadata, parse_policy, parse_retain_until, parse_size, parse_sse, parse_tag,
+    parse_time, pick_latest, rename_target, AfterUpload, AgeTest, BucketEncryption, BucketVersioning, CompareMode,
+    CorsRule, CreateBucketOutcome, DeleteOutcome, DeletePrefixOptions, DeleteReport, DiffReport, DownloadOptions,
+    FilterVerdict, FindFilter, KeyFilter, KeyPolicy, LifecycleRule, ListEntry, ListOptions, MetadataUpdate,
+    MetricsCounters, MetricsSnapshot, MultipartUpload, ObjectAcl, ObjectInfo, ObjectLock, ObjectUri, ObjectVersion,
+    OssClient, OssConfig, OssError, ProgressEvent, ProgressObserver, RestoreOutcome, RestoreStatus, SelectEvent,
+    SelectOptions, SizeTest, SyncActionKind, SyncDest, SyncOptions, SyncReason, SyncReport, SyncSource, TokenBucket,
+    TreeNode, TreeOptions, UploadCheck, UploadOptions, UploadedParts, Usage, UsageReport, WatchEvent, WatchOptions,
+    CANNED_ACLS, DELETE_BATCH_SIZE,
+};
+
+/// 退出码：没有找到对象
+const EXIT_NOT_FOUND: u8 = 3;
+
+/// exists 的退出码：对象不存在
+const EXIT_ABSENT: u8 = 1;
+
+/// diff 的退出码：本地和远程不一致
+const EXIT_DIFFERENT: u8 = 1;
+
+/// exists 和 diff 的退出码：检查失败（如认证失败、网络错误）
+const EXIT_EXISTS_FAILED: u8 = 2;
+
+/// 按 Ctrl-C 取消传输时的退出码（128 + SIGINT）
+const EXIT_CANCELLED: u8 = 130;
+
+/// 补全脚本调用的命令：`oss-uploader __complete-key <partial>` 列出以 partial 开头的 key 和下一级前缀，
+/// 超时或出错时不输出任何内容；不经过 clap 解析，不出现在帮助和补全中
+const COMPLETE_KEY_COMMAND: &str = "__complete-key";
+
+/// 补全远程 key 的超时，避免没有网络或凭证时卡住 shell
+const COMPLETE_KEY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 补全远程 key 时最多列出的条目数
+const COMPLETE_KEY_LIMIT: usize = 200;
+
+/// 补全时第一个位置参数为 key 或前缀的子命令
+const KEY_COMMANDS: [&str; 9] = ["download", "cat", "ls", "exists", "stat", "url", "delete", "copy", "mv"];
+
+/// ls 排序时缓存的条目超过该数量会提示内存占用
+const SORT_BUFFER_WARNING: usize = 100_000;
+
+/// 需要以指定退出码结束进程的错误
+#[derive(Debug)]
+struct ExitError {
+    code: u8,
+    message: String,
+}
+
+impl ExitError {
+    fn new(code: u8, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    /// 不输出任何错误信息，只设置退出码
+    fn silent(code: u8) -> Self {
+        Self::new(code, "")
+    }
+}
+
+impl std::fmt::Display for ExitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ExitError {}
 
 #[derive(Parser)]
 #[command(name = "oss-uploader")]
 #[command(about = "OSS 上传下载工具 (兼容 S3 API)")]
 #[command(version)]
 struct Cli {
+    /// 分块上传/下载的并发数
+    #[arg(short = 'w', long, global = true, default_value = "10")]
+    workers: usize,
+
+    /// 分块上传/下载的分块大小（如 64M，范围 5M 到 5G），默认 10M
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size)]
+    part_size: Option<u64>,
+
+    /// 所有上传和下载合计的带宽上限（每秒），如 10M、512K；默认不限制
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size)]
+    limit_rate: Option<u64>,
+
+    /// 操作的 bucket，覆盖环境变量 OSS_BUCKET；download、delete、url、stat、copy 的 key
+    /// 也可以写成 s3://bucket/key 或 oss://bucket/key
+    #[arg(long, global = true, value_name = "BUCKET")]
+    bucket: Option<String>,
+
+    /// 公开访问的域名（如 CDN 地址），覆盖环境变量 OSS_PUBLIC_URL；只影响不带签名的 URL
+    #[arg(long, global = true, value_name = "URL")]
+    public_url: Option<String>,
+
+    /// 合并 key 中连续的 /，并拒绝 . 和 .. 段（默认只去掉开头的一个 / 并拒绝控制字符）
+    #[arg(long, global = true, conflicts_with = "no_normalize")]
+    normalize: bool,
+
+    /// 原样使用 key，只检查非空和 1024 字节的上限
+    #[arg(long, global = true)]
+    no_normalize: bool,
+
+    /// 在 stderr 输出库的日志：-v 输出主要步骤，-vv 输出每个分块的详情，-vvv 输出全部；默认只输出警告（如限流重试）
+    /// 需要写在子命令之前，如 `oss-uploader -v upload a.zip`
+    #[arg(short = 'v', long = "verbose", action = clap::ArgAction::Count)]
+    verbosity: u8,
+
     /// 子命令
     #[command(subcommand)]
     command: Commands,
@@ -17,87 +136,4234 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// 上传文件到 OSS
-    Upload {
-        /// 本地文件路径
-        file_path: PathBuf,
+    Upload(UploadArgs),
 
-        /// 远程 key（可选，默认为 <key_prefix>/<filename>）
-        #[arg(short = 'k', long)]
-        key: Option<String>,
+    /// 并发上传多个文件，按完成的顺序输出每个文件的结果
+    UploadMany(UploadManyArgs),
+
+    /// 上传文件并输出预签名下载链接（只输出链接，有效期输出到 stderr）
+    Share {
+        #[command(flatten)]
+        upload: UploadArgs,
+
+        /// 链接有效期，秒数或 30m、12h、7d 这样的时长（默认 1 天，最长 7 天）
+        #[arg(short = 'e', long, default_value = "86400", value_parser = parse_expires)]
+        expires: u64,
+
+        /// 输出形式：URL，或者引号处理好的 curl/wget 下载命令
+        #[arg(long, value_enum, default_value_t = UrlFormat::Plain)]
+        format: UrlFormat,
 
-        /// key 前缀（可选，默认为空，即直接放在根目录）
-        #[arg(short = 'p', long)]
-        key_prefix: Option<String>,
+        #[command(flatten)]
+        qr: QrArgs,
     },
 
     /// 从 OSS 下载文件
     Download {
-        /// 远程 key
+        /// 远程 key（使用 --latest 时为前缀）
         key: String,
 
+        /// 下载前缀下最新的对象
+        #[arg(long)]
+        latest: bool,
+
+        /// 配合 --latest 使用，按 key 的字典序而不是修改时间选择最新的对象
+        #[arg(long, requires = "latest")]
+        by_name: bool,
+
         /// 本地输出路径（可选，默认为 key 的文件名）
         #[arg(short = 'o', long)]
         output: Option<PathBuf>,
+
+        /// 对象超过该大小时下载前需要确认（如 10GB）
+        #[arg(long, value_name = "SIZE", default_value = "10GB", value_parser = parse_size)]
+        confirm_over: u64,
+
+        /// 跳过确认直接下载
+        #[arg(short = 'y', long)]
+        yes: bool,
+
+        /// 确认时估算耗时使用的下载速度（每秒），如 50M；使用 --limit-rate 时取两者中较小的值
+        #[arg(long, value_name = "SIZE", default_value = "10M", value_parser = parse_size)]
+        assumed_speed: u64,
+
+        /// 断点续传（远程对象变化时自动重新下载）
+        #[arg(short = 'c', long = "continue")]
+        resume: bool,
     },
 
-    /// 删除 OSS 上的文件
-    Delete {
+    /// 输出对象内容到 stdout，对象不存在时退出码为 3
+    Cat {
+        /// 远程 key
+        key: String,
+    },
+
+    /// 列出前缀下的对象
+    Ls(LsArgs),
+
+    /// 检查对象是否存在：存在时退出码为 0，不存在为 1，检查失败为 2
+    Exists {
+        /// 远程 key
+        key: String,
+
+        /// 存在时输出大小和修改时间，不存在时输出提示
+        #[arg(short = 'v', long)]
+        verbose: bool,
+    },
+
+    /// 按名字、大小、修改时间、存储类型查找对象，多个条件同时满足
+    Find(FindArgs),
+
+    /// 统计前缀下的对象数和总大小
+    Du {
+        /// key 前缀（可选，默认统计整个 bucket）
+        #[arg(default_value = "")]
+        prefix: String,
+
+        /// 以人类可读的单位显示大小（如 1.50 GB）
+        #[arg(short = 'H', long)]
+        human: bool,
+
+        /// 按前缀之下的前 N 级目录分别统计（0 表示只输出总计）
+        #[arg(long, value_name = "N", default_value = "0")]
+        group_depth: usize,
+
+        /// 按存储类型细分
+        #[arg(long)]
+        by_storage_class: bool,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+
+    /// 用 SQL 查询 CSV/JSON/Parquet 对象（S3 Select），只返回匹配的记录
+    Select {
+        /// 远程 key
+        key: String,
+
+        /// SQL 表达式，表名固定为 S3Object，如 "SELECT s.id FROM S3Object s WHERE s.level = 'ERROR'"
+        #[arg(long)]
+        sql: String,
+
+        /// 输入格式：json 为每行一个 JSON，json-document 为单个 JSON 文档
+        #[arg(long, value_parser = ["csv", "json", "json-document", "parquet"], default_value = "csv")]
+        input_format: String,
+
+        /// 输出格式：json 为每行一条记录
+        #[arg(long, value_parser = ["csv", "json"], default_value = "csv")]
+        output_format: String,
+
+        /// 输入的压缩方式（默认按扩展名判断：.gz 为 gzip，.bz2 为 bzip2）
+        #[arg(long, value_parser = ["none", "gzip", "bzip2"])]
+        compression: Option<String>,
+
+        /// CSV 的第一行不是列名（只能用 s._1、s._2 按位置引用列）
+        #[arg(long)]
+        no_header: bool,
+
+        /// 在 stderr 输出查询进度和扫描、返回的字节数
+        #[arg(short = 'v', long)]
+        verbose: bool,
+    },
+
+    /// 以树形显示前缀下的目录和对象，逐层列出，不会列出整个前缀
+    Tree {
+        /// key 前缀（可选，默认从 bucket 根开始）
+        #[arg(default_value = "")]
+        prefix: String,
+
+        /// 展开的层数
+        #[arg(long, value_name = "N", default_value = "3", value_parser = clap::value_parser!(u32).range(1..))]
+        depth: u32,
+
+        /// 每一层最多显示的条目数，其余的显示为"… 还有 N 项"
+        #[arg(long, value_name = "N", default_value = "50")]
+        max_entries: usize,
+
+        /// 只显示目录
+        #[arg(long)]
+        dirs_only: bool,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+
+    /// 查看对象的元信息（不下载内容），对象不存在时退出码为 3
+    Stat {
+        /// 远程 key
+        key: String,
+
+        /// 查看指定版本（版本 ID 可以从 versions 命令获取）
+        #[arg(long)]
+        version_id: Option<String>,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+
+    /// 恢复归档存储（GLACIER 等）中的对象，恢复完成后才能下载
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Restore {
+        #[command(subcommand)]
+        command: Option<RestoreCommand>,
+
         /// 远程 key
+        #[arg(required = true)]
+        key: Option<String>,
+
+        /// 恢复出的临时副本保留的天数
+        #[arg(long, default_value = "7")]
+        days: i32,
+
+        /// 恢复档位：Expedited 最快最贵，Bulk 最慢最便宜
+        #[arg(long, value_parser = ["Standard", "Expedited", "Bulk"], default_value = "Standard")]
+        tier: String,
+    },
+
+    /// 列出对象的所有版本和删除标记（bucket 需要开启过版本控制）
+    Versions {
+        /// 对象 key 或前缀
+        key: String,
+
+        /// 只列出 key 完全相同的对象的版本
+        #[arg(long)]
+        exact: bool,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+
+    /// 查看或修改对象的标签
+    Tag {
+        #[command(subcommand)]
+        command: TagCommand,
+    },
+
+    /// 查看或修改对象的 ACL
+    Acl {
+        #[command(subcommand)]
+        command: AclCommand,
+    },
+
+    /// 查看或设置对象的保留期限（bucket 需要开启 Object Lock）
+    Lock {
+        #[command(subcommand)]
+        command: LockCommand,
+    },
+
+    /// 开启或关闭对象的法律保留，开启期间对象不能被删除或覆盖
+    LegalHold {
+        #[arg(value_parser = ["on", "off"])]
+        state: String,
+
+        key: String,
+
+        /// 指定版本（默认为当前版本）
+        #[arg(long)]
+        version_id: Option<String>,
+    },
+
+    /// 移动（重命名）对象：服务端复制后删除源对象
+    Mv {
+        /// 源 key（使用 --recursive 时为前缀）
+        src: String,
+
+        /// 目标 key，以 / 结尾时保留源文件名（使用 --recursive 时为前缀）
+        dst: String,
+
+        /// 移动前缀下的所有对象
+        #[arg(short = 'r', long)]
+        recursive: bool,
+    },
+
+    /// 管理未完成的分块上传
+    Uploads {
+        #[command(subcommand)]
+        command: UploadsCommand,
+    },
+
+    /// 原地修改对象的存储类型（服务端复制到自身，保留元数据、标签和 ACL）
+    SetStorageClass {
+        /// 远程 key；使用 --recursive 时为前缀
         key: String,
+
+        /// 新的存储类型，如 STANDARD_IA、GLACIER
+        class: String,
+
+        /// 修改前缀下的所有对象
+        #[arg(short = 'r', long)]
+        recursive: bool,
+    },
+
+    /// 复制文件或对象：src 和 dst 各为本地路径或 s3://bucket/key，按方向上传、下载或服务端复制
+    Cp(CpArgs),
+
+    /// 服务端复制对象，不经过本地；使用 --recursive 时并发复制整个前缀，元数据保持不变
+    Copy {
+        /// 源 key；使用 --recursive 时为源前缀
+        src: String,
+
+        /// 目标 key；使用 --recursive 时为目标前缀
+        dst: String,
+
+        /// 复制前缀下的所有对象
+        #[arg(short = 'r', long)]
+        recursive: bool,
+
+        /// 复制到另一个 bucket（默认为当前 bucket）
+        #[arg(long, value_name = "BUCKET")]
+        dest_bucket: Option<String>,
+
+        /// 只列出源 key 和目标 key 的对应关系，不实际复制
+        #[arg(long, requires = "recursive")]
+        dry_run: bool,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+
+    /// 按顺序把多个对象服务端拼接为一个对象，不经过本地；除最后一个外的源对象不能小于 5 MiB
+    Compose {
+        /// 目标 key
+        dst: String,
+
+        /// 源 key，按顺序拼接
+        #[arg(required = true)]
+        sources: Vec<String>,
+    },
+
+    /// 修改对象的 Content-Type、Cache-Control 和自定义元数据（复制到自身），内容、标签、ACL 和存储类型不变
+    SetMeta(SetMetaArgs),
+
+    /// 批量把一个前缀下的对象服务端移动到新前缀（复制、校验后删除），并发数由 -w 控制，中断后可以继续
+    RenamePrefix {
+        /// 旧前缀
+        old_prefix: String,
+
+        /// 新前缀
+        new_prefix: String,
+
+        /// 只列出将要移动的 key，不实际执行
+        #[arg(long)]
+        dry_run: bool,
+
+        /// 记录已完成复制的 key 的日志文件，中断后使用同一个日志和相同的前缀再次运行不会重复复制（日志属于其他前缀时报错），全部成功后自动删除
+        #[arg(long, value_name = "PATH", default_value = ".rename-prefix.journal")]
+        journal: PathBuf,
+    },
+
+    /// 同步本地目录和远程前缀，只传输新增或变化的文件
+    Sync(SyncArgs),
+
+    /// 监听本地目录，新建或修改的文件写完后自动上传，Ctrl-C 等进行中的上传完成后退出
+    Watch {
+        /// 监听的本地目录（包括子目录）
+        dir: PathBuf,
+
+        /// key 前缀，key 为 <前缀>/<相对路径>
+        #[arg(short = 'p', long, default_value = "")]
+        key_prefix: String,
+
+        /// 文件大小和修改时间保持不变多久后才认为写完，如 2s、1m
+        #[arg(long, default_value = "2s", value_parser = parse_duration)]
+        settle: Duration,
+
+        /// 上传成功后删除本地文件
+        #[arg(long, conflicts_with = "move_to")]
+        delete_after: bool,
+
+        /// 上传成功后把本地文件移动到该目录，保留相对路径
+        #[arg(long, value_name = "DIR")]
+        move_to: Option<PathBuf>,
     },
 
+    /// 比较本地目录和远程前缀：+ 只在本地，- 只在远程，~ 内容不同；一致时退出码为 0，有差异时为 1
+    Diff {
+        /// 本地目录
+        local_dir: PathBuf,
+
+        /// 远程前缀
+        prefix: String,
+
+        /// 只比较匹配的文件（glob，匹配相对路径，可重复）
+        #[arg(long)]
+        include: Vec<String>,
+
+        /// 排除匹配的文件（glob，匹配相对路径，可重复）
+        #[arg(long)]
+        exclude: Vec<String>,
+
+        /// 判断文件是否变化的方式，与 sync 相同
+        #[arg(long, value_name = "MODE", default_value = "size-and-mtime")]
+        compare: CompareMode,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+
+    /// 删除 OSS 上的文件
+    Delete(DeleteArgs),
+
     /// 生成预签名下载 URL（临时访问链接）
     Url {
         /// 远程 key
-        key: String,
+        #[arg(required_unless_present = "from_file", conflicts_with = "from_file")]
+        key: Option<String>,
+
+        /// 批量生成下载链接：每行一个 key，.csv 文件为 key,有效期（有效期可省略，默认同 -e）
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["public", "download_as", "response_content_type", "qr"])]
+        from_file: Option<PathBuf>,
+
+        /// 批量生成时的输出格式
+        #[arg(long, value_enum, default_value_t = UrlListFormat::Csv, requires = "from_file")]
+        output: UrlListFormat,
+
+        /// URL 有效期，秒数或 30m、12h、7d 这样的时长（默认 1 小时，最长 7 天）
+        #[arg(short = 'e', long, default_value = "3600", value_parser = parse_expires)]
+        expires: u64,
+
+        /// get 生成下载链接，put 生成上传链接
+        #[arg(long, value_enum, default_value_t = UrlMethod::Get)]
+        method: UrlMethod,
+
+        /// 上传时必须使用的 Content-Type（只用于 --method put，参与签名）
+        #[arg(long)]
+        content_type: Option<String>,
+
+        /// 浏览器下载时保存的文件名（只用于 --method get）
+        #[arg(long, value_name = "FILENAME")]
+        download_as: Option<String>,
+
+        /// 覆盖下载响应的 Content-Type（只用于 --method get）
+        #[arg(long, value_name = "MIME")]
+        response_content_type: Option<String>,
+
+        /// 输出形式：URL，或者引号处理好的 curl/wget 命令（文件名取自 key）
+        #[arg(long, value_enum, default_value_t = UrlFormat::Plain)]
+        format: UrlFormat,
+
+        /// 输出不带签名的公开 URL（用于公开可读的 bucket，不访问网络）
+        #[arg(long, conflicts_with_all = ["expires", "content_type", "download_as", "response_content_type"])]
+        public: bool,
+
+        #[command(flatten)]
+        qr: QrArgs,
+    },
+
+    /// 生成浏览器表单直传的 POST Policy（输出 JSON：地址、Policy、签名和全部表单字段）
+    PostPolicy {
+        /// 上传的 key，可以包含 ${filename}，此时只限制 key 前缀
+        #[arg(long, value_name = "KEY")]
+        key_prefix: String,
 
-        /// URL 有效期（秒，默认 3600 = 1小时）
+        /// 允许上传的最大文件大小，如 10MB
+        #[arg(long, value_parser = parse_size)]
+        max_size: Option<u64>,
+
+        /// Policy 有效期（秒，默认 3600 = 1小时）
         #[arg(short = 'e', long, default_value = "3600")]
         expires: u64,
     },
+
+    /// 创建 bucket（使用配置的区域）
+    Mb {
+        bucket: String,
+    },
+
+    /// 删除 bucket
+    Rb {
+        bucket: String,
+
+        /// bucket 不为空时先删除其中所有对象和历史版本
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// 查看或修改 bucket 的版本控制
+    Versioning {
+        #[command(subcommand)]
+        command: VersioningCommand,
+    },
+
+    /// 查看或修改 bucket 的生命周期规则
+    Lifecycle {
+        #[command(subcommand)]
+        command: LifecycleCommand,
+    },
+
+    /// 查看或修改 bucket 的跨域（CORS）规则
+    Cors {
+        #[command(subcommand)]
+        command: CorsCommand,
+    },
+
+    /// 查看或修改 bucket 的默认服务端加密
+    Encryption {
+        #[command(subcommand)]
+        command: EncryptionCommand,
+    },
+
+    /// 查看或修改 bucket policy
+    Policy {
+        #[command(subcommand)]
+        command: PolicyCommand,
+    },
+
+    /// 输出 shell 补全脚本，如 `source <(oss-uploader completions bash)`；bash 和 zsh 中还可以补全远程 key
+    Completions {
+        /// shell 类型
+        shell: Shell,
+    },
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// 输出格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// 人类可读的文本
+    Text,
+    /// JSON
+    Json,
+}
 
-    // 从环境变量读取配置
-    let config = OssConfig::from_env()
-        .map_err(|e| anyhow::anyhow!("配置错误: {}\n请确保设置了必需的环境变量", e))?;
+/// 时间显示方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum TimeStyle {
+    /// 本地时间，如 2024-01-02 15:04:05
+    Iso,
+    /// 相对时间，如 3天前
+    Relative,
+}
+
+/// ls 排序方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum SortKey {
+    /// 按 key
+    Name,
+    /// 按大小
+    Size,
+    /// 按修改时间
+    Mtime,
+}
 
-    // 创建客户端
-    let client = OssClient::new(config).await?;
+/// 预签名 URL 的请求方法
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum UrlMethod {
+    /// 下载（GetObject）
+    Get,
+    /// 上传（PutObject）
+    Put,
+}
 
-    match cli.command {
-        Commands::Upload { file_path, key, key_prefix } => {
-            let key = key.unwrap_or_else(|| {
-                let filename = file_path.file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy();
-                match key_prefix {
-                    Some(prefix) => format!("{}/{}", prefix.trim_end_matches('/'), filename),
-                    None => filename.to_string(),
-                }
-            });
+/// 预签名 URL 的输出形式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum UrlFormat {
+    /// 只输出 URL
+    Plain,
+    /// 可以直接运行的 curl 命令
+    Curl,
+    /// 可以直接运行的 wget 命令
+    Wget,
+}
+
+#[derive(Args)]
+struct LsArgs {
+    /// key 前缀（可选，默认为 bucket 根目录）；按目录列出时会自动补上结尾的 /
+    #[arg(default_value = "")]
+    prefix: String,
+
+    /// 递归列出前缀下的所有对象，而不是只列出当前层级
+    #[arg(short = 'r', long)]
+    recursive: bool,
+
+    /// 长格式：大小、本地修改时间、存储类型、ETag 和所有者
+    #[arg(short = 'l', long)]
+    long: bool,
 
-            println!("开始上传 {} ...", file_path.display());
-            let url = client.upload(&file_path, &key).await?;
-            println!("成功上传 {}\n下载 url:\n{}", file_path.display(), url);
+    /// 长格式中修改时间的显示方式
+    #[arg(long, value_enum, default_value_t = TimeStyle::Iso, requires = "long")]
+    time_style: TimeStyle,
+
+    /// 输出格式，json 时每行一个 JSON 对象（JSON Lines）
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with = "long")]
+    output: OutputFormat,
+
+    /// 只输出完整的 key，以 NUL 分隔（配合 xargs -0）
+    #[arg(short = '0', long, conflicts_with_all = ["long", "output"])]
+    null: bool,
+
+    /// 排序方式（需要先拉取整个列表再输出）
+    #[arg(long, value_enum)]
+    sort: Option<SortKey>,
+
+    /// 逆序输出
+    #[arg(long)]
+    reverse: bool,
+
+    /// 只列出不小于该大小的对象（如 1MB）
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    min_size: Option<u64>,
+
+    /// 只列出不大于该大小的对象（如 1GB）
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    max_size: Option<u64>,
+
+    /// 只列出晚于该时间修改的对象：时长（如 7d、12h）或 RFC3339 时间戳
+    #[arg(long, value_name = "AGE", value_parser = parse_time)]
+    newer_than: Option<DateTime>,
+
+    /// 只列出早于该时间修改的对象：时长（如 30d）或 RFC3339 时间戳
+    #[arg(long, value_name = "AGE", value_parser = parse_time)]
+    older_than: Option<DateTime>,
+
+    /// 最多列出的对象数量
+    #[arg(long, value_name = "N")]
+    max: Option<usize>,
+
+    /// 只列出 key 排在它之后的条目（字典序），可以接着上次输出的最后一个 key 继续
+    #[arg(long, value_name = "KEY")]
+    start_after: Option<String>,
+}
+
+impl LsArgs {
+    /// 对象是否满足所有过滤条件（多个条件同时满足）
+    fn matches(&self, obj: &ObjectInfo) -> bool {
+        self.min_size.is_none_or(|min| obj.size >= min)
+            && self.max_size.is_none_or(|max| obj.size <= max)
+            && self.newer_than.as_ref().is_none_or(|t| obj.last_modified.as_ref().is_some_and(|m| m > t))
+            && self.older_than.as_ref().is_none_or(|t| obj.last_modified.as_ref().is_some_and(|m| m < t))
+    }
+}
+
+#[derive(Args)]
+struct FindArgs {
+    /// key 前缀（可选，默认查找整个 bucket）
+    #[arg(default_value = "")]
+    prefix: String,
+
+    /// 文件名（key 的最后一段）匹配 glob，如 '*.log'
+    #[arg(long, value_name = "PATTERN")]
+    name: Option<String>,
+
+    /// 大小：+100M 大于，-1K 小于，0 等于（可重复）
+    #[arg(long, value_name = "[+-]SIZE", allow_hyphen_values = true)]
+    size: Vec<SizeTest>,
+
+    /// 修改时间距今：-7d 7 天内，+30d 30 天前（可重复）
+    #[arg(long, value_name = "[+-]AGE", allow_hyphen_values = true)]
+    mtime: Vec<AgeTest>,
+
+    /// 存储类型（不区分大小写），如 GLACIER
+    #[arg(long, value_name = "CLASS")]
+    storage_class: Option<String>,
+
+    /// 每个结果的输出格式，可用 {key} {size} {mtime} {etag} {storage_class}，默认只输出 key
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "output")]
+    exec_template: Option<String>,
+
+    /// 输出格式，json 时每行一个 JSON 对象（JSON Lines）
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+#[derive(Args)]
+struct CpArgs {
+    /// 源：本地路径，或 s3://bucket/key（oss:// 也可以）
+    src: String,
+
+    /// 目标：本地路径，或 s3://bucket/key；以 / 结尾或为已有目录时保留源的文件名
+    dst: String,
+
+    /// 复制目录或前缀下的所有文件
+    #[arg(short = 'r', long)]
+    recursive: bool,
+
+    /// 只复制匹配的文件（glob，匹配相对路径，可重复）
+    #[arg(long, requires = "recursive")]
+    include: Vec<String>,
+
+    /// 排除匹配的文件（glob，匹配相对路径，可重复）
+    #[arg(long, requires = "recursive")]
+    exclude: Vec<String>,
+
+    /// 只列出将要复制的文件，不实际执行
+    #[arg(long)]
+    dry_run: bool,
+
+    /// 使用 --recursive 时的输出格式
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, requires = "recursive")]
+    output: OutputFormat,
+}
+
+#[derive(Args)]
+struct SyncArgs {
+    /// 源：本地目录（使用 --download 时为远程前缀）
+    src: String,
+
+    /// 目标：远程前缀（使用 --download 时为本地目录）
+    dst: String,
+
+    /// 从远程前缀同步到本地目录
+    #[arg(long)]
+    download: bool,
+
+    /// 只同步匹配的文件（glob，匹配相对路径，可重复）
+    #[arg(long)]
+    include: Vec<String>,
+
+    /// 排除匹配的文件（glob，匹配相对路径，可重复），被排除的文件两边都不会被改动
+    #[arg(long)]
+    exclude: Vec<String>,
+
+    /// 判断文件是否变化的方式：size、size-and-mtime、etag（单次上传对象比较 MD5，分块上传的只比较大小）、
+    /// hash（比较元数据 x-amz-meta-content-sha256，没有时按 etag；上传时写入该元数据）
+    #[arg(long, value_name = "MODE", default_value = "size-and-mtime")]
+    compare: CompareMode,
+
+    /// 输出使用的比较方式等详细信息
+    #[arg(short = 'v', long)]
+    verbose: bool,
+
+    /// 删除目标端多出的文件（默认不删除）
+    #[arg(long)]
+    delete: bool,
+
+    /// 只列出将要传输和删除的文件，不实际执行
+    #[arg(long)]
+    dry_run: bool,
+
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+/// `url --from-file` 的输出格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum UrlListFormat {
+    /// key,url,expires_at,error 四列，第一行为表头
+    Csv,
+    /// JSON 数组
+    Json,
+}
+
+/// 批量生成的一个链接，失败时 url 为空并记录 error
+#[derive(Debug, serde::Serialize)]
+struct PresignedEntry {
+    key: String,
+    url: Option<String>,
+    expires_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// 解析 key 列表：每行一个 key（忽略空行）；CSV 清单每行为 key,有效期，有效期省略时为 None，
+/// 第一行为 key,expires 表头时跳过
+fn parse_key_list(text: &str, csv: bool) -> Result<Vec<(String, Option<u64>)>> {
+    let mut entries = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
         }
-        
-        Commands::Download { key, output } => {
-            client.download(&key, output.as_deref()).await?;
+        if !csv {
+            entries.push((line.to_string(), None));
+            continue;
         }
-        
-        Commands::Delete { key } => {
-            client.delete(&key).await?;
+        let fields = split_csv_line(line);
+        if index == 0 && fields.first().map(String::as_str) == Some("key") {
+            continue;
         }
+        let expires = match fields.get(1).map(|s| s.trim()) {
+            None | Some("") => None,
+            Some(expires) => Some(parse_expires(expires).map_err(|e| anyhow::anyhow!("第 {} 行: {:#}", index + 1, e))?),
+        };
+        if fields.len() > 2 {
+            anyhow::bail!("第 {} 行: 最多两列（key,有效期），key 中有逗号时需要加双引号", index + 1);
+        }
+        entries.push((fields.into_iter().next().unwrap_or_default(), expires));
+    }
+    Ok(entries)
+}
 
-        Commands::Url { key, expires } => {
-            let url = client.generate_presigned_url(&key, expires).await?;
-            println!("{}", url);
+/// 解析上传清单：每行一个本地路径（忽略空行）；CSV 清单每行为 本地路径,key，
+/// 第一行为 path,key 表头时跳过；key 省略时为 <key_prefix>/<文件名>
+fn parse_upload_manifest(text: &str, csv: bool, key_prefix: Option<&str>) -> Result<Vec<(PathBuf, String)>> {
+    let mut items = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = if csv { split_csv_line(line) } else { vec![line.to_string()] };
+        if csv && index == 0 && fields.first().map(String::as_str) == Some("path") {
+            continue;
         }
+        if fields.len() > 2 {
+            anyhow::bail!("第 {} 行: 最多两列（本地路径,key），路径中有逗号时需要加双引号", index + 1);
+        }
+        let mut fields = fields.into_iter();
+        let path = PathBuf::from(fields.next().unwrap_or_default());
+        let key = match fields.next().filter(|key| !key.trim().is_empty()) {
+            Some(key) => key,
+            None => derive_key_from_path(&path, key_prefix).map_err(|e| anyhow::anyhow!("第 {} 行: {}", index + 1, e))?,
+        };
+        items.push((path, key));
     }
+    Ok(items)
+}
 
-    Ok(())
+/// 按 CSV 规则拆分一行：字段可以用双引号包围，引号内的 "" 表示一个双引号
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+    fields
+}
+
+/// 把命令参数中 s3:// 或 oss:// 形式的地址换成 key，返回这次使用的 bucket（没有 --bucket 和地址时为 None）。
+/// copy 的目标地址中的 bucket 作为 --dest-bucket；地址中的 bucket 与 --bucket 或其他地址不一致时报错；cp 的两端各自带 bucket，由 cp 自己解析
+fn take_uri_buckets(command: &mut Commands, bucket: Option<String>) -> Result<Option<String>> {
+    let mut source = bucket.map(|bucket| (bucket, "--bucket".to_string()));
+    match command {
+        Commands::Download { key, .. } | Commands::Stat { key, .. } | Commands::Url { key: Some(key), .. } => {
+            take_uri_bucket(key, &mut source)?;
+        }
+        Commands::Delete(args) => {
+            for key in &mut args.keys {
+                take_uri_bucket(key, &mut source)?;
+            }
+        }
+        Commands::Copy { src, dst, dest_bucket, .. } => {
+            take_uri_bucket(src, &mut source)?;
+            let mut target = dest_bucket.take().map(|bucket| (bucket, "--dest-bucket".to_string()));
+            take_uri_bucket(dst, &mut target)?;
+            *dest_bucket = target.map(|(bucket, _)| bucket);
+        }
+        _ => {}
+    }
+    Ok(source.map(|(bucket, _)| bucket))
+}
+
+/// `key` 是对象地址时换成其中的 key；`source` 为已经确定的 bucket 和它的来源
+fn take_uri_bucket(key: &mut String, source: &mut Option<(String, String)>) -> Result<()> {
+    let Some(uri) = ObjectUri::parse(key)? else {
+        return Ok(());
+    };
+    match source {
+        Some((bucket, from)) if *bucket != uri.bucket => {
+            anyhow::bail!("{} 中的 bucket {} 与 {} 指定的 {} 不一致", key, uri.bucket, from, bucket)
+        }
+        Some(_) => {}
+        None => *source = Some((uri.bucket, key.clone())),
+    }
+    *key = uri.key;
+    Ok(())
+}
+
+/// CSV 字段：包含逗号、引号或换行时加双引号
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 为 key 列表中的每个 key 生成下载链接，单个 key 失败不影响其他 key
+async fn url_list_command(client: &OssClient, path: &Path, default_expires: u64, output: UrlListFormat) -> Result<()> {
+    let text = std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("无法读取 {}: {}", path.display(), e))?;
+    let csv = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let keys = parse_key_list(&text, csv).map_err(|e| anyhow::anyhow!("{} 格式错误: {:#}", path.display(), e))?;
+
+    let mut entries = Vec::new();
+    for (key, expires) in keys {
+        let expires = expires.unwrap_or(default_expires);
+        let entry = match client.generate_presigned_url(&key, expires, None, None).await {
+            Ok(url) => {
+                let expires_at = DateTime::from_secs(DateTime::from(SystemTime::now()).secs() + expires as i64);
+                PresignedEntry { key, url: Some(url), expires_at: expires_at.fmt(DateTimeFormat::DateTime).ok(), error: None }
+            }
+            Err(e) => PresignedEntry { key, url: None, expires_at: None, error: Some(format!("{:#}", e)) },
+        };
+        entries.push(entry);
+    }
+    note_presigned_host(client);
+
+    match output {
+        UrlListFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+        UrlListFormat::Csv => {
+            println!("key,url,expires_at,error");
+            for entry in &entries {
+                let field = |value: &Option<String>| csv_field(value.as_deref().unwrap_or_default());
+                println!("{},{},{},{}", csv_field(&entry.key), field(&entry.url), field(&entry.expires_at), field(&entry.error));
+            }
+        }
+    }
+
+    let failed: Vec<_> = entries.iter().filter(|entry| entry.error.is_some()).collect();
+    for entry in &failed {
+        eprintln!("{}: {}", entry.key, entry.error.as_deref().unwrap_or_default());
+    }
+    if !failed.is_empty() {
+        return Err(ExitError::new(1, format!("{} 个 key 生成链接失败，其余 {} 个成功", failed.len(), entries.len() - failed.len())).into());
+    }
+    Ok(())
+}
+
+async fn upload_many_command(client: &OssClient, args: UploadManyArgs) -> Result<()> {
+    let items = match &args.from_file {
+        Some(path) => {
+            let text = std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("无法读取 {}: {}", path.display(), e))?;
+            let csv = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+            parse_upload_manifest(&text, csv, args.key_prefix.as_deref())
+                .map_err(|e| anyhow::anyhow!("{} 格式错误: {:#}", path.display(), e))?
+        }
+        None => args
+            .files
+            .iter()
+            .map(|path| Ok((path.clone(), derive_key_from_path(path, args.key_prefix.as_deref())?)))
+            .collect::<oss_uploader::Result<_>>()?,
+    };
+
+    let total = items.len();
+    let options = UploadOptions::default().cancel(cancel_on_ctrl_c());
+    let mut results = std::pin::pin!(client.upload_many(futures::stream::iter(items), args.jobs, &options));
+    let (mut bytes, mut failed) = (0, 0);
+    while let Some(item) = results.next().await {
+        match item.result {
+            Ok(result) => {
+                bytes += result.size;
+                println!("{}\t{}", item.key, result.url);
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("{} 上传失败: {:#}", item.path.display(), e);
+            }
+        }
+    }
+    eprintln!("上传了 {} 个文件（{}）", total - failed, format_size(bytes));
+    if failed > 0 {
+        return Err(ExitError::new(1, format!("{} 个文件上传失败，其余 {} 个成功", failed, total - failed)).into());
+    }
+    Ok(())
+}
+
+/// 以二维码输出链接
+#[derive(Args)]
+struct QrArgs {
+    /// 在终端显示链接的二维码（输出到 stderr）
+    #[arg(long)]
+    qr: bool,
+
+    /// 把二维码保存为 PNG 图片
+    #[arg(long, value_name = "PATH")]
+    qr_file: Option<PathBuf>,
+
+    /// 只输出二维码，stdout 不输出链接（需要 --qr 或 --qr-file）
+    #[arg(long)]
+    qr_only: bool,
+}
+
+impl QrArgs {
+    /// 按选项显示或保存二维码
+    fn show(&self, url: &str) -> Result<()> {
+        if !self.qr && self.qr_file.is_none() {
+            return Ok(());
+        }
+        let (code, downgraded) = qr_code(url)?;
+        if downgraded {
+            eprintln!("警告: 链接太长，二维码改用低纠错级别（L），打印或屏幕不清晰时可能无法识别");
+        }
+        if self.qr {
+            let image = code
+                .render::<qrcode::render::unicode::Dense1x2>()
+                .dark_color(qrcode::render::unicode::Dense1x2::Light)
+                .light_color(qrcode::render::unicode::Dense1x2::Dark)
+                .build();
+            eprintln!("{}", image);
+        }
+        if let Some(path) = &self.qr_file {
+            code.render::<image::Luma<u8>>()
+                .min_dimensions(300, 300)
+                .build()
+                .save(path)
+                .map_err(|e| anyhow::anyhow!("无法保存二维码到 {}: {}", path.display(), e))?;
+            eprintln!("二维码已保存到 {}", path.display());
+        }
+        Ok(())
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.qr_only && !self.qr && self.qr_file.is_none() {
+            anyhow::bail!("--qr-only 需要同时指定 --qr 或 --qr-file");
+        }
+        Ok(())
+    }
+}
+
+/// 生成二维码，默认使用 M 级纠错；超出容量时降为 L 级，返回是否降级
+fn qr_code(url: &str) -> Result<(QrCode, bool)> {
+    match QrCode::with_error_correction_level(url, EcLevel::M) {
+        Ok(code) => Ok((code, false)),
+        Err(QrError::DataTooLong) => match QrCode::with_error_correction_level(url, EcLevel::L) {
+            Ok(code) => Ok((code, true)),
+            Err(_) => anyhow::bail!("链接太长（{} 字节），超出了二维码的最大容量", url.len()),
+        },
+        Err(e) => anyhow::bail!("无法生成二维码: {}", e),
+    }
+}
+
+#[derive(Args)]
+struct UploadArgs {
+    /// 本地文件路径
+    file_path: PathBuf,
+
+    /// 远程 key（可选，默认为 <key_prefix>/<filename>）
+    #[arg(short = 'k', long)]
+    key: Option<String>,
+
+    /// key 前缀（可选，默认为空，即直接放在根目录）
+    #[arg(short = 'p', long)]
+    key_prefix: Option<String>,
+
+    /// 上传后确认远程大小与本地一致，并显示实际使用的服务端加密
+    #[arg(long)]
+    verify: bool,
+}
+
+impl UploadArgs {
+    /// 远程 key：指定了 --key 时直接使用，否则为 <key_prefix>/<filename>
+    fn key(&self) -> oss_uploader::Result<String> {
+        match &self.key {
+            Some(key) => Ok(key.clone()),
+            None => derive_key_from_path(&self.file_path, self.key_prefix.as_deref()),
+        }
+    }
+}
+
+#[derive(Args)]
+struct UploadManyArgs {
+    /// 本地文件，key 为 <key_prefix>/<文件名>
+    #[arg(required_unless_present = "from_file", conflicts_with = "from_file")]
+    files: Vec<PathBuf>,
+
+    /// key 前缀（可选，默认为空，即直接放在根目录）
+    #[arg(short = 'p', long)]
+    key_prefix: Option<String>,
+
+    /// 清单文件：每行一个本地路径；.csv 文件每行为 本地路径,key，key 省略时同上
+    #[arg(long, value_name = "PATH")]
+    from_file: Option<PathBuf>,
+
+    /// 同时上传的文件数（默认同 --workers）
+    #[arg(short = 'j', long, default_value_t = 0, hide_default_value = true)]
+    jobs: usize,
+}
+
+#[derive(Args)]
+struct SetMetaArgs {
+    /// 远程 key；使用 --recursive 时为前缀
+    key: String,
+
+    /// 新的 Content-Type
+    #[arg(long)]
+    content_type: Option<String>,
+
+    /// 新的 Cache-Control，如 max-age=3600
+    #[arg(long)]
+    cache_control: Option<String>,
+
+    /// 添加或覆盖自定义元数据 key=value（可重复），其他已有的元数据保留
+    #[arg(short = 'm', long = "metadata", value_name = "KEY=VALUE", value_parser = parse_metadata)]
+    metadata: Vec<(String, String)>,
+
+    /// 修改前缀下的所有对象
+    #[arg(short = 'r', long)]
+    recursive: bool,
+
+    /// 只修改匹配的 key（glob，匹配相对于前缀的部分，可重复）
+    #[arg(long, requires = "recursive")]
+    include: Vec<String>,
+
+    /// 排除匹配的 key（glob，匹配相对于前缀的部分，可重复）
+    #[arg(long, requires = "recursive")]
+    exclude: Vec<String>,
+
+    /// 只列出将要修改的 key，不实际执行
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+struct DeleteArgs {
+    /// 远程 key，可以指定多个（使用 DeleteObjects 批量删除）；使用 --recursive 时为前缀（按目录处理，logs 与 logs/ 相同）
+    #[arg(required_unless_present_any = ["from_file", "from_stdin"])]
+    keys: Vec<String>,
+
+    /// 从文件读取要删除的 key（每行一个，忽略空行和 # 开头的注释）
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["keys", "from_stdin", "recursive"])]
+    from_file: Option<PathBuf>,
+
+    /// 从标准输入读取要删除的 key（格式同 --from-file）
+    #[arg(long, conflicts_with_all = ["keys", "recursive"])]
+    from_stdin: bool,
+
+    /// 递归删除前缀下的所有对象
+    #[arg(short = 'r', long)]
+    recursive: bool,
+
+    /// 跳过确认直接删除
+    #[arg(short = 'y', long)]
+    yes: bool,
+
+    /// 允许使用空前缀递归删除整个 bucket
+    #[arg(long, requires = "recursive")]
+    i_know_this_deletes_everything: bool,
+
+    /// 递归删除时排除匹配的 key（glob，匹配相对于前缀的部分，可重复）
+    #[arg(long, requires = "recursive")]
+    exclude: Vec<String>,
+
+    /// 递归删除时只删除匹配的 key（glob，匹配相对于前缀的部分，可重复）
+    #[arg(long, requires = "recursive")]
+    include: Vec<String>,
+
+    /// 永久删除指定版本（只能删除单个 key）
+    #[arg(long, conflicts_with_all = ["recursive", "from_file", "from_stdin"])]
+    version_id: Option<String>,
+
+    /// 永久删除 key 的所有版本和删除标记（只能删除单个 key）
+    #[arg(long, conflicts_with_all = ["recursive", "from_file", "from_stdin", "version_id"])]
+    purge: bool,
+
+    /// 严格模式：key 不存在时报错（退出码 3），而不是当作删除成功
+    #[arg(long, conflicts_with_all = ["recursive", "purge", "version_id", "from_file", "from_stdin"])]
+    strict: bool,
+
+    /// 逐个确认是否删除（类似 rm -i），回答 y/n/a(全部)/q(退出)
+    #[arg(short = 'i', long, conflicts_with_all = ["yes", "from_file", "from_stdin", "purge", "version_id", "dry_run"])]
+    interactive: bool,
+
+    /// 递归删除时只删除早于该时间的对象：时长（如 30d、12h）或 RFC3339 时间戳。
+    /// 注意比较的是 LastModified，即对象上传的时间，而不是内容本身的时间
+    #[arg(long, value_name = "AGE", requires = "recursive", value_parser = parse_time)]
+    older_than: Option<DateTime>,
+
+    /// 只列出将要删除的 key，不实际删除
+    #[arg(long)]
+    dry_run: bool,
+
+    /// 输出格式
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+#[derive(Subcommand)]
+enum UploadsCommand {
+    /// 列出进行中的分块上传
+    Ls {
+        /// 只列出该前缀下的 key
+        #[arg(default_value = "")]
+        prefix: String,
+
+        /// 同时显示已上传的分块数和字节数（每个上传需要额外的 ListParts 请求）
+        #[arg(long)]
+        show_parts: bool,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// 中止分块上传，丢弃已上传的分块
+    Abort {
+        key: String,
+
+        /// 要中止的 upload id
+        #[arg(long, required_unless_present = "all_for_key", conflicts_with = "all_for_key")]
+        upload_id: Option<String>,
+
+        /// 中止该 key 所有进行中的分块上传
+        #[arg(long)]
+        all_for_key: bool,
+
+        /// 跳过确认直接中止
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum RestoreCommand {
+    /// 查看恢复进度
+    Status {
+        key: String,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagCommand {
+    /// 列出对象的标签
+    Get {
+        key: String,
+
+        /// 指定版本（默认为当前版本）
+        #[arg(long)]
+        version_id: Option<String>,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// 设置对象的标签（默认替换全部标签）
+    Set {
+        key: String,
+
+        /// 标签，格式为 key=value
+        #[arg(required = true, value_parser = parse_tag)]
+        tags: Vec<(String, String)>,
+
+        /// 与已有标签合并（同名的标签被覆盖），而不是替换全部标签
+        #[arg(long)]
+        merge: bool,
+
+        /// 指定版本（默认为当前版本）
+        #[arg(long)]
+        version_id: Option<String>,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// 删除对象的全部标签
+    Delete {
+        key: String,
+
+        /// 指定版本（默认为当前版本）
+        #[arg(long)]
+        version_id: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AclCommand {
+    /// 列出对象的所有者和授权
+    Get {
+        key: String,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// 设置为预设 ACL
+    Set {
+        key: String,
+
+        /// 预设 ACL
+        #[arg(long, value_parser = CANNED_ACLS)]
+        canned: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum LockCommand {
+    /// 查看对象的保留模式、保留截止时间和法律保留状态
+    Get {
+        key: String,
+
+        /// 指定版本（默认为当前版本）
+        #[arg(long)]
+        version_id: Option<String>,
+
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// 设置保留期限：governance 模式有权限的用户可以绕过，compliance 模式期限内任何人都不能删除或缩短
+    Set {
+        key: String,
+
+        /// 保留模式
+        #[arg(long, value_parser = ["governance", "compliance"])]
+        mode: String,
+
+        /// 保留截止时间：RFC3339 时间戳（如 2026-01-01T00:00:00Z）或从现在开始的时长（如 365d）
+        #[arg(long, value_parser = parse_retain_until)]
+        retain_until: DateTime,
+
+        /// 缩短 governance 模式的保留期限（需要 s3:BypassGovernanceRetention 权限）
+        #[arg(long)]
+        bypass_governance: bool,
+
+        /// 指定版本（默认为当前版本）
+        #[arg(long)]
+        version_id: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum VersioningCommand {
+    /// 查看版本控制和 MFA Delete 状态
+    Get,
+    /// 开启或暂停版本控制
+    Set {
+        #[arg(value_enum)]
+        state: VersioningState,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum VersioningState {
+    Enabled,
+    Suspended,
+}
+
+#[derive(Subcommand)]
+enum LifecycleCommand {
+    /// 列出当前的生命周期规则
+    Get {
+        /// 输出格式（json 可以直接作为 set --from-file 的输入）
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// 用规则文件替换全部生命周期规则（规则列表为空时删除生命周期配置）
+    Set {
+        /// JSON 规则文件，格式同 get --output json
+        #[arg(long, value_name = "PATH")]
+        from_file: PathBuf,
+    },
+    /// 添加一条按前缀过期的规则（同 id 的规则会被替换）
+    AddExpiry {
+        /// 规则作用的 key 前缀
+        #[arg(long)]
+        prefix: String,
+
+        /// 对象上传多少天后删除
+        #[arg(long, required_unless_present = "abort_incomplete_days")]
+        days: Option<i32>,
+
+        /// 未完成的分块上传在开始多少天后中止
+        #[arg(long)]
+        abort_incomplete_days: Option<i32>,
+
+        /// 规则 id（默认为 expire-<prefix>）
+        #[arg(long)]
+        id: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum EncryptionCommand {
+    /// 查看默认加密方式
+    Get {
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// 设置默认加密方式，之后上传的对象没有指定加密时自动加密
+    Set {
+        /// 加密方式：aes256 为服务端托管密钥，aws:kms 使用 KMS 密钥
+        #[arg(long, value_parser = ["aes256", "aws:kms"])]
+        sse: String,
+
+        /// KMS 密钥 ARN（只用于 aws:kms，默认使用账号的 aws/s3 密钥）
+        #[arg(long)]
+        kms_key_id: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PolicyCommand {
+    /// 输出当前的 policy（JSON），没有配置时输出 {}
+    Get,
+    /// 用 JSON 文件替换 policy，发送前先检查格式
+    Set {
+        #[arg(long, value_name = "PATH")]
+        from_file: PathBuf,
+    },
+    /// 删除 policy
+    Delete,
+}
+
+#[derive(Subcommand)]
+enum CorsCommand {
+    /// 列出当前的 CORS 规则
+    Get {
+        /// 输出格式（json 可以直接作为 set --from-file 的输入）
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// 用规则文件替换全部 CORS 规则（规则列表为空时删除 CORS 配置）
+    Set {
+        /// JSON 规则文件，格式同 get --output json
+        #[arg(long, value_name = "PATH")]
+        from_file: PathBuf,
+    },
+    /// 添加一条规则，允许指定来源跨域访问（保留已有规则）
+    Allow {
+        /// 允许的来源，可重复，如 https://app.example.com 或 *
+        #[arg(long, required = true)]
+        origin: Vec<String>,
+
+        /// 允许的方法，逗号分隔
+        #[arg(long, value_delimiter = ',', default_value = "GET")]
+        methods: Vec<String>,
+
+        /// 允许的请求头，逗号分隔
+        #[arg(long, value_delimiter = ',', default_value = "*")]
+        headers: Vec<String>,
+
+        /// 浏览器缓存预检结果的秒数
+        #[arg(long)]
+        max_age: Option<i32>,
+    },
+    /// 删除全部 CORS 规则
+    Delete,
+}
+
+/// 按 -v 的次数设置库日志的级别，日志输出到 stderr，不影响 stdout 上的结果
+fn init_logging(verbosity: u8) {
+    let level = match verbosity {
+        0 => tracing::Level::WARN,
+        1 => tracing::Level::INFO,
+        2 => tracing::Level::DEBUG,
+        _ => tracing::Level::TRACE,
+    };
+    // SDK 自身的日志很多，-vvv 之前只输出其中的警告
+    let others = if verbosity >= 3 { level } else { tracing::Level::WARN };
+    let filter = Targets::new().with_target("oss_uploader", level).with_default(others);
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr).with_target(false))
+        .with(filter)
+        .init();
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // 补全不需要配置，也不输出日志
+    let mut args = std::env::args_os().skip(1);
+    if args.next().is_some_and(|arg| arg == COMPLETE_KEY_COMMAND) {
+        let partial = args.next().map(|arg| arg.to_string_lossy().into_owned()).unwrap_or_default();
+        if let Ok(Ok(names)) = tokio::time::timeout(COMPLETE_KEY_TIMEOUT, complete_key(&partial)).await {
+            for name in names {
+                println!("{}", name);
+            }
+        }
+        return ExitCode::SUCCESS;
+    }
+    let matches = Cli::command().get_matches();
+    let json = json_output(&matches);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if let Commands::Completions { shell } = cli.command {
+        print!("{}", completion_script(shell));
+        return ExitCode::SUCCESS;
+    }
+    init_logging(cli.verbosity);
+    // exists 用退出码 1 表示对象不存在，diff 用 1 表示有差异，其他错误使用 2 以示区分
+    let error_code = if matches!(cli.command, Commands::Exists { .. } | Commands::Diff { .. }) {
+        EXIT_EXISTS_FAILED
+    } else {
+        1
+    };
+
+    let Err(e) = run(cli).await else {
+        return ExitCode::SUCCESS;
+    };
+    let code = match e.downcast_ref::<ExitError>() {
+        Some(exit) => exit.code,
+        None if matches!(e.downcast_ref::<OssError>(), Some(OssError::Cancelled)) => EXIT_CANCELLED,
+        None => error_code,
+    };
+    match e.downcast_ref::<ExitError>() {
+        Some(exit) if exit.message.is_empty() => {}
+        _ if json => eprintln!("{}", error_json(&e, code)),
+        Some(exit) => eprintln!("Error: {}", exit.message),
+        None if code == EXIT_CANCELLED => eprintln!("已取消"),
+        None => eprintln!("Error: {:?}", e),
+    }
+    ExitCode::from(code)
+}
+
+/// 命令（包括嵌套的子命令）是否指定了 `--output json`
+fn json_output(matches: &clap::ArgMatches) -> bool {
+    let mut matches = matches;
+    loop {
+        if let Ok(Some(OutputFormat::Json)) = matches.try_get_one::<OutputFormat>("output") {
+            return true;
+        }
+        match matches.subcommand() {
+            Some((_, sub)) => matches = sub,
+            None => return false,
+        }
+    }
+}
+
+/// `--output json` 时输出到 stderr 的错误对象，服务端错误附带错误码、HTTP 状态码和 request id
+fn error_json(e: &anyhow::Error, exit_code: u8) -> serde_json::Value {
+    let service = e.chain().find_map(|cause| cause.downcast_ref::<OssError>()).and_then(OssError::service_error);
+    let message = match e.downcast_ref::<ExitError>() {
+        Some(exit) => exit.message.clone(),
+        None => format!("{:#}", e),
+    };
+    serde_json::json!({
+        "error": {
+            "message": message,
+            "code": service.and_then(|err| err.code()),
+            "status": service.and_then(|err| err.status()),
+            "request_id": service.and_then(|err| err.request_id()),
+            "extended_request_id": service.and_then(|err| err.extended_request_id()),
+            "exit_code": exit_code,
+        }
+    })
+}
+
+/// 生成补全脚本；bash 和 zsh 的脚本末尾注册一个包装函数，在 [`KEY_COMMANDS`] 的参数处先尝试补全远程 key
+fn completion_script(shell: Shell) -> String {
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut Cli::command(), "oss-uploader", &mut script);
+    let mut script = String::from_utf8(script).expect("补全脚本是 UTF-8");
+    let (wrapper, register) = match shell {
+        Shell::Bash => (BASH_KEY_COMPLETION, "complete -F "),
+        Shell::Zsh => (ZSH_KEY_COMPLETION, "compdef "),
+        _ => return script,
+    };
+    // 包装函数在没有远程结果时调用生成的补全函数，函数名由 clap_complete 决定
+    // zsh 脚本开头的 #compdef 也含有 compdef，取最后一处
+    let function = script
+        .rfind(register)
+        .and_then(|index| script[index + register.len()..].split_whitespace().next())
+        .expect("补全脚本中注册了补全函数")
+        .to_string();
+    let wrapper = wrapper
+        .replace("@COMMANDS@", &KEY_COMMANDS.join("|"))
+        .replace("@COMPLETE_KEY@", COMPLETE_KEY_COMMAND)
+        .replace("@FUNCTION@", &function);
+    script.push_str(&wrapper);
+    script
+}
+
+/// 当前词不是选项、前一个词也不是选项（避免补全选项的值）时列出远程 key，没有结果时交给生成的补全函数
+const BASH_KEY_COMPLETION: &str = r#"
+_oss-uploader_keys() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}" i
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case "${COMP_WORDS[i]}" in
+            @COMMANDS@)
+                if [[ "$cur" != -* && "$prev" != -* ]]; then
+                    local IFS=$'\n'
+                    COMPREPLY=($(oss-uploader @COMPLETE_KEY@ "$cur" 2>/dev/null))
+                    if [[ ${#COMPREPLY[@]} -gt 0 ]]; then
+                        [[ ${#COMPREPLY[@]} -eq 1 && "${COMPREPLY[0]}" == */ ]] && compopt -o nospace 2>/dev/null
+                        return 0
+                    fi
+                fi
+                break
+                ;;
+        esac
+    done
+    @FUNCTION@ "$@"
+}
+complete -F _oss-uploader_keys -o bashdefault -o default oss-uploader
+"#;
+
+const ZSH_KEY_COMPLETION: &str = r#"
+_oss-uploader_keys() {
+    local i
+    for ((i = 2; i < CURRENT; i++)); do
+        case "${words[i]}" in
+            @COMMANDS@)
+                if [[ "${words[CURRENT]}" != -* && "${words[CURRENT-1]}" != -* ]]; then
+                    local -a keys
+                    keys=(${(f)"$(oss-uploader @COMPLETE_KEY@ "${words[CURRENT]}" 2>/dev/null)"})
+                    if (( ${#keys} )); then
+                        # 前缀后面不加空格，可以继续补全下一级
+                        compadd -S '' -- ${keys:#*[^/]}
+                        compadd -- ${(M)keys:#*[^/]}
+                        return 0
+                    fi
+                fi
+                break
+                ;;
+        esac
+    done
+    @FUNCTION@ "$@"
+}
+compdef _oss-uploader_keys oss-uploader
+"#;
+
+/// 列出以 `partial` 开头的 key 和下一级前缀，只请求一页；使用环境变量的配置，不重试
+async fn complete_key(partial: &str) -> Result<Vec<String>> {
+    let client = OssClient::builder(OssConfig::from_env()?)
+        .retry(aws_sdk_s3::config::retry::RetryConfig::disabled())
+        .build()
+        .await?;
+    let options =
+        ListOptions { delimiter: Some("/".to_string()), max_keys: Some(COMPLETE_KEY_LIMIT), ..Default::default() };
+    let entries: Vec<ListEntry> = client.list_stream(partial, &options).try_collect().await?;
+    Ok(entries.iter().map(|entry| entry.name().to_string()).collect())
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    // 从环境变量读取配置
+    let mut config = OssConfig::from_env()
+        .map_err(|e| anyhow::anyhow!("配置错误: {}\n请确保设置了必需的环境变量", e))?;
+    if cli.public_url.is_some() {
+        config.public_base_url = cli.public_url;
+    }
+    let mut command = cli.command;
+    if let Some(bucket) = take_uri_buckets(&mut command, cli.bucket)? {
+        config.bucket = bucket;
+    }
+
+    // 创建客户端，请求统计在结束时输出（-v）
+    let stats = Arc::new(MetricsCounters::new());
+    let key_policy = match (cli.normalize, cli.no_normalize) {
+        (true, _) => KeyPolicy::Normalize,
+        (_, true) => KeyPolicy::Raw,
+        _ => KeyPolicy::Standard,
+    };
+    let mut builder = OssClient::builder(config)
+        .max_workers(cli.workers)
+        .metrics(stats.clone())
+        .key_policy(key_policy);
+    if let Some(part_size) = cli.part_size {
+        builder = builder.part_size(part_size);
+    }
+    if let Some(rate) = cli.limit_rate {
+        builder = builder.rate_limiter(Arc::new(TokenBucket::new(rate)));
+    }
+    let client = builder.build().await?;
+    let result = run_command(client, command, &stats, cli.limit_rate).await;
+    let snapshot = stats.snapshot();
+    if snapshot.total_requests() > 0 {
+        tracing::info!("{}", format_stats(&snapshot));
+    }
+    result
+}
+
+async fn run_command(client: OssClient, command: Commands, stats: &MetricsCounters, limit_rate: Option<u64>) -> Result<()> {
+    match command {
+        Commands::Upload(args) => {
+            let key = args.key()?;
+            println!("开始上传 {} ...", args.file_path.display());
+            let bar = TransferBar::upload(&args.file_path);
+            let options = UploadOptions::default().progress(&bar).cancel(cancel_on_ctrl_c());
+            let url = client.upload_with_options(&args.file_path, &key, &options).await?;
+            let label = if client.public_base_url().is_some() { "公开 url（自定义域名）" } else { "下载 url" };
+            println!("成功上传 {}\n{}:\n{}", args.file_path.display(), label, url);
+            if args.verify {
+                print_upload_check(&client.verify_upload(&args.file_path, &key).await?);
+            }
+        }
+
+        Commands::UploadMany(args) => upload_many_command(&client, args).await?,
+
+        Commands::Share { upload, expires, format, qr } => {
+            qr.check()?;
+            let key = upload.key()?;
+            let bar = TransferBar::upload(&upload.file_path);
+            let options = UploadOptions::default().progress(&bar).cancel(cancel_on_ctrl_c());
+            client.upload_with_options(&upload.file_path, &key, &options).await?;
+            if upload.verify {
+                print_upload_check(&client.verify_upload(&upload.file_path, &key).await?);
+            }
+            let url = client.generate_presigned_url(&key, expires, None, None).await?;
+            note_presigned_host(&client);
+            let expiry = DateTime::from(SystemTime::now() + Duration::from_secs(expires));
+            eprintln!("成功上传 {}，链接有效期至 {}", key, format_local_time(&expiry));
+            if !qr.qr_only {
+                println!("{}", url_command(format, UrlMethod::Get, &url, &key_filename(&key), None));
+            }
+            qr.show(&url)?;
+        }
+        
+        Commands::Download { key, latest, by_name, output, confirm_over, yes, assumed_speed, resume } => {
+            let key = if latest {
+                let objects = client.list_objects(&key).await?;
+                let chosen = pick_latest(&objects, by_name)
+                    .ok_or_else(|| ExitError::new(EXIT_NOT_FOUND, format!("前缀 {} 下没有对象", key)))?;
+                println!("选择最新的对象 {}", chosen.key);
+                chosen.key.clone()
+            } else {
+                key
+            };
+
+            let bar = TransferBar::download(&key);
+            let mut options = DownloadOptions::default().progress(&bar).cancel(cancel_on_ctrl_c()).resume(resume);
+            if !yes {
+                let info = client.head(&key, None).await?;
+                if info.size > confirm_over {
+                    let speed = limit_rate.map_or(assumed_speed, |rate| rate.min(assumed_speed)).max(1);
+                    eprintln!(
+                        "{} 大小 {}，存储类型 {}，按 {}/s 估算约需 {}",
+                        key,
+                        format_size(info.size),
+                        info.storage_class,
+                        format_size(speed),
+                        format_eta(info.size / speed),
+                    );
+                    if !confirm("确定要下载吗？")? {
+                        anyhow::bail!("已取消下载");
+                    }
+                }
+                // 下载时复用这次 HEAD 的结果
+                options = options.head(info);
+            }
+            let path = client.download_with_options(&key, output.as_deref(), &options).await?;
+            println!("成功下载 {} 到 {}", key, path.display());
+
+            let retries = stats.retries();
+            if retries > 0 {
+                eprintln!("下载期间重试了 {} 次请求（多为限流），可以尝试减小 --workers", retries);
+            }
+        }
+        
+        Commands::Cat { key } => {
+            let options = DownloadOptions::default().cancel(cancel_on_ctrl_c());
+            match client.download_to_writer(&key, &mut tokio::io::stdout(), &options).await {
+                Err(OssError::NotFound { key }) => {
+                    return Err(ExitError::new(EXIT_NOT_FOUND, format!("对象不存在: {}", key)).into());
+                }
+                result => result?,
+            };
+        }
+
+        Commands::Ls(args) => {
+            ls_command(&client, args).await?;
+        }
+
+        Commands::Exists { key, verbose } => {
+            let found = if verbose {
+                let info = client.head_if_exists(&key).await?;
+                match &info {
+                    Some(info) => println!(
+                        "{}  {}  {}",
+                        key,
+                        format_size(info.size),
+                        format_time(info.last_modified.as_ref()),
+                    ),
+                    None => println!("{} 不存在", key),
+                }
+                info.is_some()
+            } else {
+                client.exists(&key).await?
+            };
+            if !found {
+                return Err(ExitError::silent(EXIT_ABSENT).into());
+            }
+        }
+
+        Commands::Find(args) => {
+            find_command(&client, args).await?;
+        }
+
+        Commands::Du { prefix, human, group_depth, by_storage_class, output } => {
+            let report = client.disk_usage(&prefix, group_depth).await?;
+            print_usage(&report, group_depth > 0, human, by_storage_class, output)?;
+        }
+
+        Commands::Select { key, sql, input_format, output_format, compression, no_header, verbose } => {
+            let options = SelectOptions { sql, input_format, output_format, compression, csv_header: !no_header };
+            let mut out = std::io::stdout().lock();
+            let stats = client
+                .select(&key, &options, |event| {
+                    match event {
+                        SelectEvent::Records(records) => out.write_all(records)?,
+                        SelectEvent::Progress(progress) if verbose => eprintln!(
+                            "进度: 已扫描 {}，已处理 {}，已返回 {}",
+                            format_size(progress.bytes_scanned),
+                            format_size(progress.bytes_processed),
+                            format_size(progress.bytes_returned),
+                        ),
+                        SelectEvent::Progress(_) => {}
+                    }
+                    Ok(())
+                })
+                .await?;
+            out.flush()?;
+            if verbose {
+                eprintln!(
+                    "查询完成: 扫描 {}，处理 {}，返回 {}",
+                    format_size(stats.bytes_scanned),
+                    format_size(stats.bytes_processed),
+                    format_size(stats.bytes_returned),
+                );
+            }
+        }
+
+        Commands::Tree { prefix, depth, max_entries, dirs_only, output } => {
+            let options = TreeOptions { depth: depth as usize, max_entries, dirs_only };
+            let mut root = client.tree(&prefix, &options).await?;
+            match output {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&root)?),
+                OutputFormat::Text => {
+                    if root.name.is_empty() {
+                        root.name = ".".to_string();
+                    }
+                    for line in tree_lines(&root) {
+                        println!("{}", line);
+                    }
+                }
+            }
+        }
+
+        Commands::Stat { key, version_id, output } => {
+            let info = match client.head(&key, version_id.as_deref()).await {
+                Err(OssError::NotFound { key }) => {
+                    return Err(ExitError::new(EXIT_NOT_FOUND, format!("对象不存在: {}", key)).into());
+                }
+                result => result?,
+            };
+            print_stat(&info, output)?;
+        }
+
+        Commands::Restore { command: Some(RestoreCommand::Status { key, output }), .. } => {
+            let status = client.restore_status(&key).await?;
+            match output {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&status)?),
+                OutputFormat::Text => print_restore_status(&key, &status),
+            }
+        }
+        Commands::Restore { command: None, key, days, tier } => {
+            let key = key.unwrap_or_default();
+            match client.restore_object(&key, days, &tier).await? {
+                RestoreOutcome::Started => {
+                    println!("已提交恢复请求（{}），完成后临时副本保留 {} 天", tier, days);
+                    println!("查看进度: oss-uploader restore status {}", key);
+                }
+                RestoreOutcome::AlreadyRestored => println!("{} 已恢复，有效期已更新为 {} 天", key, days),
+                RestoreOutcome::InProgress => println!("{} 正在恢复中，无需重复提交", key),
+            }
+        }
+
+        Commands::Versions { key, exact, output } => {
+            let mut versions = client.list_object_versions(&key).await?;
+            if exact {
+                versions.retain(|v| v.key == key);
+            }
+            for version in &versions {
+                match output {
+                    OutputFormat::Json => println!("{}", version_json(version)),
+                    OutputFormat::Text => print_version(version),
+                }
+            }
+            if output == OutputFormat::Text && versions.is_empty() {
+                println!("{} 没有任何版本", key);
+            }
+        }
+
+        Commands::Tag { command } => tag_command(&client, command).await?,
+
+        Commands::Acl { command } => match command {
+            AclCommand::Get { key, output } => print_acl(&client.get_acl(&key).await?, output)?,
+            AclCommand::Set { key, canned } => {
+                client.set_canned_acl(&key, &canned).await?;
+                println!("成功将 {} 的 ACL 设置为 {}", key, canned);
+            }
+        },
+
+        Commands::Lock { command } => match command {
+            LockCommand::Get { key, version_id, output } => {
+                let lock = client.get_object_lock(&key, version_id.as_deref()).await?;
+                print_object_lock(&key, &lock, output)?;
+            }
+            LockCommand::Set { key, mode, retain_until, bypass_governance, version_id } => {
+                let mode = mode.to_ascii_uppercase();
+                client.set_retention(&key, version_id.as_deref(), &mode, retain_until, bypass_governance).await?;
+                println!("成功将 {} 设置为 {} 模式，保留至 {}", key, mode, format_local_time(&retain_until));
+            }
+        },
+        Commands::LegalHold { state, key, version_id } => {
+            let on = state == "on";
+            client.set_legal_hold(&key, version_id.as_deref(), on).await?;
+            println!("成功{} {} 的法律保留", if on { "开启" } else { "关闭" }, key);
+        }
+
+        Commands::Mv { src, dst, recursive } => {
+            mv_command(&client, &src, &dst, recursive).await?;
+        }
+
+        Commands::Uploads { command } => uploads_command(&client, command).await?,
+
+        Commands::SetStorageClass { key, class, recursive } => {
+            set_storage_class_command(&client, &key, &class.to_ascii_uppercase(), recursive).await?;
+        }
+
+        Commands::Cp(args) => cp_command(&client, args).await?,
+
+        Commands::Completions { .. } => unreachable!("补全脚本在 main 中输出"),
+
+        Commands::Copy { src, dst, recursive, dest_bucket, dry_run, output } => {
+            let other = dest_bucket.as_deref().map(|bucket| client.with_bucket(bucket));
+            let target = other.as_ref().unwrap_or(&client);
+            if recursive {
+                let filter = KeyFilter::new(&[], &[])?;
+                copy_prefix_command(&client, &src, target, &dst, &filter, dry_run, output).await?;
+            } else {
+                client.copy_to(&src, target, &dst).await?;
+                println!("成功复制 {} 到 {}", src, dst);
+            }
+        }
+
+        Commands::Compose { dst, sources } => {
+            let size = client.compose(&dst, &sources).await?;
+            println!("成功把 {} 个对象拼接为 {} ({})", sources.len(), dst, format_size(size));
+        }
+
+        Commands::SetMeta(args) => set_meta_command(&client, args).await?,
+
+        Commands::RenamePrefix { old_prefix, new_prefix, dry_run, journal } => {
+            rename_prefix_command(&client, &old_prefix, &new_prefix, dry_run, &journal).await?;
+        }
+
+        Commands::Sync(args) => {
+            let options = SyncOptions {
+                include: args.include,
+                exclude: args.exclude,
+                compare: args.compare,
+                delete: args.delete,
+                dry_run: args.dry_run,
+            };
+            if args.verbose {
+                eprintln!("比较方式: {}", options.compare);
+            }
+            let (source, dest) = if args.download {
+                (SyncSource::Remote(args.src), SyncDest::Local(args.dst.into()))
+            } else {
+                (SyncSource::Local(args.src.into()), SyncDest::Remote(args.dst))
+            };
+            let report = client.sync(source, dest, &options).await?;
+            print_sync_report(&report, args.download, args.dry_run, args.output)?;
+        }
+
+        Commands::Watch { dir, key_prefix, settle, delete_after, move_to } => {
+            let after = match move_to {
+                Some(target) => AfterUpload::MoveTo(target),
+                None if delete_after => AfterUpload::Delete,
+                None => AfterUpload::Keep,
+            };
+            watch_command(&client, &dir, WatchOptions { key_prefix, settle, after }).await?;
+        }
+
+        Commands::Diff { local_dir, prefix, include, exclude, compare, output } => {
+            let options = SyncOptions { include, exclude, compare, ..Default::default() };
+            let report = client.diff(&local_dir, &prefix, &options).await?;
+            print_diff_report(&report, output)?;
+            if !report.is_identical() {
+                return Err(ExitError::silent(EXIT_DIFFERENT).into());
+            }
+        }
+
+        Commands::Delete(args) => {
+            delete_command(&client, args).await?;
+        }
+
+        Commands::Url { from_file: Some(path), expires, method, output, .. } => {
+            if method == UrlMethod::Put {
+                anyhow::bail!("--from-file 只能生成下载链接（--method get）");
+            }
+            url_list_command(&client, &path, expires, output).await?;
+        }
+        Commands::Url { key, expires, method, content_type, download_as, response_content_type, format, public, qr, .. } => {
+            qr.check()?;
+            let key = key.unwrap_or_default();
+            let url = match method {
+                UrlMethod::Get if public => client.generate_url(&key),
+                UrlMethod::Put if public => anyhow::bail!("--public 只能用于 --method get"),
+                UrlMethod::Get => {
+                    if content_type.is_some() {
+                        anyhow::bail!("--content-type 只能用于 --method put");
+                    }
+                    client
+                        .generate_presigned_url(&key, expires, download_as.as_deref(), response_content_type.as_deref())
+                        .await?
+                }
+                UrlMethod::Put => {
+                    if download_as.is_some() || response_content_type.is_some() {
+                        anyhow::bail!("--download-as 和 --response-content-type 只能用于 --method get");
+                    }
+                    client.generate_presigned_put_url(&key, expires, content_type.as_deref(), None).await?
+                }
+            };
+            if !public {
+                note_presigned_host(&client);
+            }
+            let filename = download_as.unwrap_or_else(|| key_filename(&key));
+            if !qr.qr_only {
+                println!("{}", url_command(format, method, &url, &filename, content_type.as_deref()));
+            }
+            qr.show(&url)?;
+        }
+        Commands::PostPolicy { key_prefix, max_size, expires } => {
+            let form = client.post_policy(&key_prefix, max_size, expires)?;
+            println!("{}", serde_json::to_string_pretty(&form)?);
+        }
+        Commands::Mb { bucket } => match client.create_bucket(&bucket).await? {
+            CreateBucketOutcome::Created => println!("成功创建 bucket {}", bucket),
+            CreateBucketOutcome::AlreadyOwned => println!("bucket {} 已存在且属于当前账号，无需创建", bucket),
+        },
+        Commands::Rb { bucket, force } => {
+            client.delete_bucket(&bucket, force).await?;
+            println!("成功删除 bucket {}", bucket);
+        }
+        Commands::Lifecycle { command } => lifecycle_command(&client, command).await?,
+        Commands::Cors { command } => cors_command(&client, command).await?,
+        Commands::Encryption { command } => match command {
+            EncryptionCommand::Get { output } => {
+                let encryption = client.get_encryption().await?;
+                match (output, &encryption) {
+                    (OutputFormat::Json, _) => println!("{}", serde_json::to_string_pretty(&encryption)?),
+                    (OutputFormat::Text, None) => println!("未配置默认加密"),
+                    (OutputFormat::Text, Some(encryption)) => print_bucket_encryption(encryption),
+                }
+            }
+            EncryptionCommand::Set { sse, kms_key_id } => {
+                let sse = parse_sse(&sse, kms_key_id.as_deref())?;
+                client.set_encryption(sse.clone(), kms_key_id.as_deref()).await?;
+                println!("成功将默认加密设置为 {}", sse.as_str());
+            }
+        },
+        Commands::Policy { command } => match command {
+            PolicyCommand::Get => println!("{}", serde_json::to_string_pretty(&client.get_policy().await?)?),
+            PolicyCommand::Set { from_file } => {
+                let json = std::fs::read_to_string(&from_file)
+                    .map_err(|e| anyhow::anyhow!("无法读取 {}: {}", from_file.display(), e))?;
+                let policy = parse_policy(&json).map_err(|e| anyhow::anyhow!("{} 格式错误: {:#}", from_file.display(), e))?;
+                client.set_policy(&policy).await?;
+                println!("成功设置 bucket policy");
+            }
+            PolicyCommand::Delete => {
+                client.delete_policy().await?;
+                println!("成功删除 bucket policy");
+            }
+        },
+        Commands::Versioning { command } => match command {
+            VersioningCommand::Get => print_versioning(&client.get_bucket_versioning().await?),
+            VersioningCommand::Set { state } => {
+                let enabled = state == VersioningState::Enabled;
+                client.set_bucket_versioning(enabled).await?;
+                println!("成功{}版本控制", if enabled { "开启" } else { "暂停" });
+                print_versioning(&client.get_bucket_versioning().await?);
+            }
+        },
+    }
+
+    Ok(())
+}
+
+/// 结束时的请求统计，如 `请求 3 次（GetObject 2，HeadObject 1），上传 0.00 B，下载 1.00 MB，重试 1 次，失败 0 次`
+fn format_stats(stats: &MetricsSnapshot) -> String {
+    let counts = |counts: &BTreeMap<String, u64>| {
+        counts.iter().map(|(name, n)| format!("{} {}", name, n)).collect::<Vec<_>>().join("，")
+    };
+    let mut line = format!("请求 {} 次（{}）", stats.total_requests(), counts(&stats.requests));
+    line.push_str(&format!(
+        "，上传 {}，下载 {}，重试 {} 次，失败 {} 次",
+        format_size(stats.bytes_uploaded),
+        format_size(stats.bytes_downloaded),
+        stats.retries,
+        stats.total_errors(),
+    ));
+    if !stats.errors.is_empty() {
+        line.push_str(&format!("（{}）", counts(&stats.errors)));
+    }
+    line
+}
+
+/// 列出对象，不需要排序时每返回一页就输出，不等待整个列表
+/// 默认按目录列出当前层级（以 `/` 为分隔符），`--recursive` 列出前缀下的所有对象
+async fn ls_command(client: &OssClient, args: LsArgs) -> Result<()> {
+    let mut out = std::io::stdout().lock();
+    let max = args.max.unwrap_or(usize::MAX);
+    let prefix = if args.recursive { args.prefix.clone() } else { directory_prefix(&args.prefix) };
+    let printer = LsPrinter {
+        long: args.long,
+        time_style: args.time_style,
+        output: args.output,
+        null: args.null,
+        relative_to: (!args.recursive).then(|| prefix.clone()),
+    };
+    let options = ListOptions {
+        delimiter: (!args.recursive).then(|| "/".to_string()),
+        fetch_owner: args.long,
+        start_after: args.start_after.clone(),
+        ..Default::default()
+    };
+
+    // `ls builds` 时 builds 本身也可能是一个对象，和 builds/ "目录"同时存在
+    let mut itself = None;
+    if prefix != args.prefix && args.start_after.as_ref().is_none_or(|after| args.prefix > *after) {
+        itself = client.head_if_exists(&args.prefix).await?.map(|obj| ListEntry::Object(Box::new(obj)));
+    }
+
+    let mut entries = futures::stream::iter(itself.map(Ok))
+        .chain(client.list_stream(&prefix, &options))
+        .try_filter(|entry| {
+            let keep = match entry {
+                // 目录标记对象（key 与前缀相同）就是当前目录本身，不单独列出
+                ListEntry::Object(obj) if !args.recursive && obj.key == prefix => false,
+                ListEntry::Object(obj) => args.matches(obj),
+                ListEntry::Prefix(_) => true,
+            };
+            futures::future::ready(keep)
+        })
+        .boxed();
+
+    if args.sort.is_none() && !args.reverse {
+        let mut entries = entries.take(max);
+        while let Some(entry) = entries.next().await {
+            printer.entry(&mut out, &entry?)?;
+        }
+        return Ok(());
+    }
+
+    let mut all = Vec::new();
+    while let Some(entry) = entries.next().await {
+        all.push(entry?);
+        if all.len() == SORT_BUFFER_WARNING {
+            eprintln!("警告: 排序需要缓存整个列表，已超过 {} 项，可以缩小前缀或去掉 --sort", SORT_BUFFER_WARNING);
+        }
+    }
+    sort_entries(&mut all, args.sort.unwrap_or(SortKey::Name), args.reverse);
+    for entry in all.iter().take(max) {
+        printer.entry(&mut out, entry)?;
+    }
+    Ok(())
+}
+
+/// 排序 ls 的结果，按大小或修改时间排序时公共前缀排在对象之前，相同时按名字排序
+fn sort_entries(entries: &mut [ListEntry], key: SortKey, reverse: bool) {
+    entries.sort_by(|a, b| {
+        let ord = match (key, a, b) {
+            (SortKey::Name, _, _) => Ordering::Equal,
+            (_, ListEntry::Prefix(_), ListEntry::Prefix(_)) => Ordering::Equal,
+            (_, ListEntry::Prefix(_), ListEntry::Object(_)) => Ordering::Less,
+            (_, ListEntry::Object(_), ListEntry::Prefix(_)) => Ordering::Greater,
+            (SortKey::Size, ListEntry::Object(a), ListEntry::Object(b)) => a.size.cmp(&b.size),
+            (SortKey::Mtime, ListEntry::Object(a), ListEntry::Object(b)) => a.last_modified.cmp(&b.last_modified),
+        };
+        let ord = ord.then_with(|| a.name().cmp(b.name()));
+        if reverse { ord.reverse() } else { ord }
+    });
+}
+
+async fn tag_command(client: &OssClient, command: TagCommand) -> Result<()> {
+    let (key, tags, output) = match command {
+        TagCommand::Get { key, version_id, output } => {
+            let tags = client.get_tags(&key, version_id.as_deref()).await?;
+            (key, tags, output)
+        }
+        TagCommand::Set { key, tags: new_tags, merge, version_id, output } => {
+            let mut tags = if merge { client.get_tags(&key, version_id.as_deref()).await? } else { BTreeMap::new() };
+            tags.extend(new_tags);
+            client.set_tags(&key, version_id.as_deref(), &tags).await?;
+            (key, tags, output)
+        }
+        TagCommand::Delete { key, version_id } => {
+            client.delete_tags(&key, version_id.as_deref()).await?;
+            println!("成功删除 {} 的标签", key);
+            return Ok(());
+        }
+    };
+
+    match output {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "key": key, "tags": tags }))?)
+        }
+        OutputFormat::Text if tags.is_empty() => println!("{} 没有标签", key),
+        OutputFormat::Text => {
+            for (name, value) in &tags {
+                println!("{}={}", name, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 每行一个版本：修改时间、大小（删除标记显示为 DELETE）、最新版本标记 *、版本 ID、key
+fn print_version(version: &ObjectVersion) {
+    let time = version.last_modified.as_ref().map(format_local_time).unwrap_or_else(|| "-".to_string());
+    let size = if version.is_delete_marker { "DELETE".to_string() } else { format_size(version.size) };
+    let latest = if version.is_latest { "*" } else { " " };
+    println!("{}  {:>12}  {} {}  {}", time, size, latest, version.version_id, version.key);
+}
+
+fn version_json(version: &ObjectVersion) -> serde_json::Value {
+    serde_json::json!({
+        "key": version.key,
+        "version_id": version.version_id,
+        "is_delete_marker": version.is_delete_marker,
+        "is_latest": version.is_latest,
+        "size": version.size,
+        "last_modified": version.last_modified.as_ref().and_then(|t| t.fmt(DateTimeFormat::DateTime).ok()),
+    })
+}
+
+fn print_object_lock(key: &str, lock: &ObjectLock, output: OutputFormat) -> Result<()> {
+    match output {
+        OutputFormat::Json => {
+            let value = serde_json::json!({
+                "key": key,
+                "mode": lock.mode,
+                "retain_until": lock.retain_until.as_ref().and_then(|t| t.fmt(DateTimeFormat::DateTime).ok()),
+                "legal_hold": lock.legal_hold,
+            });
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        }
+        OutputFormat::Text => {
+            match (&lock.mode, &lock.retain_until) {
+                (Some(mode), Some(until)) => println!("保留模式: {}，保留至 {}", mode, format_local_time(until)),
+                _ => println!("保留模式: 未设置"),
+            }
+            println!("法律保留: {}", if lock.legal_hold { "开启" } else { "关闭" });
+        }
+    }
+    Ok(())
+}
+
+fn print_restore_status(key: &str, status: &RestoreStatus) {
+    match status {
+        RestoreStatus::NotArchived { storage_class } => {
+            println!("{} 的存储类型为 {}，可以直接下载", key, storage_class)
+        }
+        RestoreStatus::Archived { storage_class } => {
+            println!("{} 位于归档存储 {}，尚未恢复: oss-uploader restore {}", key, storage_class, key)
+        }
+        RestoreStatus::InProgress { .. } => println!("{} 正在恢复中", key),
+        RestoreStatus::Restored { expiry, .. } => match expiry {
+            Some(expiry) => println!("{} 已恢复，可以下载，有效期至 {}", key, expiry),
+            None => println!("{} 已恢复，可以下载", key),
+        },
+    }
+}
+
+fn print_acl(acl: &ObjectAcl, output: OutputFormat) -> Result<()> {
+    if output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(acl)?);
+        return Ok(());
+    }
+    println!("所有者: {}", acl.owner.as_deref().unwrap_or("-"));
+    // 权限名都是 ASCII，放在第一列便于对齐
+    println!("{:<12}  被授权者", "权限");
+    for grant in &acl.grants {
+        println!("{:<12}  {}", grant.permission, grant.grantee);
+    }
+    Ok(())
+}
+
+async fn lifecycle_command(client: &OssClient, command: LifecycleCommand) -> Result<()> {
+    match command {
+        LifecycleCommand::Get { output } => {
+            let rules = client.get_lifecycle().await?;
+            match output {
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "rules": rules }))?)
+                }
+                OutputFormat::Text if rules.is_empty() => println!("未配置生命周期规则"),
+                OutputFormat::Text => rules.iter().for_each(print_lifecycle_rule),
+            }
+        }
+        LifecycleCommand::Set { from_file } => {
+            let rules = read_rules_file(&from_file, parse_lifecycle)?;
+            client.set_lifecycle(&rules).await?;
+            println!("成功设置 {} 条生命周期规则", rules.len());
+        }
+        LifecycleCommand::AddExpiry { prefix, days, abort_incomplete_days, id } => {
+            let rule = LifecycleRule {
+                id: Some(id.unwrap_or_else(|| format!("expire-{}", prefix))),
+                prefix,
+                enabled: true,
+                expiration_days: days,
+                abort_incomplete_days,
+                ..Default::default()
+            };
+            let mut rules = client.get_lifecycle().await?;
+            rules.retain(|existing| existing.id != rule.id);
+            print_lifecycle_rule(&rule);
+            rules.push(rule);
+            client.set_lifecycle(&rules).await?;
+            println!("成功添加规则，当前共 {} 条", rules.len());
+        }
+    }
+    Ok(())
+}
+
+async fn cors_command(client: &OssClient, command: CorsCommand) -> Result<()> {
+    match command {
+        CorsCommand::Get { output } => {
+            let rules = client.get_cors().await?;
+            match output {
+                OutputFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "rules": rules }))?)
+                }
+                OutputFormat::Text if rules.is_empty() => println!("未配置 CORS 规则"),
+                OutputFormat::Text => rules.iter().for_each(print_cors_rule),
+            }
+        }
+        CorsCommand::Set { from_file } => {
+            let rules = read_rules_file(&from_file, parse_cors)?;
+            client.set_cors(&rules).await?;
+            println!("成功设置 {} 条 CORS 规则", rules.len());
+        }
+        CorsCommand::Allow { origin, methods, headers, max_age } => {
+            let rule = CorsRule {
+                allowed_origins: origin,
+                allowed_methods: methods.iter().map(|m| m.trim().to_ascii_uppercase()).collect(),
+                allowed_headers: headers,
+                max_age_seconds: max_age,
+                ..Default::default()
+            };
+            let mut rules = client.get_cors().await?;
+            print_cors_rule(&rule);
+            rules.push(rule);
+            client.set_cors(&rules).await?;
+            println!("成功添加规则，当前共 {} 条", rules.len());
+        }
+        CorsCommand::Delete => {
+            client.delete_cors().await?;
+            println!("成功删除 CORS 规则");
+        }
+    }
+    Ok(())
+}
+
+fn print_cors_rule(rule: &CorsRule) {
+    let mut line = format!("{}  {}", rule.allowed_origins.join(","), rule.allowed_methods.join(","));
+    if !rule.allowed_headers.is_empty() {
+        line += &format!("  允许请求头: {}", rule.allowed_headers.join(","));
+    }
+    if !rule.expose_headers.is_empty() {
+        line += &format!("  暴露响应头: {}", rule.expose_headers.join(","));
+    }
+    if let Some(max_age) = rule.max_age_seconds {
+        line += &format!("  缓存 {} 秒", max_age);
+    }
+    println!("{}", line);
+}
+
+/// 读取并解析 JSON 规则文件，错误信息中带有文件名和出错的字段
+fn read_rules_file<T>(path: &Path, parse: impl Fn(&str) -> Result<Vec<T>, OssError>) -> Result<Vec<T>> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("无法读取 {}: {}", path.display(), e))?;
+    parse(&json).map_err(|e| anyhow::anyhow!("{} 格式错误: {:#}", path.display(), e))
+}
+
+fn print_lifecycle_rule(rule: &LifecycleRule) {
+    let mut actions = Vec::new();
+    if let Some(days) = rule.expiration_days {
+        actions.push(format!("{} 天后删除", days));
+    }
+    for transition in &rule.transitions {
+        actions.push(format!("{} 天后转为 {}", transition.days, transition.storage_class));
+    }
+    if let Some(days) = rule.noncurrent_expiration_days {
+        actions.push(format!("历史版本 {} 天后删除", days));
+    }
+    if let Some(days) = rule.abort_incomplete_days {
+        actions.push(format!("未完成的分块上传 {} 天后中止", days));
+    }
+    println!(
+        "{}  {}  {}  {}",
+        rule.id.as_deref().unwrap_or("-"),
+        if rule.prefix.is_empty() { "(整个 bucket)" } else { &rule.prefix },
+        if rule.enabled { "启用" } else { "停用" },
+        actions.join("，"),
+    );
+}
+
+fn print_bucket_encryption(encryption: &BucketEncryption) {
+    println!("默认加密: {}", encryption.algorithm);
+    if let Some(kms_key_id) = &encryption.kms_key_id {
+        println!("KMS 密钥: {}", kms_key_id);
+    }
+    if encryption.bucket_key_enabled {
+        println!("Bucket Key: 开启");
+    }
+}
+
+/// 按 Ctrl-C 时取消传输，库会中止未完成的分块上传、删除未下载完的文件（断点续传的部分文件保留）
+fn cancel_on_ctrl_c() -> CancellationToken {
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("\n正在取消...");
+            cancel.cancel();
+        }
+    });
+    token
+}
+
+/// 上传/下载的进度条，由库的进度事件驱动
+struct TransferBar {
+    bar: ProgressBar,
+    done: &'static str,
+}
+
+impl TransferBar {
+    fn new(message: String, done: &'static str) -> Self {
+        let bar = ProgressBar::new(0);
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        bar.set_message(message);
+        Self { bar, done }
+    }
+
+    fn upload(path: &Path) -> Self {
+        Self::new(format!("上传 {}", path.file_name().unwrap_or_default().to_string_lossy()), "上传完成")
+    }
+
+    fn download(key: &str) -> Self {
+        Self::new(format!("下载 {}", key_filename(key)), "下载完成")
+    }
+}
+
+impl ProgressObserver for TransferBar {
+    fn on_event(&self, event: ProgressEvent<'_>) {
+        match event {
+            ProgressEvent::Started { total } => self.bar.set_length(total),
+            ProgressEvent::BytesTransferred { n } => self.bar.inc(n),
+            ProgressEvent::PartCompleted { .. } => {}
+            ProgressEvent::Finished => self.bar.finish_with_message(self.done),
+            ProgressEvent::Failed { .. } => self.bar.abandon(),
+        }
+    }
+}
+
+fn print_upload_check(check: &UploadCheck) {
+    let sse = match (&check.sse, &check.kms_key_id) {
+        (Some(sse), Some(kms_key_id)) => format!("{}（{}）", sse, kms_key_id),
+        (Some(sse), None) => sse.clone(),
+        (None, _) => "未加密".to_string(),
+    };
+    println!("校验通过：远程大小 {}，服务端加密 {}", format_size(check.size), sse);
+}
+
+fn print_versioning(versioning: &BucketVersioning) {
+    println!("版本控制: {}", versioning.status.as_deref().unwrap_or("未开启"));
+    println!("MFA Delete: {}", versioning.mfa_delete.as_deref().unwrap_or("未配置"));
+}
+
+/// `ls` 的输出格式
+struct LsPrinter {
+    long: bool,
+    time_style: TimeStyle,
+    output: OutputFormat,
+    null: bool,
+    /// 按目录列出时只显示相对于该前缀的部分
+    relative_to: Option<String>,
+}
+
+impl LsPrinter {
+    /// 长格式下 key 之前各列的总宽度
+    const LONG_WIDTH: usize = 10 + 2 + 19 + 2 + 12 + 2 + 34 + 2 + 16;
+    /// 短格式下 key 之前各列的总宽度
+    const SHORT_WIDTH: usize = 20 + 2 + 12;
+
+    /// 输出一行，key 放在最后一列，过长的 key 不会打乱前面各列的对齐
+    /// JSON 和 NUL 分隔模式总是输出完整的 key，不做任何人类可读的格式化
+    fn entry(&self, out: &mut impl Write, entry: &ListEntry) -> Result<()> {
+        if self.null {
+            out.write_all(entry.name().as_bytes())?;
+            out.write_all(b"\0")?;
+            return Ok(());
+        }
+        if self.output == OutputFormat::Json {
+            writeln!(out, "{}", ls_json(entry))?;
+            return Ok(());
+        }
+
+        let name = self.relative_to
+            .as_deref()
+            .and_then(|prefix| entry.name().strip_prefix(prefix))
+            .unwrap_or(entry.name());
+
+        match entry {
+            ListEntry::Prefix(_) => {
+                let width = if self.long { Self::LONG_WIDTH } else { Self::SHORT_WIDTH };
+                writeln!(out, "{:>width$}  {}", "PRE", name)?;
+            }
+            ListEntry::Object(obj) if self.long => {
+                let time = match (self.time_style, &obj.last_modified) {
+                    (_, None) => "-".to_string(),
+                    (TimeStyle::Iso, Some(t)) => format_local_time(t),
+                    (TimeStyle::Relative, Some(t)) => format_age(DateTime::from(std::time::SystemTime::now()).secs() - t.secs()),
+                };
+                let etag = obj.etag.as_deref().map_or("-", |e| e.trim_matches('"'));
+                writeln!(
+                    out,
+                    "{:>10}  {}  {:<12}  {:<34}  {}  {}",
+                    format_size(obj.size),
+                    console::pad_str(&time, 19, console::Alignment::Left, None),
+                    obj.storage_class,
+                    etag,
+                    console::pad_str(obj.owner.as_deref().unwrap_or("-"), 16, console::Alignment::Left, None),
+                    name,
+                )?;
+            }
+            ListEntry::Object(obj) => {
+                writeln!(out, "{:<20}  {:>12}  {}", format_time(obj.last_modified.as_ref()), obj.size, name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 查找命令：边列出边过滤输出，默认每行一个 key，可以直接作为 delete --from-stdin 的输入
+async fn find_command(client: &OssClient, args: FindArgs) -> Result<()> {
+    let mut filter = FindFilter::default();
+    if let Some(name) = &args.name {
+        filter = filter.name(name)?;
+    }
+    for test in args.size {
+        filter = filter.size(test);
+    }
+    for test in args.mtime {
+        filter = filter.age(test);
+    }
+    if let Some(class) = &args.storage_class {
+        filter = filter.storage_class(class);
+    }
+
+    let mut out = std::io::stdout().lock();
+    let mut objects = client.list_objects_stream(&args.prefix);
+    while let Some(obj) = objects.try_next().await? {
+        if !filter.matches(&obj) {
+            continue;
+        }
+        match (&args.exec_template, args.output) {
+            (Some(template), _) => writeln!(out, "{}", render_template(template, &obj))?,
+            (None, OutputFormat::Json) => writeln!(out, "{}", ls_json(&ListEntry::Object(Box::new(obj))))?,
+            (None, OutputFormat::Text) => writeln!(out, "{}", obj.key)?,
+        }
+    }
+    Ok(())
+}
+
+/// 按模板输出对象信息，替换 {key} {size} {mtime} {etag} {storage_class}
+fn render_template(template: &str, obj: &ObjectInfo) -> String {
+    template
+        .replace("{key}", &obj.key)
+        .replace("{size}", &obj.size.to_string())
+        .replace("{mtime}", &format_time(obj.last_modified.as_ref()))
+        .replace("{etag}", obj.etag.as_deref().unwrap_or("").trim_matches('"'))
+        .replace("{storage_class}", &obj.storage_class)
+}
+
+/// 输出用量统计
+fn print_usage(report: &UsageReport, grouped: bool, human: bool, by_storage_class: bool, output: OutputFormat) -> Result<()> {
+    if output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(report)?);
+        return Ok(());
+    }
+
+    let size = |bytes: u64| if human { format_size(bytes) } else { bytes.to_string() };
+    let print_line = |usage: &Usage, name: &str| {
+        println!("{:>12}  {:>10}  {}", size(usage.bytes), usage.objects, name);
+        if by_storage_class {
+            for (class, (objects, bytes)) in &usage.by_storage_class {
+                println!("{:>12}  {:>10}    {}", size(*bytes), objects, class);
+            }
+        }
+    };
+    if grouped {
+        for (group, usage) in &report.groups {
+            print_line(usage, group);
+        }
+    }
+    print_line(&report.total, "总计");
+    Ok(())
+}
+
+/// 输出对象的元信息
+fn print_stat(info: &ObjectInfo, output: OutputFormat) -> Result<()> {
+    match output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(info)?),
+        OutputFormat::Text => {
+            let metadata: BTreeMap<_, _> = info.metadata.iter().collect();
+            println!("Key:           {}", info.key);
+            if let Some(version_id) = &info.version_id {
+                println!("版本 ID:       {}", version_id);
+            }
+            println!("大小:          {} ({} 字节)", format_size(info.size), info.size);
+            println!("修改时间:      {}", info.last_modified.as_ref().map_or("-".to_string(), format_local_time));
+            println!("ETag:          {}", info.etag.as_deref().unwrap_or("-"));
+            println!("Content-Type:  {}", info.content_type.as_deref().unwrap_or("-"));
+            println!("存储类型:      {}", info.storage_class);
+            println!("服务端加密:    {}", info.sse.as_deref().unwrap_or("-"));
+            if !metadata.is_empty() {
+                println!("自定义元数据:");
+                for (key, value) in metadata {
+                    println!("  {}: {}", key, value);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 移动命令，递归移动时并发处理，单个对象失败不影响其他对象
+async fn mv_command(client: &OssClient, src: &str, dst: &str, recursive: bool) -> Result<()> {
+    if !recursive {
+        let dst = if dst.ends_with('/') {
+            format!("{}{}", dst, src.rsplit('/').next().unwrap_or(src))
+        } else {
+            dst.to_string()
+        };
+        if dst == src {
+            anyhow::bail!("源和目标相同: {}", src);
+        }
+        client.move_object(src, &dst).await?;
+        println!("成功移动 {} -> {}", src, dst);
+        return Ok(());
+    }
+
+    let (src, dst) = (directory_prefix(src), directory_prefix(dst));
+    if src == dst {
+        anyhow::bail!("源前缀和目标前缀相同: {}", src);
+    }
+    let objects = client.list_objects(&src).await?;
+    if objects.is_empty() {
+        println!("前缀 {} 下没有对象", src);
+        return Ok(());
+    }
+
+    let results: Vec<_> = futures::stream::iter(&objects)
+        .map(|obj| {
+            let target = format!("{}{}", dst, obj.key.strip_prefix(src.as_str()).unwrap_or(&obj.key));
+            async move {
+                let result = client.move_object(&obj.key, &target).await;
+                (obj, target, result)
+            }
+        })
+        .buffer_unordered(client.workers())
+        .collect()
+        .await;
+
+    let mut failed = 0;
+    for (obj, target, result) in results {
+        match result {
+            Ok(()) => println!("{} -> {}", obj.key, target),
+            Err(e) => {
+                failed += 1;
+                eprintln!("移动失败 {}: {:#}", obj.key, e);
+            }
+        }
+    }
+    println!("成功移动 {} 个，失败 {} 个", objects.len() - failed, failed);
+    if failed > 0 {
+        return Err(ExitError::new(1, format!("{} 个对象移动失败", failed)).into());
+    }
+    Ok(())
+}
+
+async fn uploads_command(client: &OssClient, command: UploadsCommand) -> Result<()> {
+    match command {
+        UploadsCommand::Ls { prefix, show_parts, output } => {
+            let uploads = client.list_multipart_uploads(&prefix).await?;
+            let parts: Vec<Option<UploadedParts>> = if show_parts {
+                futures::stream::iter(&uploads)
+                    .map(|upload| client.list_parts(&upload.key, &upload.upload_id))
+                    .buffered(client.workers())
+                    .map_ok(Some)
+                    .try_collect()
+                    .await?
+            } else {
+                vec![None; uploads.len()]
+            };
+
+            for (upload, parts) in uploads.iter().zip(&parts) {
+                match output {
+                    OutputFormat::Json => println!("{}", upload_json(upload, parts.as_ref())),
+                    OutputFormat::Text => {
+                        let initiated = upload.initiated.as_ref()
+                            .map(format_local_time)
+                            .unwrap_or_else(|| "-".to_string());
+                        let parts = parts
+                            .map(|p| format!("  {:>5} 块 {:>12}", p.count, format_size(p.bytes)))
+                            .unwrap_or_default();
+                        println!(
+                            "{}  {:<12}{}  {}  {}",
+                            initiated, upload.storage_class, parts, upload.key, upload.upload_id,
+                        );
+                    }
+                }
+            }
+            if output == OutputFormat::Text && uploads.is_empty() {
+                println!("没有进行中的分块上传");
+            }
+        }
+        // 没有 --upload-id 时 clap 保证指定了 --all-for-key
+        UploadsCommand::Abort { key, upload_id, yes, .. } => {
+            let upload_ids = match upload_id {
+                Some(upload_id) => vec![upload_id],
+                None => client
+                    .list_multipart_uploads(&key)
+                    .await?
+                    .into_iter()
+                    .filter(|upload| upload.key == key)
+                    .map(|upload| upload.upload_id)
+                    .collect(),
+            };
+            if upload_ids.is_empty() {
+                println!("{} 没有进行中的分块上传", key);
+                return Ok(());
+            }
+
+            if !yes {
+                // 已经完成或中止的上传查不到分块，按 0 计算
+                let mut bytes = 0;
+                for upload_id in &upload_ids {
+                    bytes += client.list_parts(&key, upload_id).await.map(|p| p.bytes).unwrap_or(0);
+                }
+                let message = format!(
+                    "将中止 {} 的 {} 个分块上传，丢弃已上传的 {}，确定吗？",
+                    key,
+                    upload_ids.len(),
+                    format_size(bytes),
+                );
+                if !confirm(&message)? {
+                    println!("已取消");
+                    return Ok(());
+                }
+            }
+
+            for upload_id in &upload_ids {
+                if client.abort_upload(&key, upload_id).await? {
+                    println!("成功中止 {} 的分块上传 {}", key, upload_id);
+                } else {
+                    println!("{} 的分块上传 {} 已经完成或已被中止", key, upload_id);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn upload_json(upload: &MultipartUpload, parts: Option<&UploadedParts>) -> serde_json::Value {
+    let mut value = serde_json::json!({
+        "key": upload.key,
+        "upload_id": upload.upload_id,
+        "initiated": upload.initiated.as_ref().and_then(|t| t.fmt(DateTimeFormat::DateTime).ok()),
+        "storage_class": upload.storage_class,
+    });
+    if let Some(parts) = parts {
+        value["parts"] = parts.count.into();
+        value["bytes"] = parts.bytes.into();
+    }
+    value
+}
+
+/// 修改存储类型，递归时跳过已经是目标存储类型的对象
+async fn set_storage_class_command(client: &OssClient, key: &str, class: &str, recursive: bool) -> Result<()> {
+    if !recursive {
+        client.set_storage_class(key, class).await?;
+        println!("成功将 {} 的存储类型修改为 {}", key, class);
+        return Ok(());
+    }
+
+    let objects: Vec<_> = client
+        .list_objects(key)
+        .await?
+        .into_iter()
+        .filter(|obj| !obj.storage_class.eq_ignore_ascii_case(class))
+        .collect();
+    if objects.is_empty() {
+        println!("前缀 {} 下没有需要修改的对象", key);
+        return Ok(());
+    }
+
+    let pb = ProgressBar::new(objects.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+    pb.set_message(format!("修改为 {}", class));
+    let results: Vec<_> = futures::stream::iter(&objects)
+        .map(|obj| {
+            let pb = &pb;
+            async move {
+                let result = client.set_storage_class(&obj.key, class).await;
+                pb.inc(1);
+                (obj, result)
+            }
+        })
+        .buffer_unordered(client.workers())
+        .collect()
+        .await;
+    pb.finish_and_clear();
+
+    let mut failed = 0;
+    for (obj, result) in results {
+        if let Err(e) = result {
+            failed += 1;
+            eprintln!("修改失败 {}: {:#}", obj.key, e);
+        }
+    }
+    println!("成功修改 {} 个，失败 {} 个", objects.len() - failed, failed);
+    if failed > 0 {
+        return Err(ExitError::new(1, format!("{} 个对象修改存储类型失败", failed)).into());
+    }
+    Ok(())
+}
+
+/// 修改元数据命令
+async fn set_meta_command(client: &OssClient, args: SetMetaArgs) -> Result<()> {
+    let update = MetadataUpdate {
+        content_type: args.content_type,
+        cache_control: args.cache_control,
+        metadata: args.metadata,
+    };
+    if update.is_empty() {
+        anyhow::bail!("请至少指定 --content-type、--cache-control 或 -m 中的一项");
+    }
+
+    if !args.recursive {
+        if args.dry_run {
+            println!("(dry-run) 修改 {}", args.key);
+        } else if client.set_metadata(&args.key, &update).await? {
+            println!("成功修改 {} 的元数据", args.key);
+        } else {
+            println!("{} 的元数据没有变化", args.key);
+        }
+        return Ok(());
+    }
+
+    let filter = KeyFilter::new(&args.include, &args.exclude)?;
+    let objects: Vec<_> = client
+        .list_objects(&args.key)
+        .await?
+        .into_iter()
+        .filter(|obj| filter.matches(obj.key.strip_prefix(args.key.as_str()).unwrap_or(&obj.key)))
+        .collect();
+    for pattern in filter.unused_excludes() {
+        eprintln!("警告: --exclude {} 没有匹配任何 key", pattern);
+    }
+    if objects.is_empty() {
+        println!("前缀 {} 下没有匹配的对象", args.key);
+        return Ok(());
+    }
+    if args.dry_run {
+        for obj in &objects {
+            println!("(dry-run) 修改 {}", obj.key);
+        }
+        println!("(dry-run) 共 {} 个对象", objects.len());
+        return Ok(());
+    }
+
+    let pb = ProgressBar::new(objects.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+    pb.set_message("修改元数据");
+    let results: Vec<_> = futures::stream::iter(&objects)
+        .map(|obj| {
+            let (pb, update) = (&pb, &update);
+            async move {
+                let result = client.set_metadata(&obj.key, update).await;
+                pb.inc(1);
+                (obj, result)
+            }
+        })
+        .buffer_unordered(client.workers())
+        .collect()
+        .await;
+    pb.finish_and_clear();
+
+    let (mut modified, mut unchanged, mut failed) = (0, 0, 0);
+    for (obj, result) in results {
+        match result {
+            Ok(true) => modified += 1,
+            Ok(false) => unchanged += 1,
+            Err(e) => {
+                failed += 1;
+                eprintln!("修改失败 {}: {:#}", obj.key, e);
+            }
+        }
+    }
+    println!("成功修改 {} 个，没有变化 {} 个，失败 {} 个", modified, unchanged, failed);
+    if failed > 0 {
+        return Err(ExitError::new(1, format!("{} 个对象修改元数据失败", failed)).into());
+    }
+    Ok(())
+}
+
+/// cp 的一端：本地路径或 s3://bucket/key
+#[derive(Debug, PartialEq)]
+enum CpEnd {
+    Local(PathBuf),
+    Remote(ObjectUri),
+}
+
+impl CpEnd {
+    fn parse(s: &str) -> Result<Self> {
+        Ok(match ObjectUri::parse(s)? {
+            Some(uri) => CpEnd::Remote(uri),
+            None => CpEnd::Local(PathBuf::from(s)),
+        })
+    }
+}
+
+/// 单个文件复制到远程时的 key：目标为空或以 `/` 结尾时在后面加上源的文件名
+fn cp_target_key(dst_key: &str, filename: &str) -> String {
+    if dst_key.is_empty() || dst_key.ends_with('/') {
+        format!("{}{}", dst_key, filename)
+    } else {
+        dst_key.to_string()
+    }
+}
+
+/// 单个对象下载到本地时的路径：目标为已有目录或以路径分隔符结尾时在目录下使用对象的文件名
+fn cp_target_path(dst: &Path, key: &str) -> PathBuf {
+    let text = dst.to_string_lossy();
+    if dst.is_dir() || text.ends_with('/') || text.ends_with(std::path::MAIN_SEPARATOR) {
+        dst.join(key_filename(key))
+    } else {
+        dst.to_path_buf()
+    }
+}
+
+/// cp：按源和目标是本地还是远程选择上传、下载或服务端复制
+async fn cp_command(client: &OssClient, args: CpArgs) -> Result<()> {
+    let (src, dst) = (CpEnd::parse(&args.src)?, CpEnd::parse(&args.dst)?);
+    if args.recursive {
+        return cp_recursive(client, src, dst, args).await;
+    }
+    match (src, dst) {
+        (CpEnd::Local(path), CpEnd::Remote(uri)) => {
+            if path.is_dir() {
+                anyhow::bail!("{} 是目录，复制目录需要 --recursive", path.display());
+            }
+            let key = cp_target_key(&uri.key, &derive_key_from_path(&path, None)?);
+            if args.dry_run {
+                println!("(dry-run) 将上传 {} 到 s3://{}/{}", path.display(), uri.bucket, key);
+                return Ok(());
+            }
+            let remote = client.with_bucket(&uri.bucket);
+            let bar = TransferBar::upload(&path);
+            let options = UploadOptions::default().progress(&bar).cancel(cancel_on_ctrl_c());
+            remote.upload_with_options(&path, &key, &options).await?;
+            println!("成功上传 {} 到 s3://{}/{}", path.display(), uri.bucket, key);
+        }
+        (CpEnd::Remote(uri), CpEnd::Local(dst)) => {
+            if uri.key.is_empty() || uri.key.ends_with('/') {
+                anyhow::bail!("{} 是前缀，复制前缀需要 --recursive", args.src);
+            }
+            let path = cp_target_path(&dst, &uri.key);
+            if args.dry_run {
+                println!("(dry-run) 将下载 s3://{}/{} 到 {}", uri.bucket, uri.key, path.display());
+                return Ok(());
+            }
+            let remote = client.with_bucket(&uri.bucket);
+            let bar = TransferBar::download(&uri.key);
+            let options = DownloadOptions::default().progress(&bar).cancel(cancel_on_ctrl_c());
+            let path = remote.download_with_options(&uri.key, Some(&path), &options).await?;
+            println!("成功下载 s3://{}/{} 到 {}", uri.bucket, uri.key, path.display());
+        }
+        (CpEnd::Remote(src), CpEnd::Remote(dst)) => {
+            if src.key.is_empty() || src.key.ends_with('/') {
+                anyhow::bail!("{} 是前缀，复制前缀需要 --recursive", args.src);
+            }
+            let key = cp_target_key(&dst.key, &key_filename(&src.key));
+            if args.dry_run {
+                println!("(dry-run) 将复制 s3://{}/{} 到 s3://{}/{}", src.bucket, src.key, dst.bucket, key);
+                return Ok(());
+            }
+            let (from, to) = (client.with_bucket(&src.bucket), client.with_bucket(&dst.bucket));
+            from.copy_to(&src.key, &to, &key).await?;
+            println!("成功复制 s3://{}/{} 到 s3://{}/{}", src.bucket, src.key, dst.bucket, key);
+        }
+        (CpEnd::Local(_), CpEnd::Local(_)) => anyhow::bail!("源和目标至少有一个是 s3://bucket/key"),
+    }
+    Ok(())
+}
+
+/// cp --recursive：本地目录和远程前缀之间复用同步的计划（全部传输，不跳过相同的文件），远程之间批量复制前缀
+async fn cp_recursive(client: &OssClient, src: CpEnd, dst: CpEnd, args: CpArgs) -> Result<()> {
+    let (remote, source, dest, download) = match (src, dst) {
+        (CpEnd::Local(path), CpEnd::Remote(uri)) => {
+            if !path.is_dir() {
+                anyhow::bail!("{} 不是目录", path.display());
+            }
+            (client.with_bucket(&uri.bucket), SyncSource::Local(path), SyncDest::Remote(uri.key), false)
+        }
+        (CpEnd::Remote(uri), CpEnd::Local(path)) => {
+            (client.with_bucket(&uri.bucket), SyncSource::Remote(uri.key), SyncDest::Local(path), true)
+        }
+        (CpEnd::Remote(src), CpEnd::Remote(dst)) => {
+            let (from, to) = (client.with_bucket(&src.bucket), client.with_bucket(&dst.bucket));
+            let filter = KeyFilter::new(&args.include, &args.exclude)?;
+            let (src_prefix, dst_prefix) = (directory_prefix(&src.key), directory_prefix(&dst.key));
+            return copy_prefix_command(&from, &src_prefix, &to, &dst_prefix, &filter, args.dry_run, args.output).await;
+        }
+        (CpEnd::Local(_), CpEnd::Local(_)) => anyhow::bail!("源和目标至少有一个是 s3://bucket/key"),
+    };
+
+    // 只比较大小即可，下面会把跳过的文件也改为传输
+    let options = SyncOptions { include: args.include, exclude: args.exclude, compare: CompareMode::Size, ..Default::default() };
+    let mut plan = remote.plan(source, dest, &options).await?;
+    plan.actions.retain(|action| action.reason != SyncReason::Extra);
+    let kind = if download { SyncActionKind::Download } else { SyncActionKind::Upload };
+    for action in &mut plan.actions {
+        action.kind = kind;
+    }
+    let report = if args.dry_run { plan.dry_run_report() } else { remote.execute_plan(&plan).await? };
+    print_sync_report(&report, download, args.dry_run, args.output)
+}
+
+/// 批量复制前缀，按对象数和字节数显示进度
+async fn copy_prefix_command(
+    client: &OssClient,
+    src_prefix: &str,
+    target: &OssClient,
+    dst_prefix: &str,
+    filter: &KeyFilter,
+    dry_run: bool,
+    output: OutputFormat,
+) -> Result<()> {
+    let pb = ProgressBar::new(0);
+    if !dry_run {
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} {msg}")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+    }
+    let report = client
+        .copy_prefix_filtered(src_prefix, target, dst_prefix, filter, dry_run, |progress| {
+            pb.set_length(progress.total_bytes);
+            pb.set_position(progress.bytes);
+            pb.set_message(format!("{}/{} 个对象", progress.objects, progress.total_objects));
+        })
+        .await?;
+    pb.finish_and_clear();
+
+    match output {
+        OutputFormat::Json => {
+            let mut summary = serde_json::to_value(&report)?;
+            summary["dry_run"] = dry_run.into();
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        OutputFormat::Text => {
+            if dry_run {
+                for (src, dst) in &report.copied {
+                    println!("{} -> {}", src, dst);
+                }
+                println!("(dry-run) 将复制 {} 个对象，共 {}", report.copied.len(), format_size(report.bytes));
+            } else {
+                for failure in &report.errors {
+                    eprintln!("复制失败 {}: {}", failure.key, failure.message);
+                }
+                println!(
+                    "成功复制 {} 个（{}），失败 {} 个",
+                    report.copied.len(),
+                    format_size(report.bytes),
+                    report.errors.len(),
+                );
+            }
+        }
+    }
+    if !report.is_success() {
+        return Err(ExitError::new(1, format!("{} 个对象复制失败", report.errors.len())).into());
+    }
+    Ok(())
+}
+
+/// 批量重命名前缀
+async fn rename_prefix_command(
+    client: &OssClient,
+    old_prefix: &str,
+    new_prefix: &str,
+    dry_run: bool,
+    journal: &Path,
+) -> Result<()> {
+    if dry_run {
+        let mut objects = client.list_objects(old_prefix).await?;
+        objects.retain(|obj| needs_rename(old_prefix, new_prefix, &obj.key));
+        for obj in &objects {
+            println!("{} -> {}", obj.key, rename_target(old_prefix, new_prefix, &obj.key));
+        }
+        println!("(dry-run) 将移动 {} 个对象", objects.len());
+        return Ok(());
+    }
+
+    if journal.exists() {
+        eprintln!("发现日志 {}，继续上次未完成的重命名", journal.display());
+    }
+    let pb = ProgressBar::new(0);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+    pb.set_message("移动中");
+    let report = client
+        .rename_prefix(old_prefix, new_prefix, Some(journal), |done, total| {
+            pb.set_length(total as u64);
+            pb.set_position(done as u64);
+        })
+        .await?;
+    pb.finish_and_clear();
+
+    for failure in &report.errors {
+        eprintln!("移动失败 {}: {}", failure.key, failure.message);
+    }
+    println!("成功移动 {} 个，失败 {} 个", report.moved.len(), report.errors.len());
+    if !report.is_success() {
+        return Err(ExitError::new(
+            1,
+            format!("{} 个对象移动失败，修复后重新运行会从日志 {} 继续", report.errors.len(), journal.display()),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// 打印同步结果，有失败时返回非 0 退出码
+fn print_sync_report(report: &SyncReport, download: bool, dry_run: bool, output: OutputFormat) -> Result<()> {
+    match output {
+        OutputFormat::Json => {
+            let mut summary = serde_json::to_value(report)?;
+            summary["dry_run"] = dry_run.into();
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        OutputFormat::Text => {
+            let mark = if dry_run { "(dry-run) " } else { "" };
+            let verb = if download { "下载" } else { "上传" };
+            for name in &report.transferred {
+                println!("{}{} {}", mark, verb, name);
+            }
+            for key in &report.deleted {
+                println!("{}删除 {}", mark, key);
+            }
+            for failure in &report.errors {
+                eprintln!("同步失败 {}: {}", failure.key, failure.message);
+            }
+            println!(
+                "{}{} {} 个，跳过 {} 个，删除 {} 个，失败 {} 个",
+                mark,
+                verb,
+                report.transferred.len(),
+                report.skipped,
+                report.deleted.len(),
+                report.errors.len(),
+            );
+        }
+    }
+
+    if !report.is_success() {
+        return Err(ExitError::new(1, format!("{} 个文件同步失败", report.errors.len())).into());
+    }
+    Ok(())
+}
+
+async fn watch_command(client: &OssClient, dir: &Path, options: WatchOptions) -> Result<()> {
+    eprintln!("开始监听 {}，按 Ctrl-C 退出", dir.display());
+    let shutdown = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    let summary = client
+        .watch(dir, &options, shutdown, |event| match event {
+            WatchEvent::Uploaded { path, key, size } => {
+                println!("成功上传 {} 到 {} ({})", path.display(), key, format_size(size))
+            }
+            WatchEvent::Retry { path, attempt, error } => {
+                eprintln!("上传 {} 失败，稍后第 {} 次尝试: {:#}", path.display(), attempt, error)
+            }
+            WatchEvent::Failed { path, error } => eprintln!("放弃上传 {}: {:#}", path.display(), error),
+            WatchEvent::CleanupFailed { path, error } => {
+                eprintln!("{} 已上传，但处理本地文件失败: {:#}", path.display(), error)
+            }
+            WatchEvent::Draining { in_flight } if in_flight > 0 => {
+                eprintln!("正在退出，等待 {} 个进行中的上传完成...", in_flight)
+            }
+            WatchEvent::Draining { .. } => {}
+        })
+        .await?;
+
+    eprintln!("已停止监听：成功上传 {} 个文件，失败 {} 个", summary.uploaded, summary.failed);
+    if summary.pending > 0 {
+        eprintln!("{} 个文件还没有上传，下次启动时会重新上传", summary.pending);
+    }
+    if summary.failed > 0 {
+        return Err(ExitError::silent(1).into());
+    }
+    Ok(())
+}
+
+/// 配置了公开域名时提示预签名 URL 仍然使用 endpoint
+fn note_presigned_host(client: &OssClient) {
+    if let Some(base) = client.public_base_url() {
+        eprintln!("注意: 预签名 URL 的签名包含 host，使用的是 endpoint 而不是 {}", base);
+    }
+}
+
+/// 把预签名 URL 包装成 curl/wget 命令，所有参数都经过 shell 引号处理
+fn url_command(format: UrlFormat, method: UrlMethod, url: &str, filename: &str, content_type: Option<&str>) -> String {
+    let header = content_type.map(|t| format!("Content-Type: {}", t));
+    let args: Vec<String> = match (format, method) {
+        (UrlFormat::Plain, _) => return url.to_string(),
+        (UrlFormat::Curl, UrlMethod::Get) => vec!["curl".into(), "-fL".into(), "-o".into(), filename.into(), url.into()],
+        (UrlFormat::Curl, UrlMethod::Put) => {
+            let mut args = vec!["curl".into(), "-f".into(), "-X".into(), "PUT".into(), "-T".into(), filename.into()];
+            if let Some(header) = header {
+                args.extend(["-H".into(), header]);
+            }
+            args.push(url.into());
+            args
+        }
+        (UrlFormat::Wget, UrlMethod::Get) => vec!["wget".into(), "-O".into(), filename.into(), url.into()],
+        (UrlFormat::Wget, UrlMethod::Put) => {
+            let mut args = vec!["wget".into(), "--method=PUT".into(), format!("--body-file={}", filename)];
+            if let Some(header) = header {
+                args.push(format!("--header={}", header));
+            }
+            args.extend(["-O".into(), "-".into(), url.into()]);
+            args
+        }
+    };
+    args.iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>().join(" ")
+}
+
+/// POSIX shell 引号：只包含安全字符时原样返回，否则用单引号包起来，单引号本身写成 '\''
+fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@".contains(c));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// 渲染目录树，目录以 `/` 结尾，对象后显示大小
+fn tree_lines(root: &TreeNode) -> Vec<String> {
+    let mut lines = vec![root.name.clone()];
+    push_tree_lines(root, "", &mut lines);
+    lines
+}
+
+fn push_tree_lines(node: &TreeNode, indent: &str, lines: &mut Vec<String>) {
+    for (index, child) in node.children.iter().enumerate() {
+        let last = index + 1 == node.children.len() && node.omitted == 0;
+        let (branch, next_indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+        match child.size {
+            Some(size) => lines.push(format!("{}{}{} ({})", indent, branch, child.name, format_size(size))),
+            None => lines.push(format!("{}{}{}", indent, branch, child.name)),
+        }
+        push_tree_lines(child, &format!("{}{}", indent, next_indent), lines);
+    }
+    if node.omitted > 0 {
+        lines.push(format!("{}└── … 还有 {} 项", indent, node.omitted));
+    }
+}
+
+/// 按路径排序输出差异，每行一个，类似 rsync --itemize-changes
+fn print_diff_report(report: &DiffReport, output: OutputFormat) -> Result<()> {
+    match output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(report)?),
+        OutputFormat::Text => {
+            for (mark, name) in diff_lines(report) {
+                println!("{} {}", mark, name);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn diff_lines(report: &DiffReport) -> Vec<(char, &str)> {
+    let mut lines: Vec<_> = report
+        .local_only
+        .iter()
+        .map(|name| ('+', name.as_str()))
+        .chain(report.remote_only.iter().map(|name| ('-', name.as_str())))
+        .chain(report.changed.iter().map(|name| ('~', name.as_str())))
+        .collect();
+    lines.sort_by(|a, b| a.1.cmp(b.1));
+    lines
+}
+
+/// 删除命令
+async fn delete_command(client: &OssClient, args: DeleteArgs) -> Result<()> {
+    if let Some(path) = &args.from_file {
+        let file = std::fs::File::open(path)
+            .map_err(|e| anyhow::anyhow!("无法打开 {}: {}", path.display(), e))?;
+        return delete_from_list(client, std::io::BufReader::new(file), &args).await;
+    }
+    if args.from_stdin {
+        return delete_from_list(client, std::io::stdin().lock(), &args).await;
+    }
+
+    if let Some(version_id) = &args.version_id {
+        let [key] = args.keys.as_slice() else {
+            anyhow::bail!("--version-id 只能用于单个 key");
+        };
+        if args.dry_run {
+            println!("(dry-run) 将删除 {} 的版本 {}", key, version_id);
+            return Ok(());
+        }
+        let deleted = client.delete_version(key, version_id).await?;
+        if deleted.delete_marker {
+            println!("成功删除 {} 的删除标记 {}", key, deleted.version_id);
+        } else {
+            println!("成功删除 {} 的版本 {}", key, deleted.version_id);
+        }
+        return Ok(());
+    }
+
+    if args.purge {
+        let [key] = args.keys.as_slice() else {
+            anyhow::bail!("--purge 只能用于单个 key");
+        };
+        return purge_command(client, key, &args).await;
+    }
+
+    if !args.recursive {
+        if args.dry_run {
+            let targets: Vec<_> = args.keys.iter().map(|key| (key.clone(), None)).collect();
+            return print_delete_plan(&targets, &[], args.output);
+        }
+        if !args.interactive {
+            match args.keys.as_slice() {
+                // JSON 输出和严格模式下单个 key 也走下面的 HEAD + 批量删除流程：
+                // 输出统一的结构化结果，严格模式 HEAD 失败时不删除
+                [key] if args.output == OutputFormat::Text && !args.strict => {
+                    return match client.delete(key).await? {
+                        DeleteOutcome::Deleted => {
+                            println!("成功删除 {}", key);
+                            Ok(())
+                        }
+                        DeleteOutcome::DidNotExist => {
+                            println!("{} 不存在，无需删除", key);
+                            Ok(())
+                        }
+                        // DeleteOutcome::Unknown：已发送删除但无法确认
+                        _ => {
+                            println!("已删除 {}（没有 HEAD 权限，无法确认删除前是否存在）", key);
+                            Ok(())
+                        }
+                    };
+                }
+                [_, _, ..] if !args.strict => {
+                    let report = client.delete_many(&args.keys).await?;
+                    return print_delete_report(&report, 0, &[], args.output);
+                }
+                _ => {}
+            }
+        }
+
+        // 严格模式需要先确认每个 key 都存在，交互模式需要大小和修改时间用于提示
+        let heads: Vec<_> = futures::stream::iter(args.keys.iter())
+            .map(|key| async move { (key, client.head_if_exists(key).await) })
+            .buffered(16)
+            .collect()
+            .await;
+        let mut existing = Vec::new();
+        let mut missing = Vec::new();
+        for (key, head) in heads {
+            match head? {
+                Some(info) => existing.push((key.clone(), Some(info))),
+                None => missing.push(key.clone()),
+            }
+        }
+
+        let (keys, skipped) = if args.interactive {
+            confirm_each(existing)?
+        } else {
+            (existing.into_iter().map(|(key, _)| key).collect(), 0)
+        };
+        let report = client.delete_many(&keys).await?;
+        print_delete_report(&report, skipped, &missing, args.output)?;
+        if !missing.is_empty() && args.strict {
+            return Err(ExitError::new(EXIT_NOT_FOUND, format!("{} 个 key 不存在", missing.len())).into());
+        }
+        return Ok(());
+    }
+
+    // 按目录删除：logs 只删除 logs/ 下的对象，不会误删 logs-archive/
+    let prefixes: Vec<String> = args.keys.iter().map(|prefix| directory_prefix(prefix)).collect();
+    if prefixes.iter().any(|prefix| prefix.is_empty()) && !args.i_know_this_deletes_everything {
+        anyhow::bail!("拒绝使用空前缀递归删除整个 bucket，确认要这样做请加上 --i-know-this-deletes-everything");
+    }
+
+    let filter = KeyFilter::new(&args.include, &args.exclude)?;
+    let too_new = AtomicUsize::new(0);
+    // 对象的过滤结果，修改时间不早于 --older-than 时为 None
+    let check = |prefix: &str, obj: &ObjectInfo| {
+        if let Some(threshold) = &args.older_than {
+            if obj.last_modified.as_ref().is_none_or(|t| t >= threshold) {
+                too_new.fetch_add(1, AtomicOrdering::Relaxed);
+                return None;
+            }
+        }
+        Some(filter.check(obj.key.strip_prefix(prefix).unwrap_or(&obj.key)))
+    };
+    let warn_skipped = || {
+        for pattern in filter.unused_excludes() {
+            eprintln!("警告: --exclude {} 没有匹配任何 key", pattern);
+        }
+        let too_new = too_new.swap(0, AtomicOrdering::Relaxed);
+        if too_new > 0 {
+            eprintln!("跳过 {} 个修改时间晚于 --older-than 的对象", too_new);
+        }
+    };
+
+    // dry-run 和逐个确认需要列出每个对象
+    if args.dry_run || args.interactive {
+        let mut targets = Vec::new();
+        let mut spared = Vec::new();
+        for prefix in &prefixes {
+            let mut objects = client.list_objects_stream(prefix);
+            while let Some(obj) = objects.try_next().await? {
+                match check(prefix, &obj) {
+                    Some(FilterVerdict::Selected) => targets.push((obj.key.clone(), Some(obj))),
+                    Some(FilterVerdict::Excluded) => spared.push(obj),
+                    _ => {}
+                }
+            }
+        }
+        warn_skipped();
+        if args.dry_run {
+            return print_delete_plan(&targets, &spared, args.output);
+        }
+        if targets.is_empty() {
+            println!("没有需要删除的对象");
+            return Ok(());
+        }
+        let (keys, skipped) = confirm_each(targets)?;
+        let report = client.delete_many(&keys).await?;
+        return print_delete_report(&report, skipped, &[], args.output);
+    }
+
+    // 确认前先数一遍，只统计数量和大小；删除时再边列出边删除，不保存整个列表
+    let mut total = None;
+    if !args.yes {
+        let (mut count, mut bytes) = (0, 0);
+        for prefix in &prefixes {
+            let mut objects = client.list_objects_stream(prefix);
+            while let Some(obj) = objects.try_next().await? {
+                if check(prefix, &obj) == Some(FilterVerdict::Selected) {
+                    count += 1;
+                    bytes += obj.size;
+                }
+            }
+        }
+        warn_skipped();
+        if count == 0 {
+            println!("没有需要删除的对象");
+            return Ok(());
+        }
+        if !confirm(&format!("将删除 {} 个对象，共 {}，确定吗？", count, format_size(bytes)))? {
+            anyhow::bail!("已取消删除");
+        }
+        total = Some(count);
+    }
+
+    let pb = ProgressBar::new(total.unwrap_or(0));
+    let template = match total {
+        Some(_) => "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}",
+        None => "{spinner:.green} [{elapsed_precise}] {pos} 个 {msg}",
+    };
+    pb.set_style(ProgressStyle::default_bar().template(template).unwrap().progress_chars("#>-"));
+    pb.set_message("删除中");
+    let cancel = cancel_on_ctrl_c();
+    let mut report = DeleteReport::default();
+    for prefix in &prefixes {
+        let exclude = |obj: &ObjectInfo| check(prefix, obj) != Some(FilterVerdict::Selected);
+        let done = report.deleted.len() + report.errors.len();
+        let on_progress = |processed| pb.set_position((done + processed) as u64);
+        let options = DeletePrefixOptions {
+            exclude: Some(&exclude),
+            allow_all: args.i_know_this_deletes_everything,
+            cancel: Some(cancel.clone()),
+            on_progress: Some(&on_progress),
+            ..Default::default()
+        };
+        report.merge(client.delete_prefix(prefix, &options).await?);
+    }
+    pb.finish_and_clear();
+    if total.is_none() {
+        warn_skipped();
+        if report.deleted.is_empty() && report.errors.is_empty() {
+            println!("没有需要删除的对象");
+            return Ok(());
+        }
+    }
+
+    print_delete_report(&report, 0, &[], args.output)
+}
+
+/// 永久删除 key 的所有版本和删除标记
+async fn purge_command(client: &OssClient, key: &str, args: &DeleteArgs) -> Result<()> {
+    let versions: Vec<_> = client
+        .list_object_versions(key)
+        .await?
+        .into_iter()
+        .filter(|v| v.key == key)
+        .collect();
+
+    if args.dry_run {
+        for v in &versions {
+            let kind = if v.is_delete_marker { "删除标记" } else { "版本" };
+            println!("{}  {}  {}  {}", kind, v.version_id, format_size(v.size), format_time(v.last_modified.as_ref()));
+        }
+        println!("(dry-run) 将删除 {} 的 {} 个版本", key, versions.len());
+        return Ok(());
+    }
+
+    if versions.is_empty() {
+        println!("{} 没有任何版本", key);
+        return Ok(());
+    }
+
+    if !args.yes {
+        let prompt = format!("将永久删除 {} 的 {} 个版本和删除标记，无法恢复，确定吗？", key, versions.len());
+        if !confirm(&prompt)? {
+            anyhow::bail!("已取消删除");
+        }
+    }
+
+    let targets: Vec<_> = versions.into_iter().map(|v| (v.key, v.version_id)).collect();
+    let report = client.delete_versions(&targets).await?;
+    print_delete_report(&report, 0, &[], args.output)
+}
+
+/// 从列表删除：逐行读取 key，去重后每凑满一批就发出一次 DeleteObjects，不会把整个列表读入内存
+async fn delete_from_list(client: &OssClient, reader: impl BufRead, args: &DeleteArgs) -> Result<()> {
+    let mut seen = HashSet::new();
+    let mut batch = Vec::with_capacity(DELETE_BATCH_SIZE);
+    let mut planned = Vec::new();
+    let mut report = DeleteReport::default();
+
+    for key in read_key_list(reader) {
+        let key = key?;
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        if args.dry_run {
+            planned.push((key, None));
+            continue;
+        }
+        batch.push(key);
+        if batch.len() == DELETE_BATCH_SIZE {
+            report.merge(client.delete_many(&batch).await?);
+            eprintln!("已处理 {} 个 key", seen.len());
+            batch.clear();
+        }
+    }
+
+    if args.dry_run {
+        return print_delete_plan(&planned, &[], args.output);
+    }
+    if !batch.is_empty() {
+        report.merge(client.delete_many(&batch).await?);
+    }
+    print_delete_report(&report, 0, &[], args.output)
+}
+
+/// 读取 key 列表：每行一个 key，跳过空行和 `#` 开头的注释
+fn read_key_list(reader: impl BufRead) -> impl Iterator<Item = std::io::Result<String>> {
+    reader.lines().filter_map(|line| match line {
+        Ok(line) => {
+            let key = line.strip_suffix('\r').unwrap_or(&line);
+            if key.trim().is_empty() || key.trim_start().starts_with('#') {
+                None
+            } else {
+                Some(Ok(key.to_string()))
+            }
+        }
+        Err(e) => Some(Err(e)),
+    })
+}
+
+/// 打印将要删除的 key（--dry-run），不发出任何删除请求
+/// 递归删除时带有对象的大小和修改时间，被 --exclude 保留的对象单独标注
+fn print_delete_plan(
+    targets: &[(String, Option<ObjectInfo>)],
+    spared: &[ObjectInfo],
+    output: OutputFormat,
+) -> Result<()> {
+    let total_bytes: u64 = targets.iter().filter_map(|(_, obj)| obj.as_ref()).map(|obj| obj.size).sum();
+
+    match output {
+        OutputFormat::Json => {
+            let objects: Vec<_> = targets
+                .iter()
+                .map(|(key, obj)| match obj {
+                    Some(obj) => serde_json::json!({
+                        "key": key,
+                        "size": obj.size,
+                        "last_modified": format_time(obj.last_modified.as_ref()),
+                    }),
+                    None => serde_json::json!({ "key": key }),
+                })
+                .collect();
+            let spared: Vec<_> = spared
+                .iter()
+                .map(|obj| serde_json::json!({
+                    "key": obj.key,
+                    "size": obj.size,
+                    "last_modified": format_time(obj.last_modified.as_ref()),
+                }))
+                .collect();
+            let plan = serde_json::json!({
+                "dry_run": true,
+                "objects": objects,
+                "spared": spared,
+                "total_objects": targets.len(),
+                "total_bytes": total_bytes,
+            });
+            println!("{}", serde_json::to_string_pretty(&plan)?);
+        }
+        OutputFormat::Text => {
+            for (key, obj) in targets {
+                match obj {
+                    Some(obj) => println!(
+                        "{:>12}  {}  {}",
+                        format_size(obj.size),
+                        format_time(obj.last_modified.as_ref()),
+                        key,
+                    ),
+                    None => println!("{}", key),
+                }
+            }
+            for obj in spared {
+                println!(
+                    "{:>12}  {}  {}  (excluded, 保留)",
+                    format_size(obj.size),
+                    format_time(obj.last_modified.as_ref()),
+                    obj.key,
+                );
+            }
+            if !spared.is_empty() {
+                println!("(dry-run) 保留 {} 个被排除的对象", spared.len());
+            }
+            println!("(dry-run) 将删除 {} 个对象，共 {}", targets.len(), format_size(total_bytes));
+        }
+    }
+    Ok(())
+}
+
+/// 逐个询问是否删除（-i），返回确认删除的 key 和跳过的数量
+/// 回答 y 删除，n 跳过，a 删除当前及剩余所有，q 跳过当前及剩余所有
+fn confirm_each(targets: Vec<(String, Option<ObjectInfo>)>) -> Result<(Vec<String>, usize)> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("-i 需要在终端中交互确认，非交互环境请去掉 -i（递归删除时使用 --yes）");
+    }
+
+    let total = targets.len();
+    let mut keys = Vec::new();
+    let mut targets = targets.into_iter();
+    while let Some((key, info)) = targets.next() {
+        let detail = info
+            .map(|info| {
+                let date = format_time(info.last_modified.as_ref());
+                format!(" ({}, 修改于 {})", format_size(info.size), date.get(..10).unwrap_or(&date))
+            })
+            .unwrap_or_default();
+        match prompt(&format!("删除 {}{}？[y/N/a/q]", key, detail))?.as_str() {
+            "y" | "yes" => keys.push(key),
+            "a" | "all" => {
+                keys.push(key);
+                keys.extend(targets.by_ref().map(|(key, _)| key));
+            }
+            "q" | "quit" => break,
+            _ => {}
+        }
+    }
+
+    let skipped = total - keys.len();
+    Ok((keys, skipped))
+}
+
+/// 打印批量删除结果，有失败时返回非 0 退出码
+/// `missing` 为事先 HEAD 确认不存在而没有删除的 key
+fn print_delete_report(report: &DeleteReport, skipped: usize, missing: &[String], output: OutputFormat) -> Result<()> {
+    match output {
+        OutputFormat::Json => {
+            let summary = serde_json::json!({
+                "deleted": report.deleted,
+                "errors": report.errors,
+                "skipped": skipped,
+                "not_found": missing,
+            });
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        OutputFormat::Text => {
+            for failure in &report.errors {
+                let request_id = failure.request_id.as_ref().map(|id| format!("（request id {}）", id)).unwrap_or_default();
+                match &failure.version_id {
+                    Some(version_id) => eprintln!(
+                        "删除失败 {} (版本 {}): {} {}{}",
+                        failure.key, version_id, failure.code, failure.message, request_id
+                    ),
+                    None => eprintln!("删除失败 {}: {} {}{}", failure.key, failure.code, failure.message, request_id),
+                }
+            }
+            for key in missing {
+                eprintln!("{} 不存在", key);
+            }
+            if skipped > 0 {
+                println!("成功删除 {} 个，失败 {} 个，跳过 {} 个", report.deleted.len(), report.errors.len(), skipped);
+            } else {
+                println!("成功删除 {} 个，失败 {} 个", report.deleted.len(), report.errors.len());
+            }
+        }
+    }
+
+    if !report.is_success() {
+        return Err(ExitError::new(1, format!("{} 个 key 删除失败", report.errors.len())).into());
+    }
+    Ok(())
+}
+
+/// 交互式确认，stdin 不是 TTY 时直接失败（需要使用 --yes）
+fn confirm(message: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("非交互环境无法确认，请使用 --yes 跳过确认");
+    }
+    Ok(matches!(prompt(&format!("{} [y/N]", message))?.as_str(), "y" | "yes"))
+}
+
+/// 在 stderr 输出提示并读取一行回答（已去除首尾空白并转为小写）
+fn prompt(message: &str) -> Result<String> {
+    eprint!("{} ", message);
+    std::io::stderr().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().to_ascii_lowercase())
+}
+
+/// 格式化时间为 RFC3339
+fn format_time(time: Option<&DateTime>) -> String {
+    time.and_then(|t| t.fmt(DateTimeFormat::DateTime).ok())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// `ls --output json` 的一行
+fn ls_json(entry: &ListEntry) -> serde_json::Value {
+    match entry {
+        ListEntry::Prefix(prefix) => serde_json::json!({ "prefix": prefix }),
+        ListEntry::Object(obj) => serde_json::json!({
+            "key": obj.key,
+            "size": obj.size,
+            "last_modified": obj.last_modified.as_ref().and_then(|t| t.fmt(DateTimeFormat::DateTime).ok()),
+            "etag": obj.etag,
+            "storage_class": obj.storage_class,
+        }),
+    }
+}
+
+/// 格式化为本地时间
+fn format_local_time(time: &DateTime) -> String {
+    chrono::DateTime::from_timestamp(time.secs(), time.subsec_nanos())
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// 格式化距今的时长（秒），如 3天前
+fn format_age(secs: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    match secs {
+        ..MINUTE => "刚刚".to_string(),
+        MINUTE..HOUR => format!("{}分钟前", secs / MINUTE),
+        HOUR..DAY => format!("{}小时前", secs / HOUR),
+        _ if secs < 30 * DAY => format!("{}天前", secs / DAY),
+        _ if secs < 365 * DAY => format!("{}个月前", secs / (30 * DAY)),
+        _ => format!("{}年前", secs / (365 * DAY)),
+    }
+}
+
+/// 格式化耗时（秒）
+fn format_eta(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{}秒", secs),
+        60..=3599 => format!("{}分{}秒", secs / 60, secs % 60),
+        _ => format!("{}小时{}分", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_restore_key_or_status() {
+        let cli = Cli::try_parse_from(["oss-uploader", "restore", "a.tar", "--days", "3", "--tier", "Bulk"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Restore { command: None, key: Some(key), days: 3, tier } if key == "a.tar" && tier == "Bulk"
+        ));
+        let cli = Cli::try_parse_from(["oss-uploader", "restore", "status", "a.tar"]).unwrap();
+        assert!(matches!(cli.command, Commands::Restore { command: Some(RestoreCommand::Status { .. }), .. }));
+        assert!(Cli::try_parse_from(["oss-uploader", "restore"]).is_err());
+    }
+
+    #[test]
+    fn test_format_eta() {
+        assert_eq!(format_eta(42), "42秒");
+        assert_eq!(format_eta(125), "2分5秒");
+        assert_eq!(format_eta(3 * 3600 + 120), "3小时2分");
+    }
+
+    #[test]
+    fn test_delete_strict_conflicts() {
+        assert!(Cli::try_parse_from(["oss-uploader", "delete", "a.txt", "b.txt", "--strict"]).is_ok());
+        // 从文件或 stdin 读取 key 时不检查是否存在，不能使用严格模式
+        assert!(Cli::try_parse_from(["oss-uploader", "delete", "--from-file", "keys.txt", "--strict"]).is_err());
+        assert!(Cli::try_parse_from(["oss-uploader", "delete", "--from-stdin", "--strict"]).is_err());
+    }
+
+    #[test]
+    fn test_take_uri_buckets() {
+        let take = |args: &[&str]| {
+            let cli = Cli::try_parse_from(["oss-uploader"].iter().chain(args)).unwrap();
+            let mut command = cli.command;
+            take_uri_buckets(&mut command, cli.bucket).map(|bucket| (bucket, command))
+        };
+
+        let (bucket, command) = take(&["download", "s3://logs/2024/a%20b.txt"]).unwrap();
+        assert_eq!(bucket.as_deref(), Some("logs"));
+        assert!(matches!(command, Commands::Download { key, .. } if key == "2024/a b.txt"));
+        // 普通 key 使用配置的 bucket
+        let (bucket, command) = take(&["stat", "2024/a.txt"]).unwrap();
+        assert_eq!(bucket, None);
+        assert!(matches!(command, Commands::Stat { key, .. } if key == "2024/a.txt"));
+        let (bucket, _) = take(&["--bucket", "logs", "url", "oss://logs/a.txt"]).unwrap();
+        assert_eq!(bucket.as_deref(), Some("logs"));
+
+        let (bucket, command) = take(&["delete", "s3://logs/a.txt", "b.txt"]).unwrap();
+        assert_eq!(bucket.as_deref(), Some("logs"));
+        assert!(matches!(command, Commands::Delete(args) if args.keys == ["a.txt", "b.txt"]));
+        let (bucket, command) = take(&["delete", "-r", "s3://logs/old/"]).unwrap();
+        assert_eq!(bucket.as_deref(), Some("logs"));
+        assert!(matches!(command, Commands::Delete(args) if args.keys == ["old/"]));
+
+        // copy 的目标地址作为 --dest-bucket
+        let (bucket, command) = take(&["copy", "a.txt", "s3://backup/a.txt"]).unwrap();
+        assert_eq!(bucket, None);
+        assert!(matches!(command, Commands::Copy { dst, dest_bucket: Some(b), .. } if dst == "a.txt" && b == "backup"));
+        let (bucket, _) = take(&["copy", "s3://logs/a", "s3://backup/a", "--dest-bucket", "backup"]).unwrap();
+        assert_eq!(bucket.as_deref(), Some("logs"));
+
+        for args in [
+            &["--bucket", "other", "download", "s3://logs/a.txt"][..],
+            &["delete", "s3://logs/a.txt", "s3://other/b.txt"],
+            &["copy", "a", "s3://backup/a", "--dest-bucket", "other"],
+        ] {
+            let Err(err) = take(args) else { panic!("{:?}", args) };
+            assert!(err.to_string().contains("不一致"), "{:?}: {}", args, err);
+        }
+        assert!(take(&["download", "s3:///a.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let json = |args: &[&str]| json_output(&Cli::command().try_get_matches_from(args).unwrap());
+        assert!(json(&["oss-uploader", "stat", "a.txt", "--output", "json"]));
+        assert!(json(&["oss-uploader", "acl", "get", "a.txt", "--output", "json"]));
+        assert!(!json(&["oss-uploader", "stat", "a.txt"]));
+        // download 的 -o 是本地路径，不是输出格式
+        assert!(!json(&["oss-uploader", "download", "a.txt", "-o", "json"]));
+
+        let err = anyhow::Error::from(ExitError::new(EXIT_NOT_FOUND, "对象不存在: a.txt"));
+        let value = error_json(&err, EXIT_NOT_FOUND);
+        assert_eq!(value["error"]["message"], "对象不存在: a.txt");
+        assert_eq!((value["error"]["exit_code"].as_u64(), value["error"]["request_id"].is_null()), (Some(3), true));
+        let err = anyhow::Error::from(OssError::Config("OSS_BUCKET not set".to_string())).context("配置错误");
+        assert_eq!(error_json(&err, 1)["error"]["message"], "配置错误: OSS_BUCKET not set");
+    }
+
+    #[test]
+    fn test_completion_scripts() {
+        let command = Cli::command();
+        for name in KEY_COMMANDS {
+            assert!(command.find_subcommand(name).is_some(), "{}", name);
+        }
+
+        // 包装函数调用生成的补全函数，并替换注册的函数
+        let bash = completion_script(Shell::Bash);
+        assert!(bash.contains("\n    _oss__uploader \"$@\"\n}\ncomplete -F _oss-uploader_keys "), "{}", bash);
+        assert!(bash.contains("download|cat|ls|exists|stat|url|delete|copy|mv)"));
+        let zsh = completion_script(Shell::Zsh);
+        assert!(zsh.contains("\n    _oss-uploader \"$@\"\n}\ncompdef _oss-uploader_keys oss-uploader\n"), "{}", zsh);
+        for script in [&bash, &zsh] {
+            assert!(script.contains("oss-uploader __complete-key "));
+        }
+        // 其他 shell 只有静态补全
+        assert!(!completion_script(Shell::Fish).contains(COMPLETE_KEY_COMMAND));
+    }
+
+    #[test]
+    fn test_cp_targets() {
+        let remote = |bucket: &str, key: &str| CpEnd::Remote(ObjectUri { bucket: bucket.into(), key: key.into() });
+        assert_eq!(CpEnd::parse("s3://logs/a.txt").unwrap(), remote("logs", "a.txt"));
+        assert_eq!(CpEnd::parse("logs/a.txt").unwrap(), CpEnd::Local(PathBuf::from("logs/a.txt")));
+        assert!(CpEnd::parse("s3:///a.txt").is_err());
+
+        // 目标为空或以 / 结尾时保留源的文件名
+        assert_eq!(cp_target_key("", "a.txt"), "a.txt");
+        assert_eq!(cp_target_key("backup/", "a.txt"), "backup/a.txt");
+        assert_eq!(cp_target_key("backup/b.txt", "a.txt"), "backup/b.txt");
+
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(cp_target_path(dir.path(), "logs/a.txt"), dir.path().join("a.txt"));
+        assert_eq!(cp_target_path(Path::new("out/"), "logs/a.txt"), Path::new("out/a.txt"));
+        assert_eq!(cp_target_path(&dir.path().join("b.txt"), "logs/a.txt"), dir.path().join("b.txt"));
+
+        // --include 等过滤参数只能用于 --recursive
+        assert!(Cli::try_parse_from(["oss-uploader", "cp", "a", "s3://b/", "--exclude", "*.log"]).is_err());
+        assert!(Cli::try_parse_from(["oss-uploader", "cp", "-r", "a", "s3://b/", "--exclude", "*.log"]).is_ok());
+    }
+
+    #[test]
+    fn test_verbosity_counts() {
+        let cli = Cli::try_parse_from(["oss-uploader", "-vv", "upload", "a.zip"]).unwrap();
+        assert_eq!(cli.verbosity, 2);
+        // 子命令自己的 -v 不受影响
+        let cli = Cli::try_parse_from(["oss-uploader", "exists", "a.zip", "-v"]).unwrap();
+        assert_eq!(cli.verbosity, 0);
+        assert!(matches!(cli.command, Commands::Exists { verbose: true, .. }));
+    }
+
+    #[test]
+    fn test_url_public_conflicts_with_expires() {
+        let cli = Cli::try_parse_from(["oss-uploader", "url", "a.png", "--public"]).unwrap();
+        assert!(matches!(cli.command, Commands::Url { public: true, .. }));
+        assert!(Cli::try_parse_from(["oss-uploader", "url", "a.png", "--public", "-e", "60"]).is_err());
+        let cli = Cli::try_parse_from(["oss-uploader", "url", "a.png", "-e", "12h"]).unwrap();
+        assert!(matches!(cli.command, Commands::Url { expires: 43200, .. }));
+        assert!(Cli::try_parse_from(["oss-uploader", "url", "a.png", "-e", "1209600"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_key_list() {
+        let keys = parse_key_list("a.zip\r\n\nrelease/b c.tar.gz\n", false).unwrap();
+        assert_eq!(keys, vec![("a.zip".to_string(), None), ("release/b c.tar.gz".to_string(), None)]);
+
+        let keys = parse_key_list("key,expires\na.zip,7d\n\"b,1.zip\",3600\nc.zip\n", true).unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                ("a.zip".to_string(), Some(7 * 86400)),
+                ("b,1.zip".to_string(), Some(3600)),
+                ("c.zip".to_string(), None),
+            ]
+        );
+        let err = parse_key_list("a.zip,30d\n", true).unwrap_err();
+        assert!(err.to_string().contains("第 1 行"), "{}", err);
+        assert!(parse_key_list("a,b,c\n", true).is_err());
+        assert_eq!(csv_field("say \"hi\", ok"), "\"say \"\"hi\"\", ok\"");
+    }
+
+    #[test]
+    fn test_parse_upload_manifest() {
+        let items = parse_upload_manifest("dist/a.zip\r\n\nb c.txt\n", false, Some("release")).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                (PathBuf::from("dist/a.zip"), "release/a.zip".to_string()),
+                (PathBuf::from("b c.txt"), "release/b c.txt".to_string()),
+            ]
+        );
+
+        let items = parse_upload_manifest("path,key\ndist/a.zip,builds/1.zip\n\"x,y.txt\"\n", true, None).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                (PathBuf::from("dist/a.zip"), "builds/1.zip".to_string()),
+                (PathBuf::from("x,y.txt"), "x,y.txt".to_string()),
+            ]
+        );
+        let err = parse_upload_manifest("a,b,c\n", true, None).unwrap_err();
+        assert!(err.to_string().contains("第 1 行"), "{}", err);
+    }
+
+    #[test]
+    fn test_qr_code_falls_back_to_low_level() {
+        let url = format!("https://bucket.example.com/{}", "a".repeat(100));
+        assert!(!qr_code(&url).unwrap().1);
+        // M 级最多 2331 字节，L 级最多 2953 字节
+        let (code, downgraded) = qr_code(&"a".repeat(2500)).unwrap();
+        assert!(downgraded);
+        assert_eq!(code.error_correction_level(), EcLevel::L);
+        assert!(qr_code(&"a".repeat(3000)).is_err());
+    }
+
+    #[test]
+    fn test_lock_set_args() {
+        let cli = Cli::try_parse_from([
+            "oss-uploader", "lock", "set", "a.pdf", "--mode", "governance", "--retain-until", "2026-01-01T00:00:00Z",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Lock { command: LockCommand::Set { mode, retain_until, bypass_governance: false, .. } }
+                if mode == "governance" && retain_until.secs() == 1767225600
+        ));
+        assert!(Cli::try_parse_from(["oss-uploader", "lock", "set", "a.pdf", "--mode", "legal", "--retain-until", "1d"])
+            .is_err());
+        assert!(Cli::try_parse_from(["oss-uploader", "legal-hold", "maybe", "a.pdf"]).is_err());
+    }
+
+    #[test]
+    fn test_upload_json() {
+        let upload = MultipartUpload {
+            key: "big.bin".to_string(),
+            upload_id: "abc".to_string(),
+            initiated: Some(DateTime::from_secs(0)),
+            storage_class: "STANDARD".to_string(),
+        };
+        let json = upload_json(&upload, None);
+        assert_eq!(json["initiated"], "1970-01-01T00:00:00Z");
+        assert!(json.get("parts").is_none());
+        let json = upload_json(&upload, Some(&UploadedParts { count: 3, bytes: 30 }));
+        assert_eq!((json["parts"].as_u64(), json["bytes"].as_u64()), (Some(3), Some(30)));
+    }
+
+    #[test]
+    fn test_read_key_list() {
+        let input = "a.txt\n\n# comment\n  \nb/c.txt\r\n  # indented comment\nd e.txt\n";
+        let keys: Vec<String> = read_key_list(input.as_bytes()).collect::<Result<_, _>>().unwrap();
+        assert_eq!(keys, vec!["a.txt", "b/c.txt", "d e.txt"]);
+    }
+
+    fn test_printer(output: OutputFormat, null: bool) -> LsPrinter {
+        LsPrinter { long: false, time_style: TimeStyle::Iso, output, null, relative_to: Some("dir/".to_string()) }
+    }
+
+    fn test_info(key: &str, size: u64, secs: i64) -> ObjectInfo {
+        ObjectInfo {
+            key: key.to_string(),
+            size,
+            etag: Some("\"abc\"".to_string()),
+            storage_class: "STANDARD".to_string(),
+            last_modified: Some(DateTime::from_secs(secs)),
+            owner: None,
+            content_type: None,
+            sse: None,
+            metadata: Default::default(),
+            version_id: None,
+        }
+    }
+
+    fn test_object(key: &str) -> ListEntry {
+        ListEntry::Object(Box::new(test_info(key, 42, 0)))
+    }
+
+    fn ls_args(args: &[&str]) -> LsArgs {
+        let cli = Cli::try_parse_from(["oss-uploader", "ls"].iter().chain(args)).unwrap();
+        match cli.command {
+            Commands::Ls(args) => args,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_ls_filters_are_combined() {
+        let args = ls_args(&["--min-size", "1K", "--max-size", "1M", "--older-than", "1970-01-02T00:00:00Z"]);
+        assert!(args.matches(&test_info("a", 2048, 0)));
+        assert!(!args.matches(&test_info("a", 100, 0)));
+        assert!(!args.matches(&test_info("a", 2 * 1024 * 1024, 0)));
+        assert!(!args.matches(&test_info("a", 2048, 2 * 86400)));
+
+        let args = ls_args(&["--newer-than", "1970-01-02T00:00:00Z"]);
+        assert!(args.matches(&test_info("a", 0, 2 * 86400)));
+        assert!(!args.matches(&test_info("a", 0, 0)));
+        assert!(ls_args(&[]).matches(&test_info("a", 0, 0)));
+    }
+
+    #[test]
+    fn test_sort_entries() {
+        let mut entries = vec![
+            ListEntry::Object(Box::new(test_info("b", 300, 1))),
+            ListEntry::Prefix("dir/".to_string()),
+            ListEntry::Object(Box::new(test_info("a", 100, 3))),
+            ListEntry::Object(Box::new(test_info("c", 200, 2))),
+        ];
+        let names = |entries: &[ListEntry]| entries.iter().map(|e| e.name().to_string()).collect::<Vec<_>>();
+
+        sort_entries(&mut entries, SortKey::Size, false);
+        assert_eq!(names(&entries), vec!["dir/", "a", "c", "b"]);
+        sort_entries(&mut entries, SortKey::Mtime, true);
+        assert_eq!(names(&entries), vec!["a", "c", "b", "dir/"]);
+        sort_entries(&mut entries, SortKey::Name, false);
+        assert_eq!(names(&entries), vec!["a", "b", "c", "dir/"]);
+    }
+
+    #[test]
+    fn test_ls_json_lines_round_trip() {
+        let mut out = Vec::new();
+        let printer = test_printer(OutputFormat::Json, false);
+        printer.entry(&mut out, &test_object("dir/a\nb\x01")).unwrap();
+        printer.entry(&mut out, &ListEntry::Prefix("dir/sub/".to_string())).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["key"], "dir/a\nb\x01");
+        assert_eq!(lines[0]["size"], 42);
+        assert_eq!(lines[0]["last_modified"], "1970-01-01T00:00:00Z");
+        assert_eq!(lines[0]["etag"], "\"abc\"");
+        assert_eq!(lines[1]["prefix"], "dir/sub/");
+    }
+
+    #[test]
+    fn test_ls_null_separated() {
+        let mut out = Vec::new();
+        let printer = test_printer(OutputFormat::Text, true);
+        printer.entry(&mut out, &test_object("dir/a\nb")).unwrap();
+        printer.entry(&mut out, &test_object("dir/c d")).unwrap();
+
+        let keys: Vec<&[u8]> = out.split(|b| *b == 0).collect();
+        assert_eq!(keys, vec![&b"dir/a\nb"[..], b"dir/c d", b""]);
+    }
+
+    #[test]
+    fn test_render_template() {
+        let ListEntry::Object(obj) = test_object("dir/a b.txt") else { unreachable!() };
+        assert_eq!(
+            render_template("{key}\t{size}\t{etag}\t{storage_class}\t{mtime}", &obj),
+            "dir/a b.txt\t42\tabc\tSTANDARD\t1970-01-01T00:00:00Z",
+        );
+        assert_eq!(render_template("rm {key} {unknown}", &obj), "rm dir/a b.txt {unknown}");
+    }
+
+    #[test]
+    fn test_find_accepts_negative_bounds() {
+        let cli = Cli::try_parse_from(["oss-uploader", "find", "logs/", "--size", "-1K", "--mtime", "-7d"]).unwrap();
+        let Commands::Find(args) = cli.command else { unreachable!() };
+        assert_eq!(args.size.len(), 1);
+        assert_eq!(args.mtime.len(), 1);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(-5), "刚刚");
+        assert_eq!(format_age(59), "刚刚");
+        assert_eq!(format_age(60), "1分钟前");
+        assert_eq!(format_age(2 * 3600 + 5), "2小时前");
+        assert_eq!(format_age(3 * 86400), "3天前");
+        assert_eq!(format_age(90 * 86400), "3个月前");
+        assert_eq!(format_age(800 * 86400), "2年前");
+    }
+
+    #[test]
+    fn test_format_stats() {
+        use oss_uploader::{Direction, FailureKind, Metrics};
+
+        let stats = MetricsCounters::new();
+        for op in ["HeadObject", "GetObject", "GetObject"] {
+            stats.on_request(op);
+        }
+        stats.on_retry("GetObject");
+        stats.on_bytes("GetObject", Direction::Download, 1536);
+        assert_eq!(
+            format_stats(&stats.snapshot()),
+            "请求 3 次（GetObject 2，HeadObject 1），上传 0.00 B，下载 1.50 KB，重试 1 次，失败 0 次"
+        );
+        stats.on_error("HeadObject", FailureKind::NotFound);
+        assert!(format_stats(&stats.snapshot()).ends_with("失败 1 次（not_found 1）"));
+    }
+
+    #[test]
+    fn test_url_command() {
+        let url = "https://oss.example.com/b/a%20b.txt?X-Amz-Expires=60&X-Amz-Signature=abc";
+        assert_eq!(url_command(UrlFormat::Plain, UrlMethod::Get, url, "a b.txt", None), url);
+        assert_eq!(
+            url_command(UrlFormat::Curl, UrlMethod::Get, url, "a b.txt", None),
+            format!("curl -fL -o 'a b.txt' '{}'", url),
+        );
+        assert_eq!(
+            url_command(UrlFormat::Curl, UrlMethod::Put, url, "logo.png", Some("image/png")),
+            format!("curl -f -X PUT -T logo.png -H 'Content-Type: image/png' '{}'", url),
+        );
+        assert_eq!(
+            url_command(UrlFormat::Wget, UrlMethod::Put, url, "logo.png", Some("image/png")),
+            format!("wget --method=PUT --body-file=logo.png '--header=Content-Type: image/png' -O - '{}'", url),
+        );
+        assert_eq!(shell_quote("it's 报告.pdf"), "'it'\\''s 报告.pdf'");
+    }
+
+    #[test]
+    fn test_upload_key() {
+        let cli = Cli::try_parse_from(["oss-uploader", "share", "dist/app.zip", "-p", "shares/"]).unwrap();
+        let Commands::Share { upload, expires, .. } = cli.command else { unreachable!() };
+        assert_eq!(upload.key().unwrap(), "shares/app.zip");
+        assert_eq!(expires, 86400);
+
+        let cli = Cli::try_parse_from(["oss-uploader", "upload", "dist/app.zip", "-k", "a/b.zip"]).unwrap();
+        let Commands::Upload(upload) = cli.command else { unreachable!() };
+        assert_eq!(upload.key().unwrap(), "a/b.zip");
+    }
+
+    #[test]
+    fn test_diff_lines_sorted_by_path() {
+        let report = DiffReport {
+            local_only: vec!["b.txt".to_string()],
+            remote_only: vec!["a.txt".to_string()],
+            changed: vec!["sub/c.txt".to_string()],
+        };
+        assert_eq!(diff_lines(&report), vec![('-', "a.txt"), ('+', "b.txt"), ('~', "sub/c.txt")]);
+        assert!(!report.is_identical());
+        assert!(DiffReport::default().is_identical());
+    }
+
+    #[test]
+    fn test_tree_lines() {
+        let leaf = |name: &str, size| TreeNode { name: name.to_string(), size: Some(size), children: vec![], omitted: 0 };
+        let root = TreeNode {
+            name: "logs/".to_string(),
+            size: None,
+            children: vec![
+                TreeNode { name: "2024/".to_string(), size: None, children: vec![leaf("a.gz", 2048)], omitted: 0 },
+                leaf("b.txt", 10),
+            ],
+            omitted: 3,
+        };
+        assert_eq!(
+            tree_lines(&root),
+            vec!["logs/", "├── 2024/", "│   └── a.gz (2.00 KB)", "├── b.txt (10.00 B)", "└── … 还有 3 项"],
+        );
+    }
 }
@@ -0,0 +1,230 @@
+//! 内存中的 [`ObjectStorage`] 实现，用于测试（需要开启 `testing` feature）
+
+use async_trait::async_trait;
+use aws_sdk_s3::primitives::DateTime;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::error::{Context, OssError, Result};
+use crate::key::KeyPolicy;
+use crate::{check_presign_expires, encode_key, DeleteOutcome, ObjectInfo, ObjectStorage, UploadOptions};
+
+/// 对象保存在内存中的存储，不访问网络
+/// key 的检查和规范化（默认的 [`KeyPolicy::Standard`]：非空、不超过 1024 字节、不含控制字符、去掉开头的 /）、
+/// 404 和删除不存在的对象等行为与 [`OssClient`](crate::OssClient) 一致；
+/// ETag 为内容的 MD5（同 S3 单次 PUT），列出结果按 key 排序且不含 Content-Type 等只有 HEAD 才返回的字段；
+/// [`upload_bytes_with_options`](Self::upload_bytes_with_options) 设置的 Content-Type、元数据和存储类型由 HEAD 原样返回，
+/// ACL、标签和加密等其他选项不保存
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    objects: Mutex<BTreeMap<String, MemoryObject>>,
+}
+
+#[derive(Debug, Clone)]
+struct MemoryObject {
+    data: Vec<u8>,
+    etag: String,
+    last_modified: DateTime,
+    content_type: Option<String>,
+    metadata: HashMap<String, String>,
+    storage_class: Option<String>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 对象的内容，不存在或 key 不合法时为 None
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let key = object_key(key).ok()?;
+        self.objects.lock().unwrap().get(&key).map(|object| object.data.clone())
+    }
+
+    /// 同 [`OssClient::upload_bytes_with_options`](crate::OssClient::upload_bytes_with_options)，
+    /// 保存 `options` 中的 Content-Type、元数据和存储类型
+    pub async fn upload_bytes_with_options(
+        &self,
+        key: &str,
+        data: &[u8],
+        options: &UploadOptions<'_>,
+    ) -> Result<String> {
+        let key = &object_key(key)?;
+        let object = MemoryObject {
+            data: data.to_vec(),
+            etag: format!("\"{:x}\"", md5::compute(data)),
+            last_modified: DateTime::from(SystemTime::now()),
+            content_type: options.content_type.clone(),
+            metadata: options.metadata.clone(),
+            storage_class: options.storage_class.clone(),
+        };
+        self.objects.lock().unwrap().insert(key.to_string(), object);
+        Ok(format!("memory://{}", encode_key(key)))
+    }
+
+    /// 所有对象的 key，按字典序
+    pub fn keys(&self) -> Vec<String> {
+        self.objects.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn info(&self, key: &str) -> Result<ObjectInfo> {
+        let key = &object_key(key)?;
+        let objects = self.objects.lock().unwrap();
+        let object = objects.get(key).ok_or_else(|| OssError::not_found(key))?;
+        Ok(ObjectInfo {
+            content_type: object.content_type.clone(),
+            metadata: object.metadata.clone(),
+            ..object_info(key, object)
+        })
+    }
+}
+
+/// 与 OssClient 默认的 [`KeyPolicy::Standard`] 相同地检查和规范化 key
+fn object_key(key: &str) -> Result<String> {
+    Ok(KeyPolicy::Standard.apply(key)?.into_string())
+}
+
+fn object_info(key: &str, object: &MemoryObject) -> ObjectInfo {
+    ObjectInfo {
+        key: key.to_string(),
+        size: object.data.len() as u64,
+        etag: Some(object.etag.clone()),
+        storage_class: object.storage_class.clone().unwrap_or_else(|| "STANDARD".to_string()),
+        last_modified: Some(object.last_modified),
+        owner: None,
+        content_type: None,
+        sse: None,
+        metadata: HashMap::new(),
+        version_id: None,
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for MemoryStorage {
+    async fn upload(&self, path: &Path, key: &str) -> Result<String> {
+        let data = tokio::fs::read(path)
+            .await
+            .with_context(|| format!("无法找到文件: {}", path.display()))?;
+        self.upload_bytes(key, &data).await
+    }
+
+    async fn upload_bytes(&self, key: &str, data: &[u8]) -> Result<String> {
+        self.upload_bytes_with_options(key, data, &UploadOptions::default()).await
+    }
+
+    async fn download_to_writer(&self, key: &str, writer: &mut (dyn AsyncWrite + Unpin + Send)) -> Result<u64> {
+        let key = &object_key(key)?;
+        let data = self.get(key).ok_or_else(|| OssError::not_found(key))?;
+        writer.write_all(&data).await.context("写入下载数据失败")?;
+        writer.flush().await.context("写入下载数据失败")?;
+        Ok(data.len() as u64)
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteOutcome> {
+        let key = object_key(key)?;
+        Ok(match self.objects.lock().unwrap().remove(&key) {
+            Some(_) => DeleteOutcome::Deleted,
+            None => DeleteOutcome::DidNotExist,
+        })
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        let key = object_key(key)?;
+        Ok(self.objects.lock().unwrap().contains_key(&key))
+    }
+
+    async fn head(&self, key: &str) -> Result<ObjectInfo> {
+        self.info(key)
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>> {
+        let objects = self.objects.lock().unwrap();
+        Ok(objects
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, object)| object_info(key, object))
+            .collect())
+    }
+
+    async fn presign(&self, key: &str, expires_in_secs: u64) -> Result<String> {
+        let key = &object_key(key)?;
+        check_presign_expires(expires_in_secs)?;
+        Ok(format!("memory://{}?expires={}", encode_key(key), expires_in_secs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 只依赖 ObjectStorage 的业务代码
+    async fn publish(storage: &impl ObjectStorage, name: &str, data: &[u8]) -> Result<String> {
+        let key = format!("releases/{}", name);
+        storage.upload_bytes(&key, data).await?;
+        storage.presign(&key, 3600).await
+    }
+
+    #[tokio::test]
+    async fn test_memory_storage() {
+        let storage = MemoryStorage::new();
+        let url = publish(&storage, "a b.txt", b"hello").await.unwrap();
+        assert_eq!(url, "memory://releases/a%20b.txt?expires=3600");
+        storage.upload_bytes("releases/z.txt", b"").await.unwrap();
+        storage.upload_bytes("releasesx", b"x").await.unwrap();
+
+        let info = storage.head("releases/a b.txt").await.unwrap();
+        assert_eq!((info.size, info.etag.as_deref()), (5, Some("\"5d41402abc4b2a76b9719d911017c592\"")));
+        assert!(storage.head("missing").await.unwrap_err().is_not_found());
+        assert!(!storage.exists("releases").await.unwrap());
+
+        let keys: Vec<_> = storage.list("releases/").await.unwrap().into_iter().map(|o| o.key).collect();
+        assert_eq!(keys, ["releases/a b.txt", "releases/z.txt"]);
+        assert_eq!(storage.list("").await.unwrap().len(), 3);
+
+        let mut buffer = Vec::new();
+        assert_eq!(storage.download_to_writer("releases/a b.txt", &mut buffer).await.unwrap(), 5);
+        assert_eq!(buffer, b"hello");
+        assert!(storage.download_to_writer("missing", &mut buffer).await.unwrap_err().is_not_found());
+
+        assert_eq!(storage.delete("releasesx").await.unwrap(), DeleteOutcome::Deleted);
+        assert_eq!(storage.delete("releasesx").await.unwrap(), DeleteOutcome::DidNotExist);
+        assert!(storage.upload_bytes("", b"x").await.is_err());
+        assert!(storage.presign("releases/z.txt", 0).await.is_err());
+        assert_eq!(storage.keys(), ["releases/a b.txt", "releases/z.txt"]);
+
+        // 与 OssClient 一样去掉开头的 /，拒绝控制字符
+        storage.upload_bytes("/top.txt", b"t").await.unwrap();
+        assert_eq!(storage.get("top.txt").as_deref(), Some(&b"t"[..]));
+        assert_eq!(storage.head("/top.txt").await.unwrap().key, "top.txt");
+        assert!(storage.exists("/top.txt").await.unwrap());
+        assert!(storage.upload_bytes("a\nb", b"x").await.is_err());
+        assert!(storage.upload_bytes("//a", b"x").await.is_err());
+        assert_eq!(storage.delete("/top.txt").await.unwrap(), DeleteOutcome::Deleted);
+    }
+
+    #[tokio::test]
+    async fn test_memory_storage_keeps_upload_options() {
+        let storage = MemoryStorage::new();
+        let options =
+            UploadOptions::default().content_type("text/html").metadata("Owner", "ci").storage_class("STANDARD_IA");
+        storage.upload_bytes_with_options("site/index.html", b"<p>", &options).await.unwrap();
+
+        let info = storage.head("site/index.html").await.unwrap();
+        assert_eq!(info.content_type.as_deref(), Some("text/html"));
+        assert_eq!(info.metadata, HashMap::from([("owner".to_string(), "ci".to_string())]));
+        assert_eq!(info.storage_class, "STANDARD_IA");
+
+        // 同 S3，列出结果不含 Content-Type 和元数据
+        let listed = storage.list("site/").await.unwrap();
+        assert_eq!((listed[0].content_type.as_deref(), listed[0].metadata.len()), (None, 0));
+        assert_eq!(listed[0].storage_class, "STANDARD_IA");
+
+        // 覆盖上传时不保留之前的属性
+        storage.upload_bytes("site/index.html", b"<p>").await.unwrap();
+        let info = storage.head("site/index.html").await.unwrap();
+        assert_eq!((info.content_type, info.metadata.len(), info.storage_class.as_str()), (None, 0, "STANDARD"));
+    }
+}
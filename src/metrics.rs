@@ -0,0 +1,329 @@
+//! 请求、流量、重试和失败的统计钩子：通过 SDK 的 interceptor 统计每一个请求，接入 Prometheus 等监控
+
+use aws_sdk_s3::config::interceptors::{BeforeTransmitInterceptorContextRef, FinalizerInterceptorContextRef};
+use aws_sdk_s3::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_smithy_runtime_api::box_error::BoxError;
+use aws_smithy_runtime_api::client::orchestrator::Metadata;
+use aws_smithy_runtime_api::client::retries::RequestAttempts;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 传输方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// 请求体，如 PutObject、UploadPart
+    Upload,
+    /// 响应体，如 GetObject
+    Download,
+}
+
+/// 请求最终失败的原因，与 [`OssError`](crate::OssError) 的分类一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FailureKind {
+    /// 404
+    NotFound,
+    /// 403
+    AccessDenied,
+    /// 503 / 429
+    Throttled,
+    /// 网络错误或超时，没有收到响应
+    Network,
+    /// 其他错误
+    Service,
+}
+
+impl FailureKind {
+    /// 用作监控指标的标签，如 `not_found`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureKind::NotFound => "not_found",
+            FailureKind::AccessDenied => "access_denied",
+            FailureKind::Throttled => "throttled",
+            FailureKind::Network => "network",
+            FailureKind::Service => "service",
+        }
+    }
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 接收统计事件，通过 [`OssClientBuilder::metrics`](crate::OssClientBuilder::metrics) 设置。
+/// `op` 为 S3 的操作名，如 `PutObject`、`UploadPart`、`GetObject`、`DeleteObjects`；
+/// 会从多个任务同时调用，实现应当尽快返回
+pub trait Metrics: Send + Sync {
+    /// 发送一次请求，重试也会计入
+    fn on_request(&self, op: &str) {
+        let _ = op;
+    }
+
+    /// 传输了 `n` 字节：上传按每次发送的请求体计算（重试会重复计入），下载按成功响应的 Content-Length 计算
+    fn on_bytes(&self, op: &str, direction: Direction, n: u64) {
+        let _ = (op, direction, n);
+    }
+
+    /// 重试一次请求：SDK 的重试，以及分段下载等被限流后的重试
+    fn on_retry(&self, op: &str) {
+        let _ = op;
+    }
+
+    /// 请求在 SDK 重试之后仍然失败；分段下载等被限流时还会再重试，之后成功的也会计入一次
+    fn on_error(&self, op: &str, kind: FailureKind) {
+        let _ = (op, kind);
+    }
+}
+
+/// 不统计时使用
+pub struct NoMetrics;
+
+impl Metrics for NoMetrics {}
+
+/// 用原子计数器实现的 [`Metrics`]，可以直接读取，也可以定期导出到监控系统
+#[derive(Debug, Default)]
+pub struct MetricsCounters {
+    requests: Mutex<BTreeMap<String, u64>>,
+    errors: Mutex<BTreeMap<String, u64>>,
+    bytes_uploaded: AtomicU64,
+    bytes_downloaded: AtomicU64,
+    retries: AtomicU64,
+}
+
+/// [`MetricsCounters`] 在某一时刻的值
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MetricsSnapshot {
+    /// 每个操作的请求数
+    pub requests: BTreeMap<String, u64>,
+    /// 每种失败原因的次数，key 为 [`FailureKind::as_str`]
+    pub errors: BTreeMap<String, u64>,
+    pub bytes_uploaded: u64,
+    pub bytes_downloaded: u64,
+    pub retries: u64,
+}
+
+impl MetricsSnapshot {
+    /// 所有操作的请求数之和
+    pub fn total_requests(&self) -> u64 {
+        self.requests.values().sum()
+    }
+
+    /// 所有原因的失败次数之和
+    pub fn total_errors(&self) -> u64 {
+        self.errors.values().sum()
+    }
+}
+
+impl MetricsCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            requests: self.requests.lock().unwrap().clone(),
+            errors: self.errors.lock().unwrap().clone(),
+            bytes_uploaded: self.bytes_uploaded.load(Ordering::Relaxed),
+            bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+}
+
+fn increment(counts: &Mutex<BTreeMap<String, u64>>, name: &str) {
+    let mut counts = counts.lock().unwrap();
+    match counts.get_mut(name) {
+        Some(count) => *count += 1,
+        None => {
+            counts.insert(name.to_string(), 1);
+        }
+    }
+}
+
+impl Metrics for MetricsCounters {
+    fn on_request(&self, op: &str) {
+        increment(&self.requests, op);
+    }
+
+    fn on_bytes(&self, _op: &str, direction: Direction, n: u64) {
+        let counter = match direction {
+            Direction::Upload => &self.bytes_uploaded,
+            Direction::Download => &self.bytes_downloaded,
+        };
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    fn on_retry(&self, _op: &str) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_error(&self, _op: &str, kind: FailureKind) {
+        increment(&self.errors, kind.as_str());
+    }
+}
+
+/// 把 SDK 每次请求的事件转发给 [`Metrics`]
+pub(crate) struct MetricsInterceptor(pub(crate) Arc<dyn Metrics>);
+
+impl fmt::Debug for MetricsInterceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MetricsInterceptor")
+    }
+}
+
+fn operation(cfg: &ConfigBag) -> &str {
+    cfg.load::<Metadata>().map_or("Unknown", Metadata::name)
+}
+
+impl Intercept for MetricsInterceptor {
+    fn name(&self) -> &'static str {
+        "MetricsInterceptor"
+    }
+
+    fn read_before_transmit(
+        &self,
+        context: &BeforeTransmitInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let op = operation(cfg);
+        if cfg.load::<RequestAttempts>().is_some_and(|attempts| attempts.attempts() > 1) {
+            self.0.on_retry(op);
+        }
+        self.0.on_request(op);
+        match context.request().body().content_length() {
+            Some(n) if n > 0 => self.0.on_bytes(op, Direction::Upload, n),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn read_after_attempt(
+        &self,
+        context: &FinalizerInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let op = operation(cfg);
+        if op == "GetObject" && matches!(context.output_or_error(), Some(Ok(_))) {
+            let length = context.response().and_then(|resp| resp.headers().get("content-length"));
+            if let Some(n) = length.and_then(|n| n.parse().ok()) {
+                self.0.on_bytes(op, Direction::Download, n);
+            }
+        }
+        Ok(())
+    }
+
+    fn read_after_execution(
+        &self,
+        context: &FinalizerInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let Some(Err(err)) = context.output_or_error() else {
+            return Ok(());
+        };
+        let kind = if err.is_timeout_error() || err.is_connector_error() {
+            FailureKind::Network
+        } else {
+            match context.response().map(|resp| resp.status().as_u16()) {
+                Some(404) => FailureKind::NotFound,
+                Some(403) => FailureKind::AccessDenied,
+                Some(429 | 503) => FailureKind::Throttled,
+                None => FailureKind::Network,
+                _ => FailureKind::Service,
+            }
+        };
+        self.0.on_error(operation(cfg), kind);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{mock_builder, query, response};
+    use aws_sdk_s3::config::retry::RetryConfig;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_metrics_count_requests_bytes_retries_and_errors() {
+        let counters = Arc::new(MetricsCounters::new());
+        let gets = Arc::new(AtomicU64::new(0));
+        let handler = {
+            let gets = gets.clone();
+            move |req: &aws_sdk_s3::config::http::HttpRequest| match (req.method(), query(req)) {
+                ("PUT", _) => response(200, &[("ETag", "\"e\"")], ""),
+                ("GET", _) if gets.fetch_add(1, Ordering::SeqCst) == 0 => response(503, &[], "<Error><Code>SlowDown</Code></Error>"),
+                ("GET", _) => response(200, &[("Content-Length", "5")], "hello"),
+                ("HEAD", _) if req.uri().ends_with("/missing.txt") => response(404, &[], ""),
+                ("HEAD", _) => response(200, &[("Content-Length", "5")], ""),
+                ("DELETE", _) => response(403, &[], "<Error><Code>AccessDenied</Code></Error>"),
+                _ => response(400, &[], ""),
+            }
+        };
+        let client = mock_builder(handler)
+            .retry(RetryConfig::standard().with_max_attempts(2).with_initial_backoff(Duration::from_millis(1)))
+            .metrics(counters.clone())
+            .build()
+            .await
+            .unwrap();
+
+        client.upload_bytes("a.txt", "hello world").await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        client.download("a.txt", Some(&dir.path().join("a.txt"))).await.unwrap();
+        assert!(client.head_if_exists("missing.txt").await.unwrap().is_none());
+        assert!(client.delete("a.txt").await.is_err());
+
+        let snapshot = counters.snapshot();
+        let requests: Vec<_> = snapshot.requests.iter().map(|(op, n)| (op.as_str(), *n)).collect();
+        // 下载和删除都先 HEAD，GET 被限流后重试一次
+        assert_eq!(requests, [("DeleteObject", 1), ("GetObject", 2), ("HeadObject", 3), ("PutObject", 1)]);
+        assert_eq!((snapshot.bytes_uploaded, snapshot.bytes_downloaded, snapshot.retries), (11, 5, 1));
+        let errors: Vec<_> = snapshot.errors.iter().map(|(kind, n)| (kind.as_str(), *n)).collect();
+        assert_eq!(errors, [("access_denied", 1), ("not_found", 1)]);
+        assert_eq!((snapshot.total_requests(), snapshot.total_errors()), (7, 2));
+    }
+
+    #[tokio::test]
+    async fn test_crate_retries_reach_metrics() {
+        // SDK 不重试时，分段下载自带的限流重试也计入 on_retry
+        let counters = Arc::new(MetricsCounters::new());
+        let size = 6 * 1024 * 1024;
+        let gets = Arc::new(AtomicU64::new(0));
+        let handler = {
+            let gets = gets.clone();
+            move |req: &aws_sdk_s3::config::http::HttpRequest| {
+                if req.method() == "HEAD" {
+                    return response(200, &[("Content-Length", &size.to_string()), ("ETag", "\"e\"")], "");
+                }
+                if gets.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return response(503, &[("Retry-After", "0")], "");
+                }
+                let range = req.headers().get("range").unwrap().trim_start_matches("bytes=").to_string();
+                let (start, end) = range.split_once('-').unwrap();
+                let len = end.parse::<usize>().unwrap() - start.parse::<usize>().unwrap() + 1;
+                response(206, &[("Content-Length", &len.to_string())], vec![0u8; len])
+            }
+        };
+        let client = mock_builder(handler).retry(RetryConfig::disabled()).metrics(counters.clone()).build().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        client.download("a.bin", Some(&dir.path().join("a.bin"))).await.unwrap();
+
+        let snapshot = counters.snapshot();
+        assert_eq!(snapshot.retries, 1);
+        assert_eq!(snapshot.requests["GetObject"], 3);
+        assert_eq!(snapshot.bytes_downloaded, size);
+        // 重试成功的请求不计入失败；被限流的那次由 SDK 报告为失败的执行
+        assert_eq!(snapshot.errors.get("throttled"), Some(&1));
+    }
+}
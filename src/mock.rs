@@ -0,0 +1,136 @@
+//! 测试用的模拟 S3：不访问网络，每个请求交给处理函数生成响应
+
+use aws_sdk_s3::config::http::{HttpRequest, HttpResponse};
+use aws_sdk_s3::config::{HttpClient, IntoShared, RuntimeComponents};
+use aws_sdk_s3::primitives::SdkBody;
+use aws_smithy_runtime_api::client::http::{
+    HttpConnector, HttpConnectorFuture, HttpConnectorSettings, SharedHttpConnector,
+};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{OssClient, OssClientBuilder, OssConfig, MIN_PART_SIZE};
+
+type Handler = Arc<dyn Fn(&HttpRequest) -> HttpResponse + Send + Sync>;
+
+#[derive(Clone)]
+struct MockHttp(Handler);
+
+impl fmt::Debug for MockHttp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MockHttp")
+    }
+}
+
+impl HttpConnector for MockHttp {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        HttpConnectorFuture::ready(Ok((self.0)(&request)))
+    }
+}
+
+impl HttpClient for MockHttp {
+    fn http_connector(&self, _: &HttpConnectorSettings, _: &RuntimeComponents) -> SharedHttpConnector {
+        self.clone().into_shared()
+    }
+}
+
+/// 统计同时进行中的上传请求（PUT，包括单次上传和分块），每个请求等待一段时间后才交给处理函数
+#[derive(Clone)]
+struct CountingHttp {
+    inner: MockHttp,
+    delay: Duration,
+    in_flight: Arc<InFlight>,
+}
+
+/// 进行中的上传请求数和出现过的最大值
+#[derive(Debug, Default)]
+pub(crate) struct InFlight {
+    current: AtomicUsize,
+    max: AtomicUsize,
+}
+
+impl InFlight {
+    pub(crate) fn max(&self) -> usize {
+        self.max.load(Ordering::SeqCst)
+    }
+}
+
+impl fmt::Debug for CountingHttp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CountingHttp")
+    }
+}
+
+impl HttpConnector for CountingHttp {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        let this = self.clone();
+        HttpConnectorFuture::new(async move {
+            let upload = request.method() == "PUT";
+            if upload {
+                let current = this.in_flight.current.fetch_add(1, Ordering::SeqCst) + 1;
+                this.in_flight.max.fetch_max(current, Ordering::SeqCst);
+            }
+            tokio::time::sleep(this.delay).await;
+            if upload {
+                this.in_flight.current.fetch_sub(1, Ordering::SeqCst);
+            }
+            Ok((this.inner.0)(&request))
+        })
+    }
+}
+
+impl HttpClient for CountingHttp {
+    fn http_connector(&self, _: &HttpConnectorSettings, _: &RuntimeComponents) -> SharedHttpConnector {
+        self.clone().into_shared()
+    }
+}
+
+/// 请求发给 `handler` 的客户端，分块大小为最小的 5MB；bucket 为 my-bucket，endpoint 为 https://s3.example.com
+pub(crate) async fn mock_client(handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static) -> OssClient {
+    mock_builder(handler).build().await.unwrap()
+}
+
+/// 与 [`mock_builder`] 相同，但每个请求延迟 `delay` 后才响应，并统计同时进行中的上传请求数
+pub(crate) fn counting_builder(
+    delay: Duration,
+    handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static,
+) -> (OssClientBuilder, Arc<InFlight>) {
+    let in_flight = Arc::new(InFlight::default());
+    let http = CountingHttp { inner: MockHttp(Arc::new(handler)), delay, in_flight: in_flight.clone() };
+    (builder(http), in_flight)
+}
+
+/// 与 [`mock_client`] 相同，但可以在构建前修改其他设置
+pub(crate) fn mock_builder(handler: impl Fn(&HttpRequest) -> HttpResponse + Send + Sync + 'static) -> OssClientBuilder {
+    builder(MockHttp(Arc::new(handler)))
+}
+
+fn builder(http: impl HttpClient + 'static) -> OssClientBuilder {
+    let config = OssConfig {
+        access_key: "a".to_string(),
+        secret_key: "b".to_string(),
+        bucket: "my-bucket".to_string(),
+        endpoint: "https://s3.example.com".to_string(),
+        region: "us-east-1".to_string(),
+        public_base_url: None,
+    };
+    OssClient::builder(config)
+        .part_size(MIN_PART_SIZE)
+        .http_client(http)
+}
+
+/// 构造响应，`headers` 为 (名称, 值)
+pub(crate) fn response(status: u16, headers: &[(&'static str, &str)], body: impl Into<SdkBody>) -> HttpResponse {
+    let mut resp = HttpResponse::new(status.try_into().unwrap(), body.into());
+    for (name, value) in headers {
+        resp.headers_mut().insert(*name, value.to_string());
+    }
+    resp
+}
+
+/// 请求的查询字符串（`?` 之后的部分）
+pub(crate) fn query(req: &HttpRequest) -> &str {
+    req.uri().split_once('?').map_or("", |(_, query)| query)
+}
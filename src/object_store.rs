@@ -0,0 +1,569 @@
+//! 为 [`OssClient`] 实现 object_store crate 的 [`ObjectStore`]（需要开启 `object-store` feature），
+//! DataFusion、Parquet 等基于 object_store 的组件可以复用同一份 OssConfig 和凭证
+
+use ::object_store::path::Path;
+use ::object_store::{
+    Attribute, Attributes, CopyMode, CopyOptions, Error, GetOptions, GetResult, GetResultPayload, ListResult,
+    MultipartUpload, ObjectMeta, ObjectStore, PutMode, PutMultipartOptions, PutOptions, PutPayload, PutResult,
+    UploadPart,
+};
+use async_trait::async_trait;
+use aws_sdk_s3::primitives::{ByteStream, DateTime};
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use bytes::Bytes;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::list::object_info;
+use crate::{ObjectInfo, OssClient, OssError};
+
+const STORE: &str = "OSS";
+
+/// 以 [`ObjectStore`] 的形式访问 OssClient 所在的 bucket
+///
+/// object_store 的路径不以 `/` 开头或结尾、没有空段，不符合的 key（如 `a//b`、`dir/`）列出时返回 InvalidPath 错误。
+/// 分块上传时除最后一块外每块至少 5MB，与 S3 相同；可以用 `object_store::WriteMultipart` 按固定大小切分
+#[derive(Clone)]
+pub struct OssObjectStore {
+    client: OssClient,
+}
+
+impl OssObjectStore {
+    pub fn new(client: OssClient) -> Self {
+        Self { client }
+    }
+
+    /// 底层的客户端
+    pub fn client(&self) -> &OssClient {
+        &self.client
+    }
+}
+
+impl From<OssClient> for OssObjectStore {
+    fn from(client: OssClient) -> Self {
+        Self::new(client)
+    }
+}
+
+impl fmt::Debug for OssObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OssObjectStore").field("bucket", &self.client.config.bucket).finish()
+    }
+}
+
+impl fmt::Display for OssObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OssObjectStore({})", self.client.config.bucket)
+    }
+}
+
+/// OssError 转换为 object_store 的错误：404、403、401、412、304 和不支持的操作分别对应，其余为 Generic
+fn store_error(path: &str, err: OssError) -> Error {
+    let path = path.to_string();
+    let status = err.service_error().and_then(|err| err.status());
+    let source = Box::new(err);
+    match (status, source.as_ref()) {
+        _ if source.is_not_found() => Error::NotFound { path, source },
+        (_, OssError::Unsupported(_)) => Error::NotSupported { source },
+        (_, OssError::AccessDenied(_)) | (Some(403), _) => Error::PermissionDenied { path, source },
+        (Some(401), _) => Error::Unauthenticated { path, source },
+        (Some(412), _) => Error::Precondition { path, source },
+        (Some(304), _) => Error::NotModified { path, source },
+        _ => Error::Generic { store: STORE, source },
+    }
+}
+
+fn to_chrono(time: Option<&DateTime>) -> chrono::DateTime<chrono::Utc> {
+    time.and_then(|t| chrono::DateTime::from_timestamp(t.secs(), t.subsec_nanos())).unwrap_or_default()
+}
+
+fn object_meta(info: &ObjectInfo) -> Result<ObjectMeta, Error> {
+    Ok(ObjectMeta {
+        location: Path::parse(&info.key)?,
+        last_modified: to_chrono(info.last_modified.as_ref()),
+        size: info.size,
+        e_tag: info.etag.clone(),
+        version: info.version_id.clone(),
+    })
+}
+
+/// 列出时的前缀：object_store 的前缀按目录匹配，`a/b` 只列出 `a/b/` 下的对象
+fn list_prefix(prefix: Option<&Path>) -> String {
+    match prefix.map(Path::as_ref) {
+        None | Some("") => String::new(),
+        Some(prefix) => format!("{}/", prefix),
+    }
+}
+
+/// 合并成一块连续的数据，PUT 只接受单个 body
+fn payload_bytes(payload: PutPayload) -> Bytes {
+    let chunks: Vec<Bytes> = payload.into_iter().collect();
+    match chunks.as_slice() {
+        [] => Bytes::new(),
+        [chunk] => chunk.clone(),
+        _ => chunks.concat().into(),
+    }
+}
+
+/// Content-Type 等属性和自定义元数据
+struct ObjectAttributes {
+    content_type: Option<String>,
+    content_disposition: Option<String>,
+    content_encoding: Option<String>,
+    content_language: Option<String>,
+    cache_control: Option<String>,
+    storage_class: Option<String>,
+    metadata: HashMap<String, String>,
+}
+
+impl ObjectAttributes {
+    fn new(attributes: &Attributes) -> Result<Self, Error> {
+        let mut result = ObjectAttributes {
+            content_type: None,
+            content_disposition: None,
+            content_encoding: None,
+            content_language: None,
+            cache_control: None,
+            storage_class: None,
+            metadata: HashMap::new(),
+        };
+        for (attribute, value) in attributes.iter() {
+            let value = value.to_string();
+            match attribute {
+                Attribute::ContentType => result.content_type = Some(value),
+                Attribute::ContentDisposition => result.content_disposition = Some(value),
+                Attribute::ContentEncoding => result.content_encoding = Some(value),
+                Attribute::ContentLanguage => result.content_language = Some(value),
+                Attribute::CacheControl => result.cache_control = Some(value),
+                Attribute::StorageClass => result.storage_class = Some(value),
+                Attribute::Metadata(key) => {
+                    result.metadata.insert(key.to_string(), value);
+                }
+                other => {
+                    let message = format!("不支持的对象属性: {:?}", other);
+                    return Err(Error::NotSupported { source: message.into() });
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// 为 PUT 和创建分块上传设置属性和标签，两者的 builder 方法同名
+macro_rules! apply_attributes {
+    ($request:expr, $attributes:expr, $tags:expr) => {{
+        let attributes = $attributes;
+        let tags: &str = $tags;
+        $request
+            .set_content_type(attributes.content_type)
+            .set_content_disposition(attributes.content_disposition)
+            .set_content_encoding(attributes.content_encoding)
+            .set_content_language(attributes.content_language)
+            .set_cache_control(attributes.cache_control)
+            .set_storage_class(attributes.storage_class.map(|class| class.as_str().into()))
+            .set_metadata((!attributes.metadata.is_empty()).then_some(attributes.metadata))
+            .set_tagging((!tags.is_empty()).then(|| tags.to_string()))
+    }};
+}
+
+#[async_trait]
+impl ObjectStore for OssObjectStore {
+    async fn put_opts(&self, location: &Path, payload: PutPayload, opts: PutOptions) -> Result<PutResult, Error> {
+        let key = location.as_ref();
+        let attributes = ObjectAttributes::new(&opts.attributes)?;
+        let request = self.client.client.put_object().bucket(&self.client.config.bucket).key(key);
+        let request = match &opts.mode {
+            PutMode::Overwrite => request,
+            PutMode::Create => request.if_none_match("*"),
+            PutMode::Update(version) => match &version.e_tag {
+                Some(etag) => request.if_match(etag),
+                None => return Err(Error::NotSupported { source: "条件更新需要 ETag".into() }),
+            },
+        };
+        let request = apply_attributes!(request, attributes, opts.tags.encoded());
+        let resp = request
+            .body(ByteStream::from(payload_bytes(payload)))
+            .send()
+            .await
+            .map_err(|err| match (&opts.mode, err.raw_response().map(|r| r.status().as_u16())) {
+                (PutMode::Create, Some(412)) => Error::AlreadyExists { path: key.to_string(), source: Box::new(OssError::from(err)) },
+                _ => store_error(key, err.into()),
+            })?;
+        Ok(PutResult {
+            e_tag: resp.e_tag().map(str::to_string),
+            version: resp.version_id().map(str::to_string),
+            extensions: Default::default(),
+        })
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> Result<Box<dyn MultipartUpload>, Error> {
+        let key = location.as_ref();
+        let attributes = ObjectAttributes::new(&opts.attributes)?;
+        let request = self.client.client.create_multipart_upload().bucket(&self.client.config.bucket).key(key);
+        let resp = apply_attributes!(request, attributes, opts.tags.encoded())
+            .send()
+            .await
+            .map_err(|err| store_error(key, err.into()))?;
+        let upload_id = resp
+            .upload_id()
+            .ok_or_else(|| store_error(key, OssError::Invalid("创建分块上传时没有返回 upload id".to_string())))?;
+        Ok(Box::new(OssMultipartUpload {
+            client: self.client.clone(),
+            key: key.to_string(),
+            upload_id: upload_id.to_string(),
+            next_part: 1,
+            parts: Arc::new(Mutex::new(Vec::new())),
+        }))
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> Result<GetResult, Error> {
+        let key = location.as_ref();
+        let bucket = &self.client.config.bucket;
+        let since = |time: Option<chrono::DateTime<chrono::Utc>>| time.map(|t| DateTime::from_secs(t.timestamp()));
+        if options.head {
+            let resp = self
+                .client
+                .client
+                .head_object()
+                .bucket(bucket)
+                .key(key)
+                .set_version_id(options.version.clone())
+                .set_if_match(options.if_match.clone())
+                .set_if_none_match(options.if_none_match.clone())
+                .set_if_modified_since(since(options.if_modified_since))
+                .set_if_unmodified_since(since(options.if_unmodified_since))
+                .send()
+                .await
+                .map_err(|err| store_error(key, err.into()))?;
+            let meta = object_meta(&crate::head_info(key, &resp))?;
+            return Ok(GetResult {
+                payload: GetResultPayload::Stream(stream::empty().boxed()),
+                range: 0..meta.size,
+                meta,
+                attributes: Attributes::new(),
+                extensions: Default::default(),
+            });
+        }
+
+        if let Some(range) = &options.range {
+            range.is_valid().map_err(|err| Error::Generic { store: STORE, source: Box::new(err) })?;
+        }
+        let resp = self
+            .client
+            .client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .set_range(options.range.as_ref().map(ToString::to_string))
+            .set_version_id(options.version.clone())
+            .set_if_match(options.if_match.clone())
+            .set_if_none_match(options.if_none_match.clone())
+            .set_if_modified_since(since(options.if_modified_since))
+            .set_if_unmodified_since(since(options.if_unmodified_since))
+            .send()
+            .await
+            .map_err(|err| store_error(key, err.into()))?;
+
+        let length = resp.content_length().unwrap_or_default().max(0) as u64;
+        let (range, size) = match resp.content_range().and_then(parse_content_range) {
+            Some((start, end, size)) => (start..end + 1, size),
+            None => (0..length, length),
+        };
+        let meta = ObjectMeta {
+            location: location.clone(),
+            last_modified: to_chrono(resp.last_modified()),
+            size,
+            e_tag: resp.e_tag().map(str::to_string),
+            version: resp.version_id().map(str::to_string),
+        };
+        let path = key.to_string();
+        let body = stream::try_unfold(resp.body, move |mut body| {
+            let path = path.clone();
+            async move {
+                match body.try_next().await {
+                    Ok(chunk) => Ok(chunk.map(|chunk| (chunk, body))),
+                    Err(err) => Err(store_error(&path, err.into())),
+                }
+            }
+        });
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(body.boxed()),
+            meta,
+            range,
+            attributes: Attributes::new(),
+            extensions: Default::default(),
+        })
+    }
+
+    fn delete_stream(&self, locations: BoxStream<'static, Result<Path, Error>>) -> BoxStream<'static, Result<Path, Error>> {
+        let client = self.client.clone();
+        let workers = client.workers;
+        locations
+            .map(move |location| {
+                let client = client.clone();
+                async move {
+                    let location = location?;
+                    // S3 删除不存在的对象也返回成功
+                    client
+                        .client
+                        .delete_object()
+                        .bucket(&client.config.bucket)
+                        .key(location.as_ref())
+                        .send()
+                        .await
+                        .map_err(|err| store_error(location.as_ref(), err.into()))?;
+                    Ok(location)
+                }
+            })
+            .buffered(workers)
+            .boxed()
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, Result<ObjectMeta, Error>> {
+        let prefix = list_prefix(prefix);
+        let pages = self
+            .client
+            .client
+            .list_objects_v2()
+            .bucket(&self.client.config.bucket)
+            .prefix(&prefix)
+            .into_paginator()
+            .send();
+        stream::try_unfold(pages, move |mut pages| {
+            let prefix = prefix.clone();
+            async move {
+                let Some(page) = pages.next().await else {
+                    return Ok(None);
+                };
+                let page = page.map_err(|err| store_error(&prefix, err.into()))?;
+                let objects = page.contents().iter().map(|obj| object_meta(&object_info(obj))).collect::<Vec<_>>();
+                Ok::<_, Error>(Some((objects, pages)))
+            }
+        })
+        .map_ok(stream::iter)
+        .try_flatten()
+        .boxed()
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult, Error> {
+        let prefix = list_prefix(prefix);
+        let mut pages = self
+            .client
+            .client
+            .list_objects_v2()
+            .bucket(&self.client.config.bucket)
+            .prefix(&prefix)
+            .delimiter("/")
+            .into_paginator()
+            .send();
+        let mut result = ListResult { common_prefixes: Vec::new(), objects: Vec::new(), extensions: Default::default() };
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|err| store_error(&prefix, err.into()))?;
+            for obj in page.contents() {
+                result.objects.push(object_meta(&object_info(obj))?);
+            }
+            for common in page.common_prefixes().iter().filter_map(|p| p.prefix()) {
+                result.common_prefixes.push(Path::parse(common)?);
+            }
+        }
+        Ok(result)
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, options: CopyOptions) -> Result<(), Error> {
+        match options.mode {
+            CopyMode::Overwrite => {
+                self.client.copy(from.as_ref(), to.as_ref()).await.map_err(|err| store_error(from.as_ref(), err))
+            }
+            // CopyObject 不支持 If-None-Match，无法原子地检查目标是否存在
+            CopyMode::Create => Err(Error::NotSupported { source: "OSS 不支持不覆盖目标的复制".into() }),
+        }
+    }
+}
+
+/// 解析 `bytes 0-9/100`，返回起始、结束（包含）和对象大小
+fn parse_content_range(value: &str) -> Option<(u64, u64, u64)> {
+    let (range, size) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, end) = range.split_once('-')?;
+    Some((start.parse().ok()?, end.parse().ok()?, size.parse().ok()?))
+}
+
+/// [`ObjectStore::put_multipart_opts`] 返回的分块上传
+struct OssMultipartUpload {
+    client: OssClient,
+    key: String,
+    upload_id: String,
+    next_part: i32,
+    parts: Arc<Mutex<Vec<CompletedPart>>>,
+}
+
+impl fmt::Debug for OssMultipartUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OssMultipartUpload")
+            .field("key", &self.key)
+            .field("upload_id", &self.upload_id)
+            .field("next_part", &self.next_part)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl MultipartUpload for OssMultipartUpload {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        let number = self.next_part;
+        self.next_part += 1;
+        let client = self.client.clone();
+        let (key, upload_id, parts) = (self.key.clone(), self.upload_id.clone(), self.parts.clone());
+        Box::pin(async move {
+            let resp = client
+                .client
+                .upload_part()
+                .bucket(&client.config.bucket)
+                .key(&key)
+                .upload_id(upload_id)
+                .part_number(number)
+                .body(ByteStream::from(payload_bytes(data)))
+                .send()
+                .await
+                .map_err(|err| store_error(&key, err.into()))?;
+            let part = CompletedPart::builder().set_e_tag(resp.e_tag().map(str::to_string)).part_number(number).build();
+            parts.lock().unwrap().push(part);
+            Ok(())
+        })
+    }
+
+    async fn complete(&mut self) -> Result<PutResult, Error> {
+        // 没有任何分块时上传一个空分块，S3 不允许完成没有分块的上传
+        if self.next_part == 1 {
+            self.put_part(PutPayload::new()).await?;
+        }
+        let mut parts = self.parts.lock().unwrap().clone();
+        parts.sort_by_key(|part| part.part_number());
+        let resp = self
+            .client
+            .client
+            .complete_multipart_upload()
+            .bucket(&self.client.config.bucket)
+            .key(&self.key)
+            .upload_id(&self.upload_id)
+            .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+            .send()
+            .await
+            .map_err(|err| store_error(&self.key, err.into()))?;
+        Ok(PutResult {
+            e_tag: resp.e_tag().map(str::to_string),
+            version: resp.version_id().map(str::to_string),
+            extensions: Default::default(),
+        })
+    }
+
+    async fn abort(&mut self) -> Result<(), Error> {
+        self.client
+            .client
+            .abort_multipart_upload()
+            .bucket(&self.client.config.bucket)
+            .key(&self.key)
+            .upload_id(&self.upload_id)
+            .send()
+            .await
+            .map_err(|err| store_error(&self.key, err.into()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{mock_client, query, response};
+    use ::object_store::ObjectStoreExt;
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(parse_content_range("bytes 0-9/100"), Some((0, 9, 100)));
+        assert_eq!(parse_content_range("bytes */100"), None);
+        assert_eq!(list_prefix(Some(&Path::from("a/b"))), "a/b/");
+        assert_eq!(list_prefix(None), "");
+    }
+
+    #[tokio::test]
+    async fn test_object_store_requests() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let client = mock_client(move |req| {
+            let path = req.uri().split('?').next().unwrap().trim_start_matches("https://my-bucket.s3.example.com").to_string();
+            let range = req.headers().get("range").unwrap_or("-").to_string();
+            requests.lock().unwrap().push(format!("{} {} {} {}", req.method(), path, query(req), range));
+            match (req.method(), path.rsplit('/').next().unwrap()) {
+                ("GET", "") => response(
+                    200,
+                    &[],
+                    "<ListBucketResult><Contents><Key>data/a.parquet</Key><Size>10</Size>\
+                     <LastModified>2024-01-02T03:04:05.000Z</LastModified></Contents>\
+                     <CommonPrefixes><Prefix>data/2024/</Prefix></CommonPrefixes></ListBucketResult>",
+                ),
+                ("GET", "a.parquet") => {
+                    response(206, &[("Content-Length", "4"), ("Content-Range", "bytes 2-5/10"), ("ETag", "\"e1\"")], "2345")
+                }
+                ("PUT", "exists.parquet") => response(412, &[], "<Error><Code>PreconditionFailed</Code></Error>"),
+                (_, "missing.parquet") => response(404, &[], "<Error><Code>NoSuchKey</Code></Error>"),
+                ("POST", _) if query(req) == "uploads" => response(
+                    200,
+                    &[],
+                    "<InitiateMultipartUploadResult><UploadId>u1</UploadId></InitiateMultipartUploadResult>",
+                ),
+                ("POST", _) => response(200, &[], "<CompleteMultipartUploadResult><ETag>\"m\"</ETag></CompleteMultipartUploadResult>"),
+                _ => response(200, &[("ETag", "\"e\"")], ""),
+            }
+        })
+        .await;
+        let store = OssObjectStore::new(client);
+
+        let bytes = store.get_range(&Path::from("data/a.parquet"), 2..6).await.unwrap();
+        assert_eq!(bytes.as_ref(), b"2345");
+        let result = store.get_opts(&Path::from("data/a.parquet"), GetOptions::new().with_range(Some(2..6))).await.unwrap();
+        assert_eq!((result.range.clone(), result.meta.size), (2..6, 10));
+        let err = store.get(&Path::from("missing.parquet")).await.unwrap_err();
+        assert!(matches!(err, Error::NotFound { .. }), "{:?}", err);
+
+        store.put(&Path::from("data/b.parquet"), PutPayload::from_static(b"xy")).await.unwrap();
+        let create = PutOptions { mode: PutMode::Create, ..Default::default() };
+        let err = store.put_opts(&Path::from("exists.parquet"), PutPayload::new(), create).await.unwrap_err();
+        assert!(matches!(err, Error::AlreadyExists { .. }), "{:?}", err);
+
+        let mut upload = store.put_multipart(&Path::from("data/c.parquet")).await.unwrap();
+        upload.put_part(PutPayload::from_static(b"part1")).await.unwrap();
+        upload.put_part(PutPayload::from_static(b"part2")).await.unwrap();
+        assert_eq!(upload.complete().await.unwrap().e_tag.as_deref(), Some("\"m\""));
+
+        let objects: Vec<_> = store.list(Some(&Path::from("data"))).try_collect().await.unwrap();
+        assert_eq!(objects[0].location.as_ref(), "data/a.parquet");
+        assert_eq!(objects[0].last_modified.to_rfc3339(), "2024-01-02T03:04:05+00:00");
+        let listed = store.list_with_delimiter(Some(&Path::from("data"))).await.unwrap();
+        assert_eq!(listed.common_prefixes, [Path::from("data/2024")]);
+        store.delete(&Path::from("data/b.parquet")).await.unwrap();
+        assert!(matches!(store.copy_if_not_exists(&Path::from("a"), &Path::from("b")).await, Err(Error::NotSupported { .. })));
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "GET /data/a.parquet x-id=GetObject bytes=2-5",
+                "GET /data/a.parquet x-id=GetObject bytes=2-5",
+                "GET /missing.parquet x-id=GetObject -",
+                "PUT /data/b.parquet x-id=PutObject -",
+                "PUT /exists.parquet x-id=PutObject -",
+                "POST /data/c.parquet uploads -",
+                "PUT /data/c.parquet x-id=UploadPart&partNumber=1&uploadId=u1 -",
+                "PUT /data/c.parquet x-id=UploadPart&partNumber=2&uploadId=u1 -",
+                "POST /data/c.parquet uploadId=u1 -",
+                "GET / list-type=2&prefix=data%2F -",
+                "GET / list-type=2&delimiter=%2F&prefix=data%2F -",
+                "DELETE /data/b.parquet x-id=DeleteObject -",
+            ]
+        );
+    }
+}
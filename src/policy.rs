@@ -0,0 +1,97 @@
+//! bucket policy：给其他账号或匿名用户授权，不支持 ACL 的 bucket 只能用 policy
+//! policy 保持为原始 JSON，只在本地检查基本结构，策略语义由服务端校验
+
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use serde_json::Value;
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::{is_not_implemented, OssClient};
+
+/// 解析并检查 policy 文件：必须是带 Statement 的 JSON 对象，每条 Statement 的 Effect 为 Allow 或 Deny
+/// JSON 格式错误时返回出错的行和列
+pub fn parse_policy(json: &str) -> Result<Value> {
+    let policy: Value = serde_json::from_str(json).context("policy 不是有效的 JSON")?;
+    let object = policy.as_object().context("policy 必须是 JSON 对象")?;
+    let statements = match object.get("Statement") {
+        Some(Value::Array(statements)) => statements.iter().collect(),
+        Some(statement @ Value::Object(_)) => vec![statement],
+        Some(_) => bail!("Statement 必须是对象或数组"),
+        None => bail!("policy 缺少 Statement"),
+    };
+    if statements.is_empty() {
+        bail!("Statement 不能为空");
+    }
+    for (index, statement) in statements.iter().enumerate() {
+        match statement.get("Effect").and_then(Value::as_str) {
+            Some("Allow" | "Deny") => {}
+            _ => bail!("Statement[{}] 的 Effect 必须为 Allow 或 Deny", index),
+        }
+    }
+    Ok(policy)
+}
+
+impl OssClient {
+    /// 查询当前 bucket 的 policy，没有配置时返回空对象
+    pub async fn get_policy(&self) -> Result<Value> {
+        match self.client.get_bucket_policy().bucket(&self.config.bucket).send().await {
+            Ok(resp) => {
+                let policy = resp.policy().unwrap_or("{}");
+                serde_json::from_str(policy).context("服务端返回的 policy 不是有效的 JSON")
+            }
+            Err(err) if err.code() == Some("NoSuchBucketPolicy") => Ok(Value::Object(Default::default())),
+            Err(err) if is_not_implemented(&err) => Err(OssError::unsupported("当前服务商不支持 bucket policy", err)),
+            Err(err) => Err(err).with_context(|| format!("无法查询 {} 的 policy", self.config.bucket)),
+        }
+    }
+
+    /// 用 `policy` 替换当前 bucket 的 policy
+    pub async fn set_policy(&self, policy: &Value) -> Result<()> {
+        match self.client
+            .put_bucket_policy()
+            .bucket(&self.config.bucket)
+            .policy(policy.to_string())
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if err.code() == Some("MalformedPolicy") => {
+                bail!("服务端拒绝了 policy: {}", err.message().unwrap_or("格式错误"))
+            }
+            Err(err) if is_not_implemented(&err) => Err(OssError::unsupported("当前服务商不支持 bucket policy", err)),
+            Err(err) => Err(err).with_context(|| format!("无法修改 {} 的 policy", self.config.bucket)),
+        }
+    }
+
+    /// 删除当前 bucket 的 policy
+    pub async fn delete_policy(&self) -> Result<()> {
+        self.client
+            .delete_bucket_policy()
+            .bucket(&self.config.bucket)
+            .send()
+            .await
+            .with_context(|| format!("无法删除 {} 的 policy", self.config.bucket))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_policy() {
+        let policy = parse_policy(
+            r#"{"Version": "2012-10-17", "Statement": [{"Effect": "Allow", "Principal": {"AWS": "arn:aws:iam::123456789012:root"},
+                "Action": "s3:GetObject", "Resource": "arn:aws:s3:::my-bucket/*"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(policy["Statement"][0]["Action"], "s3:GetObject");
+
+        let err = parse_policy("{\n  \"Statement\": [\n    {\"Effect\": \"Allow\",}\n  ]\n}").unwrap_err();
+        assert!(format!("{:#}", err).contains("line 3"), "{:#}", err);
+        let err = parse_policy(r#"{"Statement": {"Effect": "allow"}}"#).unwrap_err();
+        assert!(err.to_string().contains("Effect"), "{}", err);
+        assert!(parse_policy(r#"{"Version": "2012-10-17"}"#).is_err());
+        assert!(parse_policy("[]").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! 按 HTTP 方法生成预签名请求：下载、上传、查询和删除链接
+
+use serde::Serialize;
+
+use crate::error::{bail, Result};
+use crate::{content_disposition, format_size, presigning_config, OssClient, MAX_PUT_SIZE};
+
+/// 预签名的 HTTP 方法
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresignMethod {
+    Get,
+    /// 上传：指定的 Content-Type 和 Content-Length 参与签名，上传时必须发送相同的值。
+    /// 预签名 URL 只能限定精确的大小，需要限定大小范围时使用 [`OssClient::post_policy`]
+    Put { content_type: Option<String>, content_length: Option<u64> },
+    Head,
+    Delete,
+}
+
+/// [`OssClient::presign`] 的选项
+#[derive(Debug, Clone, Default)]
+pub struct PresignOptions {
+    /// 对象的指定版本，不适用于 PUT
+    pub version_id: Option<String>,
+    /// 仅 GET：覆盖响应的 Content-Disposition，浏览器按这个文件名下载
+    pub download_as: Option<String>,
+    /// 仅 GET：覆盖响应的 Content-Type
+    pub response_content_type: Option<String>,
+}
+
+/// 预签名请求
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PresignedUrl {
+    pub url: String,
+    /// 如 `PUT`
+    pub method: String,
+    /// 参与签名的请求头，调用方必须原样发送，否则签名校验失败
+    pub headers: Vec<(String, String)>,
+}
+
+impl OssClient {
+    /// 生成预签名请求，有效期 `expires_in_secs` 秒（最长 7 天），持有者无需凭证即可执行
+    pub async fn presign(
+        &self,
+        key: &str,
+        method: PresignMethod,
+        expires_in_secs: u64,
+        options: &PresignOptions,
+    ) -> Result<PresignedUrl> {
+        let key = &self.object_key(key)?;
+        let is_get = method == PresignMethod::Get;
+        if !is_get && (options.download_as.is_some() || options.response_content_type.is_some()) {
+            bail!("download_as 和 response_content_type 只适用于 GET");
+        }
+        let config = presigning_config(expires_in_secs)?;
+        let bucket = &self.config.bucket;
+        let version_id = options.version_id.clone();
+
+        let request = match method {
+            PresignMethod::Get => {
+                self.client
+                    .get_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .set_version_id(version_id)
+                    .set_response_content_disposition(options.download_as.as_deref().map(content_disposition))
+                    .set_response_content_type(options.response_content_type.clone())
+                    .presigned(config)
+                    .await?
+            }
+            PresignMethod::Put { content_type, content_length } => {
+                if version_id.is_some() {
+                    bail!("PUT 不能指定版本");
+                }
+                if let Some(size) = content_length {
+                    if size > MAX_PUT_SIZE {
+                        bail!(
+                            "预签名 PUT 最多上传 {}，{} 的文件请改用分块上传",
+                            format_size(MAX_PUT_SIZE),
+                            format_size(size),
+                        );
+                    }
+                }
+                self.client
+                    .put_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .set_content_type(content_type)
+                    .set_content_length(content_length.map(|size| size as i64))
+                    .presigned(config)
+                    .await?
+            }
+            PresignMethod::Head => {
+                self.client
+                    .head_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .set_version_id(version_id)
+                    .presigned(config)
+                    .await?
+            }
+            PresignMethod::Delete => {
+                self.client
+                    .delete_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .set_version_id(version_id)
+                    .presigned(config)
+                    .await?
+            }
+        };
+
+        Ok(PresignedUrl {
+            url: request.uri().to_string(),
+            method: request.method().to_string(),
+            headers: request
+                .headers()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{mock_client, response};
+
+    #[tokio::test]
+    async fn test_presign_methods() {
+        let client = mock_client(|_| response(500, &[], "")).await;
+        let put = PresignMethod::Put { content_type: Some("image/png".to_string()), content_length: Some(42) };
+        let signed = client.presign("a b.png", put, 900, &PresignOptions::default()).await.unwrap();
+        assert_eq!(signed.method, "PUT");
+        assert!(signed.url.starts_with("https://my-bucket.s3.example.com/a%20b.png?"), "{}", signed.url);
+        assert!(signed.url.contains("X-Amz-SignedHeaders=content-length%3Bcontent-type%3Bhost"), "{}", signed.url);
+        assert!(signed.headers.contains(&("content-type".to_string(), "image/png".to_string())), "{:?}", signed.headers);
+        assert!(signed.headers.contains(&("content-length".to_string(), "42".to_string())), "{:?}", signed.headers);
+
+        let options = PresignOptions { version_id: Some("v1".to_string()), ..Default::default() };
+        let signed = client.presign("a.png", PresignMethod::Delete, 60, &options).await.unwrap();
+        assert_eq!((signed.method.as_str(), signed.headers.len()), ("DELETE", 0));
+        assert!(signed.url.contains("versionId=v1"), "{}", signed.url);
+        let signed = client.presign("a.png", PresignMethod::Head, 60, &PresignOptions::default()).await.unwrap();
+        assert_eq!(signed.method, "HEAD");
+
+        let options = PresignOptions { download_as: Some("a.png".to_string()), ..Default::default() };
+        assert!(client.presign("a.png", PresignMethod::Head, 60, &options).await.is_err());
+        let put = PresignMethod::Put { content_type: None, content_length: Some(6 << 30) };
+        assert!(client.presign("a.png", put, 60, &PresignOptions::default()).await.is_err());
+        assert!(client.presign("a.png", PresignMethod::Get, 0, &PresignOptions::default()).await.is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! 带宽限制：上传每个请求体、下载每个收到的数据块之前向 [`RateLimiter`] 申请相应的字节数
+
+use async_trait::async_trait;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// 限制上传和下载的带宽，用 [`OssClientBuilder::rate_limiter`](crate::OssClientBuilder::rate_limiter) 设置。
+/// 同一个实例放进 `Arc` 后可以设置给多个客户端，它们的传输合计受同一个限制
+#[async_trait]
+pub trait RateLimiter: Send + Sync {
+    /// 发送或接收 `bytes` 字节之前调用，返回后才继续传输
+    async fn acquire(&self, bytes: u64);
+}
+
+/// 令牌桶：每秒补充 `bytes_per_sec` 个字节的额度，最多积累 `burst` 字节（默认 1 秒的量）。
+/// 额度不够时先预支，等待补足欠下的部分后返回，因此一次申请可以超过 `burst`
+pub struct TokenBucket {
+    rate: u64,
+    burst: u64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    /// 当前额度，预支后为负数
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// 每秒最多 `bytes_per_sec` 字节（至少为 1）
+    pub fn new(bytes_per_sec: u64) -> Self {
+        let rate = bytes_per_sec.max(1);
+        Self { rate, burst: rate, state: Mutex::new(BucketState { tokens: rate as f64, updated: Instant::now() }) }
+    }
+
+    /// 空闲时最多积累的额度，开始时额度是满的；设为 0 时每次传输都按速度等待
+    pub fn burst(mut self, bytes: u64) -> Self {
+        self.burst = bytes;
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        state.tokens = bytes as f64;
+        self
+    }
+
+    /// 每秒的字节数
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    /// 预支 `bytes` 字节，返回需要等待的时长
+    fn reserve(&self, bytes: u64) -> Duration {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(state.updated).as_secs_f64() * self.rate as f64;
+        state.tokens = (state.tokens + refill).min(self.burst as f64) - bytes as f64;
+        state.updated = now;
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / self.rate as f64)
+        }
+    }
+}
+
+#[async_trait]
+impl RateLimiter for TokenBucket {
+    async fn acquire(&self, bytes: u64) {
+        let wait = self.reserve(bytes);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_token_bucket_waits_for_debt() {
+        let bucket = TokenBucket::new(1000);
+        let start = Instant::now();
+        // 开始时有 1 秒的额度，之后按速度等待
+        bucket.acquire(1000).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        bucket.acquire(500).await;
+        assert_eq!(start.elapsed(), Duration::from_millis(500));
+        // 超过 burst 的申请也可以完成
+        bucket.acquire(3000).await;
+        assert_eq!(start.elapsed(), Duration::from_millis(3500));
+
+        // 空闲很久也只积累 burst 的额度
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let start = Instant::now();
+        bucket.acquire(1100).await;
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+
+        // 共享的额度按申请的顺序分配
+        let bucket = Arc::new(TokenBucket::new(1000).burst(0));
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let bucket = bucket.clone();
+                tokio::spawn(async move { bucket.acquire(250).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+}
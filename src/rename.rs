@@ -0,0 +1,296 @@
+//! 服务端批量重命名前缀（复制 + 校验 + 删除），支持中断后继续
+
+use futures::StreamExt;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::OssClient;
+
+/// 重命名失败的 key
+#[derive(Debug, Clone, Serialize)]
+pub struct RenameFailure {
+    pub key: String,
+    pub message: String,
+}
+
+/// 批量重命名结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RenameReport {
+    /// (旧 key, 新 key)
+    pub moved: Vec<(String, String)>,
+    pub errors: Vec<RenameFailure>,
+}
+
+impl RenameReport {
+    /// 是否全部成功
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// 把 `old_prefix` 下的 key 换成 `new_prefix` 开头
+pub fn rename_target(old_prefix: &str, new_prefix: &str, key: &str) -> String {
+    format!("{}{}", new_prefix, key.strip_prefix(old_prefix).unwrap_or(key))
+}
+
+/// 列出的 key 是否需要移动：`new_prefix` 位于 `old_prefix` 下时，已在 `new_prefix` 下的对象不再移动
+pub fn needs_rename(old_prefix: &str, new_prefix: &str, key: &str) -> bool {
+    !(new_prefix.starts_with(old_prefix) && key.starts_with(new_prefix))
+}
+
+/// 日志文件：第一行记录新旧前缀，之后每行记录一个已经复制并校验完成的旧 key（均经过 URL 编码，
+/// key 中的换行不会破坏按行的格式）。中断后用相同的前缀再次运行时，这些 key 不再重新复制，只需删除源对象
+struct Journal {
+    copied: HashSet<String>,
+    file: Mutex<std::fs::File>,
+}
+
+impl Journal {
+    fn open(path: &Path, old_prefix: &str, new_prefix: &str) -> Result<Self> {
+        let header = journal_header(old_prefix, new_prefix);
+        let lines: Vec<String> = match std::fs::File::open(path) {
+            Ok(file) => std::io::BufReader::new(file)
+                .lines()
+                .collect::<std::io::Result<_>>()
+                .with_context(|| format!("无法读取日志: {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e).with_context(|| format!("无法读取日志: {}", path.display())),
+        };
+        // 日志属于另一次重命名时继续使用会跳过复制直接删除源对象，必须拒绝
+        if let Some(first) = lines.first() {
+            if *first != header {
+                bail!(
+                    "日志 {} 不是 {} -> {} 的重命名留下的，确认不再需要后删除它或指定其他日志",
+                    path.display(),
+                    old_prefix,
+                    new_prefix
+                );
+            }
+        }
+        let copied = lines
+            .iter()
+            .skip(1)
+            .map(|line| {
+                urlencoding::decode(line)
+                    .map(|key| key.into_owned())
+                    .map_err(|_| OssError::Invalid(format!("日志 {} 中有无效的行: {}", path.display(), line)))
+            })
+            .collect::<Result<_>>()?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("无法打开日志: {}", path.display()))?;
+        if lines.is_empty() {
+            writeln!(file, "{}", header)?;
+        }
+        Ok(Self { copied, file: Mutex::new(file) })
+    }
+
+    fn record(&self, key: &str) -> Result<()> {
+        let mut file = self.file.lock().expect("journal lock poisoned");
+        writeln!(file, "{}", urlencoding::encode(key))?;
+        Ok(())
+    }
+}
+
+fn journal_header(old_prefix: &str, new_prefix: &str) -> String {
+    format!("rename-prefix {} {}", urlencoding::encode(old_prefix), urlencoding::encode(new_prefix))
+}
+
+impl OssClient {
+    /// 把 `old_prefix` 下的所有对象服务端移动到 `new_prefix` 下，并发数为 workers
+    /// 每个对象复制并校验大小后才删除源对象；指定 `journal` 时记录已完成复制的 key，
+    /// 中断后用同一个日志和相同的前缀再次运行不会重复复制（目标对象不存在或大小不同时仍会重新复制），
+    /// 日志属于其他前缀时报错；全部成功后删除日志。
+    /// `new_prefix` 位于 `old_prefix` 下（如 `a/` -> `a/b/`）时跳过已在 `new_prefix` 下的对象，以免再次运行时重复移动。
+    /// 每处理完一个对象以（已处理数，总数）回调 `on_progress`
+    pub async fn rename_prefix(
+        &self,
+        old_prefix: &str,
+        new_prefix: &str,
+        journal: Option<&Path>,
+        on_progress: impl Fn(usize, usize) + Sync,
+    ) -> Result<RenameReport> {
+        if old_prefix == new_prefix {
+            bail!("新旧前缀相同: {}", old_prefix);
+        }
+        let journal_log = journal.map(|path| Journal::open(path, old_prefix, new_prefix)).transpose()?;
+        let mut objects = self.list_objects(old_prefix).await?;
+        objects.retain(|obj| needs_rename(old_prefix, new_prefix, &obj.key));
+        let total = objects.len();
+        let done = AtomicUsize::new(0);
+
+        let results: Vec<_> = futures::stream::iter(&objects)
+            .map(|obj| {
+                let target = rename_target(old_prefix, new_prefix, &obj.key);
+                let (journal_log, done, on_progress) = (&journal_log, &done, &on_progress);
+                async move {
+                    let result = async {
+                        // 日志中的 key 还要确认目标确实存在，以免源对象在没有副本时被删除
+                        let journaled = journal_log.as_ref().is_some_and(|j| j.copied.contains(&obj.key));
+                        let copied = journaled
+                            && self.head_if_exists(&target).await?.is_some_and(|info| info.size == obj.size);
+                        if !copied {
+                            self.copy_verified(&obj.key, &target).await?;
+                            if let Some(journal_log) = journal_log {
+                                journal_log.record(&obj.key)?;
+                            }
+                        }
+                        self.client
+                            .delete_object()
+                            .bucket(&self.config.bucket)
+                            .key(&obj.key)
+                            .send()
+                            .await
+                            .with_context(|| format!("已复制到 {}，但删除源对象失败，对象目前同时存在于两处", target))?;
+                        Ok::<_, OssError>(())
+                    }
+                    .await;
+                    on_progress(done.fetch_add(1, Ordering::Relaxed) + 1, total);
+                    (obj.key.clone(), target, result)
+                }
+            })
+            .buffer_unordered(self.workers)
+            .collect()
+            .await;
+
+        let mut report = RenameReport::default();
+        for (key, target, result) in results {
+            match result {
+                Ok(()) => report.moved.push((key, target)),
+                Err(e) => report.errors.push(RenameFailure { key, message: e.to_string() }),
+            }
+        }
+        report.moved.sort();
+
+        if let (Some(path), true) = (journal, report.is_success()) {
+            drop(journal_log);
+            std::fs::remove_file(path).with_context(|| format!("无法删除日志: {}", path.display()))?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_target() {
+        assert_eq!(rename_target("v1/assets/", "assets/v1/", "v1/assets/a/b.js"), "assets/v1/a/b.js");
+        assert_eq!(rename_target("old", "new", "old-key"), "new-key");
+        assert!(!needs_rename("a/", "a/b/", "a/b/1"));
+        assert!(needs_rename("a/", "a/b/", "a/c/1"));
+        assert!(needs_rename("a/b/", "a/", "a/b/1"));
+    }
+
+    #[test]
+    fn test_journal_resumes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rename.journal");
+
+        let journal = Journal::open(&path, "a/", "b/").unwrap();
+        assert!(journal.copied.is_empty());
+        journal.record("a/1").unwrap();
+        journal.record("a/line\nbreak\r").unwrap();
+        drop(journal);
+
+        let journal = Journal::open(&path, "a/", "b/").unwrap();
+        assert_eq!(journal.copied, HashSet::from(["a/1".to_string(), "a/line\nbreak\r".to_string()]));
+        drop(journal);
+
+        // 前缀不同的重命名不能使用这个日志
+        let err = Journal::open(&path, "a/", "c/").err().unwrap();
+        assert!(err.to_string().contains("a/ -> c/"), "{}", err);
+
+        // 没有前缀记录的旧日志也拒绝
+        std::fs::write(&path, "a/1\n").unwrap();
+        assert!(Journal::open(&path, "a/", "b/").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rename_recopies_journaled_key_without_target() {
+        use crate::mock::{mock_client, response};
+        use std::sync::atomic::AtomicBool;
+        use std::sync::Arc;
+
+        let copied = Arc::new(AtomicBool::new(false));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let (state, log) = (copied.clone(), requests.clone());
+        let client = mock_client(move |req| {
+            let uri = req.uri().to_string();
+            log.lock().unwrap().push(format!("{} {}", req.method(), uri.split('?').next().unwrap_or_default()));
+            match req.method() {
+                "GET" => response(
+                    200,
+                    &[],
+                    "<ListBucketResult><IsTruncated>false</IsTruncated>\
+                     <Contents><Key>a/1</Key><Size>3</Size></Contents></ListBucketResult>",
+                ),
+                "HEAD" if uri.contains("/b/1") && !state.load(Ordering::SeqCst) => response(404, &[], ""),
+                "HEAD" => response(200, &[("Content-Length", "3")], ""),
+                "PUT" => {
+                    state.store(true, Ordering::SeqCst);
+                    response(200, &[], "<CopyObjectResult><ETag>\"e\"</ETag></CopyObjectResult>")
+                }
+                _ => response(204, &[], ""),
+            }
+        })
+        .await;
+
+        // 日志说 a/1 已经复制过，但目标不存在，仍要先复制再删除源对象
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rename.journal");
+        std::fs::write(&path, format!("{}\na%2F1\n", journal_header("a/", "b/"))).unwrap();
+        let report = client.rename_prefix("a/", "b/", Some(&path), |_, _| {}).await.unwrap();
+        assert_eq!(report.moved, [("a/1".to_string(), "b/1".to_string())]);
+        assert!(copied.load(Ordering::SeqCst));
+        let requests = requests.lock().unwrap();
+        let put = requests.iter().position(|r| r.starts_with("PUT")).unwrap();
+        let delete = requests.iter().position(|r| r.starts_with("DELETE")).unwrap();
+        assert!(put < delete, "{:?}", requests);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_rename_into_nested_prefix_skips_moved_objects() {
+        use crate::mock::{mock_client, response};
+        use std::sync::Arc;
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let client = mock_client(move |req| {
+            let uri = req.uri().to_string();
+            log.lock().unwrap().push(format!("{} {}", req.method(), uri));
+            match req.method() {
+                // 上次运行已把 a/1 移到 a/b/1，a/2 还没处理
+                "GET" => response(
+                    200,
+                    &[],
+                    "<ListBucketResult><IsTruncated>false</IsTruncated>\
+                     <Contents><Key>a/2</Key><Size>3</Size></Contents>\
+                     <Contents><Key>a/b/1</Key><Size>3</Size></Contents></ListBucketResult>",
+                ),
+                "HEAD" => response(200, &[("Content-Length", "3")], ""),
+                "PUT" => response(200, &[], "<CopyObjectResult><ETag>\"e\"</ETag></CopyObjectResult>"),
+                _ => response(204, &[], ""),
+            }
+        })
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rename.journal");
+        std::fs::write(&path, format!("{}\na%2F1\n", journal_header("a/", "a/b/"))).unwrap();
+        let report = client.rename_prefix("a/", "a/b/", Some(&path), |_, _| {}).await.unwrap();
+        assert_eq!(report.moved, [("a/2".to_string(), "a/b/2".to_string())]);
+        let requests = requests.lock().unwrap();
+        assert!(!requests.iter().any(|r| r.contains("a/b/b/") || r.contains("a%2Fb%2Fb")), "{:?}", requests);
+        assert!(!requests.iter().any(|r| r.starts_with("DELETE") && r.contains("/a/b/1")), "{:?}", requests);
+    }
+}
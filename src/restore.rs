@@ -0,0 +1,143 @@
+//! 恢复归档存储（GLACIER / DEEP_ARCHIVE 等）中的对象
+
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::operation::get_object::GetObjectError;
+use aws_sdk_s3::types::{GlacierJobParameters, RestoreRequest, Tier};
+use serde::Serialize;
+
+use crate::error::{bail, Context, OssError, Result, ServiceError};
+use crate::{is_not_found, OssClient};
+
+/// 恢复请求的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreOutcome {
+    /// 已提交恢复请求
+    Started,
+    /// 对象已经恢复，本次请求只更新了恢复副本的有效期
+    AlreadyRestored,
+    /// 已经有一个进行中的恢复请求
+    InProgress,
+}
+
+/// 对象的恢复状态（HEAD 返回的 x-amz-restore 头）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum RestoreStatus {
+    /// 不是归档存储，可以直接下载
+    NotArchived { storage_class: String },
+    /// 归档存储，还没有发起恢复
+    Archived { storage_class: String },
+    /// 恢复进行中
+    InProgress { storage_class: String },
+    /// 已恢复，`expiry` 之前可以下载
+    Restored { storage_class: String, expiry: Option<String> },
+}
+
+/// 下载前需要恢复的存储类型
+const ARCHIVE_CLASSES: [&str; 3] = ["GLACIER", "DEEP_ARCHIVE", "ARCHIVE"];
+
+impl OssClient {
+    /// 发起恢复请求，恢复出的临时副本保留 `days` 天
+    /// `tier` 为 Standard、Expedited 或 Bulk，不同档位的耗时和费用不同
+    pub async fn restore_object(&self, key: &str, days: i32, tier: &str) -> Result<RestoreOutcome> {
+        let outcome = match self.restore_status(key).await? {
+            RestoreStatus::NotArchived { storage_class } => {
+                bail!("{} 的存储类型为 {}，可以直接下载，无需恢复", key, storage_class)
+            }
+            RestoreStatus::InProgress { .. } => return Ok(RestoreOutcome::InProgress),
+            // 已恢复的对象再次请求只会更新有效期
+            RestoreStatus::Restored { .. } => RestoreOutcome::AlreadyRestored,
+            RestoreStatus::Archived { .. } => RestoreOutcome::Started,
+        };
+
+        let request = RestoreRequest::builder()
+            .days(days)
+            .glacier_job_parameters(GlacierJobParameters::builder().tier(Tier::from(tier)).build()?)
+            .build();
+        match self.client
+            .restore_object()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .restore_request(request)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(outcome),
+            Err(err) if err.code() == Some("RestoreAlreadyInProgress") => Ok(RestoreOutcome::InProgress),
+            Err(err) => Err(err).with_context(|| format!("无法恢复 {}", key)),
+        }
+    }
+
+    /// 查询对象的恢复状态
+    pub async fn restore_status(&self, key: &str) -> Result<RestoreStatus> {
+        let resp = match self.client.head_object().bucket(&self.config.bucket).key(key).send().await {
+            Ok(resp) => resp,
+            Err(err) if is_not_found(&err) => return Err(OssError::not_found(key)),
+            Err(err) => return Err(err).with_context(|| format!("无法获取对象信息: {}", key)),
+        };
+        let storage_class = resp.storage_class().map(|c| c.as_str()).unwrap_or("STANDARD").to_string();
+        Ok(parse_restore(storage_class, resp.restore()))
+    }
+}
+
+/// 解析 x-amz-restore 头，如 `ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT"`
+fn parse_restore(storage_class: String, header: Option<&str>) -> RestoreStatus {
+    let Some(header) = header else {
+        return if ARCHIVE_CLASSES.contains(&storage_class.as_str()) {
+            RestoreStatus::Archived { storage_class }
+        } else {
+            RestoreStatus::NotArchived { storage_class }
+        };
+    };
+    if header.contains("ongoing-request=\"true\"") {
+        return RestoreStatus::InProgress { storage_class };
+    }
+    let expiry = header
+        .split_once("expiry-date=\"")
+        .and_then(|(_, rest)| rest.split_once('"'))
+        .map(|(date, _)| date.to_string());
+    RestoreStatus::Restored { storage_class, expiry }
+}
+
+/// GetObject 的错误：归档对象给出恢复命令的提示，404 转为 [`OssError::NotFound`]，其他错误原样返回
+pub(crate) fn get_object_error(key: &str, err: SdkError<GetObjectError, HttpResponse>) -> OssError {
+    if err.code() == Some("InvalidObjectState") {
+        let context = format!(
+            "{} 位于归档存储，需要先恢复才能下载: oss-uploader restore {} --days 7（用 restore status 查看进度）",
+            key,
+            key,
+        );
+        OssError::Service(Box::new(ServiceError::from_sdk(context, err)))
+    } else if is_not_found(&err) {
+        OssError::not_found(key)
+    } else {
+        err.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_restore() {
+        let class = || "GLACIER".to_string();
+        assert_eq!(parse_restore(class(), None), RestoreStatus::Archived { storage_class: class() });
+        assert_eq!(
+            parse_restore("STANDARD".to_string(), None),
+            RestoreStatus::NotArchived { storage_class: "STANDARD".to_string() }
+        );
+        assert_eq!(
+            parse_restore(class(), Some("ongoing-request=\"true\"")),
+            RestoreStatus::InProgress { storage_class: class() }
+        );
+        assert_eq!(
+            parse_restore(class(), Some("ongoing-request=\"false\", expiry-date=\"Fri, 21 Dec 2012 00:00:00 GMT\"")),
+            RestoreStatus::Restored {
+                storage_class: class(),
+                expiry: Some("Fri, 21 Dec 2012 00:00:00 GMT".to_string()),
+            }
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! 自定义的重试判断：把服务商特有的错误码、状态码视为可重试，同时用于 SDK 的请求重试和分段传输的重试
+
+use aws_sdk_s3::config::interceptors::InterceptorContext;
+use aws_sdk_s3::config::retry::{ClassifyRetry, RetryAction};
+use aws_smithy_runtime_api::client::retries::classifiers::RetryClassifierPriority;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::OssError;
+use crate::throttle::backoff_delay;
+
+/// 失败请求的摘要，交给 [`RetryPolicy::classifier`] 判断是否重试
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorResponse<'a> {
+    /// HTTP 状态码；部分服务商限流时返回 200 和错误 XML
+    pub status: u16,
+    /// 错误码，如 `SlowDown`；HEAD 等没有响应体的请求为 None
+    pub code: Option<&'a str>,
+}
+
+type Classifier = Arc<dyn Fn(&ErrorResponse<'_>) -> bool + Send + Sync>;
+type Backoff = Arc<dyn Fn(u32) -> Duration + Send + Sync>;
+
+/// 在默认的重试判断（连接错误、5xx、限流）之外，额外视为可重试的错误，通过
+/// [`OssClientBuilder::retry_policy`](crate::OssClientBuilder::retry_policy) 设置。
+///
+/// 同时作用于 SDK 对单个请求的重试（次数和退避时长由 [`retry`](crate::OssClientBuilder::retry) 决定），
+/// 以及分段下载等自带的重试（[`backoff`](Self::backoff) 决定等待时长）。
+/// 只能让更多的错误重试，不能让默认会重试的错误不再重试
+#[derive(Clone, Default)]
+pub struct RetryPolicy {
+    codes: Vec<String>,
+    statuses: Vec<u16>,
+    classifier: Option<Classifier>,
+    backoff: Option<Backoff>,
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("codes", &self.codes)
+            .field("statuses", &self.statuses)
+            .field("classifier", &self.classifier.is_some())
+            .field("backoff", &self.backoff.is_some())
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 视为可重试的错误码，如 `["QpsLimitExceeded"]`
+    pub fn retry_codes<S: Into<String>>(mut self, codes: impl IntoIterator<Item = S>) -> Self {
+        self.codes.extend(codes.into_iter().map(Into::into));
+        self
+    }
+
+    /// 视为可重试的 HTTP 状态码，如 `[420]`
+    pub fn retry_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.statuses.extend(statuses);
+        self
+    }
+
+    /// 错误码和状态码都不匹配时，再由 `classifier` 判断，返回 true 表示重试
+    pub fn classifier(mut self, classifier: impl Fn(&ErrorResponse<'_>) -> bool + Send + Sync + 'static) -> Self {
+        self.classifier = Some(Arc::new(classifier));
+        self
+    }
+
+    /// 自带重试的等待时长，参数为已经重试的次数（从 0 开始）；默认为带抖动的指数退避。
+    /// 响应带有 Retry-After（秒数或 HTTP 日期）时优先使用它
+    pub fn backoff(mut self, backoff: impl Fn(u32) -> Duration + Send + Sync + 'static) -> Self {
+        self.backoff = Some(Arc::new(backoff));
+        self
+    }
+
+    /// `response` 是否按这个策略重试
+    pub fn is_retryable(&self, response: &ErrorResponse<'_>) -> bool {
+        self.statuses.contains(&response.status)
+            || response.code.is_some_and(|code| self.codes.iter().any(|c| c == code))
+            || self.classifier.as_ref().is_some_and(|classifier| classifier(response))
+    }
+
+    /// 库自带的整体重试（如批量复制中的单个对象）是否重试 `err`：限流、网络错误和 5xx 重试，
+    /// 其他服务端错误按这个策略判断，没有权限、对象不存在、本地错误等不重试
+    pub(crate) fn should_retry(&self, err: &OssError) -> bool {
+        let response = err.service_error().and_then(|e| Some(ErrorResponse { status: e.status()?, code: e.code() }));
+        match err {
+            OssError::Throttled(_) | OssError::Network(_) => true,
+            OssError::Service(_) if response.is_some_and(|r| r.status >= 500) => true,
+            _ => response.is_some_and(|r| self.is_retryable(&r)),
+        }
+    }
+
+    /// 第 `attempt` 次重试前的等待时长
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        match &self.backoff {
+            Some(backoff) => backoff(attempt),
+            None => backoff_delay(attempt),
+        }
+    }
+}
+
+/// 把 [`RetryPolicy`] 接入 SDK 的重试，在默认的分类器之后运行
+#[derive(Debug)]
+pub(crate) struct PolicyClassifier(pub(crate) Arc<RetryPolicy>);
+
+impl ClassifyRetry for PolicyClassifier {
+    fn classify_retry(&self, ctx: &InterceptorContext) -> RetryAction {
+        // 只判断失败的请求；连接错误等没有响应的失败由默认的分类器处理
+        let (Some(Err(_)), Some(response)) = (ctx.output_or_error(), ctx.response()) else {
+            return RetryAction::NoActionIndicated;
+        };
+        let code = response.body().bytes().and_then(error_code);
+        let response = ErrorResponse { status: response.status().as_u16(), code };
+        if self.0.is_retryable(&response) {
+            RetryAction::server_error()
+        } else {
+            RetryAction::NoActionIndicated
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "oss-uploader RetryPolicy"
+    }
+
+    fn priority(&self) -> RetryClassifierPriority {
+        RetryClassifierPriority::run_after(RetryClassifierPriority::transient_error_classifier())
+    }
+}
+
+/// 错误响应 XML 中的 `<Code>`
+fn error_code(body: &[u8]) -> Option<&str> {
+    let body = std::str::from_utf8(body).ok()?;
+    let start = body.find("<Code>")? + "<Code>".len();
+    let len = body[start..].find("</Code>")?;
+    Some(body[start..start + len].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{mock_builder, response};
+    use crate::OssError;
+    use aws_sdk_s3::config::http::{HttpRequest, HttpResponse};
+    use aws_sdk_s3::config::retry::RetryConfig;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const QUIRKY_THROTTLE: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Error><Code>QpsLimitExceeded</Code><Message>slow down</Message></Error>";
+    const ACCESS_DENIED: &str = "<Error><Code>AccessDenied</Code><Message>denied</Message></Error>";
+
+    #[test]
+    fn test_is_retryable() {
+        let policy = RetryPolicy::new()
+            .retry_codes(["QpsLimitExceeded"])
+            .retry_statuses([420])
+            .classifier(|response| response.status == 400 && response.code == Some("RequestTimeout"));
+        let check = |status, code| policy.is_retryable(&ErrorResponse { status, code });
+        assert!(check(200, Some("QpsLimitExceeded")));
+        assert!(check(420, None));
+        assert!(check(400, Some("RequestTimeout")));
+        assert!(!check(403, Some("AccessDenied")));
+        assert!(!check(400, None));
+        assert!(!RetryPolicy::new().is_retryable(&ErrorResponse { status: 420, code: Some("QpsLimitExceeded") }));
+    }
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(error_code(QUIRKY_THROTTLE.as_bytes()), Some("QpsLimitExceeded"));
+        assert_eq!(error_code(b"<Error><Code> SlowDown </Code></Error>"), Some("SlowDown"));
+        assert_eq!(error_code(b""), None);
+        assert_eq!(error_code(b"<Error><Code>unterminated"), None);
+    }
+
+    #[test]
+    fn test_delay_uses_backoff() {
+        let policy = RetryPolicy::new().backoff(|attempt| Duration::from_millis(10 * (attempt as u64 + 1)));
+        assert_eq!(policy.delay(0), Duration::from_millis(10));
+        assert_eq!(policy.delay(2), Duration::from_millis(30));
+        assert!(RetryPolicy::new().delay(0) >= Duration::from_millis(200));
+    }
+
+    /// 第一个请求返回 `status` 和 `body`，之后返回 200 和 `success`；同时返回请求计数
+    fn fail_once(
+        status: u16,
+        body: &'static str,
+        success: &'static str,
+    ) -> (Arc<AtomicUsize>, impl Fn(&HttpRequest) -> HttpResponse) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        let handler = move |_: &HttpRequest| match counter.fetch_add(1, Ordering::SeqCst) {
+            0 => response(status, &[], body),
+            _ => response(200, &[("etag", "\"e\"")], success),
+        };
+        (count, handler)
+    }
+
+    fn fast_retry() -> RetryConfig {
+        RetryConfig::standard().with_max_attempts(3).with_initial_backoff(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn test_sdk_retries_custom_classified_errors() {
+        let policy = RetryPolicy::new().retry_codes(["QpsLimitExceeded"]);
+        let (count, handler) = fail_once(400, QUIRKY_THROTTLE, "");
+        let client = mock_builder(handler).retry(fast_retry()).retry_policy(policy.clone()).build().await.unwrap();
+        client.upload_bytes("a.txt", "data").await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        // 没有设置策略时同样的错误直接失败
+        let (count, handler) = fail_once(400, QUIRKY_THROTTLE, "");
+        let client = mock_builder(handler).retry(fast_retry()).build().await.unwrap();
+        assert!(client.upload_bytes("a.txt", "data").await.is_err());
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        // 403 不在策略中，不重试
+        let (count, handler) = fail_once(403, ACCESS_DENIED, "");
+        let client = mock_builder(handler).retry(fast_retry()).retry_policy(policy).build().await.unwrap();
+        let err = client.upload_bytes("a.txt", "data").await.unwrap_err();
+        assert!(matches!(err, OssError::AccessDenied(_)), "{:?}", err);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sdk_retries_error_with_200_status() {
+        // CreateMultipartUpload 返回 200 和错误 XML 时 SDK 视为失败，由策略决定重试
+        let policy = RetryPolicy::new().retry_codes(["QpsLimitExceeded"]);
+        let created = "<InitiateMultipartUploadResult><Bucket>my-bucket</Bucket><Key>a.bin</Key><UploadId>u1</UploadId></InitiateMultipartUploadResult>";
+        let (count, handler) = fail_once(200, QUIRKY_THROTTLE, created);
+        let client = mock_builder(handler).retry(fast_retry()).retry_policy(policy).build().await.unwrap();
+        let resp = client.client.create_multipart_upload().bucket("my-bucket").key("a.bin").send().await.unwrap();
+        assert_eq!(resp.upload_id(), Some("u1"));
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_ranged_download_retries_custom_classified_errors() {
+        let size = 6 * 1024 * 1024;
+        let policy = RetryPolicy::new().retry_statuses([420]).backoff(|_| Duration::ZERO);
+        let gets = Arc::new(AtomicUsize::new(0));
+        let handler = {
+            let gets = gets.clone();
+            move |req: &HttpRequest| {
+                if req.method() == "HEAD" {
+                    return response(200, &[("content-length", &size.to_string()), ("etag", "\"e\"")], "");
+                }
+                let range = req.headers().get("range").unwrap_or_default().to_string();
+                // 第一个分段请求被拒绝一次；SDK 的重试已关闭，由分段下载自带的重试处理
+                if gets.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return response(420, &[], "");
+                }
+                let (start, end) = range.trim_start_matches("bytes=").split_once('-').unwrap();
+                let len = end.parse::<usize>().unwrap() - start.parse::<usize>().unwrap() + 1;
+                response(206, &[("content-length", &len.to_string())], vec![1u8; len])
+            }
+        };
+        let client = mock_builder(handler)
+            .retry(RetryConfig::disabled())
+            .retry_policy(policy)
+            .build()
+            .await
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("a.bin");
+        client.download("a.bin", Some(&output)).await.unwrap();
+        assert_eq!(std::fs::metadata(&output).unwrap().len(), size);
+        assert_eq!(gets.load(Ordering::SeqCst), 3);
+        assert_eq!(client.throttle_count(), 1);
+    }
+}
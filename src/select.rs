@@ -0,0 +1,218 @@
+//! S3 Select（SelectObjectContent）：在服务端用 SQL 过滤 CSV/JSON/Parquet 对象，只返回需要的数据
+
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::types::{
+    CompressionType, CsvInput, CsvOutput, ExpressionType, FileHeaderInfo, InputSerialization, JsonInput,
+    JsonOutput, JsonType, OutputSerialization, ParquetInput, RequestProgress, SelectObjectContentEventStream,
+};
+
+use crate::error::{bail, OssError, Result};
+use crate::{is_not_found, is_not_implemented, OssClient};
+
+/// S3 Select 查询选项
+#[derive(Debug, Clone)]
+pub struct SelectOptions {
+    /// SQL 表达式，如 `SELECT s.id FROM S3Object s WHERE s.level = 'ERROR'`
+    pub sql: String,
+    /// 输入格式：csv、json（每行一个 JSON）、json-document 或 parquet
+    pub input_format: String,
+    /// 输出格式：csv 或 json（每行一条记录）
+    pub output_format: String,
+    /// 输入的压缩方式：none、gzip 或 bzip2，为 None 时按 key 的扩展名判断
+    pub compression: Option<String>,
+    /// CSV 的第一行是否为列名（为 true 时可以用 `s.列名` 引用）
+    pub csv_header: bool,
+}
+
+/// 扫描、处理和返回的字节数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SelectStats {
+    pub bytes_scanned: u64,
+    pub bytes_processed: u64,
+    pub bytes_returned: u64,
+}
+
+/// 查询过程中收到的事件
+#[derive(Debug)]
+pub enum SelectEvent<'a> {
+    /// 一段结果数据，记录可能跨越多段
+    Records(&'a [u8]),
+    /// 查询进度
+    Progress(SelectStats),
+}
+
+impl OssClient {
+    /// 执行 S3 Select 查询，每收到一段结果或进度就回调 `on_event`，返回最终的统计
+    /// 只有收到 End 事件才算查询完整，连接提前中断时返回错误
+    pub async fn select(
+        &self,
+        key: &str,
+        options: &SelectOptions,
+        mut on_event: impl FnMut(SelectEvent<'_>) -> Result<()>,
+    ) -> Result<SelectStats> {
+        let mut resp = match self.client
+            .select_object_content()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .expression(&options.sql)
+            .expression_type(ExpressionType::Sql)
+            .input_serialization(input_serialization(key, options)?)
+            .output_serialization(output_serialization(&options.output_format)?)
+            .request_progress(RequestProgress::builder().enabled(true).build())
+            .send()
+            .await
+        {
+            Ok(resp) => resp,
+            Err(err) if is_not_found(&err) => return Err(OssError::not_found(key)),
+            Err(err) if is_not_implemented(&err) => return Err(OssError::unsupported("当前服务商不支持 S3 Select", err)),
+            Err(err) => return Err(select_error(err)),
+        };
+
+        let mut stats = SelectStats::default();
+        loop {
+            let event = match resp.payload.recv().await {
+                Ok(Some(event)) => event,
+                Ok(None) => bail!("查询结果不完整：连接在收到 End 事件前中断"),
+                Err(err) => return Err(select_error(err)),
+            };
+            match event {
+                SelectObjectContentEventStream::Records(records) => {
+                    if let Some(payload) = records.payload() {
+                        on_event(SelectEvent::Records(payload.as_ref()))?;
+                    }
+                }
+                SelectObjectContentEventStream::Progress(progress) => {
+                    if let Some(details) = progress.details() {
+                        on_event(SelectEvent::Progress(to_stats(
+                            details.bytes_scanned(),
+                            details.bytes_processed(),
+                            details.bytes_returned(),
+                        )))?;
+                    }
+                }
+                SelectObjectContentEventStream::Stats(event) => {
+                    if let Some(details) = event.details() {
+                        stats = to_stats(details.bytes_scanned(), details.bytes_processed(), details.bytes_returned());
+                    }
+                }
+                SelectObjectContentEventStream::End(_) => return Ok(stats),
+                // Cont 只用于保持连接
+                _ => {}
+            }
+        }
+    }
+}
+
+fn input_serialization(key: &str, options: &SelectOptions) -> Result<InputSerialization> {
+    let builder = InputSerialization::builder();
+    let builder = match options.input_format.as_str() {
+        "csv" => {
+            let header = if options.csv_header { FileHeaderInfo::Use } else { FileHeaderInfo::None };
+            builder.csv(CsvInput::builder().file_header_info(header).build())
+        }
+        "json" => builder.json(JsonInput::builder().r#type(JsonType::Lines).build()),
+        "json-document" => builder.json(JsonInput::builder().r#type(JsonType::Document).build()),
+        // Parquet 自带列压缩，不能再指定 CompressionType
+        "parquet" => return Ok(builder.parquet(ParquetInput::builder().build()).build()),
+        other => bail!("无效的输入格式: {}（可选 csv、json、json-document、parquet）", other),
+    };
+    let compression = match options.compression.as_deref() {
+        Some("none") => CompressionType::None,
+        Some("gzip") => CompressionType::Gzip,
+        Some("bzip2") => CompressionType::Bzip2,
+        Some(other) => bail!("无效的压缩方式: {}（可选 none、gzip、bzip2）", other),
+        None => compression_for_key(key),
+    };
+    Ok(builder.compression_type(compression).build())
+}
+
+fn output_serialization(format: &str) -> Result<OutputSerialization> {
+    Ok(match format {
+        "csv" => OutputSerialization::builder().csv(CsvOutput::builder().build()).build(),
+        "json" => OutputSerialization::builder()
+            .json(JsonOutput::builder().record_delimiter("\n").build())
+            .build(),
+        other => bail!("无效的输出格式: {}（可选 csv、json）", other),
+    })
+}
+
+/// 按扩展名判断压缩方式
+fn compression_for_key(key: &str) -> CompressionType {
+    if key.ends_with(".gz") {
+        CompressionType::Gzip
+    } else if key.ends_with(".bz2") {
+        CompressionType::Bzip2
+    } else {
+        CompressionType::None
+    }
+}
+
+fn to_stats(scanned: Option<i64>, processed: Option<i64>, returned: Option<i64>) -> SelectStats {
+    let bytes = |n: Option<i64>| n.unwrap_or_default().max(0) as u64;
+    SelectStats {
+        bytes_scanned: bytes(scanned),
+        bytes_processed: bytes(processed),
+        bytes_returned: bytes(returned),
+    }
+}
+
+/// SQL 写错时服务端返回的错误码前缀，如 ParseUnexpectedToken、EvaluatorInvalidArguments
+const SQL_ERROR_PREFIXES: [&str; 6] = ["Parse", "Lexer", "Evaluator", "Cast", "InvalidColumnIndex", "ExpressionTooLong"];
+
+/// 把服务端错误整理为可读的信息，SQL 错误附上错误码和服务端的说明
+fn select_error<E>(err: E) -> OssError
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    let message = err.message().unwrap_or("服务端没有返回说明");
+    match err.code() {
+        Some(code) if SQL_ERROR_PREFIXES.iter().any(|prefix| code.starts_with(prefix)) => {
+            OssError::Invalid(format!("SQL 错误（{}）: {}", code, message))
+        }
+        // 错误码由 ServiceError 的 Display 附加在后面
+        Some(_) => OssError::service(format!("查询失败: {}", message), err),
+        None => OssError::service("查询失败".to_string(), err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::error::ErrorMetadata;
+
+    fn options(input_format: &str, compression: Option<&str>) -> SelectOptions {
+        SelectOptions {
+            sql: "SELECT * FROM S3Object".to_string(),
+            input_format: input_format.to_string(),
+            output_format: "json".to_string(),
+            compression: compression.map(str::to_string),
+            csv_header: true,
+        }
+    }
+
+    #[test]
+    fn test_input_serialization() {
+        let input = input_serialization("logs/a.csv.gz", &options("csv", None)).unwrap();
+        assert_eq!(input.compression_type(), Some(&CompressionType::Gzip));
+        assert_eq!(input.csv().unwrap().file_header_info(), Some(&FileHeaderInfo::Use));
+
+        let input = input_serialization("logs/a.json", &options("json", Some("bzip2"))).unwrap();
+        assert_eq!(input.compression_type(), Some(&CompressionType::Bzip2));
+        assert_eq!(input.json().unwrap().r#type(), Some(&JsonType::Lines));
+
+        let input = input_serialization("a.parquet", &options("parquet", None)).unwrap();
+        assert!(input.compression_type().is_none());
+        assert!(input_serialization("a.xml", &options("xml", None)).is_err());
+    }
+
+    #[test]
+    fn test_select_error() {
+        let metadata = |code: &str, message: &str| ErrorMetadata::builder().code(code).message(message).build();
+        let err = select_error(metadata("ParseUnexpectedToken", "Unexpected token WHERE"));
+        assert_eq!(err.to_string(), "SQL 错误（ParseUnexpectedToken）: Unexpected token WHERE");
+        assert!(matches!(err, OssError::Invalid(_)));
+        let err = select_error(metadata("AccessDenied", "Access Denied"));
+        assert_eq!(err.to_string(), "查询失败: Access Denied（AccessDenied）");
+        assert_eq!(err.service_error().and_then(|e| e.code()), Some("AccessDenied"));
+    }
+}
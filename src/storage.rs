@@ -0,0 +1,90 @@
+//! 对象存储的抽象：业务代码接受 `impl ObjectStorage`，测试时可以换成内存实现 [`MemoryStorage`](crate::MemoryStorage)
+
+use async_trait::async_trait;
+use std::path::Path;
+use tokio::io::AsyncWrite;
+
+use crate::error::Result;
+use crate::{DeleteOutcome, ObjectInfo, OssClient};
+
+/// 常用的对象操作，由 [`OssClient`] 实现；方法的语义与 `OssClient` 的同名方法一致
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    /// 上传本地文件，返回对象的 URL
+    async fn upload(&self, path: &Path, key: &str) -> Result<String>;
+
+    /// 上传内存中的数据，返回对象的 URL
+    async fn upload_bytes(&self, key: &str, data: &[u8]) -> Result<String>;
+
+    /// 下载对象写入 `writer`，返回写入的字节数；对象不存在时返回 [`OssError::NotFound`](crate::OssError::NotFound)
+    async fn download_to_writer(&self, key: &str, writer: &mut (dyn AsyncWrite + Unpin + Send)) -> Result<u64>;
+
+    /// 删除对象，对象不存在时返回 [`DeleteOutcome::DidNotExist`]
+    async fn delete(&self, key: &str) -> Result<DeleteOutcome>;
+
+    /// 对象是否存在
+    async fn exists(&self, key: &str) -> Result<bool>;
+
+    /// 对象最新版本的元信息；对象不存在时返回 [`OssError::NotFound`](crate::OssError::NotFound)
+    async fn head(&self, key: &str) -> Result<ObjectInfo>;
+
+    /// 列出前缀下的所有对象，按 key 排序
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>>;
+
+    /// 生成有效期 `expires_in_secs` 秒的临时下载 URL
+    async fn presign(&self, key: &str, expires_in_secs: u64) -> Result<String>;
+}
+
+#[async_trait]
+impl ObjectStorage for OssClient {
+    async fn upload(&self, path: &Path, key: &str) -> Result<String> {
+        OssClient::upload(self, path, key).await
+    }
+
+    async fn upload_bytes(&self, key: &str, data: &[u8]) -> Result<String> {
+        OssClient::upload_bytes(self, key, data).await
+    }
+
+    async fn download_to_writer(&self, key: &str, mut writer: &mut (dyn AsyncWrite + Unpin + Send)) -> Result<u64> {
+        OssClient::download_to_writer(self, key, &mut writer, &self.default_options().into()).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<DeleteOutcome> {
+        OssClient::delete(self, key).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        OssClient::exists(self, key).await
+    }
+
+    async fn head(&self, key: &str) -> Result<ObjectInfo> {
+        OssClient::head(self, key, None).await
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>> {
+        self.list_objects(prefix).await
+    }
+
+    async fn presign(&self, key: &str, expires_in_secs: u64) -> Result<String> {
+        self.generate_presigned_url(key, expires_in_secs, None, None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{mock_client, response};
+
+    #[tokio::test]
+    async fn test_oss_client_as_storage() {
+        let client = mock_client(|req| match req.method() {
+            "PUT" => response(200, &[("ETag", "\"e\"")], ""),
+            _ => response(404, &[], ""),
+        })
+        .await;
+        let storage: &dyn ObjectStorage = &client;
+        assert_eq!(storage.upload_bytes("a.txt", b"hello").await.unwrap(), "https://my-bucket.s3.example.com/a.txt");
+        assert!(storage.head("a.txt").await.unwrap_err().is_not_found());
+        assert!(!storage.exists("a.txt").await.unwrap());
+    }
+}
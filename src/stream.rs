@@ -0,0 +1,451 @@
+//! 不经过本地文件的上传和下载：从任意 AsyncRead 上传（内存中最多保留两个分块），下载时把响应体交给调用方
+
+use aws_sdk_s3::operation::get_object::GetObjectOutput;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use serde::Serialize;
+use std::sync::OnceLock;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::error::{bail, Context, Result};
+use crate::restore::get_object_error;
+use crate::transfer::{finish, with_timeout};
+use crate::{DownloadOptions, ObjectInfo, OssClient, ProgressEvent, TransferOptions, UploadOptions, MAX_PARTS};
+
+/// 流式上传的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UploadResult {
+    /// 对象的访问 URL，同 [`OssClient::upload`] 的返回值
+    pub url: String,
+    /// 实际上传的字节数
+    pub size: u64,
+    /// 分块数，单次 PUT 上传时为 0
+    pub parts: usize,
+}
+
+impl OssClient {
+    /// 从 `reader` 读取数据上传到 `key`，`size_hint` 只用于进度事件的总大小，长度未知时为 None
+    /// 数据不超过一个分块（包括空数据）时使用单次 PUT，否则逐块读取并分块上传；
+    /// 分块上传失败、取消或超时时中止分块上传，不留下未完成的分块
+    pub async fn upload_stream<R: AsyncRead + Unpin + Send>(
+        &self,
+        mut reader: R,
+        key: &str,
+        size_hint: Option<u64>,
+        options: &UploadOptions<'_>,
+    ) -> Result<UploadResult> {
+        let progress = options.transfer.progress;
+        let key = match self.object_key(key) {
+            Ok(key) => key,
+            Err(err) => return finish(progress, Err(err)),
+        };
+        let key = key.as_str();
+        progress.on_event(ProgressEvent::Started { total: size_hint.unwrap_or(0) });
+        let created = OnceLock::new();
+        let result = with_timeout(options.timeout, async {
+            let client = options.client(self)?;
+            let part_size = client.part_size as usize;
+            let first = read_part(&mut reader, part_size).await?;
+            let second = if first.len() == part_size { read_part(&mut reader, part_size).await? } else { Vec::new() };
+            if second.is_empty() {
+                return client.put_stream_part(key, first, options).await;
+            }
+            client.upload_stream_multipart(&mut reader, key, [first, second], options, &created).await
+        })
+        .await;
+        self.abort_timed_out(&result, key, &created).await;
+        finish(progress, result)
+    }
+
+    /// 上传内存中的数据，返回对象的 URL；超过分块大小时分块上传
+    pub async fn upload_bytes(&self, key: &str, data: impl AsRef<[u8]>) -> Result<String> {
+        self.upload_bytes_with_options(key, data, &self.default_upload_options()).await
+    }
+
+    /// 同 [`upload_bytes`](Self::upload_bytes)，按 `options` 设置对象属性和分块参数
+    pub async fn upload_bytes_with_options(
+        &self,
+        key: &str,
+        data: impl AsRef<[u8]>,
+        options: &UploadOptions<'_>,
+    ) -> Result<String> {
+        let data = data.as_ref();
+        let result = self.upload_stream(data, key, Some(data.len() as u64), options).await?;
+        Ok(result.url)
+    }
+
+    /// 下载对象，返回元信息和未读取的响应体，由调用方写入文件或转发给 HTTP 客户端等
+    /// （`body.into_async_read()` 可以得到 AsyncRead）；对象不存在时返回 [`OssError::NotFound`](crate::OssError::NotFound)。
+    /// `options` 的版本、范围、SSE-C 和请求者付费会用于 GET；
+    /// 取消令牌只在收到响应前有效，读取响应体时不会发送进度事件
+    pub async fn download_stream(&self, key: &str, options: &DownloadOptions<'_>) -> Result<(ObjectInfo, ByteStream)> {
+        let key = &self.object_key(key)?;
+        options.client(self)?;
+        let request = self.send_throttled("GetObject", key, || {
+            let request = self.client.get_object().bucket(&self.config.bucket).key(key);
+            options.apply_get(request).set_range(options.range_header()).send()
+        });
+        let resp = options
+            .transfer
+            .until_cancelled(async { request.await.map_err(|err| get_object_error(key, err)) })
+            .await?;
+        let info = get_info(key, &resp);
+        Ok((info, resp.body))
+    }
+
+    /// 下载对象写入 `writer`（如 HTTP 响应体、内存缓冲区），按 `options` 发送进度事件，返回写入的字节数；
+    /// 返回前会 flush `writer`，失败、取消或超时时 `writer` 中可能已经写入了部分数据
+    pub async fn download_to_writer<W: AsyncWrite + Unpin>(
+        &self,
+        key: &str,
+        writer: &mut W,
+        options: &DownloadOptions<'_>,
+    ) -> Result<u64> {
+        let result = with_timeout(options.timeout, self.write_body(key, writer, options)).await;
+        finish(options.transfer.progress, result)
+    }
+
+    /// 单次 GET 下载写入 `writer`，只发送 Started 和 BytesTransferred，结束事件由调用方发送
+    pub(crate) async fn write_body<W: AsyncWrite + Unpin>(
+        &self,
+        key: &str,
+        writer: &mut W,
+        download: &DownloadOptions<'_>,
+    ) -> Result<u64> {
+        let (info, mut body) = self.download_stream(key, download).await?;
+        let options = &download.transfer;
+        options.progress.on_event(ProgressEvent::Started { total: info.size });
+        let mut written = 0;
+        while let Some(chunk) = options.until_cancelled(body.try_next()).await? {
+            options.until_cancelled(self.limit_rate(chunk.len() as u64)).await?;
+            writer.write_all(&chunk).await.context("写入下载数据失败")?;
+            options.progress.on_event(ProgressEvent::BytesTransferred { n: chunk.len() as u64 });
+            written += chunk.len() as u64;
+        }
+        writer.flush().await.context("写入下载数据失败")?;
+        Ok(written)
+    }
+
+    /// 数据只有一个分块时直接 PUT
+    async fn put_stream_part(&self, key: &str, data: Vec<u8>, options: &UploadOptions<'_>) -> Result<UploadResult> {
+        let size = data.len() as u64;
+        let _permit = options.transfer.until_cancelled(self.part_permit()).await?;
+        options.transfer.until_cancelled(self.limit_rate(size)).await?;
+        let request = self.client.put_object().bucket(&self.config.bucket).key(key).body(ByteStream::from(data));
+        let request = options.apply_put(request).send();
+        options.transfer.until_cancelled(request).await.with_context(|| format!("无法上传 {}", key))?;
+        options.transfer.progress.on_event(ProgressEvent::BytesTransferred { n: size });
+        Ok(UploadResult { url: self.generate_url(key), size, parts: 0 })
+    }
+
+    async fn upload_stream_multipart<R: AsyncRead + Unpin + Send>(
+        &self,
+        reader: &mut R,
+        key: &str,
+        first_two: [Vec<u8>; 2],
+        options: &UploadOptions<'_>,
+        created: &OnceLock<String>,
+    ) -> Result<UploadResult> {
+        let create = self.client.create_multipart_upload().bucket(&self.config.bucket).key(key);
+        let create = options.apply_create(create).send();
+        let upload_id = options
+            .transfer
+            .until_cancelled(create)
+            .await
+            .with_context(|| format!("无法创建 {} 的分块上传", key))?
+            .upload_id()
+            .context("无法获取 upload id")?
+            .to_string();
+        let _ = created.set(upload_id.clone());
+
+        let uploaded = self.upload_stream_parts(reader, key, &upload_id, first_two, &options.transfer).await;
+        let (parts, size) = match uploaded {
+            Ok(uploaded) => uploaded,
+            Err(err) => {
+                self.abort_quietly(key, &upload_id).await;
+                return Err(err);
+            }
+        };
+        let count = parts.len();
+        let completed = self.client
+            .complete_multipart_upload()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .upload_id(&upload_id)
+            .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+            .send()
+            .await
+            .with_context(|| format!("无法完成 {} 的分块上传", key));
+        if let Err(err) = completed {
+            self.abort_quietly(key, &upload_id).await;
+            return Err(err);
+        }
+        Ok(UploadResult { url: self.generate_url(key), size, parts: count })
+    }
+
+    /// 依次上传分块：上传当前分块前先读出下一块，读到空数据时结束
+    async fn upload_stream_parts<R: AsyncRead + Unpin + Send>(
+        &self,
+        reader: &mut R,
+        key: &str,
+        upload_id: &str,
+        [mut current, mut next]: [Vec<u8>; 2],
+        options: &TransferOptions<'_>,
+    ) -> Result<(Vec<CompletedPart>, u64)> {
+        let part_size = self.part_size as usize;
+        let (mut parts, mut size) = (Vec::new(), 0);
+        while !current.is_empty() {
+            let number = parts.len() + 1;
+            if number > MAX_PARTS {
+                bail!("数据超过了 {} 个分块，请增大分块大小", MAX_PARTS);
+            }
+            options.check_cancelled()?;
+            let len = current.len() as u64;
+            let _permit = options.until_cancelled(self.part_permit()).await?;
+            options.until_cancelled(self.limit_rate(len)).await?;
+            let request = self.client
+                .upload_part()
+                .bucket(&self.config.bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(number as i32)
+                .body(ByteStream::from(current))
+                .send();
+            let resp = options
+                .until_cancelled(request)
+                .await
+                .with_context(|| format!("无法上传 {} 的第 {} 个分块", key, number))?;
+            tracing::debug!(part = number, bytes = len, "分块上传完成");
+            options.progress.on_event(ProgressEvent::BytesTransferred { n: len });
+            options.progress.on_event(ProgressEvent::PartCompleted { number });
+            parts.push(CompletedPart::builder().part_number(number as i32).set_e_tag(resp.e_tag).build());
+            size += len;
+
+            current = next;
+            next = if current.len() == part_size { read_part(reader, part_size).await? } else { Vec::new() };
+        }
+        Ok((parts, size))
+    }
+}
+
+/// 从 GET 的响应构造对象元信息，字段同 HEAD
+fn get_info(key: &str, resp: &GetObjectOutput) -> ObjectInfo {
+    ObjectInfo {
+        key: key.to_string(),
+        size: resp.content_length().unwrap_or_default().max(0) as u64,
+        etag: resp.e_tag().map(|s| s.to_string()),
+        storage_class: resp.storage_class()
+            .map(|c| c.as_str().to_string())
+            .unwrap_or_else(|| "STANDARD".to_string()),
+        last_modified: resp.last_modified().cloned(),
+        owner: None,
+        content_type: resp.content_type().map(|s| s.to_string()),
+        sse: resp.server_side_encryption().map(|s| s.as_str().to_string()),
+        metadata: resp.metadata().cloned().unwrap_or_default(),
+        version_id: resp.version_id().map(|s| s.to_string()),
+    }
+}
+
+/// 读满一个分块，返回的长度小于 `size` 说明数据已经读完
+pub(crate) async fn read_part<R: AsyncRead + Unpin>(reader: &mut R, size: usize) -> Result<Vec<u8>> {
+    let mut buffer = Vec::with_capacity(size);
+    (&mut *reader)
+        .take(size as u64)
+        .read_to_end(&mut buffer)
+        .await
+        .context("读取上传数据失败")?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{mock_client, query, response};
+    use std::sync::{Arc, Mutex};
+
+    const PART: usize = 5 * 1024 * 1024;
+
+    /// 模拟分块上传：按顺序记录请求（操作和请求体大小），`fail_part` 号分块（为 "complete" 时为完成请求）返回 400
+    async fn upload_client(fail_part: Option<&'static str>) -> (OssClient, Arc<Mutex<Vec<String>>>) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let client = mock_client(move |req| {
+            let query = query(req);
+            let len = req.body().bytes().map_or(0, <[u8]>::len);
+            let part = query.split('&').find_map(|pair| pair.strip_prefix("partNumber="));
+            let (op, body) = match (req.method(), part) {
+                ("POST", _) if query == "uploads" => {
+                    ("create".to_string(), "<InitiateMultipartUploadResult><UploadId>u1</UploadId></InitiateMultipartUploadResult>")
+                }
+                ("POST", _) => ("complete".to_string(), "<CompleteMultipartUploadResult></CompleteMultipartUploadResult>"),
+                ("DELETE", _) => ("abort".to_string(), ""),
+                ("PUT", Some(part)) => (format!("part {} {}", part, len), ""),
+                _ => (format!("put {}", len), ""),
+            };
+            let fail = (part.is_some() && part == fail_part) || fail_part == Some(op.as_str());
+            requests.lock().unwrap().push(op);
+            if fail {
+                return response(400, &[], "<Error><Code>InvalidPart</Code><Message>bad part</Message></Error>");
+            }
+            response(200, &[("ETag", "\"e\"")], body)
+        })
+        .await;
+        (client, log)
+    }
+
+    async fn upload(len: usize, size_hint: Option<u64>) -> (UploadResult, Vec<String>) {
+        let (client, log) = upload_client(None).await;
+        let data = vec![7u8; len];
+        let result = client.upload_stream(&data[..], "a.bin", size_hint, &UploadOptions::default()).await.unwrap();
+        assert_eq!(result.size, len as u64);
+        let log = log.lock().unwrap().clone();
+        (result, log)
+    }
+
+    #[tokio::test]
+    async fn test_upload_stream_single_put() {
+        let (result, log) = upload(0, Some(0)).await;
+        assert_eq!((result.parts, log), (0, vec!["put 0".to_string()]));
+        let (result, log) = upload(100, None).await;
+        assert_eq!((result.parts, log), (0, vec!["put 100".to_string()]));
+        // 正好一个分块：读到下一块为空，仍然用单次 PUT
+        let (result, log) = upload(PART, None).await;
+        assert_eq!((result.parts, log), (0, vec![format!("put {}", PART)]));
+        assert_eq!(result.url, "https://my-bucket.s3.example.com/a.bin");
+    }
+
+    #[tokio::test]
+    async fn test_upload_stream_multipart() {
+        let (result, log) = upload(PART * 2 + 100, None).await;
+        assert_eq!(result.parts, 3);
+        let expected = ["create".to_string(), format!("part 1 {}", PART), format!("part 2 {}", PART), "part 3 100".to_string(), "complete".to_string()];
+        assert_eq!(log, expected);
+
+        // 正好两个分块，不会多出一个空分块
+        let (result, log) = upload(PART * 2, Some(PART as u64 * 2)).await;
+        assert_eq!(result.parts, 2);
+        assert_eq!(log, ["create".to_string(), format!("part 1 {}", PART), format!("part 2 {}", PART), "complete".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_upload_stream_aborts_on_failure() {
+        let (client, log) = upload_client(Some("2")).await;
+        let data = vec![7u8; PART * 3];
+        let err = client.upload_stream(&data[..], "a.bin", None, &UploadOptions::default()).await.unwrap_err();
+        assert_eq!(err.service_error().and_then(|e| e.code()), Some("InvalidPart"));
+        assert_eq!(log.lock().unwrap().last().map(String::as_str), Some("abort"));
+
+        // 完成分块上传失败时同样中止
+        let (client, log) = upload_client(Some("complete")).await;
+        let err = client.upload_stream(&data[..], "a.bin", None, &UploadOptions::default()).await.unwrap_err();
+        assert_eq!(err.service_error().and_then(|e| e.code()), Some("InvalidPart"));
+        assert_eq!(log.lock().unwrap()[4..], ["complete".to_string(), "abort".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_upload_options_reach_requests() {
+        use aws_sdk_s3::types::ServerSideEncryption;
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let client = mock_client(move |req| {
+            let query = query(req);
+            let op = match req.method() {
+                "POST" if query == "uploads" => "create",
+                "PUT" if !query.contains("partNumber") => "put",
+                _ => return response(200, &[("ETag", "\"e\"")], "<CompleteMultipartUploadResult></CompleteMultipartUploadResult>"),
+            };
+            let names = [
+                "content-type",
+                "x-amz-meta-build",
+                "x-amz-tagging",
+                "x-amz-acl",
+                "x-amz-storage-class",
+                "x-amz-server-side-encryption",
+                "x-amz-server-side-encryption-aws-kms-key-id",
+            ];
+            let headers: Vec<_> = names.iter().map(|name| req.headers().get(*name).unwrap_or("-").to_string()).collect();
+            requests.lock().unwrap().push(format!("{} {}", op, headers.join(" ")));
+            response(200, &[("ETag", "\"e\"")], "<InitiateMultipartUploadResult><UploadId>u1</UploadId></InitiateMultipartUploadResult>")
+        })
+        .await;
+
+        let options = || {
+            UploadOptions::default()
+                .content_type("application/zip")
+                .metadata("Build", "42")
+                .tag("env", "prod")
+                .acl("public-read")
+                .storage_class("STANDARD_IA")
+                .sse(ServerSideEncryption::AwsKms, Some("k1".to_string()))
+        };
+        let expected = "application/zip 42 env=prod public-read STANDARD_IA aws:kms k1";
+        let dir = tempfile::tempdir().unwrap();
+        let small = dir.path().join("small.zip");
+        let large = dir.path().join("large.zip");
+        std::fs::write(&small, b"zip").unwrap();
+        std::fs::write(&large, vec![0u8; PART + 1]).unwrap();
+
+        client.upload_bytes_with_options("a.zip", b"zip", &options()).await.unwrap();
+        client.upload_bytes_with_options("a.zip", vec![0u8; PART + 1], &options()).await.unwrap();
+        client.upload_with_options(&small, "a.zip", &options()).await.unwrap();
+        client.upload_with_options(&large, "a.zip", &options()).await.unwrap();
+        // 分块大小覆盖客户端的设置，超过 5MB 的数据也用单次 PUT
+        let data = vec![0u8; PART + 1];
+        let result = client.upload_stream(&data[..], "a.zip", None, &options().part_size(PART as u64 * 2)).await;
+        assert_eq!(result.unwrap().parts, 0);
+        client.upload_bytes("a.zip", b"zip").await.unwrap();
+
+        let ops = ["put", "create", "put", "create", "put"].map(|op| format!("{} {}", op, expected));
+        let mut all = ops.to_vec();
+        all.push("put application/octet-stream - - - - - -".to_string());
+        assert_eq!(*log.lock().unwrap(), all);
+
+        let invalid = [
+            options().acl("everyone"),
+            options().workers(0),
+            options().part_size(1024),
+            UploadOptions::default().sse(ServerSideEncryption::Aes256, Some("k1".to_string())),
+        ];
+        for invalid in invalid {
+            let err = client.upload_bytes_with_options("a.zip", b"zip", &invalid).await.unwrap_err();
+            assert!(matches!(err, crate::OssError::Config(_)), "{:?}", err);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_stream() {
+        let client = mock_client(|req| match req.uri().split('?').next().and_then(|path| path.rsplit('/').next()) {
+            Some("a.txt") => response(
+                200,
+                &[("Content-Length", "5"), ("ETag", "\"e1\""), ("Content-Type", "text/plain"), ("x-amz-meta-owner", "ci")],
+                "hello",
+            ),
+            _ => response(404, &[], "<Error><Code>NoSuchKey</Code><Message>missing</Message></Error>"),
+        })
+        .await;
+
+        let (info, body) = client.download_stream("a.txt", &DownloadOptions::default()).await.unwrap();
+        assert_eq!((info.key.as_str(), info.size, info.etag.as_deref()), ("a.txt", 5, Some("\"e1\"")));
+        assert_eq!((info.content_type.as_deref(), info.storage_class.as_str()), (Some("text/plain"), "STANDARD"));
+        assert_eq!(info.metadata["owner"], "ci");
+        assert_eq!(body.collect().await.unwrap().into_bytes().as_ref(), b"hello");
+
+        let events = Mutex::new(Vec::new());
+        let observer = |event: ProgressEvent<'_>| events.lock().unwrap().push(format!("{:?}", event));
+        let mut buffer = Vec::new();
+        let options = DownloadOptions::default().progress(&observer);
+        assert_eq!(client.download_to_writer("a.txt", &mut buffer, &options).await.unwrap(), 5);
+        assert_eq!(buffer, b"hello");
+        assert_eq!(events.lock().unwrap().first().map(String::as_str), Some("Started { total: 5 }"));
+        assert_eq!(events.lock().unwrap().last().map(String::as_str), Some("Finished"));
+
+        // download() 基于 download_to_writer 写入文件
+        let dir = tempfile::tempdir().unwrap();
+        let path = client.download("a.txt", Some(&dir.path().join("a.txt"))).await.unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"hello");
+
+        let err = client.download_stream("missing.txt", &DownloadOptions::default()).await.err().unwrap();
+        assert!(matches!(&err, crate::OssError::NotFound { key } if key == "missing.txt"), "{:?}", err);
+    }
+}
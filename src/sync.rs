@@ -0,0 +1,902 @@
+//! 同步本地目录和远程前缀
+
+use aws_sdk_s3::primitives::DateTime;
+use futures::{StreamExt, TryStreamExt};
+use sha2::{Digest, Sha256};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::{directory_prefix, KeyFilter, ObjectInfo, OssClient};
+
+/// 元数据中保存内容 SHA-256 的 key，即 `x-amz-meta-content-sha256`
+pub const CONTENT_SHA256_METADATA: &str = "content-sha256";
+
+/// 判断文件是否变化的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum CompareMode {
+    /// 只比较大小，适合修改时间总是最新的 CI 产物
+    Size,
+    /// 比较大小和修改时间
+    #[default]
+    SizeAndMtime,
+    /// 比较本地 MD5 和单次上传对象的 ETag，分块上传的对象（ETag 含 `-`）只比较大小
+    Etag,
+    /// 比较本地 SHA-256 和对象元数据 `x-amz-meta-content-sha256`，没有该元数据的对象按 `Etag` 方式比较。
+    /// 需要对每个远程对象发一次 HEAD 获取元数据；用这种方式同步上传的对象会写入该元数据
+    Hash,
+}
+
+impl CompareMode {
+    /// 所有可选值
+    pub const ALL: [CompareMode; 4] = [CompareMode::Size, CompareMode::SizeAndMtime, CompareMode::Etag, CompareMode::Hash];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompareMode::Size => "size",
+            CompareMode::SizeAndMtime => "size-and-mtime",
+            CompareMode::Etag => "etag",
+            CompareMode::Hash => "hash",
+        }
+    }
+}
+
+impl std::fmt::Display for CompareMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for CompareMode {
+    type Err = OssError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == s)
+            .with_context(|| format!("无效的比较方式: {}（可选 size、size-and-mtime、etag、hash）", s))
+    }
+}
+
+/// 同步方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Up,
+    Down,
+}
+
+/// 同步选项
+#[derive(Debug, Clone, Default)]
+pub struct SyncOptions {
+    /// 只同步匹配的文件（glob，匹配相对路径），为空时同步全部
+    pub include: Vec<String>,
+    /// 排除匹配的文件（glob，匹配相对路径），被排除的文件两边都不会被改动
+    pub exclude: Vec<String>,
+    /// 判断文件是否变化的方式
+    pub compare: CompareMode,
+    /// 删除目标端多出的文件（上传时为远程对象，下载时为本地文件）
+    pub delete: bool,
+    /// 只计算需要做什么，不实际上传或删除
+    pub dry_run: bool,
+}
+
+/// 同步失败的文件
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncFailure {
+    pub key: String,
+    pub message: String,
+}
+
+/// 同步结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    /// 上传的 key 或下载到的本地路径（dry-run 时为将要传输的）
+    pub transferred: Vec<String>,
+    /// 没有变化而跳过的数量
+    pub skipped: usize,
+    /// 删除的远程 key 或本地路径（只有指定 delete 时才会删除）
+    pub deleted: Vec<String>,
+    pub errors: Vec<SyncFailure>,
+}
+
+impl SyncReport {
+    /// 是否全部成功
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// 记录一批传输结果
+    fn record(&mut self, results: Vec<(String, Result<()>)>) {
+        for (name, result) in results {
+            match result {
+                Ok(()) => self.transferred.push(name),
+                Err(e) => self.errors.push(SyncFailure { key: name, message: e.to_string() }),
+            }
+        }
+        self.transferred.sort();
+    }
+}
+
+/// 本地目录与远程前缀的差异，都是相对路径并且已排序
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DiffReport {
+    /// 只在本地存在
+    pub local_only: Vec<String>,
+    /// 只在远程存在
+    pub remote_only: Vec<String>,
+    /// 两边都存在但内容不同
+    pub changed: Vec<String>,
+}
+
+impl DiffReport {
+    pub fn is_identical(&self) -> bool {
+        self.local_only.is_empty() && self.remote_only.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// 同步的源：本地目录或远程前缀
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SyncSource {
+    Local(PathBuf),
+    Remote(String),
+}
+
+/// 同步的目标：本地目录或远程前缀，必须与源一个是本地、一个是远程
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SyncDest {
+    Local(PathBuf),
+    Remote(String),
+}
+
+/// 计划中的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SyncActionKind {
+    Upload,
+    Download,
+    /// 删除目标端的文件或对象
+    Delete,
+    Skip,
+}
+
+/// 为什么计划这个操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SyncReason {
+    /// 目标端没有
+    Missing,
+    /// 两边都有但按比较方式判断为不同
+    Changed,
+    /// 两边相同
+    Unchanged,
+    /// 只在目标端存在（指定 delete 时才会删除）
+    Extra,
+}
+
+/// 对一个文件的操作，`relative` 为以 `/` 分隔的相对路径
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SyncAction {
+    pub relative: String,
+    pub kind: SyncActionKind,
+    pub reason: SyncReason,
+}
+
+/// [`OssClient::plan`] 的结果，可以检查或删掉其中的操作后交给 [`OssClient::execute_plan`] 执行
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncPlan {
+    pub source: SyncSource,
+    pub dest: SyncDest,
+    /// 上传时按相对路径排序，下载时按对象的列出顺序；删除排在最后
+    pub actions: Vec<SyncAction>,
+    /// 创建计划时的比较方式；为 [`CompareMode::Hash`] 时上传的对象写入 `x-amz-meta-content-sha256`
+    pub compare: CompareMode,
+}
+
+impl SyncPlan {
+    /// 操作对应的本地路径
+    pub fn local_path(&self, action: &SyncAction) -> PathBuf {
+        let (_, root, _) = self.endpoints();
+        local_path(root, &action.relative)
+    }
+
+    /// 操作对应的远程 key
+    pub fn key(&self, action: &SyncAction) -> String {
+        let (_, _, prefix) = self.endpoints();
+        format!("{}{}", directory_prefix(prefix), action.relative)
+    }
+
+    /// 同步方向、本地目录和远程前缀；创建计划时已经检查过源和目标
+    fn endpoints(&self) -> (Direction, &Path, &str) {
+        match (&self.source, &self.dest) {
+            (SyncSource::Local(root), SyncDest::Remote(prefix)) => (Direction::Up, root, prefix),
+            (SyncSource::Remote(prefix), SyncDest::Local(root)) => (Direction::Down, root, prefix),
+            _ => unreachable!("同步的源和目标已经检查过"),
+        }
+    }
+
+    /// 报告中的名字：上传和删除远程对象时为 key，下载和删除本地文件时为本地路径
+    fn name(&self, action: &SyncAction) -> String {
+        let remote = match action.kind {
+            SyncActionKind::Delete => self.endpoints().0 == Direction::Up,
+            kind => kind != SyncActionKind::Download,
+        };
+        if remote {
+            self.key(action)
+        } else {
+            self.local_path(action).display().to_string()
+        }
+    }
+
+    /// 不执行时的报告：列出将要传输和删除的文件
+    pub fn dry_run_report(&self) -> SyncReport {
+        let mut report = SyncReport::default();
+        for action in &self.actions {
+            match action.kind {
+                SyncActionKind::Upload | SyncActionKind::Download => report.transferred.push(self.name(action)),
+                SyncActionKind::Delete => report.deleted.push(self.name(action)),
+                SyncActionKind::Skip => report.skipped += 1,
+            }
+        }
+        report
+    }
+}
+
+/// 本地文件
+#[derive(Debug)]
+pub(crate) struct LocalFile {
+    /// 相对于同步目录的路径，以 `/` 分隔
+    pub(crate) relative: String,
+    pub(crate) path: PathBuf,
+    pub(crate) size: u64,
+    pub(crate) modified: SystemTime,
+}
+
+impl OssClient {
+    /// 同步本地目录和远程前缀：先 [`plan`](Self::plan)，再 [`execute_plan`](Self::execute_plan)；
+    /// `options.dry_run` 时只返回将要做的事
+    pub async fn sync(&self, source: SyncSource, dest: SyncDest, options: &SyncOptions) -> Result<SyncReport> {
+        let plan = self.plan(source, dest, options).await?;
+        if options.dry_run {
+            return Ok(plan.dry_run_report());
+        }
+        self.execute_plan(&plan).await
+    }
+
+    /// 把本地目录同步到远程前缀：只上传新增或变化的文件，并发上传
+    /// 按 `options.compare` 判断文件是否变化
+    pub async fn sync_up(&self, local_dir: &Path, prefix: &str, options: &SyncOptions) -> Result<SyncReport> {
+        self.sync(SyncSource::Local(local_dir.to_path_buf()), SyncDest::Remote(prefix.to_string()), options).await
+    }
+
+    /// 把远程前缀同步到本地目录：只下载缺少或变化的对象，并发下载，按需创建目录
+    /// 大对象使用分段下载；本地文件和目录同名冲突时，不做任何改动直接返回错误
+    pub async fn sync_down(&self, prefix: &str, local_dir: &Path, options: &SyncOptions) -> Result<SyncReport> {
+        self.sync(SyncSource::Remote(prefix.to_string()), SyncDest::Local(local_dir.to_path_buf()), options).await
+    }
+
+    /// 比较两边的文件，计算需要做什么，不做任何改动；只使用 `options` 中的 include、exclude、compare 和 delete。
+    /// 下载时本地文件和目录同名冲突的对象直接返回错误
+    pub async fn plan(&self, source: SyncSource, dest: SyncDest, options: &SyncOptions) -> Result<SyncPlan> {
+        let (direction, root, prefix) = match (&source, &dest) {
+            (SyncSource::Local(root), SyncDest::Remote(prefix)) => (Direction::Up, root, prefix),
+            (SyncSource::Remote(prefix), SyncDest::Local(root)) => (Direction::Down, root, prefix),
+            _ => return Err(OssError::Config("同步的源和目标必须一个是本地目录、一个是远程前缀".to_string())),
+        };
+        let prefix = directory_prefix(prefix);
+        let filter = KeyFilter::new(&options.include, &options.exclude)?;
+        let local = if direction == Direction::Up || root.exists() {
+            walk_dir(root)?.into_iter().filter(|file| filter.matches(&file.relative)).collect()
+        } else {
+            Vec::new()
+        };
+        let remote = self.list_relative(&prefix, &filter, options.compare).await?;
+
+        if direction == Direction::Down {
+            let relatives: Vec<&str> = remote.iter().map(|(relative, _)| relative.as_str()).collect();
+            let conflicts = find_conflicts(root, &relatives);
+            if !conflicts.is_empty() {
+                bail!("以下对象无法同步到本地，未做任何改动:\n  {}", conflicts.join("\n  "));
+            }
+        }
+        let actions = plan_actions(direction, local, remote, options)?;
+        Ok(SyncPlan { source, dest, actions, compare: options.compare })
+    }
+
+    /// 执行计划中的操作：并发传输，再删除；单个文件失败不影响其余文件，记录在 [`SyncReport::errors`] 中
+    pub async fn execute_plan(&self, plan: &SyncPlan) -> Result<SyncReport> {
+        let mut report = SyncReport::default();
+        let mut transfers = Vec::new();
+        let mut deletes = Vec::new();
+        for action in &plan.actions {
+            match action.kind {
+                SyncActionKind::Upload | SyncActionKind::Download => transfers.push(action),
+                SyncActionKind::Delete => deletes.push(action),
+                SyncActionKind::Skip => report.skipped += 1,
+            }
+        }
+
+        let results: Vec<_> = futures::stream::iter(transfers)
+            .map(|action| async move {
+                let (path, key) = (plan.local_path(action), plan.key(action));
+                let result = match action.kind {
+                    SyncActionKind::Upload => async {
+                        let mut options = self.default_upload_options();
+                        if plan.compare == CompareMode::Hash {
+                            let file = path.clone();
+                            let sha256 = tokio::task::spawn_blocking(move || file_sha256(&file)).await??;
+                            options = options.metadata(CONTENT_SHA256_METADATA, sha256);
+                        }
+                        self.upload_with_options(&path, &key, &options).await.map(|_| ())
+                    }
+                    .await,
+                    _ => async {
+                        if let Some(parent) = path.parent() {
+                            tokio::fs::create_dir_all(parent)
+                                .await
+                                .with_context(|| format!("无法创建目录: {}", parent.display()))?;
+                        }
+                        self.download(&key, Some(&path)).await.map(|_| ())
+                    }
+                    .await,
+                };
+                (plan.name(action), result)
+            })
+            .buffer_unordered(self.workers)
+            .collect()
+            .await;
+        report.record(results);
+
+        match plan.endpoints().0 {
+            Direction::Up if !deletes.is_empty() => {
+                let keys: Vec<_> = deletes.iter().map(|action| plan.key(action)).collect();
+                let deleted = self.delete_many(&keys).await?;
+                report.deleted = deleted.deleted;
+                report.errors.extend(deleted.errors.into_iter().map(|f| SyncFailure {
+                    key: f.key,
+                    message: format!("删除失败: {} {}", f.code, f.message),
+                }));
+            }
+            Direction::Up => {}
+            Direction::Down => {
+                for action in deletes {
+                    let path = plan.local_path(action);
+                    let name = path.display().to_string();
+                    match std::fs::remove_file(&path) {
+                        Ok(()) => report.deleted.push(name),
+                        Err(e) => report.errors.push(SyncFailure { key: name, message: format!("删除失败: {}", e) }),
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// 比较本地目录和远程前缀，不做任何改动
+    /// 只使用 `options` 中的 include、exclude 和 compare，判断方式与 [`sync_up`](Self::sync_up) 相同
+    pub async fn diff(&self, local_dir: &Path, prefix: &str, options: &SyncOptions) -> Result<DiffReport> {
+        let options = SyncOptions { delete: true, ..options.clone() };
+        let plan = self
+            .plan(SyncSource::Local(local_dir.to_path_buf()), SyncDest::Remote(prefix.to_string()), &options)
+            .await?;
+        let mut report = DiffReport::default();
+        for action in plan.actions {
+            match (action.kind, action.reason) {
+                (SyncActionKind::Upload, SyncReason::Missing) => report.local_only.push(action.relative),
+                (SyncActionKind::Upload, _) => report.changed.push(action.relative),
+                (SyncActionKind::Delete, _) => report.remote_only.push(action.relative),
+                _ => {}
+            }
+        }
+        Ok(report)
+    }
+
+    /// 列出前缀下的对象，返回（相对路径，对象），跳过目录标记对象和被过滤掉的对象
+    /// 按哈希比较时逐个 HEAD 获取元数据
+    async fn list_relative(
+        &self,
+        prefix: &str,
+        filter: &KeyFilter,
+        compare: CompareMode,
+    ) -> Result<Vec<(String, ObjectInfo)>> {
+        let objects: Vec<_> = self
+            .list_objects(prefix)
+            .await?
+            .into_iter()
+            // 以 / 结尾的目录标记对象没有对应的本地文件，不参与比较
+            .filter(|obj| !obj.key.ends_with('/'))
+            .map(|obj| (obj.key[prefix.len()..].to_string(), obj))
+            .filter(|(relative, _)| filter.matches(relative))
+            .collect();
+        if compare != CompareMode::Hash {
+            return Ok(objects);
+        }
+
+        futures::stream::iter(objects)
+            .map(|(relative, obj)| async move { Ok::<_, OssError>((relative, self.head(&obj.key, None).await?)) })
+            .buffered(self.workers)
+            .try_collect()
+            .await
+    }
+}
+
+/// 按比较方式为两边的文件计划操作，不访问网络；`remote` 为（相对路径，对象）
+fn plan_actions(
+    direction: Direction,
+    local: Vec<LocalFile>,
+    remote: Vec<(String, ObjectInfo)>,
+    options: &SyncOptions,
+) -> Result<Vec<SyncAction>> {
+    let action = |relative: String, kind, reason| SyncAction { relative, kind, reason };
+    // 两边都有的文件需要比较，源端的文件按顺序处理，剩下的是目标端多出的
+    let compare = |file: &LocalFile, obj: &ObjectInfo, kind| {
+        Ok::<_, OssError>(if differs(options.compare, direction, file, obj)? {
+            (kind, SyncReason::Changed)
+        } else {
+            (SyncActionKind::Skip, SyncReason::Unchanged)
+        })
+    };
+    let mut actions = Vec::new();
+    let mut extra: Vec<String> = match direction {
+        Direction::Up => {
+            let mut remote: HashMap<String, ObjectInfo> = remote.into_iter().collect();
+            for file in local {
+                let (kind, reason) = match remote.remove(&file.relative) {
+                    None => (SyncActionKind::Upload, SyncReason::Missing),
+                    Some(obj) => compare(&file, &obj, SyncActionKind::Upload)?,
+                };
+                actions.push(action(file.relative, kind, reason));
+            }
+            remote.into_keys().collect()
+        }
+        Direction::Down => {
+            let mut local: HashMap<String, LocalFile> = local.into_iter().map(|file| (file.relative.clone(), file)).collect();
+            for (relative, obj) in remote {
+                let (kind, reason) = match local.remove(&relative) {
+                    None => (SyncActionKind::Download, SyncReason::Missing),
+                    Some(file) => compare(&file, &obj, SyncActionKind::Download)?,
+                };
+                actions.push(action(relative, kind, reason));
+            }
+            local.into_keys().collect()
+        }
+    };
+    if options.delete {
+        extra.sort();
+        actions.extend(extra.into_iter().map(|relative| action(relative, SyncActionKind::Delete, SyncReason::Extra)));
+    }
+    Ok(actions)
+}
+
+/// 相对路径（以 `/` 分隔）对应的本地路径
+fn local_path(root: &Path, relative: &str) -> PathBuf {
+    relative.split('/').fold(root.to_path_buf(), |path, segment| path.join(segment))
+}
+
+/// 找出无法写入本地的对象：key 无法安全映射为本地路径，或者与本地或远程已有的文件、目录同名冲突
+/// 例如远程同时有 `a` 和 `a/b`，或者本地 `a` 是文件但远程需要 `a/b`
+fn find_conflicts(root: &Path, relatives: &[&str]) -> Vec<String> {
+    let remote: HashSet<&str> = relatives.iter().copied().collect();
+    let mut conflicts = Vec::new();
+    for relative in relatives {
+        if relative.split('/').any(|s| s.is_empty() || s == "." || s == "..") {
+            conflicts.push(format!("{}: 无法映射为本地路径", relative));
+            continue;
+        }
+        let mut ancestors = relative.match_indices('/').map(|(i, _)| &relative[..i]);
+        if let Some(dir) = ancestors.find(|dir| remote.contains(dir)) {
+            conflicts.push(format!("{}: 远程 {} 既是对象又是目录", relative, dir));
+            continue;
+        }
+        let mut ancestors = relative.match_indices('/').map(|(i, _)| &relative[..i]);
+        if let Some(dir) = ancestors.find(|dir| {
+            let path = local_path(root, dir);
+            path.exists() && !path.is_dir()
+        }) {
+            conflicts.push(format!("{}: 本地 {} 是文件，但远程需要它是目录", relative, local_path(root, dir).display()));
+            continue;
+        }
+        if local_path(root, relative).is_dir() {
+            conflicts.push(format!("{}: 本地 {} 是目录，但远程是对象", relative, local_path(root, relative).display()));
+        }
+    }
+    conflicts
+}
+
+/// 递归列出目录下的所有文件（跟随符号链接），按相对路径排序
+pub(crate) fn walk_dir(root: &Path) -> Result<Vec<LocalFile>> {
+    walk_dir_with(root, true)
+}
+
+/// 同 [`walk_dir`]，`follow_links` 为 false 时跳过符号链接
+pub(crate) fn walk_dir_with(root: &Path, follow_links: bool) -> Result<Vec<LocalFile>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = std::fs::read_dir(&dir).with_context(|| format!("无法读取目录: {}", dir.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            if !follow_links && entry.file_type()?.is_symlink() {
+                continue;
+            }
+            let path = entry.path();
+            let metadata = std::fs::metadata(&path).with_context(|| format!("无法读取: {}", path.display()))?;
+            if metadata.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let relative = path
+                .strip_prefix(root)?
+                .components()
+                .map(|c| c.as_os_str().to_str())
+                .collect::<Option<Vec<_>>>()
+                .with_context(|| format!("文件名不是有效的 UTF-8: {}", path.display()))?
+                .join("/");
+            files.push(LocalFile {
+                relative,
+                size: metadata.len(),
+                modified: metadata.modified()?,
+                path,
+            });
+        }
+    }
+    files.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(files)
+}
+
+/// 按大小和修改时间判断本地文件是否变化：大小不同，或者本地修改时间晚于远程对象的上传时间
+/// 远程时间只精确到秒，所以按秒比较
+fn is_changed(size: u64, modified: SystemTime, remote: &ObjectInfo) -> bool {
+    if size != remote.size {
+        return true;
+    }
+    match &remote.last_modified {
+        Some(remote_time) => DateTime::from(modified).secs() > remote_time.secs(),
+        None => true,
+    }
+}
+
+/// 按大小和修改时间判断本地文件是否已过时：大小不同，或者远程对象的上传时间晚于本地修改时间
+fn is_stale(size: u64, modified: SystemTime, remote: &ObjectInfo) -> bool {
+    if size != remote.size {
+        return true;
+    }
+    match &remote.last_modified {
+        Some(remote_time) => remote_time.secs() > DateTime::from(modified).secs(),
+        None => true,
+    }
+}
+
+/// 按指定方式判断本地文件与远程对象是否不同，即是否需要传输
+fn differs(compare: CompareMode, direction: Direction, file: &LocalFile, remote: &ObjectInfo) -> Result<bool> {
+    match compare {
+        CompareMode::Size => Ok(file.size != remote.size),
+        CompareMode::SizeAndMtime => Ok(match direction {
+            Direction::Up => is_changed(file.size, file.modified, remote),
+            Direction::Down => is_stale(file.size, file.modified, remote),
+        }),
+        CompareMode::Etag => {
+            if file.size != remote.size {
+                return Ok(true);
+            }
+            match remote.etag.as_deref().map(|etag| etag.trim_matches('"')) {
+                Some(etag) if !etag.contains('-') => Ok(file_md5(&file.path)? != etag),
+                // 分块上传的 ETag 不是内容的 MD5，大小相同即认为没有变化
+                _ => Ok(false),
+            }
+        }
+        CompareMode::Hash => match remote.metadata.get(CONTENT_SHA256_METADATA) {
+            Some(sha256) => Ok(!file_sha256(&file.path)?.eq_ignore_ascii_case(sha256)),
+            None => differs(CompareMode::Etag, direction, file, remote),
+        },
+    }
+}
+
+/// 文件内容的 MD5（十六进制），即单次上传对象的 ETag
+pub(crate) fn file_md5(path: &Path) -> Result<String> {
+    let mut context = md5::Context::new();
+    read_chunks(path, |chunk| context.consume(chunk))?;
+    Ok(format!("{:x}", context.compute()))
+}
+
+/// 文件内容的 SHA-256（十六进制）
+fn file_sha256(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    read_chunks(path, |chunk| hasher.update(chunk))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 分块读取整个文件
+fn read_chunks(path: &Path, mut on_chunk: impl FnMut(&[u8])) -> Result<()> {
+    let mut file = std::fs::File::open(path).with_context(|| format!("无法打开: {}", path.display()))?;
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(());
+        }
+        on_chunk(&buffer[..n]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn remote(size: u64, secs: i64) -> ObjectInfo {
+        ObjectInfo {
+            key: "k".to_string(),
+            size,
+            etag: None,
+            storage_class: "STANDARD".to_string(),
+            last_modified: Some(DateTime::from_secs(secs)),
+            owner: None,
+            content_type: None,
+            sse: None,
+            metadata: HashMap::new(),
+            version_id: None,
+        }
+    }
+
+    #[test]
+    fn test_is_changed() {
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        assert!(!is_changed(10, at(100), &remote(10, 100)));
+        assert!(!is_changed(10, at(50), &remote(10, 100)));
+        assert!(is_changed(11, at(50), &remote(10, 100)));
+        assert!(is_changed(10, at(150), &remote(10, 100)));
+        // 同一秒内的亚秒差异不算变化
+        assert!(!is_changed(10, at(100) + Duration::from_millis(500), &remote(10, 100)));
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        assert!(!is_stale(10, at(100), &remote(10, 100)));
+        assert!(!is_stale(10, at(150), &remote(10, 100)));
+        assert!(is_stale(10, at(50), &remote(10, 100)));
+        assert!(is_stale(11, at(150), &remote(10, 100)));
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("file"), "").unwrap();
+        std::fs::create_dir(dir.path().join("dir")).unwrap();
+
+        assert!(find_conflicts(dir.path(), &["a.txt", "sub/b.txt", "dir/c.txt"]).is_empty());
+
+        let conflicts = find_conflicts(dir.path(), &["file/x", "dir", "a", "a/b", "../escape", "x//y"]);
+        assert_eq!(conflicts.len(), 5);
+        assert!(conflicts[0].starts_with("file/x: 本地"));
+        assert!(conflicts[1].starts_with("dir: 本地"));
+        assert!(conflicts[2].starts_with("a/b: 远程 a 既是对象又是目录"));
+        assert!(conflicts[3].starts_with("../escape"));
+        assert!(conflicts[4].starts_with("x//y"));
+    }
+
+    #[test]
+    fn test_walk_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::write(dir.path().join("top.txt"), "1").unwrap();
+        std::fs::write(dir.path().join("a/b/deep.txt"), "22").unwrap();
+
+        let files = walk_dir(dir.path()).unwrap();
+        let names: Vec<_> = files.iter().map(|f| (f.relative.as_str(), f.size)).collect();
+        assert_eq!(names, vec![("a/b/deep.txt", 2), ("top.txt", 1)]);
+    }
+
+    /// 测试用的本地文件和远程对象：内容为 hello，远程对象与本地大小相同、上传于本地修改之后
+    fn fixture(dir: &Path) -> (LocalFile, ObjectInfo) {
+        let path = dir.join("hello.txt");
+        std::fs::write(&path, "hello").unwrap();
+        let file = LocalFile {
+            relative: "hello.txt".to_string(),
+            path,
+            size: 5,
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(100),
+        };
+        (file, remote(5, 200))
+    }
+
+    #[test]
+    fn test_file_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, _) = fixture(dir.path());
+        assert_eq!(file_md5(&file.path).unwrap(), "5d41402abc4b2a76b9719d911017c592");
+        assert_eq!(
+            file_sha256(&file.path).unwrap(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+        );
+    }
+
+    #[test]
+    fn test_compare_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, mut obj) = fixture(dir.path());
+        obj.last_modified = Some(DateTime::from_secs(0));
+        assert!(!differs(CompareMode::Size, Direction::Up, &file, &obj).unwrap());
+        obj.size = 6;
+        assert!(differs(CompareMode::Size, Direction::Up, &file, &obj).unwrap());
+    }
+
+    #[test]
+    fn test_compare_size_and_mtime_depends_on_direction() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, obj) = fixture(dir.path());
+        // 远程比本地新：上传时不需要，下载时需要
+        assert!(!differs(CompareMode::SizeAndMtime, Direction::Up, &file, &obj).unwrap());
+        assert!(differs(CompareMode::SizeAndMtime, Direction::Down, &file, &obj).unwrap());
+    }
+
+    #[test]
+    fn test_compare_etag() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, mut obj) = fixture(dir.path());
+        obj.etag = Some("\"5d41402abc4b2a76b9719d911017c592\"".to_string());
+        assert!(!differs(CompareMode::Etag, Direction::Down, &file, &obj).unwrap());
+        obj.etag = Some("\"00000000000000000000000000000000\"".to_string());
+        assert!(differs(CompareMode::Etag, Direction::Down, &file, &obj).unwrap());
+
+        // 分块上传的 ETag 只比较大小
+        obj.etag = Some("\"00000000000000000000000000000000-3\"".to_string());
+        assert!(!differs(CompareMode::Etag, Direction::Down, &file, &obj).unwrap());
+        obj.size = 6;
+        assert!(differs(CompareMode::Etag, Direction::Down, &file, &obj).unwrap());
+    }
+
+    #[test]
+    fn test_compare_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let (file, mut obj) = fixture(dir.path());
+        obj.metadata.insert(
+            CONTENT_SHA256_METADATA.to_string(),
+            "2CF24DBA5FB0A30E26E83B2AC5B9E29E1B161E5C1FA7425E73043362938B9824".to_string(),
+        );
+        // 有 SHA-256 元数据时不看 ETag
+        obj.etag = Some("\"00000000000000000000000000000000\"".to_string());
+        assert!(!differs(CompareMode::Hash, Direction::Up, &file, &obj).unwrap());
+        obj.metadata.insert(CONTENT_SHA256_METADATA.to_string(), "00".to_string());
+        assert!(differs(CompareMode::Hash, Direction::Up, &file, &obj).unwrap());
+
+        // 没有元数据时按 ETag 比较
+        obj.metadata.clear();
+        obj.etag = Some("\"5d41402abc4b2a76b9719d911017c592\"".to_string());
+        assert!(!differs(CompareMode::Hash, Direction::Up, &file, &obj).unwrap());
+    }
+
+    /// 测试用的本地目录：new.txt、same.txt、changed.txt，以及对应的远程列表：same.txt、changed.txt、extra.txt
+    fn plan_fixture() -> (tempfile::TempDir, Vec<(String, ObjectInfo)>) {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in [("new.txt", "n"), ("same.txt", "hello"), ("sub/changed.txt", "changed")] {
+            let path = local_path(dir.path(), name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let listing = [("same.txt", 5), ("sub/changed.txt", 3), ("extra.txt", 1)]
+            .into_iter()
+            .map(|(relative, size)| (relative.to_string(), ObjectInfo { key: format!("p/{}", relative), ..remote(size, 0) }))
+            .collect();
+        (dir, listing)
+    }
+
+    fn summary(actions: &[SyncAction]) -> Vec<(&str, SyncActionKind, SyncReason)> {
+        actions.iter().map(|action| (action.relative.as_str(), action.kind, action.reason)).collect()
+    }
+
+    #[test]
+    fn test_plan_actions_up() {
+        let (dir, listing) = plan_fixture();
+        let options = SyncOptions { compare: CompareMode::Size, delete: true, ..Default::default() };
+        let actions = plan_actions(Direction::Up, walk_dir(dir.path()).unwrap(), listing.clone(), &options).unwrap();
+        assert_eq!(
+            summary(&actions),
+            [
+                ("new.txt", SyncActionKind::Upload, SyncReason::Missing),
+                ("same.txt", SyncActionKind::Skip, SyncReason::Unchanged),
+                ("sub/changed.txt", SyncActionKind::Upload, SyncReason::Changed),
+                ("extra.txt", SyncActionKind::Delete, SyncReason::Extra),
+            ]
+        );
+
+        // 不指定 delete 时不计划删除
+        let options = SyncOptions { compare: CompareMode::Size, ..Default::default() };
+        let actions = plan_actions(Direction::Up, walk_dir(dir.path()).unwrap(), listing, &options).unwrap();
+        assert!(actions.iter().all(|action| action.kind != SyncActionKind::Delete));
+    }
+
+    #[test]
+    fn test_plan_actions_down() {
+        let (dir, listing) = plan_fixture();
+        let options = SyncOptions { compare: CompareMode::Size, delete: true, ..Default::default() };
+        let actions = plan_actions(Direction::Down, walk_dir(dir.path()).unwrap(), listing, &options).unwrap();
+        assert_eq!(
+            summary(&actions),
+            [
+                ("same.txt", SyncActionKind::Skip, SyncReason::Unchanged),
+                ("sub/changed.txt", SyncActionKind::Download, SyncReason::Changed),
+                ("extra.txt", SyncActionKind::Download, SyncReason::Missing),
+                ("new.txt", SyncActionKind::Delete, SyncReason::Extra),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_filtered_plan() {
+        use crate::mock::{mock_client, response};
+        use std::sync::{Arc, Mutex};
+
+        let (dir, listing) = plan_fixture();
+        let options = SyncOptions { compare: CompareMode::Size, delete: true, ..Default::default() };
+        let mut plan = SyncPlan {
+            source: SyncSource::Local(dir.path().to_path_buf()),
+            dest: SyncDest::Remote("p".to_string()),
+            actions: plan_actions(Direction::Up, walk_dir(dir.path()).unwrap(), listing, &options).unwrap(),
+            compare: options.compare,
+        };
+        let report = plan.dry_run_report();
+        assert_eq!(report.transferred, ["p/new.txt", "p/sub/changed.txt"]);
+        assert_eq!((report.deleted, report.skipped), (vec!["p/extra.txt".to_string()], 1));
+
+        // 调用方去掉删除后再执行
+        plan.actions.retain(|action| action.kind != SyncActionKind::Delete);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let client = mock_client(move |req| {
+            log.lock().unwrap().push(format!("{} {}", req.method(), req.uri().rsplit_once(".com/").unwrap().1));
+            response(200, &[("ETag", "\"e\"")], "")
+        })
+        .await;
+        let report = client.execute_plan(&plan).await.unwrap();
+        assert_eq!(report.transferred, ["p/new.txt", "p/sub/changed.txt"]);
+        assert!(report.deleted.is_empty() && report.is_success());
+        let mut requests = requests.lock().unwrap().clone();
+        requests.sort();
+        assert_eq!(requests, ["PUT p/new.txt?x-id=PutObject", "PUT p/sub/changed.txt?x-id=PutObject"]);
+
+        let err = client
+            .plan(SyncSource::Remote("a".to_string()), SyncDest::Remote("b".to_string()), &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OssError::Config(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_hash_sync_writes_sha256_metadata() {
+        use crate::mock::{mock_client, response};
+        use std::sync::{Arc, Mutex};
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "hello").unwrap();
+        let headers = Arc::new(Mutex::new(Vec::new()));
+        let log = headers.clone();
+        let client = mock_client(move |req| match req.method() {
+            "PUT" => {
+                log.lock().unwrap().extend(req.headers().get("x-amz-meta-content-sha256").map(str::to_string));
+                response(200, &[("ETag", "\"e\"")], "")
+            }
+            _ => response(200, &[], "<ListBucketResult><IsTruncated>false</IsTruncated></ListBucketResult>"),
+        })
+        .await;
+
+        let options = SyncOptions { compare: CompareMode::Hash, ..Default::default() };
+        let report = client.sync_up(dir.path(), "p", &options).await.unwrap();
+        assert_eq!(report.transferred, ["p/a.txt"]);
+        // 下次按哈希比较时与本地文件相同
+        assert_eq!(
+            *headers.lock().unwrap(),
+            ["2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"]
+        );
+    }
+
+    #[test]
+    fn test_parse_compare_mode() {
+        for mode in CompareMode::ALL {
+            assert_eq!(mode.as_str().parse::<CompareMode>().unwrap(), mode);
+        }
+        assert_eq!(CompareMode::default(), CompareMode::SizeAndMtime);
+        assert!("md5".parse::<CompareMode>().is_err());
+    }
+}
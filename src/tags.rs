@@ -0,0 +1,86 @@
+//! 对象标签（GetObjectTagging / PutObjectTagging / DeleteObjectTagging）
+
+use aws_sdk_s3::types::{Tag, Tagging};
+use std::collections::BTreeMap;
+
+use crate::error::{bail, Context, OssError, Result};
+use crate::{is_not_found, OssClient};
+
+/// 解析 `key=value` 形式的标签，value 可以为空
+pub fn parse_tag(s: &str) -> Result<(String, String)> {
+    let (key, value) = s.split_once('=').with_context(|| format!("无效的标签 {}，格式应为 key=value", s))?;
+    if key.is_empty() {
+        bail!("无效的标签 {}，key 不能为空", s);
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+impl OssClient {
+    /// 查询对象的标签，`version_id` 为 None 时查询当前版本
+    pub async fn get_tags(&self, key: &str, version_id: Option<&str>) -> Result<BTreeMap<String, String>> {
+        let resp = match self.client
+            .get_object_tagging()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .set_version_id(version_id.map(str::to_string))
+            .send()
+            .await
+        {
+            Ok(resp) => resp,
+            Err(err) if is_not_found(&err) => return Err(OssError::not_found(key)),
+            Err(err) => return Err(err).with_context(|| format!("无法查询 {} 的标签", key)),
+        };
+        Ok(resp.tag_set().iter().map(|tag| (tag.key().to_string(), tag.value().to_string())).collect())
+    }
+
+    /// 用 `tags` 替换对象的全部标签
+    pub async fn set_tags(&self, key: &str, version_id: Option<&str>, tags: &BTreeMap<String, String>) -> Result<()> {
+        let tag_set = tags
+            .iter()
+            .map(|(key, value)| Tag::builder().key(key).value(value).build())
+            .collect::<Result<Vec<_>, _>>()?;
+        match self.client
+            .put_object_tagging()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .set_version_id(version_id.map(str::to_string))
+            .tagging(Tagging::builder().set_tag_set(Some(tag_set)).build()?)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if is_not_found(&err) => Err(OssError::not_found(key)),
+            Err(err) => Err(err).with_context(|| format!("无法设置 {} 的标签", key)),
+        }
+    }
+
+    /// 删除对象的全部标签
+    pub async fn delete_tags(&self, key: &str, version_id: Option<&str>) -> Result<()> {
+        match self.client
+            .delete_object_tagging()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .set_version_id(version_id.map(str::to_string))
+            .send()
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if is_not_found(&err) => Err(OssError::not_found(key)),
+            Err(err) => Err(err).with_context(|| format!("无法删除 {} 的标签", key)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tag() {
+        assert_eq!(parse_tag("env=prod").unwrap(), ("env".to_string(), "prod".to_string()));
+        assert_eq!(parse_tag("expr=a=b").unwrap(), ("expr".to_string(), "a=b".to_string()));
+        assert_eq!(parse_tag("empty=").unwrap(), ("empty".to_string(), String::new()));
+        assert!(parse_tag("novalue").is_err());
+        assert!(parse_tag("=x").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! 服务端限流（503 SlowDown / 429）处理：识别限流响应、计算等待时间、临时降低并发
+
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::primitives::{DateTime, DateTimeFormat};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::retry::{ErrorResponse, RetryPolicy};
+
+/// 被限流时的最大重试次数
+pub(crate) const MAX_THROTTLE_RETRIES: u32 = 8;
+/// 退避基础时长
+const BACKOFF_BASE: Duration = Duration::from_millis(200);
+/// 退避最长时长
+const BACKOFF_MAX: Duration = Duration::from_secs(20);
+/// 恢复一个并发名额需要的连续成功次数
+const RECOVER_AFTER_SUCCESSES: usize = 4;
+
+/// 判断是否为限流错误或 `policy` 认为可重试的错误，是则返回需要等待的时长
+/// 优先使用 Retry-After 头，没有时使用 `policy` 的退避时长（默认为带随机抖动的指数退避）
+pub(crate) fn throttle_delay<E: ProvideErrorMetadata>(
+    err: &SdkError<E, HttpResponse>,
+    attempt: u32,
+    policy: &RetryPolicy,
+) -> Option<Duration> {
+    let raw = err.raw_response()?;
+    let status = raw.status().as_u16();
+    let code = err.code();
+    let throttled = status == 503
+        || status == 429
+        || matches!(code.unwrap_or_default(), "SlowDown" | "Throttling" | "ThrottlingException" | "TooManyRequests");
+    if !throttled && !policy.is_retryable(&ErrorResponse { status, code }) {
+        return None;
+    }
+
+    let retry_after = raw.headers()
+        .get("retry-after")
+        .and_then(|v| parse_retry_after(v, SystemTime::now()));
+    Some(retry_after.unwrap_or_else(|| policy.delay(attempt)))
+}
+
+/// 解析 Retry-After：秒数（`120`）或 HTTP 日期（`Wed, 21 Oct 2015 07:28:00 GMT`），
+/// 日期已经过去时不等待
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::from_str(value, DateTimeFormat::HttpDate).ok()?;
+    let date = SystemTime::try_from(date).ok()?;
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+/// 指数退避：base * 2^attempt，上限 BACKOFF_MAX，再加上最多 50% 的随机抖动
+pub(crate) fn backoff_delay(attempt: u32) -> Duration {
+    let delay = BACKOFF_BASE
+        .saturating_mul(1u32 << attempt.min(16))
+        .min(BACKOFF_MAX);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    let jitter = delay.mul_f64((nanos % 1000) as f64 / 2000.0);
+    delay + jitter
+}
+
+/// 可临时收缩的并发限制
+/// 被限流时回收一个名额（至少保留 1 个），连续成功若干次后再逐个归还
+pub(crate) struct AdaptiveLimit {
+    semaphore: Semaphore,
+    reduced: AtomicUsize,
+    max: usize,
+    successes: AtomicUsize,
+}
+
+impl AdaptiveLimit {
+    pub(crate) fn new(max: usize) -> Self {
+        let max = max.max(1);
+        Self {
+            semaphore: Semaphore::new(max),
+            reduced: AtomicUsize::new(0),
+            max,
+            successes: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) async fn acquire(&self) -> SemaphorePermit<'_> {
+        // semaphore 不会被关闭
+        self.semaphore.acquire().await.expect("semaphore closed")
+    }
+
+    /// 当前有效并发数
+    pub(crate) fn effective(&self) -> usize {
+        self.max - self.reduced.load(Ordering::SeqCst)
+    }
+
+    /// 被限流：降低一个并发名额
+    pub(crate) fn on_throttle(&self, permit: SemaphorePermit<'_>) {
+        self.successes.store(0, Ordering::SeqCst);
+        let reduced = self.reduced.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |r| {
+            (r + 1 < self.max).then_some(r + 1)
+        });
+        if reduced.is_ok() {
+            permit.forget();
+        }
+    }
+
+    /// 请求成功：累计到一定次数后归还一个并发名额
+    pub(crate) fn on_success(&self) {
+        if self.reduced.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        if self.successes.fetch_add(1, Ordering::SeqCst) + 1 >= RECOVER_AFTER_SUCCESSES {
+            self.successes.store(0, Ordering::SeqCst);
+            if self.reduced.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |r| r.checked_sub(1)).is_ok() {
+                self.semaphore.add_permits(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = UNIX_EPOCH + Duration::from_secs(1445412480);
+        assert_eq!(parse_retry_after(" 120 ", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now), Some(Duration::from_secs(30)));
+        // 已经过去的日期不等待
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-1", now), None);
+    }
+
+    #[test]
+    fn test_backoff_delay_grows_and_caps() {
+        assert!(backoff_delay(0) >= BACKOFF_BASE);
+        assert!(backoff_delay(0) < BACKOFF_BASE * 2);
+        assert!(backoff_delay(3) >= BACKOFF_BASE * 8);
+        assert!(backoff_delay(30) >= BACKOFF_MAX);
+        assert!(backoff_delay(30) <= BACKOFF_MAX.mul_f64(1.5));
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_limit_shrinks_and_recovers() {
+        let limit = AdaptiveLimit::new(2);
+        assert_eq!(limit.effective(), 2);
+
+        limit.on_throttle(limit.acquire().await);
+        assert_eq!(limit.effective(), 1);
+
+        // 至少保留一个并发
+        limit.on_throttle(limit.acquire().await);
+        assert_eq!(limit.effective(), 1);
+
+        for _ in 0..RECOVER_AFTER_SUCCESSES {
+            limit.on_success();
+        }
+        assert_eq!(limit.effective(), 2);
+        assert_eq!(limit.semaphore.available_permits(), 2);
+    }
+}
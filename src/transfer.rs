@@ -0,0 +1,495 @@
+//! 上传/下载的选项：进度事件由调用方决定如何展示（命令行进度条、GUI 等），传输可以中途取消
+
+use aws_sdk_s3::operation::create_multipart_upload::builders::CreateMultipartUploadFluentBuilder;
+use aws_sdk_s3::operation::get_object::builders::GetObjectFluentBuilder;
+use aws_sdk_s3::operation::head_object::builders::HeadObjectFluentBuilder;
+use aws_sdk_s3::operation::put_object::builders::PutObjectFluentBuilder;
+use aws_sdk_s3::types::{ObjectCannedAcl, RequestPayer, ServerSideEncryption, StorageClass};
+use base64::Engine;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::builder::check_part_size;
+use crate::copy::encode_tagging;
+use crate::{ObjectInfo, OssClient, OssError, Result, CANNED_ACLS};
+
+/// 上传/下载的选项
+pub struct TransferOptions<'a> {
+    /// 接收进度事件
+    pub progress: &'a dyn ProgressObserver,
+    /// 取消后传输尽快停止并返回 [`OssError::Cancelled`]
+    pub cancel: Option<CancellationToken>,
+}
+
+impl Default for TransferOptions<'_> {
+    fn default() -> Self {
+        Self { progress: &NoProgress, cancel: None }
+    }
+}
+
+impl TransferOptions<'_> {
+    /// 已经取消时返回 [`OssError::Cancelled`]
+    pub(crate) fn check_cancelled(&self) -> Result<()> {
+        match &self.cancel {
+            Some(token) if token.is_cancelled() => Err(OssError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// 执行 `future`，取消时不再等待，直接返回 [`OssError::Cancelled`]
+    pub(crate) async fn until_cancelled<T, E: Into<OssError>>(
+        &self,
+        future: impl Future<Output = Result<T, E>>,
+    ) -> Result<T> {
+        match &self.cancel {
+            Some(token) => tokio::select! {
+                biased;
+                _ = token.cancelled() => Err(OssError::Cancelled),
+                result = future => result.map_err(Into::into),
+            },
+            None => future.await.map_err(Into::into),
+        }
+    }
+}
+
+/// 在 `timeout` 内执行 `future`，超时时丢弃它（其中正在进行的请求随之停止），返回 [`OssError::TimedOut`]
+pub(crate) async fn with_timeout<T>(timeout: Option<Duration>, future: impl Future<Output = Result<T>>) -> Result<T> {
+    // 整个传输的 future 很大，放到堆上，避免外层的 future 再复制一份撑满栈
+    let future = Box::pin(future);
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, future).await.unwrap_or(Err(OssError::TimedOut { timeout })),
+        None => future.await,
+    }
+}
+
+/// 上传的选项：对象的属性、本次上传的分块参数，以及进度和取消（[`TransferOptions`]）
+/// 未指定的字段使用服务端或客户端的默认值，可以直接构造或用同名的方法链式设置：
+/// `UploadOptions::default().content_type("image/png").tag("env", "prod")`
+#[derive(Default)]
+pub struct UploadOptions<'a> {
+    pub transfer: TransferOptions<'a>,
+    pub content_type: Option<String>,
+    /// 自定义元数据（x-amz-meta-*）
+    pub metadata: HashMap<String, String>,
+    pub tags: BTreeMap<String, String>,
+    /// 预设 ACL，如 `public-read`
+    pub acl: Option<String>,
+    /// 如 STANDARD_IA，默认由服务端决定
+    pub storage_class: Option<String>,
+    /// 服务端加密，未指定时使用 bucket 的默认加密
+    pub sse: Option<ServerSideEncryption>,
+    /// 只能与 aws:kms 一起使用
+    pub kms_key_id: Option<String>,
+    /// 覆盖客户端的分块大小，范围 5MB 到 5GB
+    pub part_size: Option<u64>,
+    /// 覆盖客户端的分块并发数
+    pub workers: Option<usize>,
+    /// 整个上传（包括重试和等待并发名额）的时间上限，默认不限制。超时后停止所有分块请求，
+    /// 中止这次上传创建的分块上传，返回 [`OssError::TimedOut`]；[`OssClient::resume_upload`] 超时时不中止
+    pub timeout: Option<Duration>,
+}
+
+impl<'a> UploadOptions<'a> {
+    pub fn progress(mut self, observer: &'a dyn ProgressObserver) -> Self {
+        self.transfer.progress = observer;
+        self
+    }
+
+    pub fn cancel(mut self, token: CancellationToken) -> Self {
+        self.transfer.cancel = Some(token);
+        self
+    }
+
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// 添加一项元数据，key 统一转为小写（S3 返回的元数据 key 都是小写）
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into().to_ascii_lowercase(), value.into());
+        self
+    }
+
+    /// 添加一个标签
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn acl(mut self, acl: impl Into<String>) -> Self {
+        self.acl = Some(acl.into());
+        self
+    }
+
+    pub fn storage_class(mut self, class: impl Into<String>) -> Self {
+        self.storage_class = Some(class.into());
+        self
+    }
+
+    /// 服务端加密，`kms_key_id` 只能与 aws:kms 一起使用
+    pub fn sse(mut self, sse: ServerSideEncryption, kms_key_id: Option<String>) -> Self {
+        self.sse = Some(sse);
+        self.kms_key_id = kms_key_id;
+        self
+    }
+
+    pub fn part_size(mut self, bytes: u64) -> Self {
+        self.part_size = Some(bytes);
+        self
+    }
+
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 复制对象属性、分块参数和超时，进度改为发给 `progress`
+    pub(crate) fn with_progress<'b>(&self, progress: &'b dyn ProgressObserver) -> UploadOptions<'b> {
+        UploadOptions {
+            transfer: TransferOptions { progress, cancel: self.transfer.cancel.clone() },
+            content_type: self.content_type.clone(),
+            metadata: self.metadata.clone(),
+            tags: self.tags.clone(),
+            acl: self.acl.clone(),
+            storage_class: self.storage_class.clone(),
+            sse: self.sse.clone(),
+            kms_key_id: self.kms_key_id.clone(),
+            part_size: self.part_size,
+            workers: self.workers,
+            timeout: self.timeout,
+        }
+    }
+
+    /// 检查选项，返回按 `part_size` 和 `workers` 调整后的客户端
+    pub(crate) fn client(&self, client: &OssClient) -> Result<OssClient> {
+        if let Some(acl) = &self.acl {
+            if !CANNED_ACLS.contains(&acl.as_str()) {
+                return Err(OssError::Config(format!("无效的 ACL: {}（可选 {}）", acl, CANNED_ACLS.join("、"))));
+            }
+        }
+        if self.kms_key_id.is_some() && self.sse != Some(ServerSideEncryption::AwsKms) {
+            return Err(OssError::Config("kms_key_id 只能与 aws:kms 一起使用".to_string()));
+        }
+        override_client(client, self.part_size, self.workers)
+    }
+
+    /// 设置单次 PUT 的对象属性
+    pub(crate) fn apply_put(&self, request: PutObjectFluentBuilder) -> PutObjectFluentBuilder {
+        request
+            .set_content_type(self.content_type.clone())
+            .set_metadata(self.metadata_header())
+            .set_tagging(self.tagging_header())
+            .set_acl(self.acl.as_deref().map(ObjectCannedAcl::from))
+            .set_storage_class(self.storage_class.as_deref().map(StorageClass::from))
+            .set_server_side_encryption(self.sse.clone())
+            .set_ssekms_key_id(self.kms_key_id.clone())
+    }
+
+    /// 设置分块上传的对象属性，与 [`apply_put`](Self::apply_put) 相同
+    pub(crate) fn apply_create(&self, request: CreateMultipartUploadFluentBuilder) -> CreateMultipartUploadFluentBuilder {
+        request
+            .set_content_type(self.content_type.clone())
+            .set_metadata(self.metadata_header())
+            .set_tagging(self.tagging_header())
+            .set_acl(self.acl.as_deref().map(ObjectCannedAcl::from))
+            .set_storage_class(self.storage_class.as_deref().map(StorageClass::from))
+            .set_server_side_encryption(self.sse.clone())
+            .set_ssekms_key_id(self.kms_key_id.clone())
+    }
+
+    fn metadata_header(&self) -> Option<HashMap<String, String>> {
+        (!self.metadata.is_empty()).then(|| self.metadata.clone())
+    }
+
+    fn tagging_header(&self) -> Option<String> {
+        (!self.tags.is_empty()).then(|| encode_tagging(&self.tags))
+    }
+}
+
+impl<'a> From<TransferOptions<'a>> for UploadOptions<'a> {
+    fn from(transfer: TransferOptions<'a>) -> Self {
+        Self { transfer, ..Default::default() }
+    }
+}
+
+/// 目标已经存在时的处理方式：下载时为本地文件，[`OssClient::upload_dir`] 时为远程对象
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overwrite {
+    /// 直接覆盖
+    #[default]
+    Always,
+    /// 下载时返回 [`OssError::LocalIo`]（AlreadyExists），不发出下载请求；upload_dir 时跳过这个文件
+    Never,
+}
+
+/// 下载完成后的校验方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VerifyMode {
+    #[default]
+    None,
+    /// 文件大小与 HEAD 一致
+    Size,
+    /// 大小一致，且 ETag 为 MD5 时（单次上传的对象）与文件的 MD5 一致；分块上传的对象只校验大小
+    Md5,
+}
+
+/// 下载的选项：对象的版本和范围、本地文件的处理方式，以及进度和取消（[`TransferOptions`]）
+/// 默认值与 [`OssClient::download`] 相同：下载最新版本的完整对象，覆盖已有文件，不续传也不校验
+#[derive(Default)]
+pub struct DownloadOptions<'a> {
+    pub transfer: TransferOptions<'a>,
+    pub version_id: Option<String>,
+    /// 只下载 `(起始字节, 结束字节)`，结束为 None 时到对象末尾，两端都包含；不能与续传或校验一起使用
+    pub range: Option<(u64, Option<u64>)>,
+    pub overwrite: Overwrite,
+    /// 断点续传，见 [`OssClient::download_resume`]；不适用于 download_stream / download_to_writer
+    pub resume: bool,
+    /// 只在下载到文件时生效
+    pub verify: VerifyMode,
+    /// SSE-C 加密对象的 AES-256 密钥
+    pub sse_customer_key: Option<[u8; 32]>,
+    /// 请求者付费的 bucket 需要设置，流量费用由调用方承担
+    pub request_payer: bool,
+    /// 覆盖客户端的分块大小，超过分块大小的对象使用并发的分段下载
+    pub part_size: Option<u64>,
+    /// 覆盖客户端的分段下载并发数
+    pub workers: Option<usize>,
+    /// 整个下载（包括重试和校验）的时间上限，默认不限制。超时后停止所有分段请求，
+    /// 删除未下载完的文件（续传时保留），返回 [`OssError::TimedOut`]；download_stream 不使用
+    pub timeout: Option<Duration>,
+    /// 调用方已经 HEAD 得到的对象信息（版本等须与本选项一致），设置后判断是否分段下载时不再 HEAD
+    pub head: Option<ObjectInfo>,
+}
+
+impl<'a> DownloadOptions<'a> {
+    pub fn progress(mut self, observer: &'a dyn ProgressObserver) -> Self {
+        self.transfer.progress = observer;
+        self
+    }
+
+    pub fn cancel(mut self, token: CancellationToken) -> Self {
+        self.transfer.cancel = Some(token);
+        self
+    }
+
+    pub fn version_id(mut self, version_id: impl Into<String>) -> Self {
+        self.version_id = Some(version_id.into());
+        self
+    }
+
+    pub fn range(mut self, start: u64, end: Option<u64>) -> Self {
+        self.range = Some((start, end));
+        self
+    }
+
+    pub fn overwrite(mut self, overwrite: Overwrite) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    pub fn verify(mut self, verify: VerifyMode) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    pub fn sse_customer_key(mut self, key: [u8; 32]) -> Self {
+        self.sse_customer_key = Some(key);
+        self
+    }
+
+    pub fn request_payer(mut self, request_payer: bool) -> Self {
+        self.request_payer = request_payer;
+        self
+    }
+
+    pub fn part_size(mut self, bytes: u64) -> Self {
+        self.part_size = Some(bytes);
+        self
+    }
+
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn head(mut self, info: ObjectInfo) -> Self {
+        self.head = Some(info);
+        self
+    }
+
+    /// 检查选项，返回按 `part_size` 和 `workers` 调整后的客户端
+    pub(crate) fn client(&self, client: &OssClient) -> Result<OssClient> {
+        if let Some((start, Some(end))) = self.range {
+            if end < start {
+                return Err(OssError::Config(format!("无效的范围: {}-{}", start, end)));
+            }
+        }
+        if self.range.is_some() && (self.resume || self.verify != VerifyMode::None) {
+            return Err(OssError::Config("指定范围时不能续传或校验".to_string()));
+        }
+        override_client(client, self.part_size, self.workers)
+    }
+
+    /// Range 头
+    pub(crate) fn range_header(&self) -> Option<String> {
+        self.range.map(|(start, end)| match end {
+            Some(end) => format!("bytes={}-{}", start, end),
+            None => format!("bytes={}-", start),
+        })
+    }
+
+    /// 设置 GET 的版本、SSE-C 和请求者付费，Range 由调用方设置
+    pub(crate) fn apply_get(&self, request: GetObjectFluentBuilder) -> GetObjectFluentBuilder {
+        let (algorithm, key, key_md5) = self.sse_customer_headers();
+        request
+            .set_version_id(self.version_id.clone())
+            .set_sse_customer_algorithm(algorithm)
+            .set_sse_customer_key(key)
+            .set_sse_customer_key_md5(key_md5)
+            .set_request_payer(self.request_payer.then_some(RequestPayer::Requester))
+    }
+
+    /// 设置 HEAD 的版本、SSE-C 和请求者付费，与 [`apply_get`](Self::apply_get) 相同
+    pub(crate) fn apply_head(&self, request: HeadObjectFluentBuilder) -> HeadObjectFluentBuilder {
+        let (algorithm, key, key_md5) = self.sse_customer_headers();
+        request
+            .set_version_id(self.version_id.clone())
+            .set_sse_customer_algorithm(algorithm)
+            .set_sse_customer_key(key)
+            .set_sse_customer_key_md5(key_md5)
+            .set_request_payer(self.request_payer.then_some(RequestPayer::Requester))
+    }
+
+    /// SSE-C 的算法、base64 编码的密钥和密钥的 MD5
+    fn sse_customer_headers(&self) -> (Option<String>, Option<String>, Option<String>) {
+        let Some(key) = &self.sse_customer_key else {
+            return (None, None, None);
+        };
+        let base64 = base64::engine::general_purpose::STANDARD;
+        (Some("AES256".to_string()), Some(base64.encode(key)), Some(base64.encode(md5::compute(key).0)))
+    }
+}
+
+impl<'a> From<TransferOptions<'a>> for DownloadOptions<'a> {
+    fn from(transfer: TransferOptions<'a>) -> Self {
+        Self { transfer, ..Default::default() }
+    }
+}
+
+/// 检查本次传输的分块参数，返回调整后的客户端（clone 的开销很小）
+fn override_client(client: &OssClient, part_size: Option<u64>, workers: Option<usize>) -> Result<OssClient> {
+    if workers == Some(0) {
+        return Err(OssError::Config("并发数必须大于 0".to_string()));
+    }
+    if let Some(part_size) = part_size {
+        check_part_size(part_size)?;
+    }
+    let mut client = client.clone();
+    client.part_size = part_size.unwrap_or(client.part_size);
+    client.workers = workers.unwrap_or(client.workers);
+    Ok(client)
+}
+
+/// 一次上传或下载过程中的进度事件
+#[derive(Debug)]
+pub enum ProgressEvent<'a> {
+    /// 开始传输，`total` 为总字节数
+    Started { total: u64 },
+    /// 又传输了 `n` 字节（分块并发时来自不同的分块）
+    BytesTransferred { n: u64 },
+    /// 第 `number` 个分块（从 1 开始）传输完成，单次请求的传输没有这个事件
+    PartCompleted { number: usize },
+    /// 传输完成
+    Finished,
+    /// 传输失败，之后不会再有其他事件
+    Failed { error: &'a OssError },
+}
+
+/// 接收进度事件；分块并发传输时会从多个任务同时调用
+pub trait ProgressObserver: Send + Sync {
+    fn on_event(&self, event: ProgressEvent<'_>);
+}
+
+impl<F: Fn(ProgressEvent<'_>) + Send + Sync> ProgressObserver for F {
+    fn on_event(&self, event: ProgressEvent<'_>) {
+        self(event)
+    }
+}
+
+/// 不关心进度时使用
+pub struct NoProgress;
+
+impl ProgressObserver for NoProgress {
+    fn on_event(&self, _event: ProgressEvent<'_>) {}
+}
+
+/// 只转发各文件的 BytesTransferred，同时传输多个文件时总大小和结束事件由调用方发送
+pub(crate) struct BytesOnly<'a>(pub(crate) &'a dyn ProgressObserver);
+
+impl ProgressObserver for BytesOnly<'_> {
+    fn on_event(&self, event: ProgressEvent<'_>) {
+        if let ProgressEvent::BytesTransferred { .. } = event {
+            self.0.on_event(event);
+        }
+    }
+}
+
+/// 传输结束后发送 Finished 或 Failed
+pub(crate) fn finish<T>(progress: &dyn ProgressObserver, result: Result<T>) -> Result<T> {
+    match &result {
+        Ok(_) => progress.on_event(ProgressEvent::Finished),
+        Err(error) => progress.on_event(ProgressEvent::Failed { error }),
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_finish_reports_result() {
+        let events = Mutex::new(Vec::new());
+        let observer = |event: ProgressEvent<'_>| events.lock().unwrap().push(format!("{:?}", event));
+
+        assert_eq!(finish(&observer, Ok(1)).unwrap(), 1);
+        let err = finish::<()>(&observer, Err(OssError::not_found("a.txt"))).unwrap_err();
+        assert!(err.is_not_found());
+        let events = events.into_inner().unwrap();
+        assert_eq!(events[0], "Finished");
+        assert!(events[1].starts_with("Failed"), "{}", events[1]);
+    }
+
+    #[tokio::test]
+    async fn test_until_cancelled() {
+        let token = CancellationToken::new();
+        let options = TransferOptions { cancel: Some(token.clone()), ..Default::default() };
+        assert_eq!(options.until_cancelled(async { Ok::<_, OssError>(1) }).await.unwrap(), 1);
+        assert!(options.check_cancelled().is_ok());
+
+        token.cancel();
+        assert!(matches!(options.check_cancelled(), Err(OssError::Cancelled)));
+        // 取消后不再等待尚未完成的请求
+        let pending = std::future::pending::<Result<()>>();
+        assert!(matches!(options.until_cancelled(pending).await, Err(OssError::Cancelled)));
+    }
+}
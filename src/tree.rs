@@ -0,0 +1,164 @@
+//! 按层级列出前缀下的目录结构（tree）
+
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::error::{OssError, Result};
+use crate::{directory_prefix, ListEntry, ListOptions, OssClient};
+
+/// 目录树中的一个节点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TreeNode {
+    /// 相对父节点的名字，目录以 `/` 结尾；根节点为完整前缀
+    pub name: String,
+    /// 对象大小，目录为 None
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TreeNode>,
+    /// 超过 [`TreeOptions::max_entries`] 而省略的条目数
+    #[serde(skip_serializing_if = "is_zero")]
+    pub omitted: usize,
+}
+
+/// tree 选项
+#[derive(Debug, Clone)]
+pub struct TreeOptions {
+    /// 展开的层数，1 表示只列出前缀的直接子项
+    pub depth: usize,
+    /// 每一层最多显示的条目数，其余的只计数
+    pub max_entries: usize,
+    /// 只显示目录，跳过对象
+    pub dirs_only: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self { depth: 3, max_entries: 50, dirs_only: false }
+    }
+}
+
+impl TreeNode {
+    pub fn is_dir(&self) -> bool {
+        self.size.is_none()
+    }
+}
+
+/// 一个前缀下列出的条目和省略的条目数
+type Level = (Vec<ListEntry>, usize);
+
+impl OssClient {
+    /// 逐层使用分隔符列出前缀，构建目录树
+    /// 每一层的所有目录并发列出，只请求 `depth` 层，不会列出整个前缀下的全部对象
+    pub async fn tree(&self, prefix: &str, options: &TreeOptions) -> Result<TreeNode> {
+        let root = directory_prefix(prefix);
+        let mut levels: HashMap<String, Level> = HashMap::new();
+        let mut frontier = vec![root.clone()];
+
+        for _ in 0..options.depth.max(1) {
+            let listed: Vec<(String, Level)> = stream::iter(std::mem::take(&mut frontier))
+                .map(|prefix| async move {
+                    let level = self.tree_level(&prefix, options).await?;
+                    Ok::<_, OssError>((prefix, level))
+                })
+                .buffered(self.workers)
+                .try_collect()
+                .await?;
+            for (prefix, level) in listed {
+                frontier.extend(level.0.iter().filter_map(|entry| match entry {
+                    ListEntry::Prefix(p) => Some(p.clone()),
+                    ListEntry::Object(_) => None,
+                }));
+                levels.insert(prefix, level);
+            }
+        }
+
+        let mut node = build_node(&root, root.len(), &mut levels);
+        node.name = root;
+        Ok(node)
+    }
+
+    /// 列出一层，超过 `max_entries` 的条目只计数
+    async fn tree_level(&self, prefix: &str, options: &TreeOptions) -> Result<Level> {
+        let list_options = ListOptions { delimiter: Some("/".to_string()), ..Default::default() };
+        let mut entries = self.list_stream(prefix, &list_options);
+        let mut shown = Vec::new();
+        let mut omitted = 0;
+        while let Some(entry) = entries.try_next().await? {
+            // 跳过目录占位对象（key 与前缀相同）
+            if entry.name() == prefix || (options.dirs_only && matches!(entry, ListEntry::Object(_))) {
+                continue;
+            }
+            if shown.len() < options.max_entries {
+                shown.push(entry);
+            } else {
+                omitted += 1;
+            }
+        }
+        Ok((shown, omitted))
+    }
+}
+
+/// 从逐层列出的结果组装节点，没有列出的目录（超过深度）没有子节点
+fn build_node(prefix: &str, parent_len: usize, levels: &mut HashMap<String, Level>) -> TreeNode {
+    let (entries, omitted) = levels.remove(prefix).unwrap_or_default();
+    let children = entries
+        .into_iter()
+        .map(|entry| match entry {
+            ListEntry::Object(obj) => TreeNode {
+                name: obj.key[prefix.len()..].to_string(),
+                size: Some(obj.size),
+                children: Vec::new(),
+                omitted: 0,
+            },
+            ListEntry::Prefix(child) => build_node(&child, prefix.len(), levels),
+        })
+        .collect();
+    TreeNode { name: prefix[parent_len..].to_string(), size: None, children, omitted }
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectInfo;
+
+    fn object(key: &str, size: u64) -> ListEntry {
+        ListEntry::Object(Box::new(ObjectInfo {
+            key: key.to_string(),
+            size,
+            etag: None,
+            storage_class: "STANDARD".to_string(),
+            last_modified: None,
+            owner: None,
+            content_type: None,
+            sse: None,
+            metadata: Default::default(),
+            version_id: None,
+        }))
+    }
+
+    #[test]
+    fn test_build_node() {
+        let mut levels = HashMap::from([
+            (
+                "logs/".to_string(),
+                (vec![ListEntry::Prefix("logs/2024/".to_string()), object("logs/a.txt", 3)], 2),
+            ),
+            ("logs/2024/".to_string(), (vec![ListEntry::Prefix("logs/2024/01/".to_string())], 0)),
+        ]);
+        let node = build_node("logs/", 0, &mut levels);
+        assert_eq!(node.name, "logs/");
+        assert_eq!(node.omitted, 2);
+        assert_eq!(node.children[0].name, "2024/");
+        assert!(node.children[0].is_dir());
+        // 超过深度的目录没有列出，没有子节点
+        assert_eq!(node.children[0].children[0].name, "01/");
+        assert!(node.children[0].children[0].children.is_empty());
+        assert_eq!(node.children[1], TreeNode { name: "a.txt".to_string(), size: Some(3), children: vec![], omitted: 0 });
+    }
+}
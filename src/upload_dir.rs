@@ -0,0 +1,310 @@
+//! 递归上传本地目录
+
+use futures::StreamExt;
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{bail, Context, Result};
+use crate::sync::walk_dir_with;
+use crate::transfer::{self, BytesOnly, ProgressEvent, ProgressObserver, TransferOptions, UploadOptions};
+use crate::{directory_prefix, KeyFilter, OssClient, OssError, Overwrite, UploadResult};
+
+/// 目录中的符号链接
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// 上传链接指向的文件，进入链接指向的目录
+    #[default]
+    Follow,
+    /// 跳过所有符号链接
+    Skip,
+}
+
+type FileFilter = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// [`OssClient::upload_dir`] 的选项，默认上传全部文件、覆盖已有对象、跟随符号链接
+#[derive(Default)]
+pub struct DirUploadOptions<'a> {
+    /// 进度按整个目录汇总：开始时发送一次所有文件的总大小，之后是各文件的 BytesTransferred
+    pub transfer: TransferOptions<'a>,
+    /// 只上传匹配的文件（glob，匹配以 `/` 分隔的相对路径），为空时上传全部
+    pub include: Vec<String>,
+    /// 排除匹配的文件，优先于 include
+    pub exclude: Vec<String>,
+    /// 同时上传的文件数，默认为客户端的并发数
+    pub workers: Option<usize>,
+    /// 为 [`Overwrite::Never`] 时跳过已经存在的对象
+    pub overwrite: Overwrite,
+    pub symlinks: SymlinkPolicy,
+    filter: Option<FileFilter>,
+}
+
+impl fmt::Debug for DirUploadOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirUploadOptions")
+            .field("include", &self.include)
+            .field("exclude", &self.exclude)
+            .field("workers", &self.workers)
+            .field("overwrite", &self.overwrite)
+            .field("symlinks", &self.symlinks)
+            .field("filter", &self.filter.is_some())
+            .finish()
+    }
+}
+
+impl<'a> DirUploadOptions<'a> {
+    pub fn progress(mut self, observer: &'a dyn ProgressObserver) -> Self {
+        self.transfer.progress = observer;
+        self
+    }
+
+    pub fn cancel(mut self, token: CancellationToken) -> Self {
+        self.transfer.cancel = Some(token);
+        self
+    }
+
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    /// 在 include / exclude 之后再按相对路径筛选，返回 false 的文件不上传
+    pub fn filter(mut self, filter: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    pub fn overwrite(mut self, overwrite: Overwrite) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    pub fn symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+}
+
+/// 上传成功的文件
+#[derive(Debug, Clone, Serialize)]
+pub struct DirUploadEntry {
+    pub path: PathBuf,
+    pub key: String,
+    pub result: UploadResult,
+}
+
+/// 上传失败的文件
+#[derive(Debug, Clone, Serialize)]
+pub struct DirUploadFailure {
+    pub path: PathBuf,
+    pub key: String,
+    pub message: String,
+}
+
+/// 目录上传结果，都按 key 排序
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DirUploadReport {
+    pub uploaded: Vec<DirUploadEntry>,
+    /// 对象已经存在而跳过的 key（overwrite 为 [`Overwrite::Never`] 时）
+    pub skipped: Vec<String>,
+    pub errors: Vec<DirUploadFailure>,
+}
+
+impl DirUploadReport {
+    /// 是否全部成功
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// 上传成功的字节数
+    pub fn bytes(&self) -> u64 {
+        self.uploaded.iter().map(|entry| entry.result.size).sum()
+    }
+}
+
+/// 单个文件的结果，`None` 为跳过
+type FileResult = (PathBuf, String, Result<Option<UploadResult>>);
+
+impl OssClient {
+    /// 递归上传目录下的文件到 `key_prefix`，key 为 `<前缀>/<相对路径>`（路径分隔符统一为 `/`），并发上传多个文件。
+    /// 单个文件失败不会中断其余文件，记录在 [`DirUploadReport::errors`] 中；取消时返回 [`OssError::Cancelled`]
+    pub async fn upload_dir(&self, local_dir: &Path, key_prefix: &str, options: &DirUploadOptions<'_>) -> Result<DirUploadReport> {
+        let progress = options.transfer.progress;
+        let result = async {
+            if options.workers == Some(0) {
+                return Err(OssError::Config("并发数必须大于 0".to_string()));
+            }
+            if !local_dir.is_dir() {
+                bail!("{} 不是目录", local_dir.display());
+            }
+            let prefix = directory_prefix(key_prefix);
+            let filter = KeyFilter::new(&options.include, &options.exclude)?;
+            let files: Vec<_> = walk_dir_with(local_dir, options.symlinks == SymlinkPolicy::Follow)?
+                .into_iter()
+                .filter(|file| filter.matches(&file.relative))
+                .filter(|file| options.filter.as_ref().is_none_or(|filter| filter(&file.relative)))
+                .collect();
+            progress.on_event(ProgressEvent::Started { total: files.iter().map(|file| file.size).sum() });
+
+            let bytes = BytesOnly(progress);
+            let results: Vec<FileResult> = futures::stream::iter(files)
+                .map(|file| {
+                    let key = format!("{}{}", prefix, file.relative);
+                    let upload = UploadOptions {
+                        transfer: TransferOptions { progress: &bytes, cancel: options.transfer.cancel.clone() },
+                        ..Default::default()
+                    };
+                    async move {
+                        let result = self.upload_dir_file(&file.path, &key, options.overwrite, &upload).await;
+                        (file.path, key, result)
+                    }
+                })
+                .buffer_unordered(options.workers.unwrap_or(self.workers))
+                .collect()
+                .await;
+            options.transfer.check_cancelled()?;
+
+            let mut report = DirUploadReport::default();
+            for (path, key, result) in results {
+                match result {
+                    Ok(Some(result)) => report.uploaded.push(DirUploadEntry { path, key, result }),
+                    Ok(None) => report.skipped.push(key),
+                    Err(OssError::Cancelled) => return Err(OssError::Cancelled),
+                    Err(err) => report.errors.push(DirUploadFailure { path, key, message: err.to_string() }),
+                }
+            }
+            report.uploaded.sort_by(|a, b| a.key.cmp(&b.key));
+            report.skipped.sort();
+            report.errors.sort_by(|a, b| a.key.cmp(&b.key));
+            Ok(report)
+        }
+        .await;
+        transfer::finish(progress, result)
+    }
+
+    /// 上传目录中的一个文件，对象已经存在并且不覆盖时返回 None
+    async fn upload_dir_file(
+        &self,
+        path: &Path,
+        key: &str,
+        overwrite: Overwrite,
+        options: &UploadOptions<'_>,
+    ) -> Result<Option<UploadResult>> {
+        if overwrite == Overwrite::Never && self.exists(key).await.with_context(|| format!("无法检查 {} 是否存在", key))? {
+            return Ok(None);
+        }
+        self.upload_file(path, key, options).await.map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{mock_client, response};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// 测试目录：a.txt、sub/b.txt、sub/deep/c.log、skip.tmp
+    fn fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("sub/deep")).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        std::fs::write(dir.path().join("sub/b.txt"), "bb").unwrap();
+        std::fs::write(dir.path().join("sub/deep/c.log"), "ccc").unwrap();
+        std::fs::write(dir.path().join("skip.tmp"), "tmp").unwrap();
+        dir
+    }
+
+    /// PUT 到 `fail` 的请求返回 403，HEAD `existing` 返回 200，其余 HEAD 返回 404；记录所有 PUT 的 key
+    async fn client(fail: &'static str, existing: &'static str) -> (OssClient, Arc<Mutex<Vec<String>>>) {
+        let puts = Arc::new(Mutex::new(Vec::new()));
+        let log = puts.clone();
+        let client = mock_client(move |req| {
+            let key = req.uri().trim_start_matches("https://my-bucket.s3.example.com/").split('?').next().unwrap().to_string();
+            match req.method() {
+                "HEAD" if key == existing => response(200, &[("content-length", "1")], ""),
+                "HEAD" => response(404, &[], ""),
+                _ if key == fail => response(403, &[], "<Error><Code>AccessDenied</Code></Error>"),
+                _ => {
+                    log.lock().unwrap().push(key);
+                    response(200, &[("ETag", "\"e\"")], "")
+                }
+            }
+        })
+        .await;
+        (client, puts)
+    }
+
+    #[tokio::test]
+    async fn test_upload_dir_collects_results_and_errors() {
+        let dir = fixture();
+        let (client, puts) = client("out/sub/b.txt", "").await;
+        let transferred = AtomicU64::new(0);
+        let total = AtomicU64::new(0);
+        let observer = |event: ProgressEvent<'_>| match event {
+            ProgressEvent::Started { total: n } => total.store(n, Ordering::SeqCst),
+            ProgressEvent::BytesTransferred { n } => {
+                transferred.fetch_add(n, Ordering::SeqCst);
+            }
+            _ => {}
+        };
+        let options = DirUploadOptions::default().exclude("*.tmp").progress(&observer);
+        let report = client.upload_dir(dir.path(), "out", &options).await.unwrap();
+
+        let keys: Vec<_> = report.uploaded.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, ["out/a.txt", "out/sub/deep/c.log"]);
+        assert_eq!(report.uploaded[1].path, dir.path().join("sub/deep/c.log"));
+        assert_eq!((report.uploaded[1].result.size, report.bytes()), (3, 4));
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!((report.errors[0].key.as_str(), report.errors[0].path.clone()), ("out/sub/b.txt", dir.path().join("sub/b.txt")));
+        assert!(!report.is_success());
+        assert_eq!(puts.lock().unwrap().len(), 2);
+        assert_eq!((total.load(Ordering::SeqCst), transferred.load(Ordering::SeqCst)), (6, 4));
+    }
+
+    #[tokio::test]
+    async fn test_upload_dir_filter_and_overwrite() {
+        let dir = fixture();
+        let (client, puts) = client("", "a.txt").await;
+        let options = DirUploadOptions::default()
+            .include("**/*.txt")
+            .include("*.txt")
+            .filter(|relative| !relative.starts_with("sub/deep/"))
+            .overwrite(Overwrite::Never);
+        let report = client.upload_dir(dir.path(), "", &options).await.unwrap();
+        assert_eq!(report.skipped, ["a.txt"]);
+        assert_eq!(*puts.lock().unwrap(), ["sub/b.txt"]);
+        assert!(report.is_success());
+
+        let err = client.upload_dir(&dir.path().join("a.txt"), "", &DirUploadOptions::default()).await.unwrap_err();
+        assert!(matches!(err, OssError::Invalid(_)), "{:?}", err);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_upload_dir_symlink_policy() {
+        let dir = fixture();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("linked.txt"), "l").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+
+        let (client, _) = client("", "").await;
+        let options = DirUploadOptions::default().include("link/**");
+        let report = client.upload_dir(dir.path(), "p/", &options).await.unwrap();
+        assert_eq!(report.uploaded.iter().map(|entry| entry.key.as_str()).collect::<Vec<_>>(), ["p/link/linked.txt"]);
+
+        let report = client.upload_dir(dir.path(), "p/", &options.symlinks(SymlinkPolicy::Skip)).await.unwrap();
+        assert!(report.uploaded.is_empty());
+    }
+}
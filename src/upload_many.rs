@@ -0,0 +1,100 @@
+//! 并发上传多个文件：从输入流中依次取出 (本地路径, key)，同时进行若干个上传，按完成的顺序返回结果
+
+use futures::{Stream, StreamExt};
+use std::path::PathBuf;
+
+use crate::error::Result;
+use crate::transfer::BytesOnly;
+use crate::{OssClient, UploadOptions, UploadResult};
+
+/// [`OssClient::upload_many`] 中一个文件的结果
+#[derive(Debug)]
+pub struct UploadManyItem {
+    pub path: PathBuf,
+    pub key: String,
+    pub result: Result<UploadResult>,
+}
+
+impl OssClient {
+    /// 上传 `items` 中的每个 (本地路径, key)，同时最多进行 `files` 个（0 表示使用客户端的并发数），
+    /// 返回的流按完成的顺序给出每个文件的结果。
+    ///
+    /// 只有读取返回的流时才会从 `items` 取下一项，调用方处理得慢时输入也随之暂停；
+    /// 单个文件失败（包括取消）只体现在它的结果中，不会结束流。
+    /// `options` 的对象属性、分块参数和超时用于每个文件，进度只发送各文件的 BytesTransferred。
+    /// 单次 PUT 和分块请求都占用客户端的并发名额，同时进行的请求不超过 `max_workers`
+    pub fn upload_many<'a>(
+        &'a self,
+        items: impl Stream<Item = (PathBuf, String)> + 'a,
+        files: usize,
+        options: &'a UploadOptions<'a>,
+    ) -> impl Stream<Item = UploadManyItem> + 'a {
+        let files = if files == 0 { self.workers } else { files };
+        items
+            .map(move |(path, key)| async move {
+                let bytes = BytesOnly(options.transfer.progress);
+                let result = self.upload_file(&path, &key, &options.with_progress(&bytes)).await;
+                UploadManyItem { path, key, result }
+            })
+            .buffer_unordered(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{counting_builder, response};
+    use crate::OssError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_upload_many_yields_each_result() {
+        let (builder, in_flight) = counting_builder(Duration::from_millis(20), |_| response(200, &[("ETag", "\"e\"")], ""));
+        let client = builder.max_workers(3).build().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut items = Vec::new();
+        for i in 0..10 {
+            let path = dir.path().join(format!("{}.txt", i));
+            std::fs::write(&path, "x".repeat(i)).unwrap();
+            items.push((path, format!("files/{}.txt", i)));
+        }
+        items.push((dir.path().join("missing.txt"), "files/missing.txt".to_string()));
+
+        // 单次 PUT 也受客户端的并发上限约束，缺少的文件只影响自己的结果
+        let options = UploadOptions::default().content_type("text/plain");
+        let mut results: Vec<_> = client.upload_many(futures::stream::iter(items), 8, &options).collect().await;
+        assert_eq!(in_flight.max(), 3);
+        results.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(results.len(), 11);
+        assert_eq!(results[1].result.as_ref().unwrap().size, 1);
+        assert_eq!(results[1].result.as_ref().unwrap().url, "https://my-bucket.s3.example.com/files/1.txt");
+        let failed = results.iter().find(|item| item.key == "files/missing.txt").unwrap();
+        assert!(matches!(failed.result, Err(OssError::LocalIo { .. })), "{:?}", failed.result);
+    }
+
+    #[tokio::test]
+    async fn test_upload_many_pulls_input_on_demand() {
+        let (builder, _) = counting_builder(Duration::from_millis(5), |_| response(200, &[("ETag", "\"e\"")], ""));
+        let client = builder.build().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.txt");
+        std::fs::write(&path, "a").unwrap();
+
+        // 不再读取结果时，输入最多只被多取出 files 项
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let counter = pulled.clone();
+        let items = futures::stream::iter(0..100).map(move |i| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            (path.clone(), format!("{}.txt", i))
+        });
+        let options = UploadOptions::default();
+        let mut results = Box::pin(client.upload_many(items, 2, &options));
+        for _ in 0..3 {
+            assert!(results.next().await.unwrap().result.is_ok());
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(pulled.load(Ordering::SeqCst) <= 5, "{}", pulled.load(Ordering::SeqCst));
+    }
+}
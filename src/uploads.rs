@@ -0,0 +1,290 @@
+//! 未完成的分块上传（ListMultipartUploads / ListParts / AbortMultipartUpload）
+
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::primitives::DateTime;
+use aws_sdk_s3::types::{CompletedPart, Part};
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::Instrument;
+
+use crate::error::{bail, Context, Result};
+use crate::transfer::{self, ProgressEvent, UploadOptions};
+use crate::{OssClient, OssError, UploadResult, MAX_PARTS};
+
+/// 一个进行中（已开始但未完成或中止）的分块上传
+#[derive(Debug, Clone)]
+pub struct MultipartUpload {
+    pub key: String,
+    pub upload_id: String,
+    pub initiated: Option<DateTime>,
+    pub storage_class: String,
+}
+
+/// 一个分块上传已经上传的分块
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UploadedParts {
+    pub count: usize,
+    pub bytes: u64,
+}
+
+impl OssClient {
+    /// 列出前缀下所有进行中的分块上传（自动处理分页），按 key 和开始时间排序
+    pub async fn list_multipart_uploads(&self, prefix: &str) -> Result<Vec<MultipartUpload>> {
+        let mut uploads = Vec::new();
+        let mut key_marker: Option<String> = None;
+        let mut upload_id_marker: Option<String> = None;
+
+        loop {
+            let page = self.client
+                .list_multipart_uploads()
+                .bucket(&self.config.bucket)
+                .prefix(prefix)
+                .set_key_marker(key_marker.take())
+                .set_upload_id_marker(upload_id_marker.take())
+                .send()
+                .await
+                .with_context(|| format!("无法列出分块上传: {}", prefix))?;
+
+            for upload in page.uploads() {
+                uploads.push(MultipartUpload {
+                    key: upload.key().unwrap_or_default().to_string(),
+                    upload_id: upload.upload_id().unwrap_or_default().to_string(),
+                    initiated: upload.initiated().cloned(),
+                    storage_class: upload
+                        .storage_class()
+                        .map(|c| c.as_str().to_string())
+                        .unwrap_or_else(|| "STANDARD".to_string()),
+                });
+            }
+
+            if !page.is_truncated().unwrap_or(false) {
+                break;
+            }
+            key_marker = page.next_key_marker().map(|s| s.to_string());
+            upload_id_marker = page.next_upload_id_marker().map(|s| s.to_string());
+            if key_marker.is_none() {
+                break;
+            }
+        }
+
+        uploads.sort_by(|a, b| a.key.cmp(&b.key).then_with(|| a.initiated.cmp(&b.initiated)));
+        Ok(uploads)
+    }
+
+    /// 统计分块上传已经上传的分块数和字节数（自动处理分页）；
+    /// upload id 不存在时返回 [`OssError::UploadNotFound`]
+    pub async fn list_parts(&self, key: &str, upload_id: &str) -> Result<UploadedParts> {
+        let parts = self.uploaded_parts(key, upload_id).await?;
+        Ok(UploadedParts {
+            count: parts.len(),
+            bytes: parts.iter().map(|part| part.size().unwrap_or_default().max(0) as u64).sum(),
+        })
+    }
+
+    /// 分块上传已经上传的所有分块（自动处理分页）
+    async fn uploaded_parts(&self, key: &str, upload_id: &str) -> Result<Vec<Part>> {
+        let mut parts = Vec::new();
+        let mut part_number_marker: Option<String> = None;
+
+        loop {
+            let page = match self.client
+                .list_parts()
+                .bucket(&self.config.bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .set_part_number_marker(part_number_marker.take())
+                .send()
+                .await
+            {
+                Ok(page) => page,
+                Err(err) if err.code() == Some("NoSuchUpload") => return Err(upload_not_found(key, upload_id)),
+                Err(err) => return Err(err).with_context(|| format!("无法列出 {} 的分块 (upload id {})", key, upload_id)),
+            };
+            parts.extend(page.parts().iter().cloned());
+
+            if !page.is_truncated().unwrap_or(false) {
+                break;
+            }
+            part_number_marker = page.next_part_number_marker().map(|s| s.to_string());
+            if part_number_marker.is_none() {
+                break;
+            }
+        }
+        Ok(parts)
+    }
+
+    /// 继续一个已知 upload id 的分块上传：列出已上传的分块，检查它们与本地文件按分块大小切分的结果一致，
+    /// 上传其余的分块后完成上传。分块大小取自 `options`（默认为客户端的设置），必须与开始上传时相同；
+    /// 已上传的部分先作为一次 BytesTransferred 发送。
+    ///
+    /// upload id 不存在（已完成、已中止或过期）时返回 [`OssError::UploadNotFound`]，可以改为重新上传；
+    /// 已上传的分块与本地文件不符时，在发送任何数据之前返回 [`OssError::Invalid`]。
+    /// 失败、取消或超时时不中止分块上传，之后可以再次继续
+    pub async fn resume_upload(
+        &self,
+        path: &Path,
+        key: &str,
+        upload_id: &str,
+        options: &UploadOptions<'_>,
+    ) -> Result<UploadResult> {
+        let progress = options.transfer.progress;
+        let key = match self.object_key(key) {
+            Ok(key) => key,
+            Err(err) => return transfer::finish(progress, Err(err)),
+        };
+        let key = key.as_str();
+        let result = transfer::with_timeout(options.timeout, async {
+            let client = options.client(self)?;
+            let part_size = client.part_size;
+            let file_size = tokio::fs::metadata(path)
+                .await
+                .with_context(|| format!("无法找到文件: {}", path.display()))?
+                .len();
+            let total_parts = file_size.div_ceil(part_size) as usize;
+            if total_parts == 0 {
+                bail!("{} 是空文件，无法继续分块上传", path.display());
+            }
+            if total_parts > MAX_PARTS {
+                bail!("{} 超过了 {} 个分块，请增大分块大小", path.display(), MAX_PARTS);
+            }
+            progress.on_event(ProgressEvent::Started { total: file_size });
+
+            let mut done = Vec::new();
+            let mut numbers = HashSet::new();
+            let mut bytes = 0;
+            for part in client.uploaded_parts(key, upload_id).await? {
+                let number = part.part_number().unwrap_or_default().max(0) as usize;
+                let size = part.size().unwrap_or_default().max(0) as u64;
+                let expected = (1..=total_parts)
+                    .contains(&number)
+                    .then(|| part_size.min(file_size - (number as u64 - 1) * part_size));
+                if expected != Some(size) {
+                    bail!(
+                        "{} 的第 {} 个分块为 {} 字节，与 {}（{} 字节）按 {} 字节分块的结果不一致",
+                        key, number, size, path.display(), file_size, part_size
+                    );
+                }
+                numbers.insert(number);
+                bytes += size;
+                done.push(CompletedPart::builder().part_number(number as i32).set_e_tag(part.e_tag).build());
+            }
+            if bytes > 0 {
+                progress.on_event(ProgressEvent::BytesTransferred { n: bytes });
+            }
+
+            let missing: Vec<usize> = (1..=total_parts).filter(|number| !numbers.contains(number)).collect();
+            tracing::info!(upload_id, uploaded = done.len(), remaining = missing.len(), "继续分块上传");
+            let mut parts = client
+                .upload_file_parts(path, key, upload_id, file_size, missing, &options.transfer)
+                .await?;
+            parts.extend(done);
+            client.complete_parts(key, upload_id, parts).await?;
+            Ok(UploadResult { url: client.generate_url(key), size: file_size, parts: total_parts })
+        })
+        .instrument(tracing::info_span!("upload", key))
+        .await
+        .map_err(|err| match err.service_error().and_then(|service| service.code()) {
+            // 上传过程中被中止或过期
+            Some("NoSuchUpload") => upload_not_found(key, upload_id),
+            _ => err,
+        });
+        if result.is_ok() {
+            tracing::info!(key, "上传完成");
+        }
+        transfer::finish(progress, result)
+    }
+
+    /// 中止分块上传并丢弃已上传的分块
+    /// 上传已经完成或已被中止（NoSuchUpload）时返回 `false`
+    pub async fn abort_upload(&self, key: &str, upload_id: &str) -> Result<bool> {
+        match self.client
+            .abort_multipart_upload()
+            .bucket(&self.config.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(err) if err.code() == Some("NoSuchUpload") => Ok(false),
+            Err(err) => Err(err).with_context(|| format!("无法中止 {} 的分块上传 (upload id {})", key, upload_id)),
+        }
+    }
+}
+
+fn upload_not_found(key: &str, upload_id: &str) -> OssError {
+    OssError::UploadNotFound { key: key.to_string(), upload_id: upload_id.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{mock_client, query, response};
+    use crate::MIN_PART_SIZE;
+    use std::sync::{Arc, Mutex};
+
+    /// ListParts 返回 `parts`（编号, 大小），记录其余请求的方法、查询字符串和请求体
+    async fn resume(parts: &'static [(usize, u64)], list_status: u16) -> (Result<UploadResult>, Vec<String>) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let requests = log.clone();
+        let client = mock_client(move |req| {
+            let query = query(req).replace("x-id=UploadPart&", "");
+            if req.method() == "GET" {
+                if list_status != 200 {
+                    return response(list_status, &[], "<Error><Code>NoSuchUpload</Code></Error>");
+                }
+                let parts: String = parts
+                    .iter()
+                    .map(|(number, size)| format!("<Part><PartNumber>{0}</PartNumber><ETag>\"p{0}\"</ETag><Size>{1}</Size></Part>", number, size))
+                    .collect();
+                return response(200, &[], format!("<ListPartsResult><IsTruncated>false</IsTruncated>{}</ListPartsResult>", parts));
+            }
+            let body = match query.as_str() {
+                "uploadId=u1" => String::from_utf8(req.body().bytes().unwrap().to_vec()).unwrap(),
+                _ => req.body().bytes().map_or(0, <[u8]>::len).to_string(),
+            };
+            requests.lock().unwrap().push(format!("{} {} {}", req.method(), query, body));
+            response(200, &[("ETag", "\"new\"")], "<CompleteMultipartUploadResult/>")
+        })
+        .await
+        .with_workers(1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.bin");
+        std::fs::write(&path, vec![7u8; 2 * MIN_PART_SIZE as usize + 3]).unwrap();
+        let result = client.resume_upload(&path, "a.bin", "u1", &UploadOptions::default()).await;
+        let log = log.lock().unwrap().clone();
+        (result, log)
+    }
+
+    #[tokio::test]
+    async fn test_resume_upload_sends_missing_parts() {
+        let (result, log) = resume(&[(2, MIN_PART_SIZE)], 200).await;
+        let result = result.unwrap();
+        assert_eq!((result.size, result.parts), (2 * MIN_PART_SIZE + 3, 3));
+        assert_eq!(log[..2], [format!("PUT partNumber=1&uploadId=u1 {}", MIN_PART_SIZE), "PUT partNumber=3&uploadId=u1 3".to_string()]);
+        // 完成时按编号带上已有分块和新上传分块的 ETag
+        let complete = log[2].replace(['\n', ' '], "").replace("&quot;", "");
+        assert!(complete.starts_with("POSTuploadId=u1"), "{}", complete);
+        let etags: Vec<&str> = complete.split("<ETag>").skip(1).filter_map(|s| s.split('<').next()).collect();
+        assert_eq!(etags, ["new", "p2", "new"]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_upload_rejects_mismatched_parts_before_sending() {
+        for parts in [&[(1, 1000)][..], &[(4, 3)][..], &[(3, MIN_PART_SIZE)][..]] {
+            let (result, log) = resume(parts, 200).await;
+            assert!(matches!(result, Err(OssError::Invalid(_))), "{:?}", result);
+            assert!(log.is_empty(), "{:?}", log);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_upload_reports_missing_upload() {
+        let (result, log) = resume(&[], 404).await;
+        let err = result.unwrap_err();
+        assert!(matches!(&err, OssError::UploadNotFound { upload_id, .. } if upload_id == "u1"), "{:?}", err);
+        assert_eq!(err.to_string(), "分块上传不存在: a.bin (upload id u1)");
+        assert!(log.is_empty());
+    }
+}
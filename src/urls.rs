@@ -0,0 +1,121 @@
+//! 拼接对象的访问地址：bucket 子域名、path-style 和自定义域名
+
+use url::{Host, Url};
+
+use crate::encode_key;
+use crate::error::{OssError, Result};
+
+/// 对象 URL 的形式，见 [`OssClient::public_url`](crate::OssClient::public_url)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressingStyle {
+    /// bucket 作为子域名：`https://bucket.endpoint/key`，endpoint 为 IP 地址时不可用
+    VirtualHost,
+    /// bucket 作为路径：`https://endpoint/bucket/key`
+    Path,
+    /// 使用配置的 public_base_url（如 CDN 域名）：`https://static.example.com/key`
+    CustomDomain,
+}
+
+/// 解析 endpoint 或自定义域名，没有协议时使用 https
+pub(crate) fn parse_base(base: &str) -> Result<Url> {
+    let base = base.trim();
+    let with_scheme = if base.contains("://") { base.to_string() } else { format!("https://{}", base) };
+    let url = Url::parse(&with_scheme).map_err(|err| OssError::Config(format!("无效的地址 {}: {}", base, err)))?;
+    if url.host().is_none() || url.query().is_some() || url.fragment().is_some() {
+        return Err(OssError::Config(format!("无效的地址 {}：需要主机名，且不能带查询参数", base)));
+    }
+    Ok(url)
+}
+
+/// 按 `style` 拼接 `key` 的 URL；`base` 为 endpoint，CustomDomain 时为自定义域名（不拼接 bucket）
+/// key 按 [`encode_key`] 编码，`/` 保留为路径分隔符；base 自带的路径（如反向代理的前缀）保留在 key 之前
+pub(crate) fn object_url(base: &str, bucket: &str, key: &str, style: AddressingStyle) -> Result<Url> {
+    let mut url = parse_base(base)?;
+    let mut path = url.path().trim_end_matches('/').to_string();
+    match style {
+        AddressingStyle::VirtualHost => {
+            let host = match url.host() {
+                Some(Host::Domain(domain)) => format!("{}.{}", bucket, domain),
+                _ => return Err(OssError::Config(format!("IP 地址的 endpoint 只能使用 path-style: {}", base))),
+            };
+            url.set_host(Some(&host))
+                .map_err(|err| OssError::Config(format!("bucket {} 不能作为子域名: {}", bucket, err)))?;
+        }
+        AddressingStyle::Path => {
+            path.push('/');
+            path.push_str(&encode_key(bucket));
+        }
+        AddressingStyle::CustomDomain => {}
+    }
+    path.push('/');
+    path.push_str(&encode_key(key));
+    url.set_path(&path);
+    Ok(url)
+}
+
+/// endpoint 的主机是否为 IP 地址，此时无法使用 bucket 子域名
+pub(crate) fn is_ip_endpoint(base: &str) -> bool {
+    parse_base(base).is_ok_and(|url| matches!(url.host(), Some(Host::Ipv4(_) | Host::Ipv6(_))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AddressingStyle::*;
+
+    fn url(base: &str, key: &str, style: AddressingStyle) -> String {
+        object_url(base, "my-bucket", key, style).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_object_url_styles() {
+        let base = "https://s3.example.com";
+        assert_eq!(url(base, "docs/a b+c.pdf", VirtualHost), "https://my-bucket.s3.example.com/docs/a%20b%2Bc.pdf");
+        assert_eq!(url(base, "docs/a b+c.pdf", Path), "https://s3.example.com/my-bucket/docs/a%20b%2Bc.pdf");
+        assert_eq!(url("https://static.example.com/", "a.pdf", CustomDomain), "https://static.example.com/a.pdf");
+        // 反向代理的路径前缀保留
+        assert_eq!(url("https://example.com/s3/", "a.pdf", Path), "https://example.com/s3/my-bucket/a.pdf");
+        assert_eq!(url("https://cdn.example.com/assets", "a.pdf", CustomDomain), "https://cdn.example.com/assets/a.pdf");
+        // 空 key 为 bucket 的根地址，表单直传时使用
+        assert_eq!(url(base, "", VirtualHost), "https://my-bucket.s3.example.com/");
+        assert_eq!(url(base, "", Path), "https://s3.example.com/my-bucket/");
+    }
+
+    #[test]
+    fn test_object_url_scheme_and_port() {
+        assert_eq!(url("http://localhost:9000", "a.txt", Path), "http://localhost:9000/my-bucket/a.txt");
+        assert_eq!(url("http://minio.local:9000/", "a.txt", VirtualHost), "http://my-bucket.minio.local:9000/a.txt");
+        // 默认端口不出现在 URL 中，没有协议时使用 https
+        assert_eq!(url("https://s3.example.com:443", "a.txt", VirtualHost), "https://my-bucket.s3.example.com/a.txt");
+        assert_eq!(url("s3.example.com", "a.txt", VirtualHost), "https://my-bucket.s3.example.com/a.txt");
+    }
+
+    #[test]
+    fn test_object_url_ip_endpoints() {
+        assert_eq!(url("http://127.0.0.1:9000", "a.txt", Path), "http://127.0.0.1:9000/my-bucket/a.txt");
+        assert_eq!(url("http://[::1]:9000", "a.txt", Path), "http://[::1]:9000/my-bucket/a.txt");
+        for base in ["http://127.0.0.1:9000", "http://[::1]:9000"] {
+            assert!(is_ip_endpoint(base));
+            let err = object_url(base, "my-bucket", "a.txt", VirtualHost).unwrap_err();
+            assert!(matches!(err, OssError::Config(_)), "{:?}", err);
+        }
+        assert!(!is_ip_endpoint("https://s3.example.com"));
+    }
+
+    #[test]
+    fn test_object_url_unicode_keys() {
+        let base = "https://s3.example.com";
+        assert_eq!(url(base, "文档/报告 1.pdf", VirtualHost), "https://my-bucket.s3.example.com/%E6%96%87%E6%A1%A3/%E6%8A%A5%E5%91%8A%201.pdf");
+        assert_eq!(url(base, "emoji/😀.png", Path), "https://s3.example.com/my-bucket/emoji/%F0%9F%98%80.png");
+        // 查询和片段的分隔符被编码，不会截断 key
+        assert_eq!(url(base, "a?b#c&d.txt", Path), "https://s3.example.com/my-bucket/a%3Fb%23c%26d.txt");
+        assert_eq!(url(base, "a//b/", Path), "https://s3.example.com/my-bucket/a//b/");
+    }
+
+    #[test]
+    fn test_parse_base_rejects_invalid() {
+        for base in ["", "https://", "https://exa mple.com", "https://s3.example.com/?a=1"] {
+            assert!(matches!(parse_base(base), Err(OssError::Config(_))), "{}", base);
+        }
+    }
+}
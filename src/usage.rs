@@ -0,0 +1,141 @@
+//! 统计前缀下的存储用量
+
+use futures::TryStreamExt;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::error::Result;
+use crate::{ObjectInfo, OssClient};
+
+/// 一组对象的用量
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    pub objects: u64,
+    pub bytes: u64,
+    /// 按存储类型细分的用量（对象数，字节数）
+    pub by_storage_class: BTreeMap<String, (u64, u64)>,
+}
+
+impl Usage {
+    fn add(&mut self, obj: &ObjectInfo) {
+        self.objects += 1;
+        self.bytes += obj.size;
+        let class = self.by_storage_class.entry(obj.storage_class.clone()).or_default();
+        class.0 += 1;
+        class.1 += obj.size;
+    }
+
+    fn merge(&mut self, other: &Usage) {
+        self.objects += other.objects;
+        self.bytes += other.bytes;
+        for (class, (objects, bytes)) in &other.by_storage_class {
+            let entry = self.by_storage_class.entry(class.clone()).or_default();
+            entry.0 += objects;
+            entry.1 += bytes;
+        }
+    }
+}
+
+/// 用量统计结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageReport {
+    /// 按分组统计的用量，分组为 key 前缀；直接位于分组层级之上的对象归入前缀本身
+    pub groups: BTreeMap<String, Usage>,
+    pub total: Usage,
+}
+
+impl UsageReport {
+    /// 计入一个对象，`group_depth` 为按前缀之下几级目录分组，0 表示不分组
+    fn add(&mut self, prefix: &str, group_depth: usize, obj: &ObjectInfo) {
+        let group = group_of(prefix, &obj.key, group_depth);
+        let usage = self.groups.entry(group.to_string()).or_default();
+        usage.add(obj);
+    }
+
+    fn finish(mut self) -> Self {
+        let mut total = Usage::default();
+        for usage in self.groups.values() {
+            total.merge(usage);
+        }
+        self.total = total;
+        self
+    }
+}
+
+impl OssClient {
+    /// 统计前缀下的对象数和总大小，边列出边累加，不会缓存整个列表
+    /// `group_depth` 大于 0 时按前缀之下的前几级目录分别统计
+    pub async fn disk_usage(&self, prefix: &str, group_depth: usize) -> Result<UsageReport> {
+        let mut report = UsageReport::default();
+        let mut objects = self.list_objects_stream(prefix);
+        while let Some(obj) = objects.try_next().await? {
+            report.add(prefix, group_depth, &obj);
+        }
+        Ok(report.finish())
+    }
+}
+
+/// key 所属的分组：前缀加上之后的 `depth` 级目录（以 `/` 结尾），层级不够深的对象归入前缀本身
+fn group_of<'a>(prefix: &str, key: &'a str, depth: usize) -> &'a str {
+    let relative = key.strip_prefix(prefix).unwrap_or(key);
+    let mut end = key.len() - relative.len();
+    for _ in 0..depth {
+        match key[end..].find('/') {
+            Some(i) => end += i + 1,
+            None => return &key[..key.len() - relative.len()],
+        }
+    }
+    &key[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(key: &str, size: u64, class: &str) -> ObjectInfo {
+        ObjectInfo {
+            key: key.to_string(),
+            size,
+            etag: None,
+            storage_class: class.to_string(),
+            last_modified: None,
+            owner: None,
+            content_type: None,
+            sse: None,
+            metadata: Default::default(),
+            version_id: None,
+        }
+    }
+
+    #[test]
+    fn test_group_of() {
+        assert_eq!(group_of("backups/", "backups/db/a.sql", 0), "backups/");
+        assert_eq!(group_of("backups/", "backups/db/a.sql", 1), "backups/db/");
+        assert_eq!(group_of("backups/", "backups/db/2024/a.sql", 2), "backups/db/2024/");
+        // 层级不够深的对象归入前缀本身
+        assert_eq!(group_of("backups/", "backups/top.sql", 1), "backups/");
+        assert_eq!(group_of("backups/", "backups/db/a.sql", 2), "backups/");
+        assert_eq!(group_of("", "a/b", 1), "a/");
+    }
+
+    #[test]
+    fn test_usage_report() {
+        let mut report = UsageReport::default();
+        for obj in [
+            object("p/a/1", 10, "STANDARD"),
+            object("p/a/2", 20, "GLACIER"),
+            object("p/b/1", 5, "STANDARD"),
+            object("p/top", 1, "STANDARD"),
+        ] {
+            report.add("p/", 1, &obj);
+        }
+        let report = report.finish();
+
+        assert_eq!(report.groups.keys().collect::<Vec<_>>(), vec!["p/", "p/a/", "p/b/"]);
+        assert_eq!(report.groups["p/a/"].bytes, 30);
+        assert_eq!(report.groups["p/a/"].by_storage_class["GLACIER"], (1, 20));
+        assert_eq!(report.total.objects, 4);
+        assert_eq!(report.total.bytes, 36);
+        assert_eq!(report.total.by_storage_class["STANDARD"], (3, 16));
+    }
+}
@@ -0,0 +1,232 @@
+//! 不访问网络的辅助函数：拼接 key、由本地文件名得到 key、解析对象地址、解析和格式化大小与时长
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::{bail, Context, Result};
+
+/// 拼接前缀和名字，连接处只保留一个 `/`；前缀为空（或只有 `/`）时直接返回名字
+/// `name` 内部的 `/` 保持不变，如 `join_key("logs/", "/2024//a.log")` 为 `logs/2024//a.log`
+pub fn join_key(prefix: &str, name: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    let name = name.trim_start_matches('/');
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+/// 上传本地文件时的默认 key：`<prefix>/<文件名>`，没有前缀时为文件名
+/// 路径没有文件名（如 `/`、`..`）时报错；非 UTF-8 的文件名按 lossy 转换
+pub fn derive_key_from_path(path: &Path, prefix: Option<&str>) -> Result<String> {
+    let Some(filename) = path.file_name() else {
+        bail!("无法从路径得到文件名: {}", path.display());
+    };
+    Ok(join_key(prefix.unwrap_or_default(), &filename.to_string_lossy()))
+}
+
+/// key 的最后一段，作为下载保存或上传读取的文件名；没有可用的名字时为 `download`
+pub fn key_filename(key: &str) -> String {
+    match key.trim_end_matches('/').rsplit('/').next() {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => "download".to_string(),
+    }
+}
+
+/// 格式化文件大小，如 `1.50 MB`
+pub fn format_size(size: u64) -> String {
+    let mut size = size as f64;
+    for unit in ["B", "KB", "MB", "GB", "TB"] {
+        // 保留两位小数后会进位到 1024.00 的值使用更大的单位
+        if size < 1023.995 {
+            return format!("{:.2} {}", size, unit);
+        }
+        size /= 1024.0;
+    }
+    format!("{:.2} PB", size)
+}
+
+/// 解析带单位的大小，如 `10GB`、`1.5M`、`512`（单位按 1024 进制）
+pub fn parse_size(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let num: f64 = num.parse()
+        .with_context(|| format!("无效的大小: {}", s))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        "P" | "PB" | "PIB" => 1 << 50,
+        other => bail!("无效的大小单位: {}", other),
+    };
+    let bytes = num * multiplier as f64;
+    if bytes >= u64::MAX as f64 {
+        bail!("大小超出范围: {}", s);
+    }
+    Ok(bytes as u64)
+}
+
+/// 解析时长，如 `30s`、`15m`、`12h`、`7d`、`2w`，不带单位时按秒计算
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let num: u64 = num.parse()
+        .with_context(|| format!("无效的时长: {}", s))?;
+    let secs = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        other => bail!("无效的时长单位: {}（支持 s/m/h/d/w）", other),
+    };
+    match num.checked_mul(secs) {
+        Some(secs) => Ok(Duration::from_secs(secs)),
+        None => bail!("时长超出范围: {}", s),
+    }
+}
+
+/// `s3://bucket/key` 或 `oss://bucket/key` 形式的对象地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectUri {
+    pub bucket: String,
+    /// 地址中 bucket 之后的部分，保留末尾的 `/`；`s3://bucket` 和 `s3://bucket/` 的 key 为空
+    pub key: String,
+}
+
+impl ObjectUri {
+    /// 解析对象地址，不以 `s3://` 或 `oss://` 开头时返回 None，按普通 key 处理。
+    /// key 中的 `%XX` 会被解码（key 本身含有 `%` 时写成 `%25`），bucket 为空时报错
+    pub fn parse(s: &str) -> Result<Option<Self>> {
+        let Some((scheme, rest)) = s.split_once("://") else {
+            return Ok(None);
+        };
+        if !scheme.eq_ignore_ascii_case("s3") && !scheme.eq_ignore_ascii_case("oss") {
+            return Ok(None);
+        }
+        let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            bail!("地址 {} 中没有 bucket", s);
+        }
+        let Ok(key) = urlencoding::decode(key) else {
+            bail!("地址 {} 的 key 解码后不是有效的 UTF-8", s);
+        };
+        Ok(Some(Self { bucket: bucket.to_string(), key: key.into_owned() }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_object_uri() {
+        let uri = |bucket: &str, key: &str| Some(ObjectUri { bucket: bucket.to_string(), key: key.to_string() });
+        assert_eq!(ObjectUri::parse("s3://my-bucket/logs/a.txt").unwrap(), uri("my-bucket", "logs/a.txt"));
+        assert_eq!(ObjectUri::parse("oss://my-bucket/a.txt").unwrap(), uri("my-bucket", "a.txt"));
+        assert_eq!(ObjectUri::parse("S3://my-bucket/a.txt").unwrap(), uri("my-bucket", "a.txt"));
+        // 空 key 和末尾的 / 原样保留，由具体命令决定是否允许
+        assert_eq!(ObjectUri::parse("s3://my-bucket").unwrap(), uri("my-bucket", ""));
+        assert_eq!(ObjectUri::parse("s3://my-bucket/").unwrap(), uri("my-bucket", ""));
+        assert_eq!(ObjectUri::parse("s3://my-bucket/logs/").unwrap(), uri("my-bucket", "logs/"));
+        assert_eq!(ObjectUri::parse("s3://my-bucket//a").unwrap(), uri("my-bucket", "/a"));
+        // 百分号编码
+        assert_eq!(ObjectUri::parse("s3://b/a%20b%2B%E4%B8%AD.txt").unwrap(), uri("b", "a b+中.txt"));
+        assert_eq!(ObjectUri::parse("s3://b/100%25/a+b").unwrap(), uri("b", "100%/a+b"));
+        assert_eq!(ObjectUri::parse("s3://b/a%zz").unwrap(), uri("b", "a%zz"));
+        assert!(ObjectUri::parse("s3://b/%FF").is_err());
+
+        // 普通 key
+        for key in ["logs/a.txt", "s3:/a", "http://example.com/a", "a/s3://b", ""] {
+            assert_eq!(ObjectUri::parse(key).unwrap(), None, "{}", key);
+        }
+        for invalid in ["s3://", "s3:///a.txt", "oss:///"] {
+            assert!(matches!(ObjectUri::parse(invalid), Err(crate::OssError::Invalid(_))), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_join_key() {
+        assert_eq!(join_key("", "a.txt"), "a.txt");
+        assert_eq!(join_key("/", "a.txt"), "a.txt");
+        assert_eq!(join_key("logs", "a.txt"), "logs/a.txt");
+        assert_eq!(join_key("logs/", "a.txt"), "logs/a.txt");
+        assert_eq!(join_key("logs//", "//a.txt"), "logs/a.txt");
+        assert_eq!(join_key("logs/", "/2024//a.log"), "logs/2024//a.log");
+        assert_eq!(join_key("a/b", "c/"), "a/b/c/");
+        assert_eq!(join_key("logs", ""), "logs/");
+        assert_eq!(join_key("", ""), "");
+        assert_eq!(join_key("文档/", "报告 1.pdf"), "文档/报告 1.pdf");
+    }
+
+    #[test]
+    fn test_derive_key_from_path() {
+        let key = |path: &str, prefix| derive_key_from_path(Path::new(path), prefix).unwrap();
+        assert_eq!(key("dist/app.zip", None), "app.zip");
+        assert_eq!(key("dist/app.zip", Some("shares/")), "shares/app.zip");
+        assert_eq!(key("dist/app.zip", Some("")), "app.zip");
+        assert_eq!(key("./报告.pdf", Some("文档")), "文档/报告.pdf");
+        assert_eq!(key("dist/", Some("a")), "a/dist");
+        for path in ["/", "..", ""] {
+            assert!(derive_key_from_path(Path::new(path), None).is_err(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_key_filename() {
+        assert_eq!(key_filename("dir/sub/report.pdf"), "report.pdf");
+        assert_eq!(key_filename("dir/"), "dir");
+        assert_eq!(key_filename("dir//"), "dir");
+        assert_eq!(key_filename("报告.pdf"), "报告.pdf");
+        assert_eq!(key_filename(""), "download");
+        assert_eq!(key_filename("/"), "download");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0.00 B");
+        assert_eq!(format_size(100), "100.00 B");
+        assert_eq!(format_size(1023), "1023.00 B");
+        assert_eq!(format_size(1024), "1.00 KB");
+        assert_eq!(format_size(1536 * 1024), "1.50 MB");
+        assert_eq!(format_size(1024 * 1024 - 1), "1.00 MB");
+        assert_eq!(format_size(10 * 1024 * 1024 * 1024), "10.00 GB");
+        assert_eq!(format_size(1 << 50), "1.00 PB");
+        assert_eq!(format_size(u64::MAX), "16384.00 PB");
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("0").unwrap(), 0);
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("10GB").unwrap(), 10 * 1024 * 1024 * 1024);
+        assert_eq!(parse_size("1.5M").unwrap(), 1536 * 1024);
+        assert_eq!(parse_size("2 kb").unwrap(), 2048);
+        assert_eq!(parse_size(" 5MiB ").unwrap(), 5 << 20);
+        assert_eq!(parse_size("1P").unwrap(), 1 << 50);
+        assert_eq!(parse_size("8191PB").unwrap(), 8191 << 50);
+        for invalid in ["", "abc", "10XB", "-1", "1.5.5M", "16384PB", "1e3"] {
+            assert!(parse_size(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("0").unwrap(), Duration::ZERO);
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_duration("12h").unwrap(), Duration::from_secs(12 * 3600));
+        assert_eq!(parse_duration("30d").unwrap(), Duration::from_secs(30 * 86400));
+        assert_eq!(parse_duration(" 2w ").unwrap(), Duration::from_secs(14 * 86400));
+        assert_eq!(parse_duration(&u64::MAX.to_string()).unwrap(), Duration::from_secs(u64::MAX));
+        for invalid in ["", "d", "3y", "1.5h", "-1s", "30M", "18446744073709551615w"] {
+            assert!(parse_duration(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
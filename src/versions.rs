@@ -0,0 +1,76 @@
+//! 对象版本（ListObjectVersions）
+
+use aws_sdk_s3::primitives::DateTime;
+
+use crate::error::{Context, Result};
+use crate::OssClient;
+
+/// 对象的一个版本或删除标记
+#[derive(Debug, Clone)]
+pub struct ObjectVersion {
+    pub key: String,
+    pub version_id: String,
+    /// 是否为删除标记（delete marker）
+    pub is_delete_marker: bool,
+    pub is_latest: bool,
+    /// 删除标记的大小为 0
+    pub size: u64,
+    pub last_modified: Option<DateTime>,
+}
+
+impl OssClient {
+    /// 列出前缀下所有对象的全部版本和删除标记（自动处理分页）
+    /// 同一个 key 的版本按修改时间从新到旧排列
+    pub async fn list_object_versions(&self, prefix: &str) -> Result<Vec<ObjectVersion>> {
+        let mut versions = Vec::new();
+        let mut key_marker: Option<String> = None;
+        let mut version_id_marker: Option<String> = None;
+
+        loop {
+            let page = self.client
+                .list_object_versions()
+                .bucket(&self.config.bucket)
+                .prefix(prefix)
+                .set_key_marker(key_marker.take())
+                .set_version_id_marker(version_id_marker.take())
+                .send()
+                .await
+                .with_context(|| format!("无法列出版本: {}", prefix))?;
+
+            for v in page.versions() {
+                versions.push(ObjectVersion {
+                    key: v.key().unwrap_or_default().to_string(),
+                    version_id: v.version_id().unwrap_or("null").to_string(),
+                    is_delete_marker: false,
+                    is_latest: v.is_latest().unwrap_or(false),
+                    size: v.size().unwrap_or_default().max(0) as u64,
+                    last_modified: v.last_modified().cloned(),
+                });
+            }
+            for m in page.delete_markers() {
+                versions.push(ObjectVersion {
+                    key: m.key().unwrap_or_default().to_string(),
+                    version_id: m.version_id().unwrap_or("null").to_string(),
+                    is_delete_marker: true,
+                    is_latest: m.is_latest().unwrap_or(false),
+                    size: 0,
+                    last_modified: m.last_modified().cloned(),
+                });
+            }
+
+            if !page.is_truncated().unwrap_or(false) {
+                break;
+            }
+            key_marker = page.next_key_marker().map(|s| s.to_string());
+            version_id_marker = page.next_version_id_marker().map(|s| s.to_string());
+            if key_marker.is_none() {
+                break;
+            }
+        }
+
+        versions.sort_by(|a, b| {
+            a.key.cmp(&b.key).then_with(|| b.last_modified.cmp(&a.last_modified))
+        });
+        Ok(versions)
+    }
+}
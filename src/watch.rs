@@ -0,0 +1,341 @@
+//! 监听本地目录，文件写完（一段时间内大小和修改时间不变）后自动上传
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::error::{Context, OssError, Result};
+use crate::sync::walk_dir;
+use crate::throttle::backoff_delay;
+use crate::{directory_prefix, OssClient};
+
+/// 每个文件最多尝试上传的次数，之后放弃，文件修改后会重新上传
+const WATCH_ATTEMPTS: u32 = 10;
+/// 检查待上传文件是否写完的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 上传成功后如何处理本地文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AfterUpload {
+    Keep,
+    Delete,
+    /// 移动到该目录下，保留相对路径
+    MoveTo(PathBuf),
+}
+
+/// watch 选项
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// 远程 key 前缀，key 为 <前缀>/<相对路径>
+    pub key_prefix: String,
+    /// 文件的大小和修改时间保持不变多久后才上传
+    pub settle: Duration,
+    pub after: AfterUpload,
+}
+
+/// 监听过程中的事件
+#[derive(Debug)]
+pub enum WatchEvent<'a> {
+    Uploaded { path: &'a Path, key: &'a str, size: u64 },
+    /// 上传失败，稍后第 `attempt` 次重试
+    Retry { path: &'a Path, attempt: u32, error: &'a OssError },
+    /// 多次重试后放弃
+    Failed { path: &'a Path, error: &'a OssError },
+    /// 本地文件删除或移动失败，对象已上传
+    CleanupFailed { path: &'a Path, error: &'a OssError },
+    /// 收到退出信号，等待进行中的上传完成
+    Draining { in_flight: usize },
+}
+
+/// 退出时的统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchSummary {
+    pub uploaded: usize,
+    pub failed: usize,
+    /// 退出时还没写完或等待重试、没有上传的文件数
+    pub pending: usize,
+}
+
+/// 文件的大小和修改时间
+type FileState = (u64, SystemTime);
+/// 一次上传的结果：本地路径、上传时的文件状态、key
+type Finished = (PathBuf, FileState, String, Result<()>);
+
+/// 等待文件写完：记录每个文件最近一次变化的时间，保持不变超过 `settle` 才算写完
+#[derive(Debug, Default)]
+struct Debouncer {
+    settle: Duration,
+    files: HashMap<PathBuf, (Option<FileState>, Instant)>,
+}
+
+impl Debouncer {
+    fn new(settle: Duration) -> Self {
+        Self { settle, files: HashMap::new() }
+    }
+
+    /// 文件有变化，重新开始计时
+    fn touch(&mut self, path: PathBuf, now: Instant) {
+        self.files.insert(path, (None, now));
+    }
+
+    /// 检查所有文件，返回已经写完的；不存在的文件直接丢弃，`skip` 中的文件暂不处理
+    fn ready(
+        &mut self,
+        now: Instant,
+        skip: &HashSet<PathBuf>,
+        stat: impl Fn(&Path) -> Option<FileState>,
+    ) -> Vec<(PathBuf, FileState)> {
+        let mut ready = Vec::new();
+        self.files.retain(|path, (last, since)| {
+            if skip.contains(path) {
+                return true;
+            }
+            let Some(state) = stat(path) else {
+                return false;
+            };
+            if *last != Some(state) {
+                *last = Some(state);
+                *since = now;
+                return true;
+            }
+            if now.duration_since(*since) < self.settle {
+                return true;
+            }
+            ready.push((path.clone(), state));
+            false
+        });
+        ready.sort();
+        ready
+    }
+
+    fn len(&self) -> usize {
+        self.files.len()
+    }
+}
+
+impl OssClient {
+    /// 监听 `dir`（包括子目录），新建或修改的文件写完后上传，并发数为 workers
+    /// 启动时目录中已有的文件也会上传。上传失败的文件按指数退避重试，最多尝试 10 次。
+    /// `shutdown` 完成后不再开始新的上传，等进行中的上传结束后返回
+    pub async fn watch(
+        &self,
+        dir: &Path,
+        options: &WatchOptions,
+        shutdown: impl Future<Output = ()>,
+        on_event: impl Fn(WatchEvent<'_>),
+    ) -> Result<WatchSummary> {
+        let root = dir.canonicalize().with_context(|| format!("无法找到目录: {}", dir.display()))?;
+        let prefix = directory_prefix(&options.key_prefix);
+        // 移动目标在监听目录内时，移进去的文件不能再触发上传
+        let move_to = match &options.after {
+            AfterUpload::MoveTo(target) => {
+                std::fs::create_dir_all(target).with_context(|| format!("无法创建目录: {}", target.display()))?;
+                Some(target.canonicalize()?)
+            }
+            _ => None,
+        };
+        let ignored = |path: &Path| move_to.as_ref().is_some_and(|target| path.starts_with(target));
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if !matches!(event.kind, EventKind::Access(_) | EventKind::Remove(_)) {
+                    let _ = tx.send(event.paths);
+                }
+            }
+        })
+        .context("无法创建目录监听")?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .with_context(|| format!("无法监听目录: {}", root.display()))?;
+
+        let mut debouncer = Debouncer::new(options.settle);
+        let now = Instant::now();
+        for file in walk_dir(&root)? {
+            if !ignored(&file.path) {
+                debouncer.touch(file.path, now);
+            }
+        }
+
+        let mut summary = WatchSummary::default();
+        let mut queue: VecDeque<(PathBuf, FileState)> = VecDeque::new();
+        let mut retries: Vec<(Instant, PathBuf)> = Vec::new();
+        let mut attempts: HashMap<PathBuf, u32> = HashMap::new();
+        // 已上传的文件状态，没有变化的重复事件（如修改权限）不会再次上传
+        let mut uploaded: HashMap<PathBuf, FileState> = HashMap::new();
+        let mut busy: HashSet<PathBuf> = HashSet::new();
+        let mut in_flight = FuturesUnordered::new();
+        let mut tick = tokio::time::interval(POLL_INTERVAL);
+        let mut stopping = false;
+        tokio::pin!(shutdown);
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown, if !stopping => {
+                    stopping = true;
+                    on_event(WatchEvent::Draining { in_flight: in_flight.len() });
+                }
+                Some(paths) = rx.recv(), if !stopping => {
+                    let now = Instant::now();
+                    for path in paths {
+                        if path.is_file() && !ignored(&path) {
+                            debouncer.touch(path, now);
+                        }
+                    }
+                }
+                _ = tick.tick(), if !stopping => {
+                    let now = Instant::now();
+                    let due: Vec<_> = retries.iter().filter(|(at, _)| *at <= now).map(|(_, p)| p.clone()).collect();
+                    retries.retain(|(at, _)| *at > now);
+                    for path in due {
+                        busy.remove(&path);
+                        debouncer.touch(path, now);
+                    }
+                    for (path, state) in debouncer.ready(now, &busy, file_state) {
+                        if uploaded.get(&path) != Some(&state) {
+                            busy.insert(path.clone());
+                            queue.push_back((path, state));
+                        }
+                    }
+                }
+                Some(finished) = in_flight.next(), if !in_flight.is_empty() => {
+                    let (path, state, key, result): Finished = finished;
+                    match result {
+                        Ok(()) => {
+                            summary.uploaded += 1;
+                            attempts.remove(&path);
+                            busy.remove(&path);
+                            on_event(WatchEvent::Uploaded { path: &path, key: &key, size: state.0 });
+                            match after_upload(&root, &path, &options.after) {
+                                Ok(()) if options.after == AfterUpload::Keep => {
+                                    uploaded.insert(path, state);
+                                }
+                                Ok(()) => {}
+                                Err(error) => on_event(WatchEvent::CleanupFailed { path: &path, error: &error }),
+                            }
+                        }
+                        Err(error) => {
+                            let attempt = attempts.entry(path.clone()).or_insert(0);
+                            *attempt += 1;
+                            if *attempt >= WATCH_ATTEMPTS {
+                                summary.failed += 1;
+                                attempts.remove(&path);
+                                busy.remove(&path);
+                                on_event(WatchEvent::Failed { path: &path, error: &error });
+                            } else {
+                                on_event(WatchEvent::Retry { path: &path, attempt: *attempt + 1, error: &error });
+                                retries.push((Instant::now() + backoff_delay(*attempt), path));
+                            }
+                        }
+                    }
+                }
+            }
+
+            while !stopping && in_flight.len() < self.workers {
+                let Some((path, state)) = queue.pop_front() else {
+                    break;
+                };
+                let Some(relative) = relative_key(&root, &path) else {
+                    busy.remove(&path);
+                    continue;
+                };
+                let key = format!("{}{}", prefix, relative);
+                in_flight.push(async move {
+                    let result = self.upload(&path, &key).await.map(|_| ());
+                    (path, state, key, result)
+                });
+            }
+            if stopping && in_flight.is_empty() {
+                break;
+            }
+        }
+
+        summary.pending = debouncer.len() + queue.len() + retries.len();
+        Ok(summary)
+    }
+}
+
+fn file_state(path: &Path) -> Option<FileState> {
+    let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// 相对于监听目录的路径，以 `/` 分隔；文件名不是有效的 UTF-8 时返回 None
+fn relative_key(root: &Path, path: &Path) -> Option<String> {
+    let parts = path
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    Some(parts.join("/"))
+}
+
+/// 上传成功后删除或移动本地文件，移动时保留相对路径
+fn after_upload(root: &Path, path: &Path, after: &AfterUpload) -> Result<()> {
+    match after {
+        AfterUpload::Keep => Ok(()),
+        AfterUpload::Delete => std::fs::remove_file(path).with_context(|| format!("无法删除 {}", path.display())),
+        AfterUpload::MoveTo(target) => {
+            let dest = target.join(path.strip_prefix(root)?);
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent).with_context(|| format!("无法创建目录: {}", parent.display()))?;
+            }
+            // 跨文件系统时 rename 失败，改为复制后删除
+            if std::fs::rename(path, &dest).is_err() {
+                std::fs::copy(path, &dest).with_context(|| format!("无法移动 {} 到 {}", path.display(), dest.display()))?;
+                std::fs::remove_file(path).with_context(|| format!("无法删除 {}", path.display()))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debouncer_waits_until_stable() {
+        let settle = Duration::from_secs(2);
+        let mut debouncer = Debouncer::new(settle);
+        let start = Instant::now();
+        let path = PathBuf::from("/in/a.csv");
+        let time = SystemTime::UNIX_EPOCH;
+        debouncer.touch(path.clone(), start);
+
+        let none = HashSet::new();
+        // 第一次检查只记录状态
+        assert!(debouncer.ready(start, &none, |_| Some((10, time))).is_empty());
+        // 还在写入，重新计时
+        assert!(debouncer.ready(start + settle, &none, |_| Some((20, time))).is_empty());
+        assert!(debouncer.ready(start + settle + Duration::from_secs(1), &none, |_| Some((20, time))).is_empty());
+        let busy = HashSet::from([path.clone()]);
+        assert!(debouncer.ready(start + 3 * settle, &busy, |_| Some((20, time))).is_empty());
+        assert_eq!(debouncer.ready(start + 3 * settle, &none, |_| Some((20, time))), vec![(path.clone(), (20, time))]);
+        assert_eq!(debouncer.len(), 0);
+
+        // 文件在写完前被删除
+        debouncer.touch(path, start);
+        assert!(debouncer.ready(start, &none, |_| None).is_empty());
+        assert_eq!(debouncer.len(), 0);
+    }
+
+    #[test]
+    fn test_after_upload_move_keeps_relative_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("incoming");
+        let file = root.join("2024/a.csv");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, b"a,b").unwrap();
+        assert_eq!(relative_key(&root, &file).as_deref(), Some("2024/a.csv"));
+
+        let done = dir.path().join("done");
+        after_upload(&root, &file, &AfterUpload::MoveTo(done.clone())).unwrap();
+        assert!(!file.exists());
+        assert_eq!(std::fs::read(done.join("2024/a.csv")).unwrap(), b"a,b");
+    }
+}
@@ -0,0 +1,29 @@
+//! 集成测试共用的辅助函数
+
+use oss_uploader::PresignedUrl;
+
+/// 不带凭证发送预签名请求，返回 HTTP 状态码和响应体；只支持 http:// 的 endpoint（如本地 MinIO）
+pub async fn send_presigned(signed: &PresignedUrl, body: &[u8]) -> (u16, Vec<u8>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let rest = signed.url.strip_prefix("http://").expect("只支持 http:// 的 endpoint");
+    let (host, path) = rest.split_at(rest.find('/').unwrap());
+    let address = if host.contains(':') { host.to_string() } else { format!("{}:80", host) };
+    let mut request = format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", signed.method, path, host);
+    for (name, value) in &signed.headers {
+        if !name.eq_ignore_ascii_case("host") && !name.eq_ignore_ascii_case("content-length") {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    request.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+
+    let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+    stream.write_all(request.as_bytes()).await.unwrap();
+    stream.write_all(body).await.unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    let status_line = String::from_utf8_lossy(&response).lines().next().unwrap_or_default().to_string();
+    let status = status_line.split(' ').nth(1).and_then(|code| code.parse().ok()).unwrap_or(0);
+    let body = response.windows(4).position(|w| w == b"\r\n\r\n").map_or(Vec::new(), |end| response[end + 4..].to_vec());
+    (status, body)
+}
@@ -0,0 +1,40 @@
+//! 检查各个 feature 组合都能编译：只用库时（default-features = false）不依赖 clap、indicatif、notify 等命令行依赖
+//! 需要重新编译依赖，耗时较长，默认忽略：cargo test --test feature_builds -- --ignored
+
+use std::path::Path;
+use std::process::Command;
+
+fn cargo_check(args: &[&str]) {
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    let status = Command::new(env!("CARGO"))
+        .arg("check")
+        .args(["--manifest-path", &format!("{}/Cargo.toml", manifest_dir)])
+        .args(args)
+        // 使用单独的目录，避免和外层 cargo 争用 target 的锁
+        .env("CARGO_TARGET_DIR", Path::new(manifest_dir).join("target/feature-builds"))
+        .status()
+        .expect("无法运行 cargo");
+    assert!(status.success(), "cargo check {} 失败", args.join(" "));
+}
+
+#[test]
+#[ignore]
+fn test_library_without_default_features() {
+    cargo_check(&["--lib", "--no-default-features"]);
+}
+
+#[test]
+#[ignore]
+fn test_library_with_optional_features() {
+    cargo_check(&["--lib", "--no-default-features", "--features", "blocking,testing,object-store"]);
+    // 监听目录不依赖命令行程序的其他依赖
+    cargo_check(&["--lib", "--no-default-features", "--features", "watch"]);
+}
+
+#[test]
+#[ignore]
+fn test_binary_requires_cli() {
+    cargo_check(&["--bins"]);
+    // 没有 cli feature 时跳过命令行程序，只编译库
+    cargo_check(&["--bins", "--no-default-features"]);
+}
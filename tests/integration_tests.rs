@@ -1,6 +1,9 @@
 use std::io::Write;
 use tempfile::NamedTempFile;
-use oss_uploader::{OssClient, OssConfig};
+use oss_uploader::{format_size, OssClient, OssConfig, PresignMethod, PresignOptions, SyncOptions};
+
+mod common;
+use common::send_presigned;
 
 /// 测试辅助函数：创建临时配置文件
 fn create_test_config() -> OssConfig {
@@ -10,6 +13,7 @@ fn create_test_config() -> OssConfig {
         bucket: "test-bucket".to_string(),
         endpoint: "https://test.endpoint.com".to_string(),
         region: "test_region".to_string(),
+        public_base_url: None,
     }
 }
 
@@ -34,20 +38,10 @@ fn test_url_generation() {
 #[test]
 fn test_format_size() {
     // 测试文件大小格式化
-    fn format_size(mut size_bytes: f64) -> String {
-        for unit in ["B", "KB", "MB", "GB", "TB"] {
-            if size_bytes < 1024.0 {
-                return format!("{:.2} {}", size_bytes, unit);
-            }
-            size_bytes /= 1024.0;
-        }
-        format!("{:.2} PB", size_bytes)
-    }
-
-    assert_eq!(format_size(100.0), "100.00 B");
-    assert_eq!(format_size(1024.0), "1.00 KB");
-    assert_eq!(format_size(1024.0 * 1024.0), "1.00 MB");
-    assert_eq!(format_size(10.0 * 1024.0 * 1024.0), "10.00 MB");
+    assert_eq!(format_size(100), "100.00 B");
+    assert_eq!(format_size(1024), "1.00 KB");
+    assert_eq!(format_size(1024 * 1024), "1.00 MB");
+    assert_eq!(format_size(10 * 1024 * 1024), "10.00 MB");
 }
 
 /// 集成测试（需要真实 OSS 凭证）
@@ -111,3 +105,162 @@ async fn test_multipart_upload_integration() {
     // 清理
     let _ = client.delete(key).await;
 }
+
+#[tokio::test]
+#[ignore] // 默认忽略，需要配置真实环境变量（也可以指向本地 MinIO）
+async fn test_sync_up_integration() {
+    if std::env::var("OSS_ACCESS_KEY").is_err() {
+        println!("跳过集成测试：未设置 OSS_ACCESS_KEY");
+        return;
+    }
+
+    let config = OssConfig::from_env().expect("Failed to load config");
+    let client = OssClient::new(config).await.expect("Failed to create client");
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("sub")).unwrap();
+    std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+    std::fs::write(dir.path().join("sub/b.txt"), "b").unwrap();
+
+    let prefix = "test/sync_up/";
+    let options = SyncOptions { delete: true, ..Default::default() };
+
+    // 第一次全部上传，第二次没有变化全部跳过
+    let report = client.sync_up(dir.path(), prefix, &options).await.expect("同步失败");
+    assert_eq!(report.transferred, vec!["test/sync_up/a.txt", "test/sync_up/sub/b.txt"]);
+    let report = client.sync_up(dir.path(), prefix, &options).await.expect("同步失败");
+    assert!(report.transferred.is_empty());
+    assert_eq!(report.skipped, 2);
+
+    // 删除本地文件后，--delete 删除远程多出的对象
+    std::fs::remove_file(dir.path().join("a.txt")).unwrap();
+    let report = client.sync_up(dir.path(), prefix, &options).await.expect("同步失败");
+    assert_eq!(report.deleted, vec!["test/sync_up/a.txt"]);
+
+    // 清理
+    let _ = client.delete_many(&["test/sync_up/sub/b.txt".to_string()]).await;
+}
+
+#[tokio::test]
+#[ignore] // 默认忽略，需要指向 http:// 的 MinIO 等服务
+async fn test_presigned_put_integration() {
+    if std::env::var("OSS_ACCESS_KEY").is_err() {
+        println!("跳过集成测试：未设置 OSS_ACCESS_KEY");
+        return;
+    }
+
+    let config = OssConfig::from_env().expect("Failed to load config");
+    if !config.endpoint.starts_with("http://") {
+        println!("跳过集成测试：endpoint 不是 http://");
+        return;
+    }
+    let client = OssClient::new(config).await.expect("Failed to create client");
+    let key = "test/presigned_put.txt";
+    let body = b"uploaded with a presigned url";
+
+    let put = PresignMethod::Put { content_type: Some("text/plain".to_string()), content_length: Some(body.len() as u64) };
+    let signed = client.presign(key, put, 300, &PresignOptions::default()).await.unwrap();
+    assert_eq!(send_presigned(&signed, body).await.0, 200);
+    let info = client.head(key, None).await.expect("上传后对象应当存在");
+    assert_eq!((info.size, info.content_type.as_deref()), (body.len() as u64, Some("text/plain")));
+
+    // 大小与签名不一致时服务端拒绝
+    assert_eq!(send_presigned(&signed, b"short").await.0 / 100, 4);
+
+    let signed = client.presign(key, PresignMethod::Delete, 300, &PresignOptions::default()).await.unwrap();
+    assert_eq!(send_presigned(&signed, b"").await.0, 204);
+    assert!(!client.exists(key).await.unwrap());
+}
+
+/// 并发上传 `count` 个小对象，返回耗时
+async fn upload_small_objects(client: &OssClient, prefix: &str, count: usize) -> std::time::Duration {
+    use futures::StreamExt;
+
+    let start = std::time::Instant::now();
+    let results: Vec<_> = futures::stream::iter(0..count)
+        .map(|i| async move { client.upload_bytes(&format!("{}{}.txt", prefix, i), b"small").await })
+        .buffer_unordered(client.workers())
+        .collect()
+        .await;
+    assert!(results.iter().all(|r| r.is_ok()), "上传失败: {:?}", results.iter().find(|r| r.is_err()));
+    start.elapsed()
+}
+
+#[tokio::test]
+#[ignore] // 默认忽略，需要配置真实环境变量（建议指向本地 MinIO，用 mc admin trace 观察连接）
+async fn bench_connection_reuse() {
+    if std::env::var("OSS_ACCESS_KEY").is_err() {
+        println!("跳过集成测试：未设置 OSS_ACCESS_KEY");
+        return;
+    }
+
+    let config = OssConfig::from_env().expect("Failed to load config");
+    let prefix = "test/bench_pool/";
+    let count = 500;
+
+    // 不保留空闲连接：每个请求都重新建立连接
+    let client = OssClient::builder(config.clone()).pool_max_idle_per_host(0).build().await.unwrap();
+    let without_pool = upload_small_objects(&client, prefix, count).await;
+    let client = OssClient::builder(config)
+        .pool_max_idle_per_host(client.workers())
+        .pool_idle_timeout(Some(std::time::Duration::from_secs(30)))
+        .build()
+        .await
+        .unwrap();
+    let with_pool = upload_small_objects(&client, prefix, count).await;
+    println!("上传 {} 个小对象：不复用连接 {:?}，复用连接 {:?}", count, without_pool, with_pool);
+
+    let keys: Vec<_> = (0..count).map(|i| format!("{}{}.txt", prefix, i)).collect();
+    let _ = client.delete_many(&keys).await;
+}
+
+#[cfg(feature = "object-store")]
+#[tokio::test]
+#[ignore] // 默认忽略，需要配置真实环境变量（建议指向本地 MinIO）：cargo test --features object-store -- --ignored
+async fn test_object_store_integration() {
+    use futures::{StreamExt, TryStreamExt};
+    use object_store::path::Path;
+    use object_store::{ObjectStore, ObjectStoreExt, PutPayload, WriteMultipart};
+    use oss_uploader::OssObjectStore;
+
+    if std::env::var("OSS_ACCESS_KEY").is_err() {
+        println!("跳过集成测试：未设置 OSS_ACCESS_KEY");
+        return;
+    }
+
+    let config = OssConfig::from_env().expect("Failed to load config");
+    let client = OssClient::new(config).await.expect("Failed to create client");
+    let store = OssObjectStore::new(client.clone());
+    let prefix = "test/object_store";
+
+    // 超过一页（1000 个）的对象，list 需要自动翻页
+    let count = 1005;
+    upload_small_objects(&client, &format!("{}/many/", prefix), count).await;
+    let listed: Vec<_> = store.list(Some(&Path::from(format!("{}/many", prefix)))).try_collect().await.unwrap();
+    assert_eq!(listed.len(), count);
+    let listed = store.list_with_delimiter(Some(&Path::from(prefix))).await.unwrap();
+    assert_eq!(listed.common_prefixes, [Path::from(format!("{}/many", prefix))]);
+
+    // 两个 5MB 的分块加最后不足一块的数据
+    let location = Path::from(format!("{}/multipart.bin", prefix));
+    let upload = store.put_multipart(&location).await.unwrap();
+    let mut writer = WriteMultipart::new_with_chunk_size(upload, 5 * 1024 * 1024);
+    let data: Vec<u8> = (0..11 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    writer.write(&data);
+    writer.finish().await.unwrap();
+    assert_eq!(store.head(&location).await.unwrap().size, data.len() as u64);
+    let range = store.get_range(&location, 6_000_000..6_000_100).await.unwrap();
+    assert_eq!(range.as_ref(), &data[6_000_000..6_000_100]);
+    let all = store.get(&location).await.unwrap().bytes().await.unwrap();
+    assert_eq!(all.as_ref(), &data[..]);
+
+    let copy = Path::from(format!("{}/copy.bin", prefix));
+    store.copy(&location, &copy).await.unwrap();
+    store.put(&Path::from(format!("{}/small.txt", prefix)), PutPayload::from_static(b"small")).await.unwrap();
+
+    // 清理
+    let locations = store.list(Some(&Path::from(prefix))).map_ok(|meta| meta.location).boxed();
+    let deleted: Vec<_> = store.delete_stream(locations).try_collect().await.unwrap();
+    assert_eq!(deleted.len(), count + 3);
+    assert!(matches!(store.head(&copy).await, Err(object_store::Error::NotFound { .. })));
+}
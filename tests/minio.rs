@@ -0,0 +1,305 @@
+//! 基于本地 MinIO 的端到端测试：`make test-minio` 启动 docker-compose.yml 中的 MinIO 并运行
+//! 未设置 `OSS_MINIO_ENDPOINT` 时每个测试直接跳过，离线的 `cargo test` 不受影响
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use aws_sdk_s3::config::retry::RetryConfig;
+use aws_sdk_s3::config::{HttpClient, IntoShared, RuntimeComponents, SharedHttpClient};
+use aws_smithy_runtime_api::client::http::{
+    HttpConnector, HttpConnectorFuture, HttpConnectorSettings, SharedHttpConnector,
+};
+use aws_smithy_runtime_api::client::orchestrator::HttpRequest;
+use aws_smithy_runtime_api::client::result::ConnectorError;
+use oss_uploader::{DeleteOutcome, DownloadOptions, OssClient, OssClientBuilder, OssConfig, PresignMethod, PresignOptions};
+use tempfile::TempDir;
+
+mod common;
+use common::send_presigned;
+
+const PART_SIZE: u64 = 5 * 1024 * 1024;
+
+/// MinIO 的连接配置，未设置 `OSS_MINIO_ENDPOINT` 时返回 None
+fn minio_config(bucket: &str) -> Option<OssConfig> {
+    let Ok(endpoint) = std::env::var("OSS_MINIO_ENDPOINT") else {
+        println!("跳过 MinIO 测试：未设置 OSS_MINIO_ENDPOINT");
+        return None;
+    };
+    let var = |name: &str| std::env::var(name).unwrap_or_else(|_| "minioadmin".to_string());
+    Some(OssConfig {
+        access_key: var("OSS_MINIO_ACCESS_KEY"),
+        secret_key: var("OSS_MINIO_SECRET_KEY"),
+        bucket: bucket.to_string(),
+        endpoint,
+        region: "us-east-1".to_string(),
+        public_base_url: None,
+    })
+}
+
+fn minio_builder(bucket: &str) -> Option<OssClientBuilder> {
+    let config = minio_config(bucket)?;
+    Some(OssClient::builder(config).path_style(true).part_size(PART_SIZE))
+}
+
+/// 每个测试使用独立的 bucket，测试结束后调用 [`TestBucket::cleanup`] 删除
+struct TestBucket {
+    name: String,
+    client: OssClient,
+    dir: TempDir,
+}
+
+impl TestBucket {
+    async fn create(name: &str) -> Option<Self> {
+        let bucket = unique_bucket(name);
+        let client = minio_builder(&bucket)?.build().await.unwrap();
+        client.create_bucket(&bucket).await.unwrap();
+        Some(Self { name: bucket, client, dir: TempDir::new().unwrap() })
+    }
+
+    fn write_file(&self, name: &str, data: &[u8]) -> std::path::PathBuf {
+        let path = self.dir.path().join(name);
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    async fn cleanup(self) {
+        self.client.delete_bucket(&self.name, true).await.unwrap();
+    }
+}
+
+fn unique_bucket(name: &str) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+    format!("oss-test-{}-{}-{}", name, nanos % 1_000_000_000, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+/// 内容随位置变化的测试数据，错位的分块会被校验发现
+fn test_data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+#[tokio::test]
+async fn test_minio_small_object_roundtrip() {
+    let Some(bucket) = TestBucket::create("small").await else { return };
+    let client = &bucket.client;
+    let data = b"hello minio";
+    let path = bucket.write_file("small.txt", data);
+
+    client.upload(&path, "dir/small.txt").await.unwrap();
+    let info = client.head("dir/small.txt", None).await.unwrap();
+    assert_eq!(info.size, data.len() as u64);
+
+    let output = bucket.dir.path().join("small.out");
+    client.download("dir/small.txt", Some(&output)).await.unwrap();
+    assert_eq!(std::fs::read(&output).unwrap(), data);
+
+    assert_eq!(client.delete("dir/small.txt").await.unwrap(), DeleteOutcome::Deleted);
+    assert_eq!(client.delete("dir/small.txt").await.unwrap(), DeleteOutcome::DidNotExist);
+    assert!(client.head_if_exists("dir/small.txt").await.unwrap().is_none());
+    bucket.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_minio_multipart_and_ranged_download() {
+    let Some(bucket) = TestBucket::create("multipart").await else { return };
+    let client = &bucket.client;
+    let data = test_data(2 * PART_SIZE as usize + 1024 * 1024);
+    let path = bucket.write_file("large.bin", &data);
+
+    client.upload(&path, "large.bin").await.unwrap();
+    let info = client.head("large.bin", None).await.unwrap();
+    assert_eq!(info.size, data.len() as u64);
+    // 分块上传的 ETag 以分块数结尾
+    assert!(info.etag.as_deref().unwrap().trim_matches('"').ends_with("-3"), "{:?}", info.etag);
+
+    // 超过分块大小的对象使用并发的分段下载
+    let output = bucket.dir.path().join("large.out");
+    client.download("large.bin", Some(&output)).await.unwrap();
+    assert!(std::fs::read(&output).unwrap() == data);
+
+    // 只下载跨越分块边界的一段
+    let start = PART_SIZE - 10;
+    let options = DownloadOptions::default().range(start, Some(start + 19));
+    let ranged = bucket.dir.path().join("range.out");
+    client.download_with_options("large.bin", Some(&ranged), &options).await.unwrap();
+    assert_eq!(std::fs::read(&ranged).unwrap(), &data[start as usize..start as usize + 20]);
+    bucket.cleanup().await;
+}
+
+#[tokio::test]
+async fn test_minio_presigned_get() {
+    let Some(bucket) = TestBucket::create("presign").await else { return };
+    let client = &bucket.client;
+    let path = bucket.write_file("shared.txt", b"shared content");
+    client.upload(&path, "shared.txt").await.unwrap();
+
+    let signed = client.presign("shared.txt", PresignMethod::Get, 300, &PresignOptions::default()).await.unwrap();
+    let (status, body) = send_presigned(&signed, b"").await;
+    assert_eq!(status, 200);
+    assert_eq!(body, b"shared content");
+
+    // 过期时间以外的修改使签名失效
+    let mut tampered = signed.clone();
+    tampered.url = tampered.url.replace("X-Amz-Expires=300", "X-Amz-Expires=301");
+    assert_eq!(send_presigned(&tampered, b"").await.0, 403);
+    bucket.cleanup().await;
+}
+
+/// 第 2 个分块的请求在连接层失败，其余请求正常发送
+#[derive(Debug, Clone)]
+struct FailingHttp {
+    inner: SharedHttpClient,
+}
+
+#[derive(Debug)]
+struct FailingConnector {
+    inner: SharedHttpConnector,
+}
+
+impl HttpConnector for FailingConnector {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        if request.uri().contains("partNumber=2&") {
+            return HttpConnectorFuture::ready(Err(ConnectorError::io("模拟的分块上传失败".into())));
+        }
+        self.inner.call(request)
+    }
+}
+
+impl HttpClient for FailingHttp {
+    fn http_connector(&self, settings: &HttpConnectorSettings, components: &RuntimeComponents) -> SharedHttpConnector {
+        FailingConnector { inner: self.inner.http_connector(settings, components) }.into_shared()
+    }
+}
+
+#[tokio::test]
+async fn test_minio_failed_part_aborts_upload() {
+    let Some(bucket) = TestBucket::create("abort").await else { return };
+    let failing = minio_builder(&bucket.name)
+        .unwrap()
+        .retry(RetryConfig::disabled())
+        .http_client(FailingHttp { inner: aws_smithy_http_client::Builder::new().build_http() })
+        .build()
+        .await
+        .unwrap();
+    let path = bucket.write_file("large.bin", &test_data(2 * PART_SIZE as usize + 1));
+
+    assert!(failing.upload(&path, "large.bin").await.is_err());
+    // 失败后分块上传被取消，不会留下未完成的上传和对象
+    assert!(bucket.client.list_multipart_uploads("large.bin").await.unwrap().is_empty());
+    assert!(bucket.client.head_if_exists("large.bin").await.unwrap().is_none());
+    bucket.cleanup().await;
+}
+
+/// 用 MinIO 的配置运行命令行程序，返回是否成功和标准输出；OSS_BUCKET 为 `bucket`
+#[cfg(feature = "cli")]
+async fn run_cli(bucket: &TestBucket, args: &[&str]) -> (bool, String) {
+    let config = minio_config(&bucket.name).unwrap();
+    // 端点为 IP 地址时 SDK 自动使用 path-style，命令行不需要额外设置
+    let output = tokio::process::Command::new(env!("CARGO_BIN_EXE_oss-uploader"))
+        .args(args)
+        .env("OSS_ACCESS_KEY", &config.access_key)
+        .env("OSS_SECRET_KEY", &config.secret_key)
+        .env("OSS_BUCKET", &config.bucket)
+        .env("OSS_ENDPOINT", &config.endpoint)
+        .env("OSS_REGION", &config.region)
+        .env_remove("OSS_PUBLIC_URL")
+        .output()
+        .await
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() {
+        println!("{}\n{}", stdout, String::from_utf8_lossy(&output.stderr));
+    }
+    (output.status.success(), stdout)
+}
+
+#[cfg(feature = "cli")]
+#[tokio::test]
+async fn test_minio_cp_local_to_remote() {
+    let Some(bucket) = TestBucket::create("cp-up").await else { return };
+    let uri = |key: &str| format!("s3://{}/{}", bucket.name, key);
+    let data = test_data(2 * PART_SIZE as usize + 1);
+    let path = bucket.write_file("large.bin", &data);
+
+    // 目标以 / 结尾时保留文件名，--part-size 对 cp 同样有效
+    let (ok, _) = run_cli(&bucket, &["cp", "--part-size", "5M", path.to_str().unwrap(), &uri("single/")]).await;
+    assert!(ok);
+    let info = bucket.client.head("single/large.bin", None).await.unwrap();
+    assert_eq!(info.size, data.len() as u64);
+    assert!(info.etag.as_deref().unwrap().trim_matches('"').ends_with("-3"), "{:?}", info.etag);
+
+    let dir = bucket.dir.path().join("tree");
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("a.txt"), "a").unwrap();
+    std::fs::write(dir.join("sub/b.txt"), "b").unwrap();
+    std::fs::write(dir.join("sub/c.log"), "c").unwrap();
+    let dir = dir.to_str().unwrap();
+    // 目录需要 --recursive；dry-run 不上传
+    assert!(!run_cli(&bucket, &["cp", dir, &uri("tree")]).await.0);
+    let (ok, stdout) = run_cli(&bucket, &["cp", "-r", "--dry-run", "--exclude", "**/*.log", dir, &uri("tree")]).await;
+    assert!(ok);
+    assert!(stdout.contains("tree/sub/b.txt") && !stdout.contains("c.log"), "{}", stdout);
+    assert!(bucket.client.list_objects("tree/").await.unwrap().is_empty());
+
+    // 已有的同名对象也会覆盖
+    bucket.client.upload_bytes("tree/a.txt", "x").await.unwrap();
+    assert!(run_cli(&bucket, &["cp", "-r", "--exclude", "**/*.log", dir, &uri("tree")]).await.0);
+    let keys: Vec<_> = bucket.client.list_objects("tree/").await.unwrap().into_iter().map(|obj| obj.key).collect();
+    assert_eq!(keys, ["tree/a.txt", "tree/sub/b.txt"]);
+    let output = bucket.dir.path().join("a.out");
+    bucket.client.download("tree/a.txt", Some(&output)).await.unwrap();
+    assert_eq!(std::fs::read(&output).unwrap(), b"a");
+
+    // 两边都是本地路径时拒绝
+    assert!(!run_cli(&bucket, &["cp", path.to_str().unwrap(), output.to_str().unwrap()]).await.0);
+    bucket.cleanup().await;
+}
+
+#[cfg(feature = "cli")]
+#[tokio::test]
+async fn test_minio_cp_remote_to_local() {
+    let Some(bucket) = TestBucket::create("cp-down").await else { return };
+    let uri = |key: &str| format!("s3://{}/{}", bucket.name, key);
+    for (key, body) in [("data/a.txt", "a"), ("data/sub/b.txt", "b"), ("data/sub/c.log", "c")] {
+        bucket.client.upload_bytes(key, body).await.unwrap();
+    }
+
+    // 目标为已有目录时使用对象的文件名
+    let dir = bucket.dir.path();
+    assert!(run_cli(&bucket, &["cp", &uri("data/a.txt"), dir.to_str().unwrap()]).await.0);
+    assert_eq!(std::fs::read(dir.join("a.txt")).unwrap(), b"a");
+    assert!(!run_cli(&bucket, &["cp", &uri("data/"), dir.to_str().unwrap()]).await.0);
+
+    let out = dir.join("out");
+    let (ok, _) = run_cli(&bucket, &["cp", "-r", "--include", "**/*.txt", &uri("data"), out.to_str().unwrap()]).await;
+    assert!(ok);
+    assert_eq!(std::fs::read(out.join("sub/b.txt")).unwrap(), b"b");
+    assert!(out.join("a.txt").exists() && !out.join("sub/c.log").exists());
+    bucket.cleanup().await;
+}
+
+#[cfg(feature = "cli")]
+#[tokio::test]
+async fn test_minio_cp_remote_to_remote() {
+    let Some(bucket) = TestBucket::create("cp-src").await else { return };
+    let Some(other) = TestBucket::create("cp-dst").await else { return };
+    for (key, body) in [("src/a.txt", "a"), ("src/sub/b.txt", "b"), ("src/sub/c.log", "c")] {
+        bucket.client.upload_bytes(key, body).await.unwrap();
+    }
+    let src = |key: &str| format!("s3://{}/{}", bucket.name, key);
+    let dst = |key: &str| format!("oss://{}/{}", other.name, key);
+
+    // 跨 bucket 复制单个对象，目标以 / 结尾时保留文件名
+    assert!(run_cli(&bucket, &["cp", &src("src/a.txt"), &dst("single/")]).await.0);
+    assert!(other.client.head_if_exists("single/a.txt").await.unwrap().is_some());
+
+    let (ok, stdout) = run_cli(&bucket, &["cp", "-r", "--dry-run", "--exclude", "**/*.log", &src("src"), &dst("copy")]).await;
+    assert!(ok);
+    assert!(stdout.contains("src/sub/b.txt -> copy/sub/b.txt"), "{}", stdout);
+    assert!(other.client.list_objects("copy/").await.unwrap().is_empty());
+
+    assert!(run_cli(&bucket, &["cp", "-r", "--exclude", "**/*.log", &src("src"), &dst("copy")]).await.0);
+    let keys: Vec<_> = other.client.list_objects("copy/").await.unwrap().into_iter().map(|obj| obj.key).collect();
+    assert_eq!(keys, ["copy/a.txt", "copy/sub/b.txt"]);
+    other.cleanup().await;
+    bucket.cleanup().await;
+}